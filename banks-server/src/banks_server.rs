@@ -182,6 +182,7 @@ fn simulate_transaction(
         post_simulation_accounts: _,
         units_consumed,
         return_data,
+        instruction_trace: _,
     } = bank.simulate_transaction_unchecked(sanitized_transaction);
     let simulation_details = TransactionSimulationDetails {
         logs,
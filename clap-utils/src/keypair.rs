@@ -33,6 +33,7 @@ use {
             keypair_from_seed_and_derivation_path, keypair_from_seed_phrase_and_passphrase,
             read_keypair, read_keypair_file, Keypair, NullSigner, Presigner, Signature, Signer,
         },
+        signer::keystore::{is_keystore_file, read_keystore_file, KEYSTORE_PASSPHRASE_ENV},
     },
     std::{
         cell::RefCell,
@@ -345,6 +346,7 @@ impl DefaultSigner {
     /// // Allow pubkey signers without accompanying signatures
     /// let config = SignerFromPathConfig {
     ///     allow_null_signer: true,
+    ///     ..SignerFromPathConfig::default()
     /// };
     ///
     /// let signer = default_signer.signer_from_path_with_config(
@@ -524,6 +526,8 @@ pub fn presigner_from_pubkey_sigs(
 #[derive(Debug, Default)]
 pub struct SignerFromPathConfig {
     pub allow_null_signer: bool,
+    /// Passphrase of a keystore file, instead of the environment or a prompt
+    pub keystore_passphrase: Option<String>,
 }
 
 /// Loads a [Signer] from one of several possible sources.
@@ -568,6 +572,11 @@ pub struct SignerFromPathConfig {
 /// - `file:` &mdash; Read the keypair from a JSON keypair file. The path portion
 ///    of the URI is the file path.
 ///
+///    If the file is an encrypted keystore, as written by `renec-keygen new
+///    --encrypt`, it is unlocked with the passphrase of the
+///    `RENEC_KEYSTORE_PASSPHRASE` environment variable, or else one prompted
+///    from the user. This applies to plain file paths as well.
+///
 /// - `stdin:` &mdash; Read the keypair from stdin, in the JSON format used by
 ///   the keypair file.
 ///
@@ -738,6 +747,7 @@ pub fn signer_from_path(
 /// // Allow pubkey signers without accompanying signatures
 /// let config = SignerFromPathConfig {
 ///     allow_null_signer: true,
+///     ..SignerFromPathConfig::default()
 /// };
 ///
 /// let signer = signer_from_path_with_config(
@@ -772,6 +782,12 @@ pub fn signer_from_path_with_config(
                 legacy,
             )?))
         }
+        SignerSourceKind::Filepath(path) if is_keystore_file(&path) => {
+            Ok(Box::new(match &config.keystore_passphrase {
+                Some(passphrase) => keypair_from_keystore_file_with_passphrase(&path, passphrase)?,
+                None => keypair_from_keystore_file(&path, keypair_name)?,
+            }))
+        }
         SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -865,6 +881,10 @@ pub fn pubkey_from_path(
     let SignerSource { kind, .. } = parse_signer_source(path)?;
     match kind {
         SignerSourceKind::Pubkey(pubkey) => Ok(pubkey),
+        // The pubkey of a keystore is readable without its passphrase
+        SignerSourceKind::Filepath(path) if is_keystore_file(&path) => {
+            Ok(read_keystore_file(&path)?.pubkey()?)
+        }
         _ => Ok(signer_from_path(matches, path, keypair_name, wallet_manager)?.pubkey()),
     }
 }
@@ -894,6 +914,15 @@ pub fn resolve_signer_from_path(
             )
             .map(|_| None)
         }
+        SignerSourceKind::Filepath(path) if is_keystore_file(&path) => read_keystore_file(&path)
+            .map(|_| Some(path.to_string()))
+            .map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    format!("could not read keystore file \"{}\": {}", path, e),
+                )
+                .into()
+            }),
         SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -955,6 +984,35 @@ pub fn prompt_passphrase(prompt: &str) -> Result<String, Box<dyn error::Error>>
     Ok(passphrase)
 }
 
+/// Unlocks an encrypted keystore file, with the passphrase of the
+/// `RENEC_KEYSTORE_PASSPHRASE` environment variable if set, or else prompted
+/// from the user.
+pub fn keypair_from_keystore_file(
+    path: &str,
+    keypair_name: &str,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let passphrase = match std::env::var(KEYSTORE_PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => prompt_password(&format!(
+            "[{}] passphrase of keystore {}: ",
+            keypair_name, path
+        ))?,
+    };
+    keypair_from_keystore_file_with_passphrase(path, &passphrase)
+}
+
+/// Unlocks an encrypted keystore file with `passphrase`
+pub fn keypair_from_keystore_file_with_passphrase(
+    path: &str,
+    passphrase: &str,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let keystore = read_keystore_file(path)
+        .map_err(|e| format!("could not read keystore file \"{}\": {}", path, e))?;
+    Ok(keystore
+        .decrypt(passphrase)
+        .map_err(|e| format!("could not unlock keystore file \"{}\": {}", path, e))?)
+}
+
 /// Loads a [Keypair] from one of several possible sources.
 ///
 /// The `path` is not strictly a file system path, but is interpreted as various
@@ -1017,6 +1075,9 @@ pub fn keypair_from_path(
                 legacy,
             )?)
         }
+        SignerSourceKind::Filepath(path) if is_keystore_file(&path) => {
+            keypair_from_keystore_file(&path, keypair_name)
+        }
         SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -1324,6 +1385,57 @@ mod tests {
 
         assert_eq!(keypair.pubkey(), signer.pubkey());
 
+        Ok(())
+    }
+    #[test]
+    fn signer_from_path_with_keystore() -> Result<(), Box<dyn std::error::Error>> {
+        use solana_sdk::signer::keystore::{write_keystore_file, Keystore, ScryptParams};
+
+        let dir = TempDir::new()?;
+        let keystore_path = dir.path().join("id.json");
+        let keystore_path_str = keystore_path.to_str().expect("utf-8");
+
+        let keypair = Keypair::new();
+        let keystore = Keystore::encrypt_with_params(
+            &keypair,
+            "hunter2",
+            ScryptParams {
+                log_n: 4,
+                ..ScryptParams::default()
+            },
+        )?;
+        write_keystore_file(&keystore, &keystore_path)?;
+
+        let clap_app = App::new("my-program").arg(
+            Arg::with_name("keypair")
+                .required(true)
+                .help("The signing keypair"),
+        );
+        let clap_matches = clap_app.get_matches_from(vec!["program", keystore_path_str]);
+
+        // The pubkey is read without the passphrase
+        assert_eq!(
+            pubkey_from_path(&clap_matches, keystore_path_str, "signer", &mut None)?,
+            keypair.pubkey()
+        );
+
+        let signer_from_keystore = |passphrase: &str| {
+            signer_from_path_with_config(
+                &clap_matches,
+                keystore_path_str,
+                "signer",
+                &mut None,
+                &SignerFromPathConfig {
+                    keystore_passphrase: Some(passphrase.to_string()),
+                    ..SignerFromPathConfig::default()
+                },
+            )
+        };
+        assert_eq!(signer_from_keystore("hunter2")?.pubkey(), keypair.pubkey());
+        assert!(signer_from_keystore("hunter3").is_err());
+        let unlocked = keypair_from_keystore_file_with_passphrase(keystore_path_str, "hunter2")?;
+        assert_eq!(unlocked.to_bytes(), keypair.to_bytes());
+
         Ok(())
     }
 }
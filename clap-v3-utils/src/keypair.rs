@@ -30,9 +30,10 @@ use {
         pubkey::Pubkey,
         signature::{
             generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed,
-            keypair_from_seed_and_derivation_path, keypair_from_seed_phrase_and_passphrase,
-            read_keypair, read_keypair_file, Keypair, NullSigner, Presigner, Signature, Signer,
+            keypair_from_seed_and_derivation_path, read_keypair, read_keypair_file, Keypair,
+            NullSigner, Presigner, Signature, Signer,
         },
+        signer::keystore::{is_keystore_file, read_keystore_file, KEYSTORE_PASSPHRASE_ENV},
     },
     std::{
         cell::RefCell,
@@ -345,6 +346,7 @@ impl DefaultSigner {
     /// // Allow pubkey signers without accompanying signatures
     /// let config = SignerFromPathConfig {
     ///     allow_null_signer: true,
+    ///     ..SignerFromPathConfig::default()
     /// };
     ///
     /// let signer = default_signer.signer_from_path_with_config(
@@ -524,6 +526,8 @@ pub fn presigner_from_pubkey_sigs(
 #[derive(Debug, Default)]
 pub struct SignerFromPathConfig {
     pub allow_null_signer: bool,
+    /// Passphrase of a keystore file, instead of the environment or a prompt
+    pub keystore_passphrase: Option<String>,
 }
 
 /// Loads a [Signer] from one of several possible sources.
@@ -568,6 +572,11 @@ pub struct SignerFromPathConfig {
 /// - `file:` &mdash; Read the keypair from a JSON keypair file. The path portion
 ///    of the URI is the file path.
 ///
+///    If the file is an encrypted keystore, as written by `renec-keygen new
+///    --encrypt`, it is unlocked with the passphrase of the
+///    `RENEC_KEYSTORE_PASSPHRASE` environment variable, or else one prompted
+///    from the user. This applies to plain file paths as well.
+///
 /// - `stdin:` &mdash; Read the keypair from stdin, in the JSON format used by
 ///   the keypair file.
 ///
@@ -738,6 +747,7 @@ pub fn signer_from_path(
 /// // Allow pubkey signers without accompanying signatures
 /// let config = SignerFromPathConfig {
 ///     allow_null_signer: true,
+///     ..SignerFromPathConfig::default()
 /// };
 ///
 /// let signer = signer_from_path_with_config(
@@ -772,6 +782,12 @@ pub fn signer_from_path_with_config(
                 legacy,
             )?))
         }
+        SignerSourceKind::Filepath(path) if is_keystore_file(&path) => {
+            Ok(Box::new(match &config.keystore_passphrase {
+                Some(passphrase) => keypair_from_keystore_file_with_passphrase(&path, passphrase)?,
+                None => keypair_from_keystore_file(&path, keypair_name)?,
+            }))
+        }
         SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -865,6 +881,10 @@ pub fn pubkey_from_path(
     let SignerSource { kind, .. } = parse_signer_source(path)?;
     match kind {
         SignerSourceKind::Pubkey(pubkey) => Ok(pubkey),
+        // The pubkey of a keystore is readable without its passphrase
+        SignerSourceKind::Filepath(path) if is_keystore_file(&path) => {
+            Ok(read_keystore_file(&path)?.pubkey()?)
+        }
         _ => Ok(signer_from_path(matches, path, keypair_name, wallet_manager)?.pubkey()),
     }
 }
@@ -894,6 +914,15 @@ pub fn resolve_signer_from_path(
             )
             .map(|_| None)
         }
+        SignerSourceKind::Filepath(path) if is_keystore_file(&path) => read_keystore_file(&path)
+            .map(|_| Some(path.to_string()))
+            .map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    format!("could not read keystore file \"{}\": {}", path, e),
+                )
+                .into()
+            }),
         SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -955,6 +984,35 @@ pub fn prompt_passphrase(prompt: &str) -> Result<String, Box<dyn error::Error>>
     Ok(passphrase)
 }
 
+/// Unlocks an encrypted keystore file, with the passphrase of the
+/// `RENEC_KEYSTORE_PASSPHRASE` environment variable if set, or else prompted
+/// from the user.
+pub fn keypair_from_keystore_file(
+    path: &str,
+    keypair_name: &str,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let passphrase = match std::env::var(KEYSTORE_PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => prompt_password(&format!(
+            "[{}] passphrase of keystore {}: ",
+            keypair_name, path
+        ))?,
+    };
+    keypair_from_keystore_file_with_passphrase(path, &passphrase)
+}
+
+/// Unlocks an encrypted keystore file with `passphrase`
+pub fn keypair_from_keystore_file_with_passphrase(
+    path: &str,
+    passphrase: &str,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let keystore = read_keystore_file(path)
+        .map_err(|e| format!("could not read keystore file \"{}\": {}", path, e))?;
+    Ok(keystore
+        .decrypt(passphrase)
+        .map_err(|e| format!("could not unlock keystore file \"{}\": {}", path, e))?)
+}
+
 /// Loads a [Keypair] from one of several possible sources.
 ///
 /// The `path` is not strictly a file system path, but is interpreted as various
@@ -1017,6 +1075,9 @@ pub fn keypair_from_path(
                 legacy,
             )?)
         }
+        SignerSourceKind::Filepath(path) if is_keystore_file(&path) => {
+            keypair_from_keystore_file(&path, keypair_name)
+        }
         SignerSourceKind::Filepath(path) => match read_keypair_file(&path) {
             Err(e) => Err(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -1044,17 +1105,97 @@ pub fn keypair_from_path(
     }
 }
 
-/// Reads user input from stdin to retrieve a seed phrase and passphrase for keypair derivation.
+/// A source of many keys derived from one root, such as a seed phrase or a hardware wallet.
+///
+/// The root is unlocked once, after which the pubkey at any derivation path can be derived
+/// without further user input, as needed to scan a wallet for used accounts.
+pub enum DerivationSource {
+    Seed(Vec<u8>),
+    RemoteWallet {
+        locator: RemoteWalletLocator,
+        wallet_manager: Arc<RemoteWalletManager>,
+    },
+}
+
+impl DerivationSource {
+    /// Derives the pubkey at `derivation_path`.
+    pub fn derive_pubkey(
+        &self,
+        derivation_path: &DerivationPath,
+        keypair_name: &str,
+    ) -> Result<Pubkey, Box<dyn error::Error>> {
+        match self {
+            Self::Seed(seed) => Ok(keypair_from_seed_and_derivation_path(
+                seed,
+                Some(derivation_path.clone()),
+            )?
+            .pubkey()),
+            Self::RemoteWallet {
+                locator,
+                wallet_manager,
+            } => Ok(generate_remote_keypair(
+                locator.clone(),
+                derivation_path.clone(),
+                wallet_manager,
+                false,
+                keypair_name,
+            )?
+            .pubkey),
+        }
+    }
+}
+
+/// Loads a [DerivationSource] from a `prompt:` or `usb:` signing source.
 ///
-/// Optionally skips validation of seed phrase. Optionally confirms recovered
-/// public key.
-pub fn keypair_from_seed_phrase(
+/// Any derivation path of `path` is ignored, as keys are derived with
+/// [`DerivationSource::derive_pubkey`].
+pub fn derivation_source_from_path(
+    matches: &ArgMatches,
+    path: &str,
+    keypair_name: &str,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<DerivationSource, Box<dyn error::Error>> {
+    let SignerSource { kind, .. } = parse_signer_source(path)?;
+    match kind {
+        SignerSourceKind::Prompt => {
+            let skip_validation = matches.is_present(SKIP_SEED_PHRASE_VALIDATION_ARG.name);
+            Ok(DerivationSource::Seed(seed_from_seed_phrase(
+                keypair_name,
+                skip_validation,
+            )?))
+        }
+        SignerSourceKind::Usb(locator) => {
+            if wallet_manager.is_none() {
+                *wallet_manager = maybe_wallet_manager()?;
+            }
+            if let Some(wallet_manager) = wallet_manager {
+                Ok(DerivationSource::RemoteWallet {
+                    locator,
+                    wallet_manager: wallet_manager.clone(),
+                })
+            } else {
+                Err(RemoteWalletError::NoDeviceFound.into())
+            }
+        }
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!(
+                "signer of type `{:?}` does not support key derivation",
+                kind
+            ),
+        )
+        .into()),
+    }
+}
+
+/// Reads user input from stdin to retrieve a seed phrase and passphrase, and
+/// returns the seed they generate.
+///
+/// Optionally skips validation of seed phrase.
+pub fn seed_from_seed_phrase(
     keypair_name: &str,
     skip_validation: bool,
-    confirm_pubkey: bool,
-    derivation_path: Option<DerivationPath>,
-    legacy: bool,
-) -> Result<Keypair, Box<dyn error::Error>> {
+) -> Result<Vec<u8>, Box<dyn error::Error>> {
     let seed_phrase = prompt_password(&format!("[{}] seed phrase: ", keypair_name))?;
     let seed_phrase = seed_phrase.trim();
     let passphrase_prompt = format!(
@@ -1062,14 +1203,12 @@ pub fn keypair_from_seed_phrase(
         keypair_name,
     );
 
-    let keypair = if skip_validation {
+    if skip_validation {
         let passphrase = prompt_passphrase(&passphrase_prompt)?;
-        if legacy {
-            keypair_from_seed_phrase_and_passphrase(seed_phrase, &passphrase)?
-        } else {
-            let seed = generate_seed_from_seed_phrase_and_passphrase(seed_phrase, &passphrase);
-            keypair_from_seed_and_derivation_path(&seed, derivation_path)?
-        }
+        Ok(generate_seed_from_seed_phrase_and_passphrase(
+            seed_phrase,
+            &passphrase,
+        ))
     } else {
         let sanitized = sanitize_seed_phrase(seed_phrase);
         let parse_language_fn = || {
@@ -1091,12 +1230,26 @@ pub fn keypair_from_seed_phrase(
         };
         let mnemonic = parse_language_fn()?;
         let passphrase = prompt_passphrase(&passphrase_prompt)?;
-        let seed = Seed::new(&mnemonic, &passphrase);
-        if legacy {
-            keypair_from_seed(seed.as_bytes())?
-        } else {
-            keypair_from_seed_and_derivation_path(seed.as_bytes(), derivation_path)?
-        }
+        Ok(Seed::new(&mnemonic, &passphrase).as_bytes().to_vec())
+    }
+}
+
+/// Reads user input from stdin to retrieve a seed phrase and passphrase for keypair derivation.
+///
+/// Optionally skips validation of seed phrase. Optionally confirms recovered
+/// public key.
+pub fn keypair_from_seed_phrase(
+    keypair_name: &str,
+    skip_validation: bool,
+    confirm_pubkey: bool,
+    derivation_path: Option<DerivationPath>,
+    legacy: bool,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let seed = seed_from_seed_phrase(keypair_name, skip_validation)?;
+    let keypair = if legacy {
+        keypair_from_seed(&seed)?
+    } else {
+        keypair_from_seed_and_derivation_path(&seed, derivation_path)?
     };
 
     if confirm_pubkey {
@@ -18,8 +18,9 @@ use {
     solana_account_decoder::parse_token::UiTokenAccount,
     solana_clap_utils::keypair::SignOnly,
     solana_client::rpc_response::{
-        RpcAccountBalance, RpcContactInfo, RpcInflationGovernor, RpcInflationRate, RpcKeyedAccount,
-        RpcSupply, RpcVoteAccountInfo,
+        RpcAccountBalance, RpcContactInfo, RpcInflationGovernor, RpcInflationRate,
+        RpcInstructionTrace, RpcKeyedAccount, RpcSimulateTransactionResult, RpcSupply,
+        RpcVoteAccountInfo,
     },
     solana_sdk::{
         clock::{Epoch, Slot, UnixTimestamp},
@@ -33,8 +34,8 @@ use {
         transaction::{Transaction, TransactionError, VersionedTransaction},
     },
     solana_transaction_status::{
-        EncodedConfirmedBlock, EncodedTransaction, TransactionConfirmationStatus,
-        UiTransactionStatusMeta,
+        EncodedConfirmedBlock, EncodedTransaction, TransactionBinaryEncoding,
+        TransactionConfirmationStatus, UiTransactionStatusMeta,
     },
     solana_vote_program::{
         authorized_voters::AuthorizedVoters,
@@ -1653,6 +1654,81 @@ impl fmt::Display for CliSignOnlyData {
     }
 }
 
+/// A partially signed transaction written to a file, for signers to add their signatures to
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CliTransactionFile {
+    pub file: String,
+    #[serde(flatten)]
+    pub sign_only_data: CliSignOnlyData,
+}
+
+impl QuietDisplay for CliTransactionFile {}
+impl VerboseDisplay for CliTransactionFile {}
+
+impl fmt::Display for CliTransactionFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Transaction File:", &self.file)?;
+        write!(f, "{}", self.sign_only_data)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliSimulatedTransaction {
+    #[serde(flatten)]
+    pub result: RpcSimulateTransactionResult,
+}
+
+impl QuietDisplay for CliSimulatedTransaction {}
+impl VerboseDisplay for CliSimulatedTransaction {}
+
+impl fmt::Display for CliSimulatedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        match &self.result.err {
+            Some(err) => writeln_name_value(f, "Simulation Status:", &format!("Error: {}", err))?,
+            None => writeln_name_value(f, "Simulation Status:", "Ok")?,
+        }
+        if let Some(units_consumed) = self.result.units_consumed {
+            writeln_name_value(f, "Compute Units Consumed:", &units_consumed.to_string())?;
+        }
+        if let Some(logs) = self.result.logs.as_ref().filter(|logs| !logs.is_empty()) {
+            writeln!(f, "{}", style("Log Messages:").bold())?;
+            for log in logs {
+                writeln!(f, "  {}", log)?;
+            }
+        }
+        if let Some(trace) = self.result.trace.as_ref().filter(|trace| !trace.is_empty()) {
+            writeln!(f, "{}", style("Instructions:").bold())?;
+            for instruction in trace {
+                write_instruction_trace(f, instruction, 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_instruction_trace(
+    f: &mut fmt::Formatter,
+    instruction: &RpcInstructionTrace,
+    depth: usize,
+) -> fmt::Result {
+    writeln!(
+        f,
+        "{:indent$}{} ({} compute units)",
+        "",
+        instruction.program_id,
+        instruction.compute_units_consumed,
+        indent = depth * 2
+    )?;
+    for inner_instruction in &instruction.inner_instructions {
+        write_instruction_trace(f, inner_instruction, depth + 1)?;
+    }
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliSignature {
@@ -1670,6 +1746,57 @@ impl fmt::Display for CliSignature {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliBatchTransfer {
+    pub recipients: usize,
+    pub transactions: usize,
+    pub completed_transactions: usize,
+    /// Amount of the transfers of the transactions not completed before this run
+    pub lamports: u64,
+    /// Estimated fee of the transactions not completed before this run
+    pub fee: u64,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub signatures: Vec<String>,
+}
+
+impl QuietDisplay for CliBatchTransfer {}
+impl VerboseDisplay for CliBatchTransfer {}
+
+impl fmt::Display for CliBatchTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Recipients:", &self.recipients.to_string())?;
+        writeln_name_value(
+            f,
+            "Transactions:",
+            &format!(
+                "{} ({} completed)",
+                self.transactions, self.completed_transactions
+            ),
+        )?;
+        writeln_name_value(
+            f,
+            "Total Amount:",
+            &format!("{} RENEC", lamports_to_sol(self.lamports)),
+        )?;
+        writeln_name_value(
+            f,
+            "Estimated Fees:",
+            &format!("{} RENEC", lamports_to_sol(self.fee)),
+        )?;
+        if !self.signatures.is_empty() {
+            writeln!(f, "{}", style("Signatures:").bold())?;
+            for signature in &self.signatures {
+                writeln!(f, "  {}", signature)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliAccountBalances {
@@ -1726,7 +1853,11 @@ impl VerboseDisplay for CliSupply {}
 
 impl fmt::Display for CliSupply {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln_name_value(f, "Total:", &format!("{} RENEC", lamports_to_sol(self.total)))?;
+        writeln_name_value(
+            f,
+            "Total:",
+            &format!("{} RENEC", lamports_to_sol(self.total)),
+        )?;
         writeln_name_value(
             f,
             "Circulating:",
@@ -1981,6 +2112,113 @@ impl fmt::Display for CliUpgradeableProgram {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProgramVerification {
+    pub program_id: String,
+    pub program_location: String,
+    pub program_hash: String,
+    pub local_program_hash: String,
+    pub verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_deploy_slot: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attestation_location: Option<String>,
+}
+impl QuietDisplay for CliProgramVerification {}
+impl VerboseDisplay for CliProgramVerification {}
+impl fmt::Display for CliProgramVerification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Program Id:", &self.program_id)?;
+        if let Some(authority) = &self.authority {
+            writeln_name_value(f, "Authority:", authority)?;
+        }
+        if let Some(last_deploy_slot) = self.last_deploy_slot {
+            writeln_name_value(f, "Last Deployed In Slot:", &last_deploy_slot.to_string())?;
+        }
+        writeln_name_value(f, "Program Hash:", &self.program_hash)?;
+        writeln_name_value(
+            f,
+            &format!("Hash of {}:", self.program_location),
+            &self.local_program_hash,
+        )?;
+        writeln_name_value(f, "Verified:", if self.verified { "yes" } else { "no" })?;
+        if let Some(attestation_location) = &self.attestation_location {
+            writeln_name_value(f, "Attestation:", attestation_location)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliInstructionAccount {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliInstruction {
+    pub program_id: String,
+    pub accounts: Vec<CliInstructionAccount>,
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliProgramUpgradeInstruction {
+    pub program_id: String,
+    pub buffer: String,
+    pub upgrade_authority: String,
+    pub spill: String,
+    pub encoding: TransactionBinaryEncoding,
+    pub instruction: CliInstruction,
+    pub serialized_instruction: String,
+}
+impl QuietDisplay for CliProgramUpgradeInstruction {}
+impl VerboseDisplay for CliProgramUpgradeInstruction {}
+impl fmt::Display for CliProgramUpgradeInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Program Id:", &self.program_id)?;
+        writeln_name_value(f, "Buffer:", &self.buffer)?;
+        writeln_name_value(f, "Upgrade Authority:", &self.upgrade_authority)?;
+        writeln_name_value(f, "Spill:", &self.spill)?;
+        writeln!(f)?;
+        writeln!(f, "{}", style("Upgrade Instruction:").bold())?;
+        writeln_name_value(f, "  Program Id:", &self.instruction.program_id)?;
+        for (index, account) in self.instruction.accounts.iter().enumerate() {
+            writeln_name_value(
+                f,
+                &format!("  Account {}:", index),
+                &format!(
+                    "{} {}{}",
+                    account.pubkey,
+                    if account.is_signer { "s" } else { "-" },
+                    if account.is_writable { "w" } else { "-" },
+                ),
+            )?;
+        }
+        writeln_name_value(
+            f,
+            &format!("  Data ({:?}):", self.encoding),
+            &self.instruction.data,
+        )?;
+        writeln!(f)?;
+        writeln_name_value(
+            f,
+            &format!("Serialized Instruction ({:?}):", self.encoding),
+            &self.serialized_instruction,
+        )?;
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliUpgradeablePrograms {
@@ -2202,13 +2440,52 @@ pub fn return_signers_with_config(
 }
 
 pub fn return_signers_data(tx: &Transaction, config: &ReturnSignersConfig) -> CliSignOnlyData {
-    let verify_results = tx.verify_with_results();
+    signers_data(
+        &tx.signatures,
+        &tx.message.account_keys,
+        tx.verify_with_results(),
+        &tx.message.recent_blockhash,
+        config.dump_transaction_message.then(|| tx.message_data()),
+    )
+}
+
+pub fn return_versioned_signers_with_config(
+    tx: &VersionedTransaction,
+    output_format: &OutputFormat,
+    config: &ReturnSignersConfig,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let cli_command = return_versioned_signers_data(tx, config);
+    Ok(output_format.formatted_string(&cli_command))
+}
+
+pub fn return_versioned_signers_data(
+    tx: &VersionedTransaction,
+    config: &ReturnSignersConfig,
+) -> CliSignOnlyData {
+    signers_data(
+        &tx.signatures,
+        tx.message.static_account_keys(),
+        tx.verify_with_results(),
+        tx.message.recent_blockhash(),
+        config
+            .dump_transaction_message
+            .then(|| tx.message.serialize()),
+    )
+}
+
+fn signers_data(
+    signatures: &[Signature],
+    account_keys: &[Pubkey],
+    verify_results: Vec<bool>,
+    blockhash: &Hash,
+    message_data: Option<Vec<u8>>,
+) -> CliSignOnlyData {
     let mut signers = Vec::new();
     let mut absent = Vec::new();
     let mut bad_sig = Vec::new();
-    tx.signatures
+    signatures
         .iter()
-        .zip(tx.message.account_keys.iter())
+        .zip(account_keys.iter())
         .zip(verify_results.into_iter())
         .for_each(|((sig, key), res)| {
             if res {
@@ -2219,16 +2496,10 @@ pub fn return_signers_data(tx: &Transaction, config: &ReturnSignersConfig) -> Cl
                 bad_sig.push(key.to_string());
             }
         });
-    let message = if config.dump_transaction_message {
-        let message_data = tx.message_data();
-        Some(base64::encode(&message_data))
-    } else {
-        None
-    };
 
     CliSignOnlyData {
-        blockhash: tx.message.recent_blockhash.to_string(),
-        message,
+        blockhash: blockhash.to_string(),
+        message: message_data.map(|message_data| base64::encode(&message_data)),
         signers,
         absent,
         bad_sig,
@@ -1,5 +1,8 @@
 use {
-    crate::cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
+    crate::{
+        cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
+    },
     clap::{App, AppSettings, Arg, ArgMatches, SubCommand},
     solana_address_lookup_table_program::{
         instruction::{
@@ -13,8 +16,9 @@ use {
     solana_client::{rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig},
     solana_remote_wallet::remote_wallet::RemoteWalletManager,
     solana_sdk::{
-        account::from_account, clock::Clock, commitment_config::CommitmentConfig, message::Message,
-        pubkey::Pubkey, sysvar, transaction::Transaction,
+        account::from_account, address_lookup_table_account::AddressLookupTableAccount,
+        clock::Clock, commitment_config::CommitmentConfig, message::Message, pubkey::Pubkey,
+        sysvar,
     },
     std::sync::Arc,
 };
@@ -534,10 +538,11 @@ fn process_create_lookup_table(
         create_lookup_table(authority_address, payer_address, clock.slot);
 
     let blockhash = rpc_client.get_latest_blockhash()?;
-    let mut tx = Transaction::new_unsigned(Message::new(
-        &[create_lookup_table_ix],
-        Some(&config.signers[0].pubkey()),
-    ));
+    let mut tx = new_unsigned_transaction(
+        rpc_client,
+        config,
+        Message::new(&[create_lookup_table_ix], Some(&config.signers[0].pubkey())),
+    )?;
 
     tx.try_sign(
         &[config.signers[0], authority_signer, payer_signer],
@@ -596,10 +601,11 @@ fn process_freeze_lookup_table(
     let freeze_lookup_table_ix = freeze_lookup_table(lookup_table_pubkey, authority_address);
 
     let blockhash = rpc_client.get_latest_blockhash()?;
-    let mut tx = Transaction::new_unsigned(Message::new(
-        &[freeze_lookup_table_ix],
-        Some(&config.signers[0].pubkey()),
-    ));
+    let mut tx = new_unsigned_transaction(
+        rpc_client,
+        config,
+        Message::new(&[freeze_lookup_table_ix], Some(&config.signers[0].pubkey())),
+    )?;
 
     tx.try_sign(&[config.signers[0], authority_signer], blockhash)?;
     let result = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
@@ -656,10 +662,11 @@ fn process_extend_lookup_table(
     );
 
     let blockhash = rpc_client.get_latest_blockhash()?;
-    let mut tx = Transaction::new_unsigned(Message::new(
-        &[extend_lookup_table_ix],
-        Some(&config.signers[0].pubkey()),
-    ));
+    let mut tx = new_unsigned_transaction(
+        rpc_client,
+        config,
+        Message::new(&[extend_lookup_table_ix], Some(&config.signers[0].pubkey())),
+    )?;
 
     tx.try_sign(&[config.signers[0], authority_signer], blockhash)?;
     let result = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
@@ -714,10 +721,14 @@ fn process_deactivate_lookup_table(
         deactivate_lookup_table(lookup_table_pubkey, authority_address);
 
     let blockhash = rpc_client.get_latest_blockhash()?;
-    let mut tx = Transaction::new_unsigned(Message::new(
-        &[deactivate_lookup_table_ix],
-        Some(&config.signers[0].pubkey()),
-    ));
+    let mut tx = new_unsigned_transaction(
+        rpc_client,
+        config,
+        Message::new(
+            &[deactivate_lookup_table_ix],
+            Some(&config.signers[0].pubkey()),
+        ),
+    )?;
 
     tx.try_sign(&[config.signers[0], authority_signer], blockhash)?;
     let result = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
@@ -771,10 +782,11 @@ fn process_close_lookup_table(
         close_lookup_table(lookup_table_pubkey, authority_address, recipient_pubkey);
 
     let blockhash = rpc_client.get_latest_blockhash()?;
-    let mut tx = Transaction::new_unsigned(Message::new(
-        &[close_lookup_table_ix],
-        Some(&config.signers[0].pubkey()),
-    ));
+    let mut tx = new_unsigned_transaction(
+        rpc_client,
+        config,
+        Message::new(&[close_lookup_table_ix], Some(&config.signers[0].pubkey())),
+    )?;
 
     tx.try_sign(&[config.signers[0], authority_signer], blockhash)?;
     let result = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
@@ -830,3 +842,31 @@ fn process_show_lookup_table(
                 .collect(),
         }))
 }
+
+/// Fetch the lookup tables at `addresses`, for compiling version 0 transaction messages
+pub(crate) fn get_address_lookup_table_accounts(
+    rpc_client: &RpcClient,
+    addresses: &[Pubkey],
+    commitment: CommitmentConfig,
+) -> Result<Vec<AddressLookupTableAccount>, Box<dyn std::error::Error>> {
+    addresses
+        .iter()
+        .map(|address| {
+            let account = rpc_client
+                .get_account_with_commitment(address, commitment)?
+                .value
+                .ok_or_else(|| format!("Lookup table account {address} not found"))?;
+            if !solana_address_lookup_table_program::check_id(&account.owner) {
+                return Err(format!(
+                    "Account {address} is not owned by the Address Lookup Table program"
+                )
+                .into());
+            }
+            let lookup_table = AddressLookupTable::deserialize(&account.data)?;
+            Ok(AddressLookupTableAccount {
+                key: *address,
+                addresses: lookup_table.addresses.to_vec(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,570 @@
+//! Transfers to the recipients of a CSV file, packed into as few transactions as possible
+use {
+    crate::{
+        checks::{check_account_for_spend_and_fee_with_commitment, get_fee_for_messages},
+        cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
+        compute_unit_price::WithComputeUnitPrice,
+        memo::WithMemo,
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
+    },
+    clap::ArgMatches,
+    csv::{ReaderBuilder, Trim},
+    serde::{Deserialize, Serialize},
+    solana_clap_utils::{
+        compute_unit_price::COMPUTE_UNIT_PRICE_ARG,
+        fee_payer::FEE_PAYER_ARG,
+        input_parsers::*,
+        keypair::{DefaultSigner, SignerIndex},
+        memo::MEMO_ARG,
+    },
+    renec_cli_output::CliBatchTransfer,
+    solana_client::{rpc_client::RpcClient, rpc_request::MAX_MULTIPLE_ACCOUNTS},
+    solana_remote_wallet::remote_wallet::RemoteWalletManager,
+    solana_sdk::{
+        commitment_config::{CommitmentConfig, CommitmentLevel},
+        hash::hash,
+        message::Message,
+        native_token::sol_to_lamports,
+        packet::PACKET_DATA_SIZE,
+        pubkey::Pubkey,
+        signature::Signature,
+        system_instruction,
+        transaction::Transaction,
+    },
+    std::{fs, path::Path, str::FromStr, sync::Arc, thread::sleep, time::Duration},
+};
+
+/// Commitment of the blockhashes of the transactions sent, and of the block height their
+/// expiry is judged by
+const BLOCKHASH_COMMITMENT: CommitmentConfig = CommitmentConfig {
+    commitment: CommitmentLevel::Confirmed,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TransferAllocation {
+    pub recipient: Pubkey,
+    pub lamports: u64,
+}
+
+/// Progress of the transfers of a CSV file, saved after every change so an interrupted run
+/// can be resumed
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferState {
+    /// Hash of the CSV file, to not resume the transfers of another file
+    csv_hash: String,
+    /// Arguments the batches were packed for, which a resumed run must repeat
+    from: String,
+    fee_payer: String,
+    memo: Option<String>,
+    compute_unit_price: Option<u64>,
+    batches: Vec<TransferBatch>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferBatch {
+    first_recipient: usize,
+    num_recipients: usize,
+    /// Signature of the last transaction sent for the batch, which may or may not have landed
+    signature: Option<String>,
+    last_valid_block_height: Option<u64>,
+    confirmed: bool,
+}
+
+pub fn parse_batch_transfer(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let csv_file = matches.value_of("from_csv").unwrap().to_string();
+    let state_file = matches
+        .value_of("state_file")
+        .map(ToString::to_string)
+        .unwrap_or_else(|| format!("{}.state.json", csv_file));
+    let (fee_payer, fee_payer_pubkey) = signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+    let (from, from_pubkey) = signer_of(matches, "from", wallet_manager)?;
+    let signer_info =
+        default_signer.generate_unique_signers(vec![fee_payer, from], matches, wallet_manager)?;
+
+    Ok(CliCommandInfo {
+        command: CliCommand::BatchTransfer {
+            csv_file,
+            state_file,
+            from: signer_info.index_of(from_pubkey).unwrap(),
+            fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+            memo: matches.value_of(MEMO_ARG.name).map(String::from),
+            compute_unit_price: value_of(matches, COMPUTE_UNIT_PRICE_ARG.name),
+            dry_run: matches.is_present("dry_run"),
+        },
+        signers: signer_info.signers,
+    })
+}
+
+/// Read the `recipient,amount` rows of a CSV file with a header row, with amounts in RENEC
+fn read_allocations(csv: &str) -> Result<Vec<TransferAllocation>, CliError> {
+    let mut reader = ReaderBuilder::new()
+        .trim(Trim::All)
+        .from_reader(csv.as_bytes());
+    reader
+        .deserialize::<(String, f64)>()
+        .enumerate()
+        .map(|(i, row)| {
+            // Row 1 is the header
+            let bad_row = |err: String| CliError::BadParameter(format!("Row {}: {}", i + 2, err));
+            let (recipient, amount) = row.map_err(|err| bad_row(err.to_string()))?;
+            let recipient = Pubkey::from_str(&recipient)
+                .map_err(|_| bad_row(format!("Invalid recipient address {}", recipient)))?;
+            if !amount.is_finite() || amount <= 0.0 {
+                return Err(bad_row(format!("Invalid amount {}", amount)));
+            }
+            Ok(TransferAllocation {
+                recipient,
+                lamports: sol_to_lamports(amount),
+            })
+        })
+        .collect()
+}
+
+fn transfer_message(
+    allocations: &[TransferAllocation],
+    from: &Pubkey,
+    fee_payer: &Pubkey,
+    memo: Option<&String>,
+    compute_unit_price: Option<&u64>,
+) -> Message {
+    let ixs = allocations
+        .iter()
+        .map(|allocation| {
+            system_instruction::transfer(from, &allocation.recipient, allocation.lamports)
+        })
+        .collect::<Vec<_>>()
+        .with_memo(memo)
+        .with_compute_unit_price(compute_unit_price);
+    Message::new(&ixs, Some(fee_payer))
+}
+
+/// Split the allocations into batches of consecutive allocations, each as large as fits in a
+/// legacy transaction. Transactions of system transfers hit the packet size limit long before
+/// the compute limit, and compiling them with lookup tables only makes them smaller.
+fn pack_batches(
+    allocations: &[TransferAllocation],
+    from: &Pubkey,
+    fee_payer: &Pubkey,
+    memo: Option<&String>,
+    compute_unit_price: Option<&u64>,
+) -> Result<Vec<TransferBatch>, CliError> {
+    let fits = |allocations: &[TransferAllocation]| {
+        let message = transfer_message(allocations, from, fee_payer, memo, compute_unit_price);
+        let transaction = Transaction::new_unsigned(message);
+        let signatures = vec![Signature::default(); transaction.signatures.len()];
+        let transaction = Transaction {
+            signatures,
+            ..transaction
+        };
+        bincode::serialized_size(&transaction).unwrap() as usize <= PACKET_DATA_SIZE
+    };
+
+    let mut batches = vec![];
+    let mut first_recipient = 0;
+    while first_recipient < allocations.len() {
+        let mut num_recipients = 1;
+        if !fits(&allocations[first_recipient..first_recipient + 1]) {
+            return Err(CliError::BadParameter(
+                "A single transfer does not fit in a transaction".to_string(),
+            ));
+        }
+        while first_recipient + num_recipients < allocations.len()
+            && fits(&allocations[first_recipient..first_recipient + num_recipients + 1])
+        {
+            num_recipients += 1;
+        }
+        batches.push(TransferBatch {
+            first_recipient,
+            num_recipients,
+            ..TransferBatch::default()
+        });
+        first_recipient += num_recipients;
+    }
+    Ok(batches)
+}
+
+fn load_state(state_file: &str) -> Result<Option<TransferState>, CliError> {
+    if !Path::new(state_file).exists() {
+        return Ok(None);
+    }
+    let state = fs::read(state_file)
+        .map_err(|err| err.to_string())
+        .and_then(|state| serde_json::from_slice(&state).map_err(|err| err.to_string()))
+        .map_err(|err| {
+            CliError::BadParameter(format!("Unable to read state file {}: {}", state_file, err))
+        })?;
+    Ok(Some(state))
+}
+
+fn save_state(state_file: &str, state: &TransferState) -> Result<(), CliError> {
+    // Write a new file and rename it, so an interruption never leaves a partial state behind
+    let tmp_file = format!("{}.tmp", state_file);
+    serde_json::to_vec_pretty(state)
+        .map_err(|err| err.to_string())
+        .and_then(|state| fs::write(&tmp_file, state).map_err(|err| err.to_string()))
+        .and_then(|_| fs::rename(&tmp_file, state_file).map_err(|err| err.to_string()))
+        .map_err(|err| {
+            CliError::BadParameter(format!(
+                "Unable to write state file {}: {}",
+                state_file, err
+            ))
+        })
+}
+
+/// Check that new accounts are created with at least the rent exempt minimum balance, as
+/// one failed transfer would fail the other transfers of its transaction too
+fn check_rent_exemption(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    allocations: &[&TransferAllocation],
+) -> Result<(), CliError> {
+    let minimum_balance = rpc_client.get_minimum_balance_for_rent_exemption(0)?;
+    let small_allocations: Vec<_> = allocations
+        .iter()
+        .filter(|allocation| allocation.lamports < minimum_balance)
+        .collect();
+    for chunk in small_allocations.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let addresses: Vec<_> = chunk
+            .iter()
+            .map(|allocation| allocation.recipient)
+            .collect();
+        let accounts = rpc_client
+            .get_multiple_accounts_with_commitment(&addresses, config.commitment)?
+            .value;
+        if let Some((allocation, _)) = chunk
+            .iter()
+            .zip(accounts)
+            .find(|(_, account)| account.is_none())
+        {
+            return Err(CliError::BadParameter(format!(
+                "Transfer to the new account {} is below the rent exempt minimum of {} lamports",
+                allocation.recipient, minimum_balance
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Wait for the outcome of the last transaction sent for `batch`. Returns whether it landed;
+/// otherwise its blockhash has expired, and the batch can be sent again.
+fn wait_for_batch(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    batch: &TransferBatch,
+) -> Result<bool, CliError> {
+    let (signature, last_valid_block_height) =
+        match (&batch.signature, batch.last_valid_block_height) {
+            (Some(signature), Some(last_valid_block_height)) => {
+                (signature, last_valid_block_height)
+            }
+            _ => return Ok(false),
+        };
+    let signature = Signature::from_str(signature)
+        .map_err(|_| CliError::BadParameter(format!("Invalid signature {}", signature)))?;
+    loop {
+        let status = rpc_client
+            .get_signature_statuses_with_history(&[signature])?
+            .value
+            .pop()
+            .flatten();
+        match status {
+            Some(status) if status.err.is_some() => return Ok(false),
+            Some(status) if status.satisfies_commitment(config.commitment) => return Ok(true),
+            None if rpc_client.get_block_height_with_commitment(BLOCKHASH_COMMITMENT)?
+                > last_valid_block_height =>
+            {
+                return Ok(false)
+            }
+            _ => sleep(Duration::from_secs(1)),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_batch_transfer(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    csv_file: &str,
+    state_file: &str,
+    from: SignerIndex,
+    fee_payer: SignerIndex,
+    memo: Option<&String>,
+    compute_unit_price: Option<&u64>,
+    dry_run: bool,
+) -> ProcessResult {
+    let from = config.signers[from];
+    let fee_payer = config.signers[fee_payer];
+    let csv = fs::read_to_string(csv_file)
+        .map_err(|err| CliError::BadParameter(format!("Unable to read {}: {}", csv_file, err)))?;
+    let csv_hash = hash(csv.as_bytes()).to_string();
+    let allocations = read_allocations(&csv)?;
+
+    let mut state = match load_state(state_file)? {
+        Some(state) if state.csv_hash != csv_hash => {
+            return Err(CliError::BadParameter(format!(
+                "State file {} belongs to another CSV file",
+                state_file
+            ))
+            .into());
+        }
+        Some(state)
+            if state.from != from.pubkey().to_string()
+                || state.fee_payer != fee_payer.pubkey().to_string()
+                || state.memo.as_ref() != memo
+                || state.compute_unit_price.as_ref() != compute_unit_price =>
+        {
+            return Err(CliError::BadParameter(format!(
+                "State file {} was created with another sender, fee payer, memo or compute \
+                 unit price. Resume with the same arguments",
+                state_file
+            ))
+            .into());
+        }
+        Some(state) => state,
+        None => TransferState {
+            csv_hash,
+            from: from.pubkey().to_string(),
+            fee_payer: fee_payer.pubkey().to_string(),
+            memo: memo.cloned(),
+            compute_unit_price: compute_unit_price.copied(),
+            batches: pack_batches(
+                &allocations,
+                &from.pubkey(),
+                &fee_payer.pubkey(),
+                memo,
+                compute_unit_price,
+            )?,
+        },
+    };
+    let batch_message = |batch: &TransferBatch| {
+        transfer_message(
+            &allocations[batch.first_recipient..batch.first_recipient + batch.num_recipients],
+            &from.pubkey(),
+            &fee_payer.pubkey(),
+            memo,
+            compute_unit_price,
+        )
+    };
+
+    // Preview the cost of the batches not confirmed yet
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let pending_batches: Vec<_> = state
+        .batches
+        .iter()
+        .filter(|batch| !batch.confirmed)
+        .collect();
+    let messages: Vec<_> = pending_batches
+        .iter()
+        .map(|batch| Message {
+            recent_blockhash: blockhash,
+            ..batch_message(batch)
+        })
+        .collect();
+    let fee = get_fee_for_messages(rpc_client, &messages.iter().collect::<Vec<_>>())?;
+    let pending_allocations: Vec<_> = pending_batches
+        .iter()
+        .flat_map(|batch| {
+            allocations[batch.first_recipient..batch.first_recipient + batch.num_recipients].iter()
+        })
+        .collect();
+    let lamports = pending_allocations
+        .iter()
+        .map(|allocation| allocation.lamports)
+        .sum();
+    let mut output = CliBatchTransfer {
+        recipients: allocations.len(),
+        transactions: state.batches.len(),
+        completed_transactions: state.batches.len() - pending_batches.len(),
+        lamports,
+        fee,
+        dry_run,
+        signatures: vec![],
+    };
+    if dry_run {
+        return Ok(config.output_format.formatted_string(&output));
+    }
+
+    if from.pubkey() == fee_payer.pubkey() {
+        check_account_for_spend_and_fee_with_commitment(
+            rpc_client,
+            &from.pubkey(),
+            lamports,
+            fee,
+            config.commitment,
+        )?;
+    } else {
+        check_account_for_spend_and_fee_with_commitment(
+            rpc_client,
+            &from.pubkey(),
+            lamports,
+            0,
+            config.commitment,
+        )?;
+        check_account_for_spend_and_fee_with_commitment(
+            rpc_client,
+            &fee_payer.pubkey(),
+            0,
+            fee,
+            config.commitment,
+        )?;
+    }
+    check_rent_exemption(rpc_client, config, &pending_allocations)?;
+    save_state(state_file, &state)?;
+
+    for i in 0..state.batches.len() {
+        if state.batches[i].confirmed {
+            continue;
+        }
+        // A transaction sent by an interrupted run may still land, and must not be sent twice
+        if wait_for_batch(rpc_client, config, &state.batches[i])? {
+            state.batches[i].confirmed = true;
+            save_state(state_file, &state)?;
+            continue;
+        }
+
+        let (blockhash, last_valid_block_height) =
+            rpc_client.get_latest_blockhash_with_commitment(BLOCKHASH_COMMITMENT)?;
+        let mut tx =
+            new_unsigned_transaction(rpc_client, config, batch_message(&state.batches[i]))?;
+        tx.try_sign(&config.signers, blockhash)?;
+        state.batches[i].signature = Some(tx.signatures[0].to_string());
+        state.batches[i].last_valid_block_height = Some(last_valid_block_height);
+        save_state(state_file, &state)?;
+
+        let signature = rpc_client
+            .send_and_confirm_transaction_with_spinner(&tx)
+            .map_err(|err| {
+                format!(
+                    "Transaction {} of {} failed: {}. Run the command again to resume",
+                    i + 1,
+                    state.batches.len(),
+                    err
+                )
+            })?;
+        state.batches[i].confirmed = true;
+        save_state(state_file, &state)?;
+        output.completed_transactions += 1;
+        output.signatures.push(signature.to_string());
+    }
+    Ok(config.output_format.formatted_string(&output))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_read_allocations() {
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let csv = format!("recipient,amount\n{}, 1.5\n{},0.000000001\n", alice, bob);
+        assert_eq!(
+            read_allocations(&csv).unwrap(),
+            vec![
+                TransferAllocation {
+                    recipient: alice,
+                    lamports: 1_500_000_000,
+                },
+                TransferAllocation {
+                    recipient: bob,
+                    lamports: 1,
+                },
+            ]
+        );
+
+        let csv = format!("recipient,amount\n{},1\nbob,1\n", alice);
+        assert!(matches!(
+            read_allocations(&csv),
+            Err(CliError::BadParameter(err)) if err.starts_with("Row 3:")
+        ));
+        let csv = format!("recipient,amount\n{},-1\n", alice);
+        assert!(read_allocations(&csv).is_err());
+    }
+
+    #[test]
+    fn test_pack_batches() {
+        let from = Pubkey::new_unique();
+        let fee_payer = Pubkey::new_unique();
+        let allocations: Vec<_> = (0..100)
+            .map(|lamports| TransferAllocation {
+                recipient: Pubkey::new_unique(),
+                lamports,
+            })
+            .collect();
+        let memo = "memo".to_string();
+
+        for (fee_payer, memo, compute_unit_price) in
+            [(from, None, None), (fee_payer, Some(&memo), Some(&1))]
+        {
+            let batches =
+                pack_batches(&allocations, &from, &fee_payer, memo, compute_unit_price).unwrap();
+            assert!(batches.len() > 1);
+            let mut next_recipient = 0;
+            for batch in &batches {
+                assert_eq!(batch.first_recipient, next_recipient);
+                next_recipient += batch.num_recipients;
+
+                let message = transfer_message(
+                    &allocations[batch.first_recipient..next_recipient],
+                    &from,
+                    &fee_payer,
+                    memo,
+                    compute_unit_price,
+                );
+                let transaction = Transaction {
+                    signatures: vec![
+                        Signature::default();
+                        usize::from(message.header.num_required_signatures)
+                    ],
+                    message,
+                };
+                assert!(
+                    bincode::serialized_size(&transaction).unwrap() as usize <= PACKET_DATA_SIZE
+                );
+            }
+            assert_eq!(next_recipient, allocations.len());
+            // Every batch but the last is full
+            for batch in &batches[..batches.len() - 1] {
+                assert_eq!(batch.num_recipients, batches[0].num_recipients);
+            }
+        }
+    }
+
+    #[test]
+    fn test_transfer_state() {
+        let dir = TempDir::new().unwrap();
+        let state_file = dir.path().join("transfers.state.json");
+        let state_file = state_file.to_str().unwrap();
+        assert_eq!(load_state(state_file).unwrap(), None);
+
+        let state = TransferState {
+            csv_hash: hash(b"recipient,amount").to_string(),
+            from: Pubkey::new_unique().to_string(),
+            fee_payer: Pubkey::new_unique().to_string(),
+            memo: Some("memo".to_string()),
+            compute_unit_price: None,
+            batches: vec![
+                TransferBatch {
+                    first_recipient: 0,
+                    num_recipients: 20,
+                    signature: Some(Signature::default().to_string()),
+                    last_valid_block_height: Some(150),
+                    confirmed: true,
+                },
+                TransferBatch {
+                    first_recipient: 20,
+                    num_recipients: 3,
+                    ..TransferBatch::default()
+                },
+            ],
+        };
+        save_state(state_file, &state).unwrap();
+        assert_eq!(load_state(state_file).unwrap(), Some(state));
+    }
+}
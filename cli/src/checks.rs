@@ -5,7 +5,9 @@ use {
         rpc_client::RpcClient,
     },
     solana_sdk::{
-        commitment_config::CommitmentConfig, message::Message, native_token::lamports_to_sol,
+        commitment_config::CommitmentConfig,
+        message::{Message, VersionedMessage},
+        native_token::lamports_to_sol,
         pubkey::Pubkey,
     },
 };
@@ -32,6 +34,19 @@ pub fn check_account_for_fee_with_commitment(
     )
 }
 
+pub fn check_account_for_versioned_fee_with_commitment(
+    rpc_client: &RpcClient,
+    account_pubkey: &Pubkey,
+    message: &VersionedMessage,
+    commitment: CommitmentConfig,
+) -> Result<(), CliError> {
+    let fee = match message {
+        VersionedMessage::Legacy(message) => rpc_client.get_fee_for_message(message)?,
+        VersionedMessage::V0(message) => rpc_client.get_fee_for_message(message)?,
+    };
+    check_account_for_spend_and_fee_with_commitment(rpc_client, account_pubkey, 0, fee, commitment)
+}
+
 pub fn check_account_for_multiple_fees(
     rpc_client: &RpcClient,
     account_pubkey: &Pubkey,
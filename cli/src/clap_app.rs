@@ -1,7 +1,8 @@
 use {
     crate::{
         address_lookup_table::AddressLookupTableSubCommands, cli::*, cluster_query::*, feature::*,
-        inflation::*, nonce::*, program::*, stake::*, validator_info::*, vote::*, wallet::*,
+        inflation::*, nonce::*, program::*, stake::*, transaction::*, validator_info::*, vote::*,
+        wallet::*,
     },
     clap::{App, AppSettings, Arg, ArgGroup, SubCommand},
     solana_clap_utils::{self, input_validators::*, keypair::*},
@@ -105,6 +106,21 @@ pub fn get_clap_app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> A
                 .global(true)
                 .help(SKIP_SEED_PHRASE_VALIDATION_ARG.help),
         )
+        .arg(
+            Arg::with_name("lookup_tables")
+                .long("lookup-table")
+                .value_name("LOOKUP_TABLE_ADDRESS")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .global(true)
+                .validator(is_pubkey)
+                .help(
+                    "Address lookup table to load the accounts of transactions from, \
+                     sending them as version 0 transactions. May be specified multiple times. \
+                     Not supported with --sign-only, since the table contents are fetched over RPC",
+                ),
+        )
         .arg(
             Arg::with_name("rpc_timeout")
                 .long("rpc-timeout")
@@ -132,6 +148,7 @@ pub fn get_clap_app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> A
         .program_subcommands()
         .address_lookup_table_subcommands()
         .stake_subcommands()
+        .transaction_subcommands()
         .validator_info_subcommands()
         .vote_subcommands()
         .wallet_subcommands()
@@ -1,7 +1,8 @@
 use {
     crate::{
-        address_lookup_table::*, clap_app::*, cluster_query::*, feature::*, inflation::*, nonce::*,
-        program::*, spend_utils::*, stake::*, validator_info::*, vote::*, wallet::*,
+        address_lookup_table::*, batch_transfer::*, clap_app::*, cluster_query::*, feature::*,
+        inflation::*, nonce::*, program::*, spend_utils::*, stake::*, transaction::*,
+        validator_info::*, vote::*, wallet::*,
     },
     clap::{crate_description, crate_name, value_t_or_exit, ArgMatches, Shell},
     log::*,
@@ -437,11 +438,22 @@ pub enum CliCommand {
         derived_address_program_id: Option<Pubkey>,
         compute_unit_price: Option<u64>,
     },
+    BatchTransfer {
+        csv_file: String,
+        state_file: String,
+        from: SignerIndex,
+        fee_payer: SignerIndex,
+        memo: Option<String>,
+        compute_unit_price: Option<u64>,
+        dry_run: bool,
+    },
     StakeMinimumDelegation {
         use_lamports_unit: bool,
     },
     // Address lookup table commands
     AddressLookupTable(AddressLookupTableCliCommand),
+    // Transaction commands
+    Transaction(TransactionCliCommand),
 }
 
 #[derive(Debug, PartialEq)]
@@ -503,6 +515,7 @@ pub struct CliConfig<'a> {
     pub send_transaction_config: RpcSendTransactionConfig,
     pub confirm_transaction_initial_timeout: Duration,
     pub address_labels: HashMap<String, String>,
+    pub lookup_tables: Vec<Pubkey>,
 }
 
 impl CliConfig<'_> {
@@ -550,6 +563,7 @@ impl Default for CliConfig<'_> {
                 u64::from_str(DEFAULT_CONFIRM_TX_TIMEOUT_SECONDS).unwrap(),
             ),
             address_labels: HashMap::new(),
+            lookup_tables: Vec::new(),
         }
     }
 }
@@ -692,6 +706,9 @@ pub fn parse_command(
         ("address-lookup-table", Some(matches)) => {
             parse_address_lookup_table_subcommand(matches, default_signer, wallet_manager)
         }
+        ("transaction", Some(matches)) => {
+            parse_transaction_subcommand(matches, default_signer, wallet_manager)
+        }
         ("wait-for-max-stake", Some(matches)) => {
             let max_stake_percent = value_t_or_exit!(matches, "max_percent", f32);
             Ok(CliCommandInfo {
@@ -818,7 +835,13 @@ pub fn parse_command(
                 signers: vec![],
             })
         }
-        ("transfer", Some(matches)) => parse_transfer(matches, default_signer, wallet_manager),
+        ("transfer", Some(matches)) => {
+            if matches.is_present("from_csv") {
+                parse_batch_transfer(matches, default_signer, wallet_manager)
+            } else {
+                parse_transfer(matches, default_signer, wallet_manager)
+            }
+        }
         //
         ("", None) => {
             eprintln!("{}", matches.usage());
@@ -1632,11 +1655,35 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             derived_address_program_id.as_ref(),
             compute_unit_price.as_ref(),
         ),
+        CliCommand::BatchTransfer {
+            csv_file,
+            state_file,
+            from,
+            fee_payer,
+            memo,
+            compute_unit_price,
+            dry_run,
+        } => process_batch_transfer(
+            &rpc_client,
+            config,
+            csv_file,
+            state_file,
+            *from,
+            *fee_payer,
+            memo.as_ref(),
+            compute_unit_price.as_ref(),
+            *dry_run,
+        ),
 
         // Address Lookup Table Commands
         CliCommand::AddressLookupTable(subcommand) => {
             process_address_lookup_table_subcommand(rpc_client, config, subcommand)
         }
+
+        // Transaction Commands
+        CliCommand::Transaction(subcommand) => {
+            process_transaction_subcommand(&rpc_client, config, subcommand)
+        }
     }
 }
 
@@ -2700,5 +2747,65 @@ mod tests {
                 signers: vec![read_keypair_file(&default_keypair_file).unwrap().into(),],
             }
         );
+
+        //Test Transfer Subcommand, from CSV
+        let test_transfer = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transfer",
+            "--from-csv",
+            "transfers.csv",
+        ]);
+        assert_eq!(
+            parse_command(&test_transfer, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::BatchTransfer {
+                    csv_file: "transfers.csv".to_string(),
+                    state_file: "transfers.csv.state.json".to_string(),
+                    from: 0,
+                    fee_payer: 0,
+                    memo: None,
+                    compute_unit_price: None,
+                    dry_run: false,
+                },
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into(),],
+            }
+        );
+        let test_transfer = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transfer",
+            "--from-csv",
+            "transfers.csv",
+            "--state-file",
+            "state.json",
+            "--dry-run",
+            "--with-compute-unit-price",
+            "5",
+        ]);
+        assert_eq!(
+            parse_command(&test_transfer, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::BatchTransfer {
+                    csv_file: "transfers.csv".to_string(),
+                    state_file: "state.json".to_string(),
+                    from: 0,
+                    fee_payer: 0,
+                    memo: None,
+                    compute_unit_price: Some(5),
+                    dry_run: true,
+                },
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into(),],
+            }
+        );
+        assert!(test_commands
+            .clone()
+            .get_matches_from_safe(vec![
+                "test",
+                "transfer",
+                &to_string,
+                "42",
+                "--from-csv",
+                "transfers.csv",
+            ])
+            .is_err());
     }
 }
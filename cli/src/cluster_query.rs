@@ -3,6 +3,7 @@ use {
         cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
         compute_unit_price::WithComputeUnitPrice,
         spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
     },
     clap::{value_t, value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand},
     console::style,
@@ -60,7 +61,6 @@ use {
             stake_history::{self},
         },
         timing,
-        transaction::Transaction,
     },
     solana_transaction_status::UiTransactionEncoding,
     solana_vote_program::vote_state::VoteState,
@@ -1448,7 +1448,7 @@ pub fn process_ping(
             build_message,
             config.commitment,
         )?;
-        let mut tx = Transaction::new_unsigned(message);
+        let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
         tx.try_sign(&config.signers, blockhash)?;
 
         let timestamp = || {
@@ -2,6 +2,7 @@ use {
     crate::{
         cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
         spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
     },
     clap::{App, AppSettings, Arg, ArgMatches, SubCommand},
     console::style,
@@ -20,7 +21,6 @@ use {
         feature_set::FEATURE_NAMES,
         message::Message,
         pubkey::Pubkey,
-        transaction::Transaction,
     },
     std::{cmp::Ordering, collections::HashMap, fmt, str::FromStr, sync::Arc},
 };
@@ -903,7 +903,7 @@ fn process_activate(
         },
         config.commitment,
     )?;
-    let mut transaction = Transaction::new_unsigned(message);
+    let mut transaction = new_unsigned_transaction(rpc_client, config, message)?;
     transaction.try_sign(&config.signers, blockhash)?;
 
     println!(
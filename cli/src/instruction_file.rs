@@ -0,0 +1,628 @@
+//! Instructions described in JSON or YAML files, as read by `transaction build`
+use {
+    crate::cli::CliError,
+    clap::ArgMatches,
+    heck::SnakeCase,
+    serde::Deserialize,
+    serde_json::{Map, Value},
+    solana_clap_utils::keypair::{pubkey_from_path, signer_from_path},
+    solana_remote_wallet::remote_wallet::RemoteWalletManager,
+    solana_sdk::{
+        hash::hashv,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        signature::Signer,
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        io::{self, Read},
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::Arc,
+    },
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct InstructionsFile {
+    pub instructions: Vec<InstructionConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct InstructionConfig {
+    pub program_id: String,
+    #[serde(default)]
+    pub accounts: Vec<AccountMetaConfig>,
+    pub data: Option<InstructionData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountMetaConfig {
+    /// An address, or a signer source such as a keypair file or `usb://ledger`
+    pub pubkey: String,
+    #[serde(default)]
+    pub is_signer: bool,
+    #[serde(default)]
+    pub is_writable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub enum InstructionData {
+    Hex(String),
+    Base58(String),
+    Base64(String),
+    Borsh(BorshInstructionData),
+}
+
+/// Arguments of an Anchor IDL instruction, encoded with Borsh after the instruction discriminator
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BorshInstructionData {
+    /// Path of the IDL, relative to the instructions file
+    pub idl: String,
+    pub instruction: String,
+    #[serde(default)]
+    pub args: Map<String, Value>,
+}
+
+impl InstructionData {
+    pub fn decode(&self, base_dir: &Path) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Hex(data) => {
+                hex::decode(data).map_err(|err| format!("Invalid hex data: {}", err))
+            }
+            Self::Base58(data) => bs58::decode(data)
+                .into_vec()
+                .map_err(|err| format!("Invalid base58 data: {}", err)),
+            Self::Base64(data) => {
+                base64::decode(data).map_err(|err| format!("Invalid base64 data: {}", err))
+            }
+            Self::Borsh(BorshInstructionData {
+                idl,
+                instruction,
+                args,
+            }) => {
+                let idl_path = base_dir.join(idl);
+                let idl: Idl = fs::read(&idl_path)
+                    .map_err(|err| err.to_string())
+                    .and_then(|idl| serde_json::from_slice(&idl).map_err(|err| err.to_string()))
+                    .map_err(|err| format!("Unable to read {}: {}", idl_path.display(), err))?;
+                idl.encode_instruction(instruction, args)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Idl {
+    instructions: Vec<IdlInstruction>,
+    #[serde(default)]
+    types: Vec<IdlTypeDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdlInstruction {
+    name: String,
+    #[serde(default)]
+    args: Vec<IdlField>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdlField {
+    name: String,
+    #[serde(rename = "type")]
+    ty: IdlType,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum IdlType {
+    Primitive(String),
+    Vec { vec: Box<IdlType> },
+    Option { option: Box<IdlType> },
+    Array { array: (Box<IdlType>, usize) },
+    Defined { defined: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct IdlTypeDefinition {
+    name: String,
+    #[serde(rename = "type")]
+    ty: IdlTypeDefinitionType,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum IdlTypeDefinitionType {
+    Struct { fields: Vec<IdlField> },
+    Enum { variants: Vec<IdlEnumVariant> },
+}
+
+#[derive(Debug, Deserialize)]
+struct IdlEnumVariant {
+    name: String,
+    fields: Option<IdlEnumFields>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum IdlEnumFields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlType>),
+}
+
+/// The name an instruction is hashed under in its discriminator, converted to snake case the same
+/// way as Anchor does, acronyms included
+fn discriminator_name(name: &str) -> String {
+    name.to_snake_case()
+}
+
+fn parse_integer<T>(value: &Value) -> Option<T>
+where
+    T: FromStr + TryFrom<u64> + TryFrom<i64>,
+{
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .and_then(|number| T::try_from(number).ok())
+            .or_else(|| number.as_i64().and_then(|number| T::try_from(number).ok())),
+        // Integers beyond the range of JSON numbers are written as strings
+        Value::String(number) => number.parse().ok(),
+        _ => None,
+    }
+}
+
+fn encode_length(length: usize, data: &mut Vec<u8>) -> Result<(), String> {
+    let length = u32::try_from(length).map_err(|_| "Value is too long".to_string())?;
+    data.extend_from_slice(&length.to_le_bytes());
+    Ok(())
+}
+
+impl Idl {
+    fn encode_instruction(&self, name: &str, args: &Map<String, Value>) -> Result<Vec<u8>, String> {
+        let instruction = self
+            .instructions
+            .iter()
+            .find(|instruction| instruction.name == name)
+            .ok_or_else(|| format!("Instruction {} not found in the IDL", name))?;
+        if let Some(unknown_arg) = args
+            .keys()
+            .find(|arg| !instruction.args.iter().any(|field| field.name == **arg))
+        {
+            return Err(format!(
+                "Unknown argument {} of instruction {}",
+                unknown_arg, name
+            ));
+        }
+
+        // Anchor's instruction discriminator
+        let preimage = format!("global:{}", discriminator_name(name));
+        let mut data = hashv(&[preimage.as_bytes()]).to_bytes()[..8].to_vec();
+        self.encode_fields(&instruction.args, args, &mut data)
+            .map_err(|err| format!("Instruction {}: {}", name, err))?;
+        Ok(data)
+    }
+
+    fn encode_fields(
+        &self,
+        fields: &[IdlField],
+        values: &Map<String, Value>,
+        data: &mut Vec<u8>,
+    ) -> Result<(), String> {
+        for field in fields {
+            let value = values
+                .get(&field.name)
+                .ok_or_else(|| format!("Missing value of {}", field.name))?;
+            self.encode_value(&field.ty, value, data)
+                .map_err(|err| format!("{}: {}", field.name, err))?;
+        }
+        Ok(())
+    }
+
+    fn encode_value(&self, ty: &IdlType, value: &Value, data: &mut Vec<u8>) -> Result<(), String> {
+        let invalid = || format!("Invalid value {} for type {:?}", value, ty);
+        macro_rules! integer {
+            ($t:ty) => {
+                data.extend_from_slice(
+                    &parse_integer::<$t>(value)
+                        .ok_or_else(invalid)?
+                        .to_le_bytes(),
+                )
+            };
+        }
+
+        match ty {
+            IdlType::Primitive(primitive) => match primitive.as_str() {
+                "bool" => data.push(u8::from(value.as_bool().ok_or_else(invalid)?)),
+                "u8" => integer!(u8),
+                "i8" => integer!(i8),
+                "u16" => integer!(u16),
+                "i16" => integer!(i16),
+                "u32" => integer!(u32),
+                "i32" => integer!(i32),
+                "u64" => integer!(u64),
+                "i64" => integer!(i64),
+                "u128" => integer!(u128),
+                "i128" => integer!(i128),
+                "f32" => data
+                    .extend_from_slice(&(value.as_f64().ok_or_else(invalid)? as f32).to_le_bytes()),
+                "f64" => data.extend_from_slice(&value.as_f64().ok_or_else(invalid)?.to_le_bytes()),
+                "string" => {
+                    let string = value.as_str().ok_or_else(invalid)?;
+                    encode_length(string.len(), data)?;
+                    data.extend_from_slice(string.as_bytes());
+                }
+                "bytes" => {
+                    let bytes = value
+                        .as_array()
+                        .ok_or_else(invalid)?
+                        .iter()
+                        .map(parse_integer::<u8>)
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or_else(invalid)?;
+                    encode_length(bytes.len(), data)?;
+                    data.extend_from_slice(&bytes);
+                }
+                "publicKey" | "pubkey" => {
+                    let pubkey = value
+                        .as_str()
+                        .and_then(|pubkey| Pubkey::from_str(pubkey).ok())
+                        .ok_or_else(invalid)?;
+                    data.extend_from_slice(pubkey.as_ref());
+                }
+                _ => return Err(format!("Unsupported type {}", primitive)),
+            },
+            IdlType::Vec { vec } => {
+                let values = value.as_array().ok_or_else(invalid)?;
+                encode_length(values.len(), data)?;
+                for value in values {
+                    self.encode_value(vec, value, data)?;
+                }
+            }
+            IdlType::Option { option } => {
+                if value.is_null() {
+                    data.push(0);
+                } else {
+                    data.push(1);
+                    self.encode_value(option, value, data)?;
+                }
+            }
+            IdlType::Array { array: (ty, len) } => {
+                let values = value
+                    .as_array()
+                    .filter(|values| values.len() == *len)
+                    .ok_or_else(invalid)?;
+                for value in values {
+                    self.encode_value(ty, value, data)?;
+                }
+            }
+            IdlType::Defined { defined } => {
+                let definition = self
+                    .types
+                    .iter()
+                    .find(|definition| definition.name == *defined)
+                    .ok_or_else(|| format!("Type {} not found in the IDL", defined))?;
+                match &definition.ty {
+                    IdlTypeDefinitionType::Struct { fields } => {
+                        self.encode_fields(fields, value.as_object().ok_or_else(invalid)?, data)?
+                    }
+                    IdlTypeDefinitionType::Enum { variants } => {
+                        // Unit variants are written as their name, others as a single entry map
+                        let (name, fields_value) = match value {
+                            Value::String(name) => (name, None),
+                            Value::Object(object) if object.len() == 1 => {
+                                let (name, fields_value) = object.iter().next().unwrap();
+                                (name, Some(fields_value))
+                            }
+                            _ => return Err(invalid()),
+                        };
+                        let (index, variant) = variants
+                            .iter()
+                            .enumerate()
+                            .find(|(_, variant)| variant.name == *name)
+                            .ok_or_else(|| format!("Unknown variant {} of {}", name, defined))?;
+                        data.push(u8::try_from(index).map_err(|_| invalid())?);
+                        match (&variant.fields, fields_value) {
+                            (None, None) => {}
+                            (Some(IdlEnumFields::Named(fields)), Some(Value::Object(values))) => {
+                                self.encode_fields(fields, values, data)?
+                            }
+                            (Some(IdlEnumFields::Tuple(types)), Some(Value::Array(values)))
+                                if types.len() == values.len() =>
+                            {
+                                for (ty, value) in types.iter().zip(values) {
+                                    self.encode_value(ty, value, data)?;
+                                }
+                            }
+                            _ => return Err(invalid()),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read the instructions in `path`, or stdin if `path` is "-". Signer accounts are resolved
+/// through `signer_from_path` and appended to `bulk_signers`.
+pub fn parse_instructions_file(
+    matches: &ArgMatches<'_>,
+    path: &str,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+    bulk_signers: &mut Vec<Option<Box<dyn Signer>>>,
+) -> Result<Vec<Instruction>, CliError> {
+    let (contents, base_dir) = if path == "-" {
+        let mut contents = String::new();
+        io::stdin()
+            .read_to_string(&mut contents)
+            .map_err(|err| CliError::BadParameter(format!("Unable to read stdin: {}", err)))?;
+        (contents, PathBuf::from("."))
+    } else {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| CliError::BadParameter(format!("Unable to read {}: {}", path, err)))?;
+        let base_dir = Path::new(path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        (contents, base_dir)
+    };
+    // YAML is a superset of JSON, so this reads both
+    let instructions_file: InstructionsFile = serde_yaml::from_str(&contents)
+        .map_err(|err| CliError::BadParameter(format!("Invalid instructions file: {}", err)))?;
+
+    // Each signer source is only resolved once, so a hardware wallet isn't queried repeatedly
+    let mut resolved_signers = HashMap::new();
+    let mut instructions = vec![];
+    for (i, instruction) in instructions_file.instructions.into_iter().enumerate() {
+        let bad_parameter =
+            |err: String| CliError::BadParameter(format!("Instruction {}: {}", i, err));
+        let program_id = Pubkey::from_str(&instruction.program_id)
+            .map_err(|err| bad_parameter(format!("Invalid program id: {}", err)))?;
+        let mut accounts = vec![];
+        for account in instruction.accounts {
+            let pubkey = if let Some(pubkey) = resolved_signers.get(&account.pubkey) {
+                *pubkey
+            } else if account.is_signer {
+                let signer = signer_from_path(matches, &account.pubkey, "account", wallet_manager)
+                    .map_err(|err| bad_parameter(err.to_string()))?;
+                let pubkey = signer.pubkey();
+                resolved_signers.insert(account.pubkey, pubkey);
+                bulk_signers.push(Some(signer));
+                pubkey
+            } else {
+                pubkey_from_path(matches, &account.pubkey, "account", wallet_manager)
+                    .map_err(|err| bad_parameter(err.to_string()))?
+            };
+            accounts.push(AccountMeta {
+                pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            });
+        }
+        let data = instruction
+            .data
+            .map(|data| data.decode(&base_dir))
+            .transpose()
+            .map_err(bad_parameter)?
+            .unwrap_or_default();
+        instructions.push(Instruction {
+            program_id,
+            accounts,
+            data,
+        });
+    }
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json, tempfile::TempDir};
+
+    #[test]
+    fn test_decode_instruction_data() {
+        let base_dir = Path::new(".");
+        let data = vec![1, 2, 3, 255];
+        assert_eq!(
+            InstructionData::Hex("010203ff".to_string())
+                .decode(base_dir)
+                .unwrap(),
+            data
+        );
+        assert_eq!(
+            InstructionData::Base58(bs58::encode(&data).into_string())
+                .decode(base_dir)
+                .unwrap(),
+            data
+        );
+        assert_eq!(
+            InstructionData::Base64(base64::encode(&data))
+                .decode(base_dir)
+                .unwrap(),
+            data
+        );
+        assert!(InstructionData::Hex("0g".to_string())
+            .decode(base_dir)
+            .is_err());
+    }
+
+    #[test]
+    fn test_discriminator_name() {
+        for (name, expected) in [
+            ("initialize", "initialize"),
+            ("setConfig", "set_config"),
+            ("initializeNFTMint", "initialize_nft_mint"),
+            ("withdrawSOL", "withdraw_sol"),
+            ("mintV2", "mint_v2"),
+            ("set_config", "set_config"),
+        ] {
+            assert_eq!(discriminator_name(name), expected);
+        }
+    }
+
+    #[test]
+    fn test_encode_idl_instruction() {
+        let idl: Idl = serde_json::from_value(json!({
+            "version": "0.1.0",
+            "name": "example",
+            "instructions": [{
+                "name": "setConfig",
+                "accounts": [],
+                "args": [
+                    {"name": "amount", "type": "u64"},
+                    {"name": "delta", "type": "i16"},
+                    {"name": "label", "type": "string"},
+                    {"name": "owner", "type": {"option": "publicKey"}},
+                    {"name": "config", "type": {"defined": "Config"}},
+                    {"name": "modes", "type": {"vec": {"defined": "Mode"}}},
+                ],
+            }],
+            "types": [
+                {
+                    "name": "Config",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            {"name": "enabled", "type": "bool"},
+                            {"name": "limits", "type": {"array": ["u128", 2]}},
+                        ],
+                    },
+                },
+                {
+                    "name": "Mode",
+                    "type": {
+                        "kind": "enum",
+                        "variants": [
+                            {"name": "Off"},
+                            {"name": "Fixed", "fields": ["u8"]},
+                            {"name": "Range", "fields": [
+                                {"name": "low", "type": "u8"},
+                                {"name": "high", "type": "u8"},
+                            ]},
+                        ],
+                    },
+                },
+            ],
+        }))
+        .unwrap();
+        let args = json!({
+            "amount": 42,
+            "delta": -2,
+            "label": "ab",
+            "owner": null,
+            "config": {"enabled": true, "limits": [1, "340282366920938463463374607431768211455"]},
+            "modes": ["Off", {"Fixed": [7]}, {"Range": {"low": 1, "high": 2}}],
+        });
+        let data = idl
+            .encode_instruction("setConfig", args.as_object().unwrap())
+            .unwrap();
+
+        let mut expected = hashv(&[b"global:set_config"]).to_bytes()[..8].to_vec();
+        expected.extend_from_slice(&42u64.to_le_bytes());
+        expected.extend_from_slice(&(-2i16).to_le_bytes());
+        expected.extend_from_slice(&[2, 0, 0, 0, b'a', b'b']);
+        expected.push(0);
+        expected.push(1);
+        expected.extend_from_slice(&1u128.to_le_bytes());
+        expected.extend_from_slice(&u128::MAX.to_le_bytes());
+        expected.extend_from_slice(&[3, 0, 0, 0, 0, 1, 7, 2, 1, 2]);
+        assert_eq!(data, expected);
+
+        // Values must match the IDL
+        let mut bad_args = args.as_object().unwrap().clone();
+        bad_args.insert("delta".to_string(), json!(40_000));
+        assert!(idl.encode_instruction("setConfig", &bad_args).is_err());
+        bad_args.remove("delta");
+        assert!(idl.encode_instruction("setConfig", &bad_args).is_err());
+        let mut bad_args = args.as_object().unwrap().clone();
+        bad_args.insert("unknown".to_string(), json!(1));
+        assert!(idl.encode_instruction("setConfig", &bad_args).is_err());
+        assert!(idl
+            .encode_instruction("unknown", args.as_object().unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_instructions_file() {
+        let dir = TempDir::new().unwrap();
+        let program_id = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        fs::write(
+            dir.path().join("example.json"),
+            json!({
+                "instructions": [{
+                    "name": "initialize",
+                    "args": [{"name": "value", "type": "u32"}],
+                }],
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let path = dir.path().join("instructions.yaml");
+        fs::write(
+            &path,
+            format!(
+                "instructions:
+  - programId: {program_id}
+    accounts:
+      - pubkey: {account}
+        isWritable: true
+    data:
+      hex: \"0a0b\"
+  - programId: {program_id}
+    data:
+      borsh:
+        idl: example.json
+        instruction: initialize
+        args:
+          value: 7
+"
+            ),
+        )
+        .unwrap();
+
+        let matches = clap::App::new("test").get_matches_from(vec!["test"]);
+        let mut bulk_signers = vec![];
+        let instructions = parse_instructions_file(
+            &matches,
+            path.to_str().unwrap(),
+            &mut None,
+            &mut bulk_signers,
+        )
+        .unwrap();
+        let mut initialize_data = hashv(&[b"global:initialize"]).to_bytes()[..8].to_vec();
+        initialize_data.extend_from_slice(&7u32.to_le_bytes());
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction::new_with_bytes(
+                    program_id,
+                    &[10, 11],
+                    vec![AccountMeta::new(account, false)]
+                ),
+                Instruction::new_with_bytes(program_id, &initialize_data, vec![]),
+            ]
+        );
+        assert!(bulk_signers.is_empty());
+
+        // JSON is read as well, and unknown fields are rejected
+        let path = dir.path().join("instructions.json");
+        fs::write(
+            &path,
+            json!({"instructions": [{"programId": program_id.to_string(), "unknown": 1}]})
+                .to_string(),
+        )
+        .unwrap();
+        assert!(parse_instructions_file(
+            &matches,
+            path.to_str().unwrap(),
+            &mut None,
+            &mut bulk_signers
+        )
+        .is_err());
+    }
+}
@@ -24,6 +24,7 @@ extern crate const_format;
 extern crate serde_derive;
 
 pub mod address_lookup_table;
+pub mod batch_transfer;
 pub mod checks;
 pub mod clap_app;
 pub mod cli;
@@ -31,12 +32,15 @@ pub mod cluster_query;
 pub mod compute_unit_price;
 pub mod feature;
 pub mod inflation;
+pub mod instruction_file;
 pub mod memo;
 pub mod nonce;
 pub mod program;
 pub mod spend_utils;
 pub mod stake;
 pub mod test_utils;
+pub mod transaction;
 pub mod validator_info;
+pub mod versioned_transaction;
 pub mod vote;
 pub mod wallet;
@@ -2,13 +2,15 @@ use {
     clap::{crate_description, crate_name, value_t_or_exit, ArgMatches},
     console::style,
     solana_clap_utils::{
+        input_parsers::pubkeys_of,
         input_validators::normalize_to_url_if_moniker,
         keypair::{CliSigners, DefaultSigner},
+        offline::SIGN_ONLY_ARG,
         DisplayError,
     },
     renec_cli::{
         clap_app::get_clap_app,
-        cli::{parse_command, process_command, CliCommandInfo, CliConfig},
+        cli::{parse_command, process_command, CliCommandInfo, CliConfig, CliError},
     },
     renec_cli_config::{Config, ConfigInput},
     renec_cli_output::{
@@ -139,6 +141,15 @@ fn parse_settings(matches: &ArgMatches<'_>) -> Result<bool, Box<dyn error::Error
     Ok(parse_args)
 }
 
+fn is_sign_only(matches: &ArgMatches<'_>) -> bool {
+    match matches.subcommand() {
+        (_, Some(subcommand_matches)) => {
+            subcommand_matches.is_present(SIGN_ONLY_ARG.name) || is_sign_only(subcommand_matches)
+        }
+        _ => false,
+    }
+}
+
 pub fn parse_args<'a>(
     matches: &ArgMatches<'_>,
     wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
@@ -175,6 +186,17 @@ pub fn parse_args<'a>(
 
     let default_signer = DefaultSigner::new(default_signer_arg_name, &default_signer_path);
 
+    let lookup_tables = pubkeys_of(matches, "lookup_tables").unwrap_or_default();
+    // Lookup table contents are fetched over RPC, which isn't available when signing offline
+    if !lookup_tables.is_empty() && is_sign_only(matches) {
+        return Err(CliError::BadParameter(format!(
+            "--lookup-table cannot be used with --{}; build the transaction online or \
+             sign without lookup tables",
+            SIGN_ONLY_ARG.long
+        ))
+        .into());
+    }
+
     let CliCommandInfo {
         command,
         mut signers,
@@ -220,6 +242,7 @@ pub fn parse_args<'a>(
             },
             confirm_transaction_initial_timeout,
             address_labels,
+            lookup_tables,
         },
         signers,
     ))
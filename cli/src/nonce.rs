@@ -1,6 +1,6 @@
 use {
     crate::{
-        checks::{check_account_for_fee_with_commitment, check_unique_pubkeys},
+        checks::{check_account_for_versioned_fee_with_commitment, check_unique_pubkeys},
         cli::{
             log_instruction_custom_error, log_instruction_custom_error_ex, CliCommand,
             CliCommandInfo, CliConfig, CliError, ProcessResult,
@@ -9,6 +9,7 @@ use {
         feature::get_feature_is_active,
         memo::WithMemo,
         spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
     },
     clap::{App, Arg, ArgMatches, SubCommand},
     solana_clap_utils::{
@@ -36,7 +37,7 @@ use {
             withdraw_nonce_account, NonceError, SystemError,
         },
         system_program,
-        transaction::{Transaction, TransactionError},
+        transaction::TransactionError,
     },
     std::sync::Arc,
 };
@@ -417,10 +418,10 @@ pub fn process_authorize_nonce_account(
     .with_memo(memo)
     .with_compute_unit_price(compute_unit_price);
     let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     tx.try_sign(&config.signers, latest_blockhash)?;
 
-    check_account_for_fee_with_commitment(
+    check_account_for_versioned_fee_with_commitment(
         rpc_client,
         &config.signers[0].pubkey(),
         &tx.message,
@@ -525,7 +526,7 @@ pub fn process_create_nonce_account(
         .into());
     }
 
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     tx.try_sign(&config.signers, latest_blockhash)?;
     let merge_errors =
         get_feature_is_active(rpc_client, &merge_nonce_error_into_system_error::id())?;
@@ -607,9 +608,9 @@ pub fn process_new_nonce(
     .with_compute_unit_price(compute_unit_price);
     let latest_blockhash = rpc_client.get_latest_blockhash()?;
     let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     tx.try_sign(&config.signers, latest_blockhash)?;
-    check_account_for_fee_with_commitment(
+    check_account_for_versioned_fee_with_commitment(
         rpc_client,
         &config.signers[0].pubkey(),
         &tx.message,
@@ -684,9 +685,9 @@ pub fn process_withdraw_from_nonce_account(
     .with_memo(memo)
     .with_compute_unit_price(compute_unit_price);
     let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     tx.try_sign(&config.signers, latest_blockhash)?;
-    check_account_for_fee_with_commitment(
+    check_account_for_versioned_fee_with_commitment(
         rpc_client,
         &config.signers[0].pubkey(),
         &tx.message,
@@ -721,9 +722,9 @@ pub(crate) fn process_upgrade_nonce_account(
         .with_memo(memo)
         .with_compute_unit_price(compute_unit_price);
     let message = Message::new(&ixs, Some(&config.signers[0].pubkey()));
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     tx.try_sign(&config.signers, latest_blockhash)?;
-    check_account_for_fee_with_commitment(
+    check_account_for_versioned_fee_with_commitment(
         rpc_client,
         &config.signers[0].pubkey(),
         &tx.message,
@@ -1,21 +1,28 @@
 use {
     crate::{
+        address_lookup_table::get_address_lookup_table_accounts,
         checks::*,
         cli::{
             log_instruction_custom_error, CliCommand, CliCommandInfo, CliConfig, CliError,
             ProcessResult,
         },
+        versioned_transaction::{
+            compile_unsigned_transaction, new_unsigned_transaction, SignVersionedTransaction,
+        },
     },
     bip39::{Language, Mnemonic, MnemonicType, Seed},
     clap::{App, AppSettings, Arg, ArgMatches, SubCommand},
     log::*,
+    serde::{Deserialize, Serialize},
     solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
     solana_bpf_loader_program::{syscalls::register_syscalls, BpfError, ThisInstructionMeter},
     solana_clap_utils::{self, input_parsers::*, input_validators::*, keypair::*},
     renec_cli_output::{
-        CliProgram, CliProgramAccountType, CliProgramAuthority, CliProgramBuffer, CliProgramId,
-        CliUpgradeableBuffer, CliUpgradeableBuffers, CliUpgradeableProgram,
-        CliUpgradeableProgramClosed, CliUpgradeablePrograms,
+        display::new_spinner_progress_bar, CliInstruction, CliInstructionAccount, CliProgram,
+        CliProgramAccountType, CliProgramAuthority, CliProgramBuffer, CliProgramId,
+        CliProgramUpgradeInstruction, CliProgramVerification, CliUpgradeableBuffer,
+        CliUpgradeableBuffers, CliUpgradeableProgram, CliUpgradeableProgramClosed,
+        CliUpgradeablePrograms,
     },
     solana_client::{
         client_error::ClientErrorKind,
@@ -23,6 +30,7 @@ use {
         rpc_client::RpcClient,
         rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig},
         rpc_filter::{Memcmp, RpcFilterType},
+        rpc_request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
         tpu_client::{TpuClient, TpuClientConfig},
     },
     solana_program_runtime::invoke_context::InvokeContext,
@@ -35,31 +43,44 @@ use {
     solana_sdk::{
         account::Account,
         account_utils::StateMut,
+        address_lookup_table_account::AddressLookupTableAccount,
         bpf_loader, bpf_loader_deprecated,
         bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        hash::hash,
         instruction::{Instruction, InstructionError},
         loader_instruction,
         message::Message,
         native_token::Sol,
         packet::PACKET_DATA_SIZE,
         pubkey::Pubkey,
-        signature::{keypair_from_seed, read_keypair_file, Keypair, Signature, Signer},
+        signature::{keypair_from_seed, read_keypair_file, write_keypair_file, Keypair, Signer},
+        signer::signers::Signers,
         system_instruction::{self, SystemError},
         system_program,
         sysvar::rent::Rent,
-        transaction::{Transaction, TransactionError},
+        transaction::TransactionError,
         transaction_context::TransactionContext,
     },
+    solana_transaction_status::TransactionBinaryEncoding,
     std::{
-        fs::File,
+        collections::HashMap,
+        fs::{self, File},
         io::{Read, Write},
         mem::size_of,
-        path::PathBuf,
+        path::{Path, PathBuf},
         str::FromStr,
         sync::Arc,
+        thread::sleep,
+        time::{Duration, Instant},
     },
 };
 
+/// Attempts at landing the write transactions of a deploy, each with a new blockhash
+const MAX_WRITE_ATTEMPTS: usize = 5;
+/// Number of write transactions sent to the leaders at once
+const WRITE_BATCH_SIZE: usize = 64;
+const WRITE_RESEND_INTERVAL: Duration = Duration::from_secs(4);
+
 pub const CLOSE_PROGRAM_WARNING: &str = "WARNING! \
 Closed programs cannot be recreated at the same program id. \
 Once a program is closed, it can never be invoked again. \
@@ -78,6 +99,7 @@ pub enum ProgramCliCommand {
         max_len: Option<usize>,
         allow_excessive_balance: bool,
         skip_fee_check: bool,
+        buffer_only: bool,
     },
     WriteBuffer {
         program_location: String,
@@ -87,6 +109,19 @@ pub enum ProgramCliCommand {
         max_len: Option<usize>,
         skip_fee_check: bool,
     },
+    Upgrade {
+        program_pubkey: Pubkey,
+        buffer_pubkey: Pubkey,
+        upgrade_authority_index: SignerIndex,
+        spill_pubkey: Option<Pubkey>,
+    },
+    PrintUpgradeInstruction {
+        program_pubkey: Pubkey,
+        buffer_pubkey: Pubkey,
+        upgrade_authority_pubkey: Option<Pubkey>,
+        spill_pubkey: Option<Pubkey>,
+        encoding: TransactionBinaryEncoding,
+    },
     SetBufferAuthority {
         buffer_pubkey: Pubkey,
         buffer_authority_index: Option<SignerIndex>,
@@ -97,6 +132,11 @@ pub enum ProgramCliCommand {
         upgrade_authority_index: Option<SignerIndex>,
         new_upgrade_authority: Option<Pubkey>,
     },
+    SetUpgradeAuthorityChecked {
+        program_pubkey: Pubkey,
+        upgrade_authority_index: Option<SignerIndex>,
+        new_upgrade_authority_index: SignerIndex,
+    },
     Show {
         account_pubkey: Option<Pubkey>,
         authority_pubkey: Pubkey,
@@ -109,6 +149,11 @@ pub enum ProgramCliCommand {
         account_pubkey: Option<Pubkey>,
         output_location: String,
     },
+    Verify {
+        program_pubkey: Pubkey,
+        program_location: String,
+        attestation_location: Option<String>,
+    },
     Close {
         account_pubkey: Option<Pubkey>,
         recipient_pubkey: Pubkey,
@@ -152,7 +197,8 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .takes_value(true)
                                 .validator(is_valid_signer)
                                 .help("Intermediate buffer account to write data to, which can be used to resume a failed deploy \
-                                      [default: random address]")
+                                      [default: address of keypair at /path/to/program-buffer.json, which a failed \
+                                      deploy leaves behind to resume from, otherwise a random address]")
                         )
                         .arg(
                             Arg::with_name("upgrade_authority")
@@ -188,6 +234,16 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .long("allow-excessive-deploy-account-balance")
                                 .takes_value(false)
                                 .help("Use the designated program id even if the account already holds a large balance of RENEC")
+                        )
+                        .arg(
+                            Arg::with_name("buffer_only")
+                                .long("buffer-only")
+                                .takes_value(false)
+                                .requires("program_location")
+                                .conflicts_with_all(&["upgrade_authority", "final", "max_len"])
+                                .help("Only write the program to a buffer for an upgrade of the existing program, \
+                                      and hand the buffer to the program's upgrade authority, \
+                                      to upgrade with `program upgrade`")
                         ),
                 )
                 .subcommand(
@@ -207,7 +263,9 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .value_name("BUFFER_SIGNER")
                                 .takes_value(true)
                                 .validator(is_valid_signer)
-                                .help("Buffer account to write data into [default: random address]")
+                                .help("Buffer account to write data into [default: address of keypair at \
+                                      /path/to/program-buffer.json, which a failed write leaves behind to resume \
+                                      from, otherwise a random address]")
                         )
                         .arg(
                             Arg::with_name("buffer_authority")
@@ -274,11 +332,23 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .help("Upgrade authority [default: the default configured keypair]")
                         )
                         .arg(
-                            pubkey!(Arg::with_name("new_upgrade_authority")
+                            Arg::with_name("new_upgrade_authority")
                                 .long("new-upgrade-authority")
                                 .required_unless("final")
-                                .value_name("NEW_UPGRADE_AUTHORITY"),
-                                "Address of the new upgrade authority"),
+                                .value_name("NEW_UPGRADE_AUTHORITY")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("New upgrade authority, which must sign unless \
+                                      --skip-new-upgrade-authority-signer-check is used")
+                        )
+                        .arg(
+                            Arg::with_name("skip_new_upgrade_authority_signer_check")
+                                .long("skip-new-upgrade-authority-signer-check")
+                                .requires("new_upgrade_authority")
+                                .takes_value(false)
+                                .help("Set a new upgrade authority that does not sign, such as \
+                                      the program derived address of a multisig. A mistyped \
+                                      address makes the program impossible to upgrade")
                         )
                         .arg(
                             Arg::with_name("final")
@@ -287,6 +357,61 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .help("The program will not be upgradeable")
                         )
                 )
+                .subcommand(
+                    SubCommand::with_name("upgrade")
+                        .about("Upgrade a program with the program in a buffer account")
+                        .arg(
+                            Arg::with_name("buffer")
+                                .index(1)
+                                .value_name("BUFFER_ADDRESS")
+                                .takes_value(true)
+                                .required(true)
+                                .validator(is_pubkey)
+                                .help("Address of the buffer holding the new program")
+                        )
+                        .arg(
+                            Arg::with_name("program_id")
+                                .index(2)
+                                .value_name("PROGRAM_ID")
+                                .takes_value(true)
+                                .required(true)
+                                .validator(is_pubkey)
+                                .help("Address of the program to upgrade")
+                        )
+                        .arg(
+                            Arg::with_name("upgrade_authority")
+                                .long("upgrade-authority")
+                                .value_name("UPGRADE_AUTHORITY")
+                                .takes_value(true)
+                                .validator(is_valid_signer)
+                                .help("Upgrade authority, which can be an address with --print-instruction \
+                                      [default: the default configured keypair, or the program's \
+                                      upgrade authority with --print-instruction]")
+                        )
+                        .arg(
+                            pubkey!(Arg::with_name("spill")
+                                .long("spill")
+                                .value_name("SPILL_ADDRESS"),
+                                "Account receiving the lamports of the buffer \
+                                [default: the fee payer, or the upgrade authority with --print-instruction]. "),
+                        )
+                        .arg(
+                            Arg::with_name("print_instruction")
+                                .long("print-instruction")
+                                .takes_value(false)
+                                .help("Print the upgrade instruction instead of sending it, to submit \
+                                      through the governance program of an upgrade authority such as a multisig")
+                        )
+                        .arg(
+                            Arg::with_name("encoding")
+                                .long("encoding")
+                                .value_name("ENCODING")
+                                .takes_value(true)
+                                .possible_values(&["base58", "base64"])
+                                .default_value("base58")
+                                .help("Encoding of the printed instruction")
+                        )
+                )
                 .subcommand(
                     SubCommand::with_name("show")
                         .about("Display information about a buffer or program")
@@ -354,6 +479,35 @@ impl ProgramSubCommands for App<'_, '_> {
                                 .help("/path/to/program.so"),
                         ),
                 )
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about("Verify that a deployed program matches a program file")
+                        .arg(
+                            Arg::with_name("program_id")
+                                .index(1)
+                                .value_name("PROGRAM_ID")
+                                .takes_value(true)
+                                .required(true)
+                                .validator(is_pubkey)
+                                .help("Address of the program")
+                        )
+                        .arg(
+                            Arg::with_name("program_location")
+                                .long("so")
+                                .value_name("PROGRAM_FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help("/path/to/program.so, as built from the program's source"),
+                        )
+                        .arg(
+                            Arg::with_name("attestation_location")
+                                .long("attestation")
+                                .value_name("FILEPATH")
+                                .takes_value(true)
+                                .help("Write an attestation of the verification, signed by the \
+                                       default signer, to this file"),
+                        ),
+                )
                 .subcommand(
                     SubCommand::with_name("close")
                         .about("Close a program or buffer account and withdraw all lamports. This command will destroy the program completely and CAN'T BE RECOVERED")
@@ -517,6 +671,7 @@ pub fn parse_program_subcommand(
                     max_len,
                     allow_excessive_balance: matches.is_present("allow_excessive_balance"),
                     skip_fee_check,
+                    buffer_only: matches.is_present("buffer_only"),
                 }),
                 signers: signer_info.signers,
             }
@@ -597,28 +752,114 @@ pub fn parse_program_subcommand(
             let (upgrade_authority_signer, upgrade_authority_pubkey) =
                 signer_of(matches, "upgrade_authority", wallet_manager)?;
             let program_pubkey = pubkey_of(matches, "program_id").unwrap();
-            let new_upgrade_authority = if matches.is_present("final") {
-                None
-            } else {
-                pubkey_of_signer(matches, "new_upgrade_authority", wallet_manager)?
-            };
+            let is_final = matches.is_present("final");
 
-            let signer_info = default_signer.generate_unique_signers(
-                vec![
-                    Some(default_signer.signer_from_path(matches, wallet_manager)?),
-                    upgrade_authority_signer,
-                ],
-                matches,
-                wallet_manager,
-            )?;
+            if is_final || matches.is_present("skip_new_upgrade_authority_signer_check") {
+                let new_upgrade_authority = if is_final {
+                    None
+                } else {
+                    pubkey_of_signer(matches, "new_upgrade_authority", wallet_manager)?
+                };
 
-            CliCommandInfo {
-                command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthority {
-                    program_pubkey,
-                    upgrade_authority_index: signer_info.index_of(upgrade_authority_pubkey),
-                    new_upgrade_authority,
-                }),
-                signers: signer_info.signers,
+                let signer_info = default_signer.generate_unique_signers(
+                    vec![
+                        Some(default_signer.signer_from_path(matches, wallet_manager)?),
+                        upgrade_authority_signer,
+                    ],
+                    matches,
+                    wallet_manager,
+                )?;
+
+                CliCommandInfo {
+                    command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthority {
+                        program_pubkey,
+                        upgrade_authority_index: signer_info.index_of(upgrade_authority_pubkey),
+                        new_upgrade_authority,
+                    }),
+                    signers: signer_info.signers,
+                }
+            } else {
+                let (new_upgrade_authority_signer, new_upgrade_authority_pubkey) =
+                    signer_of(matches, "new_upgrade_authority", wallet_manager).map_err(|err| {
+                        CliError::BadParameter(format!(
+                            "{}. The new upgrade authority must sign, to make sure it can \
+                             upgrade the program; use --skip-new-upgrade-authority-signer-check \
+                             for an authority that cannot sign",
+                            err
+                        ))
+                    })?;
+
+                let signer_info = default_signer.generate_unique_signers(
+                    vec![
+                        Some(default_signer.signer_from_path(matches, wallet_manager)?),
+                        upgrade_authority_signer,
+                        new_upgrade_authority_signer,
+                    ],
+                    matches,
+                    wallet_manager,
+                )?;
+
+                CliCommandInfo {
+                    command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthorityChecked {
+                        program_pubkey,
+                        upgrade_authority_index: signer_info.index_of(upgrade_authority_pubkey),
+                        new_upgrade_authority_index: signer_info
+                            .index_of(new_upgrade_authority_pubkey)
+                            .unwrap(),
+                    }),
+                    signers: signer_info.signers,
+                }
+            }
+        }
+        ("upgrade", Some(matches)) => {
+            let program_pubkey = pubkey_of(matches, "program_id").unwrap();
+            let buffer_pubkey = pubkey_of(matches, "buffer").unwrap();
+            let spill_pubkey = pubkey_of_signer(matches, "spill", wallet_manager)?;
+
+            if matches.is_present("print_instruction") {
+                let encoding = match matches.value_of("encoding").unwrap() {
+                    "base58" => TransactionBinaryEncoding::Base58,
+                    "base64" => TransactionBinaryEncoding::Base64,
+                    _ => unreachable!(),
+                };
+                CliCommandInfo {
+                    command: CliCommand::Program(ProgramCliCommand::PrintUpgradeInstruction {
+                        program_pubkey,
+                        buffer_pubkey,
+                        upgrade_authority_pubkey: pubkey_of_signer(
+                            matches,
+                            "upgrade_authority",
+                            wallet_manager,
+                        )?,
+                        spill_pubkey,
+                        encoding,
+                    }),
+                    signers: vec![],
+                }
+            } else {
+                let (upgrade_authority_signer, upgrade_authority_pubkey) =
+                    signer_of(matches, "upgrade_authority", wallet_manager)?;
+
+                let signer_info = default_signer.generate_unique_signers(
+                    vec![
+                        Some(default_signer.signer_from_path(matches, wallet_manager)?),
+                        upgrade_authority_signer,
+                    ],
+                    matches,
+                    wallet_manager,
+                )?;
+
+                CliCommandInfo {
+                    command: CliCommand::Program(ProgramCliCommand::Upgrade {
+                        program_pubkey,
+                        buffer_pubkey,
+                        upgrade_authority_index: signer_info
+                            .index_of(upgrade_authority_pubkey)
+                            .unwrap(),
+                        spill_pubkey,
+                    }),
+                    signers: signer_info.signers,
+                }
             }
         }
         ("show", Some(matches)) => {
@@ -651,6 +892,24 @@ pub fn parse_program_subcommand(
             }),
             signers: vec![],
         },
+        ("verify", Some(matches)) => {
+            let attestation_location = matches
+                .value_of("attestation_location")
+                .map(ToString::to_string);
+            let signers = if attestation_location.is_some() {
+                vec![default_signer.signer_from_path(matches, wallet_manager)?]
+            } else {
+                vec![]
+            };
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Verify {
+                    program_pubkey: pubkey_of(matches, "program_id").unwrap(),
+                    program_location: matches.value_of("program_location").unwrap().to_string(),
+                    attestation_location,
+                }),
+                signers,
+            }
+        }
         ("close", Some(matches)) => {
             let account_pubkey = if matches.is_present("buffers") {
                 None
@@ -713,6 +972,7 @@ pub fn process_program_subcommand(
             max_len,
             allow_excessive_balance,
             skip_fee_check,
+            buffer_only,
         } => process_program_deploy(
             rpc_client,
             config,
@@ -726,6 +986,7 @@ pub fn process_program_subcommand(
             *max_len,
             *allow_excessive_balance,
             *skip_fee_check,
+            *buffer_only,
         ),
         ProgramCliCommand::WriteBuffer {
             program_location,
@@ -768,6 +1029,45 @@ pub fn process_program_subcommand(
             *upgrade_authority_index,
             *new_upgrade_authority,
         ),
+        ProgramCliCommand::SetUpgradeAuthorityChecked {
+            program_pubkey,
+            upgrade_authority_index,
+            new_upgrade_authority_index,
+        } => process_set_upgrade_authority_checked(
+            &rpc_client,
+            config,
+            program_pubkey,
+            *upgrade_authority_index,
+            *new_upgrade_authority_index,
+        ),
+        ProgramCliCommand::Upgrade {
+            program_pubkey,
+            buffer_pubkey,
+            upgrade_authority_index,
+            spill_pubkey,
+        } => process_upgrade(
+            &rpc_client,
+            config,
+            program_pubkey,
+            buffer_pubkey,
+            *upgrade_authority_index,
+            *spill_pubkey,
+        ),
+        ProgramCliCommand::PrintUpgradeInstruction {
+            program_pubkey,
+            buffer_pubkey,
+            upgrade_authority_pubkey,
+            spill_pubkey,
+            encoding,
+        } => process_print_upgrade_instruction(
+            &rpc_client,
+            config,
+            program_pubkey,
+            buffer_pubkey,
+            upgrade_authority_pubkey.as_ref(),
+            *spill_pubkey,
+            *encoding,
+        ),
         ProgramCliCommand::Show {
             account_pubkey,
             authority_pubkey,
@@ -789,6 +1089,17 @@ pub fn process_program_subcommand(
             account_pubkey,
             output_location,
         } => process_dump(&rpc_client, config, *account_pubkey, output_location),
+        ProgramCliCommand::Verify {
+            program_pubkey,
+            program_location,
+            attestation_location,
+        } => process_verify(
+            &rpc_client,
+            config,
+            program_pubkey,
+            program_location,
+            attestation_location.as_deref(),
+        ),
         ProgramCliCommand::Close {
             account_pubkey,
             recipient_pubkey,
@@ -843,18 +1154,74 @@ fn process_program_deploy(
     max_len: Option<usize>,
     allow_excessive_balance: bool,
     skip_fee_check: bool,
+    buffer_only: bool,
+) -> ProcessResult {
+    // The buffer is handed to the upgrade authority only once written with --buffer-only
+    let buffer_authority_signer = if buffer_only {
+        config.signers[0]
+    } else {
+        config.signers[upgrade_authority_signer_index]
+    };
+    let resume_buffer = match (buffer_signer_index, buffer_pubkey, program_location) {
+        (None, None, Some(program_location)) => Some(ResumeBuffer::load_or_create(
+            &rpc_client,
+            config,
+            program_location,
+            buffer_authority_signer,
+        )?),
+        _ => None,
+    };
+    let result = do_process_program_deploy(
+        rpc_client.clone(),
+        config,
+        program_location,
+        program_signer_index,
+        program_pubkey,
+        buffer_signer_index,
+        buffer_pubkey,
+        resume_buffer.as_ref(),
+        upgrade_authority_signer_index,
+        is_final,
+        max_len,
+        allow_excessive_balance,
+        skip_fee_check,
+        buffer_only,
+    );
+    if let Some(resume_buffer) = resume_buffer {
+        resume_buffer.finish(&rpc_client, config, result.is_ok());
+    }
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+fn do_process_program_deploy(
+    rpc_client: Arc<RpcClient>,
+    config: &CliConfig,
+    program_location: &Option<String>,
+    program_signer_index: Option<SignerIndex>,
+    program_pubkey: Option<Pubkey>,
+    buffer_signer_index: Option<SignerIndex>,
+    buffer_pubkey: Option<Pubkey>,
+    resume_buffer: Option<&ResumeBuffer>,
+    upgrade_authority_signer_index: SignerIndex,
+    is_final: bool,
+    max_len: Option<usize>,
+    allow_excessive_balance: bool,
+    skip_fee_check: bool,
+    buffer_only: bool,
 ) -> ProcessResult {
-    let (words, mnemonic, buffer_keypair) = create_ephemeral_keypair()?;
     let (buffer_provided, buffer_signer, buffer_pubkey) = if let Some(i) = buffer_signer_index {
         (true, Some(config.signers[i]), config.signers[i].pubkey())
     } else if let Some(pubkey) = buffer_pubkey {
         (true, None, pubkey)
-    } else {
+    } else if let Some(resume_buffer) = resume_buffer {
         (
             false,
-            Some(&buffer_keypair as &dyn Signer),
-            buffer_keypair.pubkey(),
+            Some(&resume_buffer.keypair as &dyn Signer),
+            resume_buffer.keypair.pubkey(),
         )
+    } else {
+        return Err("Program location required if buffer not supplied".into());
     };
     let upgrade_authority_signer = config.signers[upgrade_authority_signer_index];
 
@@ -870,6 +1237,21 @@ fn process_program_deploy(
         )
     };
 
+    if buffer_only {
+        let program_location = program_location
+            .as_ref()
+            .ok_or("Program location required with --buffer-only")?;
+        return process_write_upgrade_buffer(
+            rpc_client,
+            config,
+            program_location,
+            &program_pubkey,
+            buffer_signer,
+            &buffer_pubkey,
+            skip_fee_check,
+        );
+    }
+
     let do_deploy = if let Some(account) = rpc_client
         .get_account_with_commitment(&program_pubkey, config.commitment)?
         .value
@@ -1049,9 +1431,6 @@ fn process_program_deploy(
             None,
         )?;
     }
-    if result.is_err() && buffer_signer_index.is_none() {
-        report_ephemeral_mnemonic(words, mnemonic);
-    }
     result
 }
 
@@ -1065,22 +1444,58 @@ fn process_write_buffer(
     max_len: Option<usize>,
     skip_fee_check: bool,
 ) -> ProcessResult {
-    // Create ephemeral keypair to use for Buffer account, if not provided
-    let (words, mnemonic, buffer_keypair) = create_ephemeral_keypair()?;
+    let buffer_authority = if let Some(i) = buffer_authority_signer_index {
+        config.signers[i]
+    } else {
+        config.signers[0]
+    };
+    // Resume the buffer of an interrupted run, or create a new one, if not provided
+    let resume_buffer = if buffer_signer_index.is_none() && buffer_pubkey.is_none() {
+        Some(ResumeBuffer::load_or_create(
+            &rpc_client,
+            config,
+            program_location,
+            buffer_authority,
+        )?)
+    } else {
+        None
+    };
+    let result = do_process_write_buffer(
+        rpc_client.clone(),
+        config,
+        program_location,
+        buffer_signer_index,
+        buffer_pubkey,
+        resume_buffer.as_ref(),
+        buffer_authority,
+        max_len,
+        skip_fee_check,
+    );
+    if let Some(resume_buffer) = resume_buffer {
+        resume_buffer.finish(&rpc_client, config, result.is_ok());
+    }
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+fn do_process_write_buffer(
+    rpc_client: Arc<RpcClient>,
+    config: &CliConfig,
+    program_location: &str,
+    buffer_signer_index: Option<SignerIndex>,
+    buffer_pubkey: Option<Pubkey>,
+    resume_buffer: Option<&ResumeBuffer>,
+    buffer_authority: &dyn Signer,
+    max_len: Option<usize>,
+    skip_fee_check: bool,
+) -> ProcessResult {
     let (buffer_signer, buffer_pubkey) = if let Some(i) = buffer_signer_index {
         (Some(config.signers[i]), config.signers[i].pubkey())
     } else if let Some(pubkey) = buffer_pubkey {
         (None, pubkey)
     } else {
-        (
-            Some(&buffer_keypair as &dyn Signer),
-            buffer_keypair.pubkey(),
-        )
-    };
-    let buffer_authority = if let Some(i) = buffer_authority_signer_index {
-        config.signers[i]
-    } else {
-        config.signers[0]
+        let buffer_keypair = &resume_buffer.unwrap().keypair;
+        (Some(buffer_keypair as &dyn Signer), buffer_keypair.pubkey())
     };
 
     if let Some(account) = rpc_client
@@ -1118,7 +1533,7 @@ fn process_write_buffer(
         UpgradeableLoaderState::size_of_programdata(buffer_data_len),
     )?;
 
-    let result = do_process_program_write_and_deploy(
+    do_process_program_write_and_deploy(
         rpc_client,
         config,
         &program_data,
@@ -1132,19 +1547,333 @@ fn process_write_buffer(
         Some(buffer_authority),
         true,
         skip_fee_check,
-    );
+    )
+}
 
-    if result.is_err() && buffer_signer_index.is_none() && buffer_signer.is_some() {
-        report_ephemeral_mnemonic(words, mnemonic);
+/// Write the program to a buffer for an upgrade of `program_pubkey`, and hand the buffer to the
+/// program's upgrade authority, which can't sign for the writes when it is a multisig
+fn process_write_upgrade_buffer(
+    rpc_client: Arc<RpcClient>,
+    config: &CliConfig,
+    program_location: &str,
+    program_pubkey: &Pubkey,
+    buffer_signer: Option<&dyn Signer>,
+    buffer_pubkey: &Pubkey,
+    skip_fee_check: bool,
+) -> ProcessResult {
+    let (upgrade_authority, max_program_len) =
+        get_program_upgrade_authority(&rpc_client, config, program_pubkey)?;
+    let program_data = read_and_verify_elf(program_location)?;
+    if program_data.len() > max_program_len {
+        return Err(format!(
+            "Program {} is larger than the {} bytes available to program {}",
+            program_location, max_program_len, program_pubkey
+        )
+        .into());
     }
-    result
+    let minimum_balance = rpc_client.get_minimum_balance_for_rent_exemption(
+        UpgradeableLoaderState::size_of_programdata(program_data.len()),
+    )?;
+
+    do_process_program_write_and_deploy(
+        rpc_client.clone(),
+        config,
+        &program_data,
+        program_data.len(),
+        program_data.len(),
+        minimum_balance,
+        &bpf_loader_upgradeable::id(),
+        None,
+        buffer_signer,
+        buffer_pubkey,
+        Some(config.signers[0]),
+        true,
+        skip_fee_check,
+    )?;
+    process_set_authority(
+        &rpc_client,
+        config,
+        None,
+        Some(*buffer_pubkey),
+        Some(0),
+        Some(upgrade_authority),
+    )?;
+
+    let buffer = CliProgramBuffer {
+        buffer: buffer_pubkey.to_string(),
+    };
+    Ok(config.output_format.formatted_string(&buffer))
 }
 
-fn process_set_authority(
+/// The upgrade authority of an upgradeable program, and the length of the largest program it
+/// can be upgraded to
+fn get_program_upgrade_authority(
     rpc_client: &RpcClient,
     config: &CliConfig,
-    program_pubkey: Option<Pubkey>,
-    buffer_pubkey: Option<Pubkey>,
+    program_pubkey: &Pubkey,
+) -> Result<(Pubkey, usize), Box<dyn std::error::Error>> {
+    let account = rpc_client
+        .get_account_with_commitment(program_pubkey, config.commitment)?
+        .value
+        .ok_or_else(|| format!("Unable to find the account {}", program_pubkey))?;
+    let programdata_address = match account.state() {
+        Ok(UpgradeableLoaderState::Program {
+            programdata_address,
+        }) if account.owner == bpf_loader_upgradeable::id() => programdata_address,
+        _ => return Err(format!("{} is not an upgradeable program", program_pubkey).into()),
+    };
+    let programdata_account = rpc_client
+        .get_account_with_commitment(&programdata_address, config.commitment)?
+        .value
+        .ok_or_else(|| format!("Program {} has been closed", program_pubkey))?;
+    match programdata_account.state() {
+        Ok(UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address: Some(upgrade_authority_address),
+            ..
+        }) => Ok((
+            upgrade_authority_address,
+            programdata_account
+                .data
+                .len()
+                .saturating_sub(UpgradeableLoaderState::size_of_programdata_metadata()),
+        )),
+        Ok(UpgradeableLoaderState::ProgramData { .. }) => {
+            Err(format!("Program {} is no longer upgradeable", program_pubkey).into())
+        }
+        _ => Err(format!("Program {} has been closed", program_pubkey).into()),
+    }
+}
+
+/// Check that the program of `buffer_pubkey` can upgrade `program_pubkey`, with `upgrade_authority`
+/// if given. Returns the upgrade authority of the program.
+fn check_program_upgrade(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_pubkey: &Pubkey,
+    buffer_pubkey: &Pubkey,
+    upgrade_authority: Option<&Pubkey>,
+) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    let (program_authority, max_program_len) =
+        get_program_upgrade_authority(rpc_client, config, program_pubkey)?;
+    if let Some(upgrade_authority) = upgrade_authority {
+        if *upgrade_authority != program_authority {
+            return Err(format!(
+                "Program's authority {} does not match authority provided {}",
+                program_authority, upgrade_authority,
+            )
+            .into());
+        }
+    }
+
+    let buffer_account = rpc_client
+        .get_account_with_commitment(buffer_pubkey, config.commitment)?
+        .value
+        .ok_or_else(|| format!("Buffer account {} not found", buffer_pubkey))?;
+    match buffer_account.state() {
+        Ok(UpgradeableLoaderState::Buffer { authority_address })
+            if buffer_account.owner == bpf_loader_upgradeable::id() =>
+        {
+            if authority_address != Some(program_authority) {
+                return Err(format!(
+                    "Buffer's authority {:?} does not match the program's upgrade authority {}",
+                    authority_address, program_authority
+                )
+                .into());
+            }
+        }
+        _ => {
+            return Err(format!(
+                "{} is not an upgradeable loader buffer account",
+                buffer_pubkey
+            )
+            .into())
+        }
+    }
+    let program_len = buffer_account
+        .data
+        .len()
+        .saturating_sub(UpgradeableLoaderState::size_of_buffer_metadata());
+    if program_len > max_program_len {
+        return Err(format!(
+            "Program of buffer {} is larger than the {} bytes available to program {}",
+            buffer_pubkey, max_program_len, program_pubkey
+        )
+        .into());
+    }
+    Ok(program_authority)
+}
+
+fn process_upgrade(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_pubkey: &Pubkey,
+    buffer_pubkey: &Pubkey,
+    upgrade_authority_index: SignerIndex,
+    spill_pubkey: Option<Pubkey>,
+) -> ProcessResult {
+    let upgrade_authority_signer = config.signers[upgrade_authority_index];
+    check_program_upgrade(
+        rpc_client,
+        config,
+        program_pubkey,
+        buffer_pubkey,
+        Some(&upgrade_authority_signer.pubkey()),
+    )?;
+
+    let blockhash = rpc_client.get_latest_blockhash()?;
+    let mut tx = new_unsigned_transaction(
+        rpc_client,
+        config,
+        Message::new(
+            &[bpf_loader_upgradeable::upgrade(
+                program_pubkey,
+                buffer_pubkey,
+                &upgrade_authority_signer.pubkey(),
+                &spill_pubkey.unwrap_or_else(|| config.signers[0].pubkey()),
+            )],
+            Some(&config.signers[0].pubkey()),
+        ),
+    )?;
+    tx.try_sign(&[config.signers[0], upgrade_authority_signer], blockhash)?;
+    rpc_client
+        .send_and_confirm_transaction_with_spinner_and_config(
+            &tx,
+            config.commitment,
+            RpcSendTransactionConfig {
+                skip_preflight: true,
+                preflight_commitment: Some(config.commitment.commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+        .map_err(|e| format!("Upgrading program failed: {}", e))?;
+
+    let program_id = CliProgramId {
+        program_id: program_pubkey.to_string(),
+    };
+    Ok(config.output_format.formatted_string(&program_id))
+}
+
+fn encode_instruction_data(data: &[u8], encoding: TransactionBinaryEncoding) -> String {
+    match encoding {
+        TransactionBinaryEncoding::Base58 => bs58::encode(data).into_string(),
+        TransactionBinaryEncoding::Base64 => base64::encode(data),
+    }
+}
+
+fn process_print_upgrade_instruction(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_pubkey: &Pubkey,
+    buffer_pubkey: &Pubkey,
+    upgrade_authority_pubkey: Option<&Pubkey>,
+    spill_pubkey: Option<Pubkey>,
+    encoding: TransactionBinaryEncoding,
+) -> ProcessResult {
+    let upgrade_authority = check_program_upgrade(
+        rpc_client,
+        config,
+        program_pubkey,
+        buffer_pubkey,
+        upgrade_authority_pubkey,
+    )?;
+    // The lamports of the buffer go back to the upgrade authority, as it funds the upgrade
+    let spill_pubkey = spill_pubkey.unwrap_or(upgrade_authority);
+    let instruction = bpf_loader_upgradeable::upgrade(
+        program_pubkey,
+        buffer_pubkey,
+        &upgrade_authority,
+        &spill_pubkey,
+    );
+
+    let upgrade_instruction = CliProgramUpgradeInstruction {
+        program_id: program_pubkey.to_string(),
+        buffer: buffer_pubkey.to_string(),
+        upgrade_authority: upgrade_authority.to_string(),
+        spill: spill_pubkey.to_string(),
+        encoding,
+        instruction: CliInstruction {
+            program_id: instruction.program_id.to_string(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|account_meta| CliInstructionAccount {
+                    pubkey: account_meta.pubkey.to_string(),
+                    is_signer: account_meta.is_signer,
+                    is_writable: account_meta.is_writable,
+                })
+                .collect(),
+            data: encode_instruction_data(&instruction.data, encoding),
+        },
+        serialized_instruction: encode_instruction_data(
+            &bincode::serialize(&instruction)?,
+            encoding,
+        ),
+    };
+    Ok(config.output_format.formatted_string(&upgrade_instruction))
+}
+
+fn process_set_upgrade_authority_checked(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_pubkey: &Pubkey,
+    upgrade_authority_index: Option<SignerIndex>,
+    new_upgrade_authority_index: SignerIndex,
+) -> ProcessResult {
+    let upgrade_authority_signer = if let Some(index) = upgrade_authority_index {
+        config.signers[index]
+    } else {
+        return Err("Set authority requires the current authority".into());
+    };
+    let new_upgrade_authority_signer = config.signers[new_upgrade_authority_index];
+
+    trace!("Set a new authority");
+    let blockhash = rpc_client.get_latest_blockhash()?;
+
+    let mut instruction = bpf_loader_upgradeable::set_upgrade_authority(
+        program_pubkey,
+        &upgrade_authority_signer.pubkey(),
+        Some(&new_upgrade_authority_signer.pubkey()),
+    );
+    // The loader does not require the new authority to sign, but the runtime checks every
+    // signature of the transaction, which proves that the new authority is not mistyped
+    instruction.accounts[2].is_signer = true;
+    let mut tx = new_unsigned_transaction(
+        rpc_client,
+        config,
+        Message::new(&[instruction], Some(&config.signers[0].pubkey())),
+    )?;
+    tx.try_sign(
+        &[
+            config.signers[0],
+            upgrade_authority_signer,
+            new_upgrade_authority_signer,
+        ],
+        blockhash,
+    )?;
+    rpc_client
+        .send_and_confirm_transaction_with_spinner_and_config(
+            &tx,
+            config.commitment,
+            RpcSendTransactionConfig {
+                skip_preflight: true,
+                preflight_commitment: Some(config.commitment.commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+        .map_err(|e| format!("Setting authority failed: {}", e))?;
+
+    let authority = CliProgramAuthority {
+        authority: new_upgrade_authority_signer.pubkey().to_string(),
+        account_type: CliProgramAccountType::Program,
+    };
+    Ok(config.output_format.formatted_string(&authority))
+}
+
+fn process_set_authority(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_pubkey: Option<Pubkey>,
+    buffer_pubkey: Option<Pubkey>,
     authority: Option<SignerIndex>,
     new_authority: Option<Pubkey>,
 ) -> ProcessResult {
@@ -1158,24 +1887,32 @@ fn process_set_authority(
     let blockhash = rpc_client.get_latest_blockhash()?;
 
     let mut tx = if let Some(ref pubkey) = program_pubkey {
-        Transaction::new_unsigned(Message::new(
-            &[bpf_loader_upgradeable::set_upgrade_authority(
-                pubkey,
-                &authority_signer.pubkey(),
-                new_authority.as_ref(),
-            )],
-            Some(&config.signers[0].pubkey()),
-        ))
-    } else if let Some(pubkey) = buffer_pubkey {
-        if let Some(ref new_authority) = new_authority {
-            Transaction::new_unsigned(Message::new(
-                &[bpf_loader_upgradeable::set_buffer_authority(
-                    &pubkey,
+        new_unsigned_transaction(
+            rpc_client,
+            config,
+            Message::new(
+                &[bpf_loader_upgradeable::set_upgrade_authority(
+                    pubkey,
                     &authority_signer.pubkey(),
-                    new_authority,
+                    new_authority.as_ref(),
                 )],
                 Some(&config.signers[0].pubkey()),
-            ))
+            ),
+        )?
+    } else if let Some(pubkey) = buffer_pubkey {
+        if let Some(ref new_authority) = new_authority {
+            new_unsigned_transaction(
+                rpc_client,
+                config,
+                Message::new(
+                    &[bpf_loader_upgradeable::set_buffer_authority(
+                        &pubkey,
+                        &authority_signer.pubkey(),
+                        new_authority,
+                    )],
+                    Some(&config.signers[0].pubkey()),
+                ),
+            )?
         } else {
             return Err("Buffer authority cannot be None".into());
         }
@@ -1511,6 +2248,138 @@ fn process_dump(
     }
 }
 
+/// Program data without its trailing zeros, as program data accounts are allocated larger than
+/// their program to make room for upgrades
+fn strip_trailing_zeros(program_data: &[u8]) -> &[u8] {
+    let len = program_data
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |i| i + 1);
+    &program_data[..len]
+}
+
+fn get_program_hash(program_data: &[u8]) -> String {
+    hex::encode(hash(strip_trailing_zeros(program_data)).to_bytes())
+}
+
+/// Statement that a deployed program matched a program file, signed by the verifier over the
+/// compact JSON serialization of the statement
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProgramAttestation {
+    program_id: String,
+    program_hash: String,
+    last_deploy_slot: Option<u64>,
+    authority: Option<String>,
+    verified_slot: u64,
+    verifier: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SignedProgramAttestation {
+    #[serde(flatten)]
+    attestation: ProgramAttestation,
+    signature: String,
+}
+
+impl ProgramAttestation {
+    fn sign(
+        self,
+        signer: &dyn Signer,
+    ) -> Result<SignedProgramAttestation, Box<dyn std::error::Error>> {
+        let signature = signer.try_sign_message(&serde_json::to_vec(&self)?)?;
+        Ok(SignedProgramAttestation {
+            attestation: self,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+fn process_verify(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    program_pubkey: &Pubkey,
+    program_location: &str,
+    attestation_location: Option<&str>,
+) -> ProcessResult {
+    let account = rpc_client
+        .get_account_with_commitment(program_pubkey, config.commitment)?
+        .value
+        .ok_or_else(|| format!("Unable to find the account {}", program_pubkey))?;
+    let (program_data, last_deploy_slot, authority) =
+        if account.owner == bpf_loader::id() || account.owner == bpf_loader_deprecated::id() {
+            (account.data, None, None)
+        } else if account.owner == bpf_loader_upgradeable::id() {
+            let programdata_address = match account.state() {
+                Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) => programdata_address,
+                _ => return Err(format!("{} is not an upgradeable program", program_pubkey).into()),
+            };
+            let programdata_account = rpc_client
+                .get_account_with_commitment(&programdata_address, config.commitment)?
+                .value
+                .ok_or_else(|| format!("Program {} has been closed", program_pubkey))?;
+            match programdata_account.state() {
+                Ok(UpgradeableLoaderState::ProgramData {
+                    slot,
+                    upgrade_authority_address,
+                }) => {
+                    let offset = UpgradeableLoaderState::size_of_programdata_metadata();
+                    let authority = upgrade_authority_address
+                        .map(|pubkey| pubkey.to_string())
+                        .unwrap_or_else(|| "none".to_string());
+                    (
+                        programdata_account.data[offset..].to_vec(),
+                        Some(slot),
+                        Some(authority),
+                    )
+                }
+                _ => return Err(format!("Program {} has been closed", program_pubkey).into()),
+            }
+        } else {
+            return Err(format!("{} is not a BPF program", program_pubkey).into());
+        };
+
+    let local_program_data = std::fs::read(program_location)
+        .map_err(|err| format!("Unable to read program file: {}", err))?;
+    let program_hash = get_program_hash(&program_data);
+    let local_program_hash = get_program_hash(&local_program_data);
+    if program_hash != local_program_hash {
+        return Err(format!(
+            "Program {} with hash {} does not match {} with hash {}",
+            program_pubkey, program_hash, program_location, local_program_hash
+        )
+        .into());
+    }
+
+    if let Some(attestation_location) = attestation_location {
+        let attestation = ProgramAttestation {
+            program_id: program_pubkey.to_string(),
+            program_hash: program_hash.clone(),
+            last_deploy_slot,
+            authority: authority.clone(),
+            verified_slot: rpc_client.get_slot_with_commitment(config.commitment)?,
+            verifier: config.signers[0].pubkey().to_string(),
+        }
+        .sign(config.signers[0])?;
+        let mut f = File::create(attestation_location)?;
+        f.write_all(&serde_json::to_vec_pretty(&attestation)?)?;
+    }
+
+    let verification = CliProgramVerification {
+        program_id: program_pubkey.to_string(),
+        program_location: program_location.to_string(),
+        program_hash,
+        local_program_hash,
+        verified: true,
+        last_deploy_slot,
+        authority,
+        attestation_location: attestation_location.map(ToString::to_string),
+    };
+    Ok(config.output_format.formatted_string(&verification))
+}
+
 fn close(
     rpc_client: &RpcClient,
     config: &CliConfig,
@@ -1521,15 +2390,19 @@ fn close(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let blockhash = rpc_client.get_latest_blockhash()?;
 
-    let mut tx = Transaction::new_unsigned(Message::new(
-        &[bpf_loader_upgradeable::close_any(
-            account_pubkey,
-            recipient_pubkey,
-            Some(&authority_signer.pubkey()),
-            program_pubkey,
-        )],
-        Some(&config.signers[0].pubkey()),
-    ));
+    let mut tx = new_unsigned_transaction(
+        rpc_client,
+        config,
+        Message::new(
+            &[bpf_loader_upgradeable::close_any(
+                account_pubkey,
+                recipient_pubkey,
+                Some(&authority_signer.pubkey()),
+                program_pubkey,
+            )],
+            Some(&config.signers[0].pubkey()),
+        ),
+    )?;
 
     tx.try_sign(&[config.signers[0], authority_signer], blockhash)?;
     let result = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
@@ -1740,21 +2613,57 @@ pub fn process_deploy(
     result
 }
 
-fn calculate_max_chunk_size<F>(create_msg: &F) -> usize
+fn calculate_max_chunk_size<F>(
+    create_msg: &F,
+    lookup_table_accounts: &[AddressLookupTableAccount],
+) -> Result<usize, Box<dyn std::error::Error>>
 where
     F: Fn(u32, Vec<u8>) -> Message,
 {
-    let baseline_msg = create_msg(0, Vec::new());
-    let tx_size = bincode::serialized_size(&Transaction {
-        signatures: vec![
-            Signature::default();
-            baseline_msg.header.num_required_signatures as usize
-        ],
-        message: baseline_msg,
-    })
-    .unwrap() as usize;
+    let baseline_tx =
+        compile_unsigned_transaction(create_msg(0, Vec::new()), lookup_table_accounts)?;
+    let tx_size = bincode::serialized_size(&baseline_tx).unwrap() as usize;
     // add 1 byte buffer to account for shortvec encoding
-    PACKET_DATA_SIZE.saturating_sub(tx_size).saturating_sub(1)
+    Ok(PACKET_DATA_SIZE.saturating_sub(tx_size).saturating_sub(1))
+}
+
+/// The program data written so far to a buffer account of `loader_id`
+fn get_buffer_program_data<'a>(loader_id: &Pubkey, account: &'a Account) -> &'a [u8] {
+    if account.owner != *loader_id {
+        return &[];
+    }
+    let metadata_len = if loader_id == &bpf_loader_upgradeable::id() {
+        UpgradeableLoaderState::size_of_buffer_metadata()
+    } else {
+        0
+    };
+    account.data.get(metadata_len..).unwrap_or_default()
+}
+
+/// Create the messages writing the chunks of `program_data` that `buffer_data` does not hold
+/// yet, so that a deploy resumes the writes of an interrupted one instead of starting over
+fn create_write_messages<F>(
+    program_data: &[u8],
+    buffer_data: &[u8],
+    create_msg: &F,
+    lookup_table_accounts: &[AddressLookupTableAccount],
+) -> Result<Vec<Message>, Box<dyn std::error::Error>>
+where
+    F: Fn(u32, Vec<u8>) -> Message,
+{
+    let chunk_size = calculate_max_chunk_size(create_msg, lookup_table_accounts)?;
+    Ok(program_data
+        .chunks(chunk_size)
+        .zip(0..)
+        .filter_map(|(chunk, i)| {
+            let offset = i * chunk_size;
+            if buffer_data.get(offset..offset + chunk.len()) == Some(chunk) {
+                None
+            } else {
+                Some(create_msg(offset as u32, chunk.to_vec()))
+            }
+        })
+        .collect())
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1776,19 +2685,21 @@ fn do_process_program_write_and_deploy(
     // Build messages to calculate fees
     let mut messages: Vec<&Message> = Vec::new();
     let blockhash = rpc_client.get_latest_blockhash()?;
+    let lookup_table_accounts =
+        get_address_lookup_table_accounts(&rpc_client, &config.lookup_tables, config.commitment)?;
 
     // Initialize buffer account or complete if already partially initialized
     let (initial_message, write_messages, balance_needed) =
         if let Some(buffer_authority_signer) = buffer_authority_signer {
-            let (initial_instructions, balance_needed) = if let Some(account) = rpc_client
+            let buffer_account = rpc_client
                 .get_account_with_commitment(buffer_pubkey, config.commitment)?
-                .value
-            {
+                .value;
+            let (initial_instructions, balance_needed) = if let Some(account) = &buffer_account {
                 complete_partial_program_init(
                     loader_id,
                     &config.signers[0].pubkey(),
                     buffer_pubkey,
-                    &account,
+                    account,
                     if loader_id == &bpf_loader_upgradeable::id() {
                         UpgradeableLoaderState::size_of_buffer(program_len)
                     } else {
@@ -1847,11 +2758,16 @@ fn do_process_program_write_and_deploy(
                 Message::new_with_blockhash(&[instruction], Some(&payer_pubkey), &blockhash)
             };
 
-            let mut write_messages = vec![];
-            let chunk_size = calculate_max_chunk_size(&create_msg);
-            for (chunk, i) in program_data.chunks(chunk_size).zip(0..) {
-                write_messages.push(create_msg((i * chunk_size) as u32, chunk.to_vec()));
-            }
+            let buffer_data = buffer_account
+                .as_ref()
+                .map(|account| get_buffer_program_data(loader_id, account))
+                .unwrap_or_default();
+            let write_messages = create_write_messages(
+                program_data,
+                buffer_data,
+                &create_msg,
+                &lookup_table_accounts,
+            )?;
 
             (initial_message, Some(write_messages), balance_needed)
         } else {
@@ -1922,6 +2838,7 @@ fn do_process_program_write_and_deploy(
         buffer_signer,
         buffer_authority_signer,
         program_signers,
+        &lookup_table_accounts,
     )?;
 
     if let Some(program_signers) = program_signers {
@@ -1956,19 +2873,21 @@ fn do_process_program_upgrade(
     // Build messages to calculate fees
     let mut messages: Vec<&Message> = Vec::new();
     let blockhash = rpc_client.get_latest_blockhash()?;
+    let lookup_table_accounts =
+        get_address_lookup_table_accounts(&rpc_client, &config.lookup_tables, config.commitment)?;
 
     let (initial_message, write_messages, balance_needed) =
         if let Some(buffer_signer) = buffer_signer {
             // Check Buffer account to see if partial initialization has occurred
-            let (initial_instructions, balance_needed) = if let Some(account) = rpc_client
+            let buffer_account = rpc_client
                 .get_account_with_commitment(&buffer_signer.pubkey(), config.commitment)?
-                .value
-            {
+                .value;
+            let (initial_instructions, balance_needed) = if let Some(account) = &buffer_account {
                 complete_partial_program_init(
                     &loader_id,
                     &config.signers[0].pubkey(),
                     &buffer_signer.pubkey(),
-                    &account,
+                    account,
                     UpgradeableLoaderState::size_of_buffer(data_len),
                     minimum_balance,
                     true,
@@ -2010,11 +2929,16 @@ fn do_process_program_upgrade(
             };
 
             // Create and add write messages
-            let mut write_messages = vec![];
-            let chunk_size = calculate_max_chunk_size(&create_msg);
-            for (chunk, i) in program_data.chunks(chunk_size).zip(0..) {
-                write_messages.push(create_msg((i * chunk_size) as u32, chunk.to_vec()));
-            }
+            let buffer_data = buffer_account
+                .as_ref()
+                .map(|account| get_buffer_program_data(&loader_id, account))
+                .unwrap_or_default();
+            let write_messages = create_write_messages(
+                program_data,
+                buffer_data,
+                &create_msg,
+                &lookup_table_accounts,
+            )?;
 
             (initial_message, Some(write_messages), balance_needed)
         } else {
@@ -2066,6 +2990,7 @@ fn do_process_program_upgrade(
         buffer_signer,
         Some(upgrade_authority),
         Some(&[upgrade_authority]),
+        &lookup_table_accounts,
     )?;
 
     let program_id = CliProgramId {
@@ -2195,6 +3120,7 @@ fn send_deploy_messages(
     initial_signer: Option<&dyn Signer>,
     write_signer: Option<&dyn Signer>,
     final_signers: Option<&[&dyn Signer]>,
+    lookup_table_accounts: &[AddressLookupTableAccount],
 ) -> Result<(), Box<dyn std::error::Error>> {
     let payer_signer = config.signers[0];
 
@@ -2203,7 +3129,8 @@ fn send_deploy_messages(
             trace!("Preparing the required accounts");
             let blockhash = rpc_client.get_latest_blockhash()?;
 
-            let mut initial_transaction = Transaction::new_unsigned(message.clone());
+            let mut initial_transaction =
+                new_unsigned_transaction(&rpc_client, config, message.clone())?;
             // Most of the initial_transaction combinations require both the fee-payer and new program
             // account to sign the transaction. One (transfer) only requires the fee-payer signature.
             // This check is to ensure signing does not fail on a KeypairPubkeyMismatch error from an
@@ -2221,7 +3148,7 @@ fn send_deploy_messages(
         }
     }
 
-    if let Some(write_messages) = write_messages {
+    if let Some(write_messages) = write_messages.as_ref().filter(|m| !m.is_empty()) {
         if let Some(write_signer) = write_signer {
             trace!("Writing program data");
             let connection_cache = Arc::new(ConnectionCache::default());
@@ -2231,15 +3158,15 @@ fn send_deploy_messages(
                 TpuClientConfig::default(),
                 connection_cache,
             )?;
-            let transaction_errors = tpu_client
-                .send_and_confirm_messages_with_spinner(
-                    write_messages,
-                    &[payer_signer, write_signer],
-                )
-                .map_err(|err| format!("Data writes to account failed: {}", err))?
-                .into_iter()
-                .flatten()
-                .collect::<Vec<_>>();
+            let transaction_errors = send_write_messages(
+                &rpc_client,
+                &tpu_client,
+                config,
+                write_messages,
+                lookup_table_accounts,
+                &[payer_signer, write_signer],
+            )
+            .map_err(|err| format!("Data writes to account failed: {}", err))?;
 
             if !transaction_errors.is_empty() {
                 for transaction_error in &transaction_errors {
@@ -2257,7 +3184,7 @@ fn send_deploy_messages(
             trace!("Deploying program");
             let blockhash = rpc_client.get_latest_blockhash()?;
 
-            let mut final_tx = Transaction::new_unsigned(message.clone());
+            let mut final_tx = new_unsigned_transaction(&rpc_client, config, message.clone())?;
             let mut signers = final_signers.to_vec();
             signers.push(payer_signer);
             final_tx.try_sign(&signers, blockhash)?;
@@ -2278,6 +3205,219 @@ fn send_deploy_messages(
     Ok(())
 }
 
+/// Send the write transactions in batches to the upcoming leaders, resending the ones not
+/// confirmed yet until they land or their blockhash expires, for up to `MAX_WRITE_ATTEMPTS`
+/// blockhashes. Returns the errors of the transactions that landed but failed.
+fn send_write_messages<T: Signers>(
+    rpc_client: &RpcClient,
+    tpu_client: &TpuClient,
+    config: &CliConfig,
+    messages: &[Message],
+    lookup_table_accounts: &[AddressLookupTableAccount],
+    signers: &T,
+) -> Result<Vec<TransactionError>, Box<dyn std::error::Error>> {
+    let progress_bar = new_spinner_progress_bar();
+    let mut unconfirmed_transactions = messages
+        .iter()
+        .map(|message| compile_unsigned_transaction(message.clone(), lookup_table_accounts))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut transaction_errors = vec![];
+    for attempt in 1..=MAX_WRITE_ATTEMPTS {
+        if unconfirmed_transactions.is_empty() {
+            break;
+        }
+        let (blockhash, last_valid_block_height) =
+            rpc_client.get_latest_blockhash_with_commitment(config.commitment)?;
+        let mut pending_transactions = HashMap::new();
+        for mut transaction in unconfirmed_transactions {
+            transaction.try_sign(signers, blockhash)?;
+            pending_transactions.insert(transaction.signatures[0], transaction);
+        }
+
+        let mut last_send: Option<Instant> = None;
+        loop {
+            if last_send.map_or(true, |last_send| {
+                last_send.elapsed() > WRITE_RESEND_INTERVAL
+            }) {
+                let transactions: Vec<_> = pending_transactions.values().collect();
+                for batch in transactions.chunks(WRITE_BATCH_SIZE) {
+                    let wire_transactions = batch
+                        .iter()
+                        .map(|transaction| bincode::serialize(transaction).unwrap())
+                        .collect();
+                    if tpu_client
+                        .try_send_wire_transaction_batch(wire_transactions)
+                        .is_err()
+                    {
+                        for transaction in batch {
+                            let _ = rpc_client.send_transaction_with_config(
+                                *transaction,
+                                RpcSendTransactionConfig {
+                                    skip_preflight: true,
+                                    ..RpcSendTransactionConfig::default()
+                                },
+                            );
+                        }
+                    }
+                }
+                last_send = Some(Instant::now());
+            }
+            sleep(Duration::from_millis(500));
+
+            let signatures: Vec<_> = pending_transactions.keys().cloned().collect();
+            for signatures in signatures.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS) {
+                // Statuses that could not be fetched are checked again after the next block
+                if let Ok(statuses) = rpc_client.get_signature_statuses(signatures) {
+                    for (signature, status) in signatures.iter().zip(statuses.value) {
+                        if let Some(status) =
+                            status.filter(|status| status.satisfies_commitment(config.commitment))
+                        {
+                            pending_transactions.remove(signature);
+                            transaction_errors.extend(status.err);
+                        }
+                    }
+                }
+            }
+            let block_height = rpc_client.get_block_height_with_commitment(config.commitment)?;
+            let confirmed_transactions = messages.len() - pending_transactions.len();
+            progress_bar.set_message(format!(
+                "{:>5.1}% | Writing program data, {}/{} transactions confirmed \
+                 [attempt {}/{}; re-sign in {} blocks]",
+                confirmed_transactions as f64 * 100. / messages.len() as f64,
+                confirmed_transactions,
+                messages.len(),
+                attempt,
+                MAX_WRITE_ATTEMPTS,
+                last_valid_block_height.saturating_sub(block_height),
+            ));
+            if pending_transactions.is_empty() || block_height > last_valid_block_height {
+                break;
+            }
+        }
+        unconfirmed_transactions = pending_transactions.into_values().collect();
+    }
+    progress_bar.finish_and_clear();
+
+    if !unconfirmed_transactions.is_empty() {
+        return Err(format!(
+            "{} write transactions were not confirmed after {} attempts",
+            unconfirmed_transactions.len(),
+            MAX_WRITE_ATTEMPTS
+        )
+        .into());
+    }
+    Ok(transaction_errors)
+}
+
+/// Keypair of the buffer written to when no buffer is given. It is kept in a file next to the
+/// program until the writes succeed, so that running the same command again after a failed or
+/// interrupted run resumes the writes to the same buffer.
+struct ResumeBuffer {
+    keypair: Keypair,
+    keypair_file: PathBuf,
+}
+
+impl ResumeBuffer {
+    /// Load the keypair of the buffer of an earlier run, or save a new one. A buffer that is not
+    /// the size of the program, as when the program was rebuilt since, can't be resumed; it is
+    /// closed with `authority_signer` and replaced by a new one.
+    fn load_or_create(
+        rpc_client: &RpcClient,
+        config: &CliConfig,
+        program_location: &str,
+        authority_signer: &dyn Signer,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut keypair_file = PathBuf::from(program_location);
+        let mut filename = keypair_file.file_stem().unwrap().to_os_string();
+        filename.push("-buffer");
+        keypair_file.set_file_name(filename);
+        keypair_file.set_extension("json");
+        if keypair_file.exists() {
+            let keypair = read_keypair_file(&keypair_file).map_err(|err| {
+                format!(
+                    "Unable to read buffer keypair {}: {}",
+                    keypair_file.display(),
+                    err
+                )
+            })?;
+            let buffer_pubkey = keypair.pubkey();
+            let program_len = fs::metadata(program_location)
+                .map_err(|err| format!("Unable to read {}: {}", program_location, err))?
+                .len() as usize;
+            match rpc_client
+                .get_account_with_commitment(&buffer_pubkey, config.commitment)?
+                .value
+            {
+                Some(account)
+                    if bpf_loader_upgradeable::check_id(&account.owner)
+                        && account.data.len()
+                            != UpgradeableLoaderState::size_of_buffer(program_len) =>
+                {
+                    eprintln!(
+                        "Closing buffer {} of {}, which is not the size of the program",
+                        buffer_pubkey,
+                        keypair_file.display()
+                    );
+                    close(
+                        rpc_client,
+                        config,
+                        &buffer_pubkey,
+                        &config.signers[0].pubkey(),
+                        authority_signer,
+                        None,
+                    )
+                    .map_err(|err| {
+                        format!(
+                            "Unable to close buffer {}: {}. Close it with `renec program close` \
+                            and delete {}",
+                            buffer_pubkey,
+                            err,
+                            keypair_file.display()
+                        )
+                    })?;
+                }
+                _ => {
+                    eprintln!(
+                        "Resuming the writes to buffer {} of {}",
+                        buffer_pubkey,
+                        keypair_file.display()
+                    );
+                    return Ok(Self {
+                        keypair,
+                        keypair_file,
+                    });
+                }
+            }
+        }
+        let keypair = Keypair::new();
+        write_keypair_file(&keypair, &keypair_file).map_err(|err| {
+            format!(
+                "Unable to write buffer keypair {}: {}",
+                keypair_file.display(),
+                err
+            )
+        })?;
+        Ok(Self {
+            keypair,
+            keypair_file,
+        })
+    }
+
+    /// Remove the keypair file once the buffer is no longer needed, or else tell how to resume
+    fn finish(self, rpc_client: &RpcClient, config: &CliConfig, succeeded: bool) {
+        let buffer_exists = || {
+            rpc_client
+                .get_account_with_commitment(&self.keypair.pubkey(), config.commitment)
+                .map_or(true, |response| response.value.is_some())
+        };
+        if succeeded || !buffer_exists() {
+            let _ = fs::remove_file(&self.keypair_file);
+        } else {
+            report_resume_buffer(&self.keypair.pubkey(), &self.keypair_file);
+        }
+    }
+}
+
 fn create_ephemeral_keypair(
 ) -> Result<(usize, bip39::Mnemonic, Keypair), Box<dyn std::error::Error>> {
     const WORDS: usize = 12;
@@ -2309,6 +3449,20 @@ fn report_ephemeral_mnemonic(words: usize, mnemonic: bip39::Mnemonic) {
     );
 }
 
+fn report_resume_buffer(buffer_pubkey: &Pubkey, keypair_file: &Path) {
+    let divider = "=".repeat(80);
+    eprintln!("{}", divider);
+    eprintln!(
+        "The keypair of intermediate buffer {} is saved to",
+        buffer_pubkey
+    );
+    eprintln!("{}", keypair_file.display());
+    eprintln!("Run the same command again to resume the writes to the buffer.");
+    eprintln!("Or to recover the account's lamports, pass its address as the");
+    eprintln!("[BUFFER_ACCOUNT_ADDRESS] argument to `renec program close`,");
+    eprintln!("and delete the keypair file.\n{}", divider);
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -2317,9 +3471,17 @@ mod tests {
             clap_app::get_clap_app,
             cli::{parse_command, process_command},
         },
-        serde_json::Value,
         renec_cli_output::OutputFormat,
-        solana_sdk::signature::write_keypair_file,
+        serde_json::{json, Value},
+        solana_account_decoder::UiAccount,
+        solana_client::{
+            rpc_request::RpcRequest,
+            rpc_response::{Response, RpcResponseContext},
+        },
+        solana_sdk::{
+            loader_upgradeable_instruction::UpgradeableLoaderInstruction, signature::Signature,
+        },
+        std::collections::HashMap,
     };
 
     fn make_tmp_path(name: &str) -> String {
@@ -2366,6 +3528,7 @@ mod tests {
                     max_len: None,
                     allow_excessive_balance: false,
                     skip_fee_check: false,
+                    buffer_only: false,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2393,6 +3556,7 @@ mod tests {
                     max_len: Some(42),
                     allow_excessive_balance: false,
                     skip_fee_check: false,
+                    buffer_only: false,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2422,6 +3586,7 @@ mod tests {
                     max_len: None,
                     allow_excessive_balance: false,
                     skip_fee_check: false,
+                    buffer_only: false,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2453,6 +3618,7 @@ mod tests {
                     max_len: None,
                     allow_excessive_balance: false,
                     skip_fee_check: false,
+                    buffer_only: false,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
@@ -2483,6 +3649,7 @@ mod tests {
                     max_len: None,
                     allow_excessive_balance: false,
                     skip_fee_check: false,
+                    buffer_only: false,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2516,6 +3683,7 @@ mod tests {
                     max_len: None,
                     allow_excessive_balance: false,
                     skip_fee_check: false,
+                    buffer_only: false,
                 }),
                 signers: vec![
                     read_keypair_file(&keypair_file).unwrap().into(),
@@ -2544,11 +3712,51 @@ mod tests {
                     is_final: true,
                     max_len: None,
                     skip_fee_check: false,
+                    buffer_only: false,
                     allow_excessive_balance: false,
                 }),
                 signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
             }
         );
+
+        let program_pubkey = Pubkey::new_unique();
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "deploy",
+            "/Users/test/program.so",
+            "--program-id",
+            &program_pubkey.to_string(),
+            "--buffer-only",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Deploy {
+                    program_location: Some("/Users/test/program.so".to_string()),
+                    buffer_signer_index: None,
+                    buffer_pubkey: None,
+                    program_signer_index: None,
+                    program_pubkey: Some(program_pubkey),
+                    upgrade_authority_signer_index: 0,
+                    is_final: false,
+                    max_len: None,
+                    skip_fee_check: false,
+                    buffer_only: true,
+                    allow_excessive_balance: false,
+                }),
+                signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
+            }
+        );
+        let test_command = test_commands.clone().get_matches_from_safe(vec![
+            "test",
+            "program",
+            "deploy",
+            "/Users/test/program.so",
+            "--buffer-only",
+            "--final",
+        ]);
+        assert!(test_command.is_err());
     }
 
     #[test]
@@ -2724,6 +3932,17 @@ mod tests {
             "--new-upgrade-authority",
             &new_authority_pubkey.to_string(),
         ]);
+        assert!(parse_command(&test_command, &default_signer, &mut None).is_err());
+
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "set-upgrade-authority",
+            &program_pubkey.to_string(),
+            "--new-upgrade-authority",
+            &new_authority_pubkey.to_string(),
+            "--skip-new-upgrade-authority-signer-check",
+        ]);
         assert_eq!(
             parse_command(&test_command, &default_signer, &mut None).unwrap(),
             CliCommandInfo {
@@ -2751,12 +3970,17 @@ mod tests {
         assert_eq!(
             parse_command(&test_command, &default_signer, &mut None).unwrap(),
             CliCommandInfo {
-                command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthority {
+                command: CliCommand::Program(ProgramCliCommand::SetUpgradeAuthorityChecked {
                     program_pubkey,
                     upgrade_authority_index: Some(0),
-                    new_upgrade_authority: Some(new_authority_pubkey.pubkey()),
+                    new_upgrade_authority_index: 1,
                 }),
-                signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
+                signers: vec![
+                    read_keypair_file(&keypair_file).unwrap().into(),
+                    read_keypair_file(&new_authority_pubkey_file)
+                        .unwrap()
+                        .into(),
+                ],
             }
         );
 
@@ -2812,6 +4036,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cli_parse_upgrade() {
+        let test_commands = get_clap_app("test", "desc", "version");
+
+        let default_keypair = Keypair::new();
+        let keypair_file = make_tmp_path("keypair_file");
+        write_keypair_file(&default_keypair, &keypair_file).unwrap();
+        let default_signer = DefaultSigner::new("", &keypair_file);
+
+        let program_pubkey = Pubkey::new_unique();
+        let buffer_pubkey = Pubkey::new_unique();
+        let authority = Keypair::new();
+        let authority_keypair_file = make_tmp_path("authority_keypair_file");
+        write_keypair_file(&authority, &authority_keypair_file).unwrap();
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "upgrade",
+            &buffer_pubkey.to_string(),
+            &program_pubkey.to_string(),
+            "--upgrade-authority",
+            &authority_keypair_file,
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Upgrade {
+                    program_pubkey,
+                    buffer_pubkey,
+                    upgrade_authority_index: 1,
+                    spill_pubkey: None,
+                }),
+                signers: vec![
+                    read_keypair_file(&keypair_file).unwrap().into(),
+                    read_keypair_file(&authority_keypair_file).unwrap().into(),
+                ],
+            }
+        );
+
+        let multisig_pubkey = Pubkey::new_unique();
+        let spill_pubkey = Pubkey::new_unique();
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "upgrade",
+            &buffer_pubkey.to_string(),
+            &program_pubkey.to_string(),
+            "--upgrade-authority",
+            &multisig_pubkey.to_string(),
+            "--spill",
+            &spill_pubkey.to_string(),
+            "--print-instruction",
+            "--encoding",
+            "base64",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::PrintUpgradeInstruction {
+                    program_pubkey,
+                    buffer_pubkey,
+                    upgrade_authority_pubkey: Some(multisig_pubkey),
+                    spill_pubkey: Some(spill_pubkey),
+                    encoding: TransactionBinaryEncoding::Base64,
+                }),
+                signers: vec![],
+            }
+        );
+
+        // An upgrade authority that is not a signer can only be printed
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "upgrade",
+            &buffer_pubkey.to_string(),
+            &program_pubkey.to_string(),
+            "--upgrade-authority",
+            &multisig_pubkey.to_string(),
+        ]);
+        assert!(parse_command(&test_command, &default_signer, &mut None).is_err());
+    }
+
     #[test]
     #[allow(clippy::cognitive_complexity)]
     fn test_cli_parse_set_buffer_authority() {
@@ -2999,6 +4305,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cli_parse_verify() {
+        let test_commands = get_clap_app("test", "desc", "version");
+
+        let default_keypair = Keypair::new();
+        let keypair_file = make_tmp_path("keypair_file");
+        write_keypair_file(&default_keypair, &keypair_file).unwrap();
+        let default_signer = DefaultSigner::new("", &keypair_file);
+
+        let program_pubkey = Pubkey::new_unique();
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "verify",
+            &program_pubkey.to_string(),
+            "--so",
+            "/Users/test/program.so",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Verify {
+                    program_pubkey,
+                    program_location: "/Users/test/program.so".to_string(),
+                    attestation_location: None,
+                }),
+                signers: vec![],
+            }
+        );
+
+        let test_command = test_commands.clone().get_matches_from(vec![
+            "test",
+            "program",
+            "verify",
+            &program_pubkey.to_string(),
+            "--so",
+            "/Users/test/program.so",
+            "--attestation",
+            "/Users/test/attestation.json",
+        ]);
+        assert_eq!(
+            parse_command(&test_command, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Program(ProgramCliCommand::Verify {
+                    program_pubkey,
+                    program_location: "/Users/test/program.so".to_string(),
+                    attestation_location: Some("/Users/test/attestation.json".to_string()),
+                }),
+                signers: vec![read_keypair_file(&keypair_file).unwrap().into()],
+            }
+        );
+    }
+
     #[test]
     #[allow(clippy::cognitive_complexity)]
     fn test_cli_parse_close() {
@@ -3164,6 +4523,7 @@ mod tests {
                 max_len: None,
                 allow_excessive_balance: false,
                 skip_fee_check: false,
+                buffer_only: false,
             }),
             signers: vec![&default_keypair],
             output_format: OutputFormat::JsonCompact,
@@ -3185,4 +4545,224 @@ mod tests {
             program_pubkey.pubkey()
         );
     }
+
+    #[test]
+    fn test_create_write_messages() {
+        let buffer_pubkey = Pubkey::new_unique();
+        let authority_pubkey = Pubkey::new_unique();
+        let create_msg = |offset: u32, bytes: Vec<u8>| {
+            Message::new(
+                &[bpf_loader_upgradeable::write(
+                    &buffer_pubkey,
+                    &authority_pubkey,
+                    offset,
+                    bytes,
+                )],
+                Some(&authority_pubkey),
+            )
+        };
+        let chunk_size = calculate_max_chunk_size(&create_msg, &[]).unwrap();
+        let program_data: Vec<u8> = (0..chunk_size * 3 + 10).map(|i| i as u8).collect();
+        let write_offsets = |buffer_data: &[u8]| {
+            create_write_messages(&program_data, buffer_data, &create_msg, &[])
+                .unwrap()
+                .iter()
+                .map(
+                    |message| match bincode::deserialize(&message.instructions[0].data).unwrap() {
+                        UpgradeableLoaderInstruction::Write { offset, .. } => offset as usize,
+                        instruction => panic!("unexpected instruction {:?}", instruction),
+                    },
+                )
+                .collect::<Vec<_>>()
+        };
+
+        // A new buffer gets all chunks
+        let all_offsets = vec![0, chunk_size, chunk_size * 2, chunk_size * 3];
+        assert_eq!(write_offsets(&[]), all_offsets);
+        assert_eq!(write_offsets(&vec![0; program_data.len()]), all_offsets);
+
+        // A buffer interrupted after its second chunk only gets the chunks after it, and the
+        // chunks that differ
+        let mut buffer_data = vec![0; program_data.len()];
+        buffer_data[..chunk_size * 2].copy_from_slice(&program_data[..chunk_size * 2]);
+        assert_eq!(
+            write_offsets(&buffer_data),
+            vec![chunk_size * 2, chunk_size * 3]
+        );
+        buffer_data[1] = 0xff;
+        assert_eq!(
+            write_offsets(&buffer_data),
+            vec![0, chunk_size * 2, chunk_size * 3]
+        );
+        assert!(write_offsets(&program_data).is_empty());
+
+        // Only the data after the metadata of a buffer account is program data
+        let mut data = bincode::serialize(&UpgradeableLoaderState::Buffer {
+            authority_address: Some(authority_pubkey),
+        })
+        .unwrap();
+        data.extend_from_slice(&program_data);
+        let account = Account {
+            lamports: 1,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        assert_eq!(
+            get_buffer_program_data(&bpf_loader_upgradeable::id(), &account),
+            &program_data[..]
+        );
+        assert!(get_buffer_program_data(&bpf_loader::id(), &account).is_empty());
+    }
+
+    #[test]
+    fn test_write_chunk_size_with_lookup_tables() {
+        let buffer_pubkey = Pubkey::new_unique();
+        let authority_pubkey = Pubkey::new_unique();
+        let create_msg = |offset: u32, bytes: Vec<u8>| {
+            Message::new(
+                &[bpf_loader_upgradeable::write(
+                    &buffer_pubkey,
+                    &authority_pubkey,
+                    offset,
+                    bytes,
+                )],
+                Some(&authority_pubkey),
+            )
+        };
+        // Full chunks fit in a packet whether the lookup tables hold an account of the writes
+        // or not
+        for lookup_table_address in [buffer_pubkey, Pubkey::new_unique()] {
+            let lookup_table_accounts = [AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![lookup_table_address],
+            }];
+            let chunk_size = calculate_max_chunk_size(&create_msg, &lookup_table_accounts).unwrap();
+            let transaction = compile_unsigned_transaction(
+                create_msg(0, vec![0; chunk_size]),
+                &lookup_table_accounts,
+            )
+            .unwrap();
+            assert!(transaction.message.address_table_lookups().is_some());
+            assert!(bincode::serialized_size(&transaction).unwrap() as usize <= PACKET_DATA_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_resume_buffer() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let program_location = dir.path().join("program.so");
+        fs::write(&program_location, [1; 10]).unwrap();
+        let program_location = program_location.to_str().unwrap();
+        let keypair_file = dir.path().join("program-buffer.json");
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let authority = Keypair::new();
+        let config = CliConfig {
+            signers: vec![&authority],
+            ..CliConfig::default()
+        };
+
+        // A new buffer keypair is saved before any write, and reused by the next run
+        let resume_buffer =
+            ResumeBuffer::load_or_create(&rpc_client, &config, program_location, &authority)
+                .unwrap();
+        assert_eq!(resume_buffer.keypair_file, keypair_file);
+        assert_eq!(
+            read_keypair_file(&keypair_file).unwrap(),
+            resume_buffer.keypair
+        );
+        let buffer_pubkey = resume_buffer.keypair.pubkey();
+        let resume_buffer =
+            ResumeBuffer::load_or_create(&rpc_client, &config, program_location, &authority)
+                .unwrap();
+        assert_eq!(resume_buffer.keypair.pubkey(), buffer_pubkey);
+
+        // A buffer that is not the size of the rebuilt program is closed and replaced
+        let buffer_account = Account {
+            lamports: 1,
+            data: vec![0; UpgradeableLoaderState::size_of_buffer(5)],
+            owner: bpf_loader_upgradeable::id(),
+            ..Account::default()
+        };
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            json!(Response {
+                context: RpcResponseContext {
+                    slot: 1,
+                    api_version: None,
+                },
+                value: UiAccount::encode(
+                    &buffer_pubkey,
+                    &buffer_account,
+                    UiAccountEncoding::Base64,
+                    None,
+                    None,
+                ),
+            }),
+        );
+        let mismatched_rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        let resume_buffer = ResumeBuffer::load_or_create(
+            &mismatched_rpc_client,
+            &config,
+            program_location,
+            &authority,
+        )
+        .unwrap();
+        assert_ne!(resume_buffer.keypair.pubkey(), buffer_pubkey);
+        assert_eq!(
+            read_keypair_file(&keypair_file).unwrap(),
+            resume_buffer.keypair
+        );
+        let buffer_pubkey = resume_buffer.keypair.pubkey();
+
+        // The keypair file is removed once the writes succeed, and the next run starts over
+        resume_buffer.finish(&rpc_client, &config, true);
+        assert!(!keypair_file.exists());
+        let resume_buffer =
+            ResumeBuffer::load_or_create(&rpc_client, &config, program_location, &authority)
+                .unwrap();
+        assert_ne!(resume_buffer.keypair.pubkey(), buffer_pubkey);
+
+        // As it is when a run fails before creating the buffer
+        resume_buffer.finish(&rpc_client, &config, false);
+        assert!(!keypair_file.exists());
+    }
+
+    #[test]
+    fn test_program_hash_and_attestation() {
+        // Padding of the program data account does not change the hash
+        let program_data = vec![1, 0, 2, 3, 0];
+        let mut padded_program_data = program_data.clone();
+        padded_program_data.resize(100, 0);
+        assert_eq!(strip_trailing_zeros(&padded_program_data), &[1, 0, 2, 3]);
+        assert_eq!(strip_trailing_zeros(&[0, 0]), &[] as &[u8]);
+        assert_eq!(
+            get_program_hash(&program_data),
+            get_program_hash(&padded_program_data)
+        );
+        assert_ne!(
+            get_program_hash(&program_data),
+            get_program_hash(&[1, 0, 2])
+        );
+
+        let verifier = Keypair::new();
+        let attestation = ProgramAttestation {
+            program_id: Pubkey::new_unique().to_string(),
+            program_hash: get_program_hash(&program_data),
+            last_deploy_slot: Some(42),
+            authority: Some("none".to_string()),
+            verified_slot: 100,
+            verifier: verifier.pubkey().to_string(),
+        };
+        let signed_attestation = attestation.sign(&verifier).unwrap();
+        let json = serde_json::to_string(&signed_attestation).unwrap();
+        let signed_attestation: SignedProgramAttestation = serde_json::from_str(&json).unwrap();
+        let signature = Signature::from_str(&signed_attestation.signature).unwrap();
+        assert!(signature.verify(
+            verifier.pubkey().as_ref(),
+            &serde_json::to_vec(&signed_attestation.attestation).unwrap()
+        ));
+    }
 }
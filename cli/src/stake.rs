@@ -1,6 +1,6 @@
 use {
     crate::{
-        checks::{check_account_for_fee_with_commitment, check_unique_pubkeys},
+        checks::{check_account_for_versioned_fee_with_commitment, check_unique_pubkeys},
         cli::{
             log_instruction_custom_error, CliCommand, CliCommandInfo, CliConfig, CliError,
             ProcessResult,
@@ -9,6 +9,7 @@ use {
         memo::WithMemo,
         nonce::check_nonce_account,
         spend_utils::{resolve_spend_tx_and_check_account_balances, SpendAmount},
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
     },
     clap::{value_t, App, Arg, ArgGroup, ArgMatches, SubCommand},
     solana_clap_utils::{
@@ -23,7 +24,7 @@ use {
         ArgConstant,
     },
     renec_cli_output::{
-        self, display::BuildBalanceMessageConfig, return_signers_with_config, CliBalance,
+        self, display::BuildBalanceMessageConfig, return_versioned_signers_with_config, CliBalance,
         CliEpochReward, CliStakeHistory, CliStakeHistoryEntry, CliStakeState, CliStakeType,
         OutputFormat, ReturnSignersConfig,
     },
@@ -49,7 +50,6 @@ use {
         stake_history::StakeHistory,
         system_instruction::SystemError,
         sysvar::{clock, stake_history},
-        transaction::Transaction,
     },
     solana_vote_program::vote_state::VoteState,
     std::{ops::Deref, sync::Arc},
@@ -1441,10 +1441,10 @@ pub fn process_create_stake_account(
         }
     }
 
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1559,11 +1559,11 @@ pub fn process_stake_authorize(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1580,9 +1580,9 @@ pub fn process_stake_authorize(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
-            &tx.message.account_keys[0],
+            &tx.message.static_account_keys()[0],
             &tx.message,
             config.commitment,
         )?;
@@ -1705,11 +1705,11 @@ pub fn process_deactivate_stake_account(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1726,9 +1726,9 @@ pub fn process_deactivate_stake_account(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
-            &tx.message.account_keys[0],
+            &tx.message.static_account_keys()[0],
             &tx.message,
             config.commitment,
         )?;
@@ -1804,11 +1804,11 @@ pub fn process_withdraw_stake(
         config.commitment,
     )?;
 
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1825,9 +1825,9 @@ pub fn process_withdraw_stake(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
-            &tx.message.account_keys[0],
+            &tx.message.static_account_keys()[0],
             &tx.message,
             config.commitment,
         )?;
@@ -1950,11 +1950,11 @@ pub fn process_split_stake(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1971,9 +1971,9 @@ pub fn process_split_stake(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
-            &tx.message.account_keys[0],
+            &tx.message.static_account_keys()[0],
             &tx.message,
             config.commitment,
         )?;
@@ -2057,11 +2057,11 @@ pub fn process_merge_stake(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -2078,9 +2078,9 @@ pub fn process_merge_stake(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
-            &tx.message.account_keys[0],
+            &tx.message.static_account_keys()[0],
             &tx.message,
             config.commitment,
         )?;
@@ -2153,11 +2153,11 @@ pub fn process_stake_set_lockup(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -2174,9 +2174,9 @@ pub fn process_stake_set_lockup(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
-            &tx.message.account_keys[0],
+            &tx.message.static_account_keys()[0],
             &tx.message,
             config.commitment,
         )?;
@@ -2624,11 +2624,11 @@ pub fn process_delegate_stake(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -2645,9 +2645,9 @@ pub fn process_delegate_stake(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
-            &tx.message.account_keys[0],
+            &tx.message.static_account_keys()[0],
             &tx.message,
             config.commitment,
         )?;
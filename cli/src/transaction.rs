@@ -0,0 +1,862 @@
+use {
+    crate::{
+        cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
+        compute_unit_price::WithComputeUnitPrice,
+        instruction_file::parse_instructions_file,
+        nonce::check_nonce_account,
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
+    },
+    clap::{App, AppSettings, Arg, ArgMatches, SubCommand},
+    solana_clap_utils::{
+        compute_unit_price::{compute_unit_price_arg, COMPUTE_UNIT_PRICE_ARG},
+        fee_payer::*,
+        input_parsers::*,
+        input_validators::*,
+        keypair::*,
+        nonce::*,
+        offline::*,
+    },
+    renec_cli_output::{
+        return_versioned_signers_data, CliSignature, CliSimulatedTransaction, CliTransactionFile,
+        ReturnSignersConfig,
+    },
+    solana_client::{
+        blockhash_query::BlockhashQuery, nonce_utils, rpc_client::RpcClient,
+        rpc_config::RpcSimulateTransactionConfig,
+    },
+    solana_remote_wallet::remote_wallet::RemoteWalletManager,
+    solana_sdk::{
+        instruction::Instruction,
+        message::{Message, VersionedMessage},
+        pubkey::Pubkey,
+        signature::Signature,
+        transaction::VersionedTransaction,
+    },
+    solana_transaction_status::{EncodedTransaction, TransactionBinaryEncoding},
+    std::{error, fs, sync::Arc},
+};
+
+const INSTRUCTIONS_FILE_HELP: &str = "\
+The instructions file is JSON or YAML, for example:
+
+instructions:
+  - programId: <PROGRAM_ID>
+    accounts:
+      - pubkey: <ADDRESS>
+        isWritable: true
+      - pubkey: <SIGNER, e.g. a keypair file or usb://ledger>
+        isSigner: true
+    data:
+      hex: 0a0b0c
+
+Instruction data is given as `hex`, `base58` or `base64`, or with
+`borsh: {idl: <IDL_FILE>, instruction: <NAME>, args: {...}}` to encode
+the arguments of an instruction of an Anchor IDL.";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TransactionCliCommand {
+    Build {
+        instructions: Vec<Instruction>,
+        fee_payer: SignerIndex,
+        blockhash_query: BlockhashQuery,
+        nonce_account: Option<Pubkey>,
+        nonce_authority: SignerIndex,
+        compute_unit_price: Option<u64>,
+        sign_only: bool,
+        dump_transaction_message: bool,
+        simulate: bool,
+        output_file: Option<String>,
+    },
+    Create {
+        message: VersionedMessage,
+        presigners: Vec<(Pubkey, Signature)>,
+        output_file: String,
+    },
+    Sign {
+        transaction_file: String,
+        signer_index: SignerIndex,
+        output_file: Option<String>,
+    },
+    Combine {
+        transaction_files: Vec<String>,
+        output_file: String,
+    },
+    Broadcast {
+        transaction_file: String,
+    },
+}
+
+pub trait TransactionSubCommands {
+    fn transaction_subcommands(self) -> Self;
+}
+
+fn transaction_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("transaction_file")
+        .index(1)
+        .value_name("TRANSACTION_FILEPATH")
+        .takes_value(true)
+        .required(true)
+        .help("File holding the base64 encoded transaction")
+}
+
+fn output_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("output_file")
+        .long("output-file")
+        .short("o")
+        .value_name("FILEPATH")
+        .takes_value(true)
+}
+
+impl TransactionSubCommands for App<'_, '_> {
+    fn transaction_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("transaction")
+                .about("Build transactions and collect their signatures from several signers")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("build")
+                        .about("Build a transaction from instructions described in a file")
+                        .after_help(INSTRUCTIONS_FILE_HELP)
+                        .arg(
+                            Arg::with_name("instructions_file")
+                                .index(1)
+                                .value_name("INSTRUCTIONS_FILEPATH")
+                                .takes_value(true)
+                                .required(true)
+                                .help(
+                                    "JSON or YAML file describing the instructions, or - for stdin",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("simulate")
+                                .long("simulate")
+                                .takes_value(false)
+                                .conflicts_with(SIGN_ONLY_ARG.name)
+                                .help("Simulate the transaction instead of sending it"),
+                        )
+                        .arg(
+                            output_file_arg()
+                                .conflicts_with_all(&[SIGN_ONLY_ARG.name, "simulate"])
+                                .help(
+                                    "Write the transaction to this file instead of sending it, \
+                                     for signers to add their signatures",
+                                ),
+                        )
+                        .offline_args()
+                        .nonce_args(false)
+                        .arg(fee_payer_arg())
+                        .arg(compute_unit_price_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("create")
+                        .about("Write an unsigned transaction to a file")
+                        .arg(
+                            Arg::with_name("message")
+                                .index(1)
+                                .value_name("MESSAGE")
+                                .takes_value(true)
+                                .required(true)
+                                .help(
+                                    "Base64 encoded transaction message, as displayed by \
+                                     --dump-transaction-message",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name(SIGNER_ARG.name)
+                                .long(SIGNER_ARG.long)
+                                .takes_value(true)
+                                .value_name("PUBKEY=SIGNATURE")
+                                .validator(is_pubkey_sig)
+                                .multiple(true)
+                                .help(SIGNER_ARG.help),
+                        )
+                        .arg(
+                            output_file_arg()
+                                .required(true)
+                                .help("Write the transaction to this file"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("sign")
+                        .about("Add the signature of the default signer to a transaction file")
+                        .arg(transaction_file_arg())
+                        .arg(output_file_arg().help(
+                            "Write the signed transaction to this file \
+                             [default: the transaction file]",
+                        )),
+                )
+                .subcommand(
+                    SubCommand::with_name("combine")
+                        .about("Merge the signatures of transaction files holding the same message")
+                        .arg(
+                            Arg::with_name("transaction_files")
+                                .index(1)
+                                .value_name("TRANSACTION_FILEPATH")
+                                .takes_value(true)
+                                .multiple(true)
+                                .min_values(2)
+                                .required(true)
+                                .help("Files holding the base64 encoded transactions"),
+                        )
+                        .arg(
+                            output_file_arg()
+                                .required(true)
+                                .help("Write the combined transaction to this file"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("broadcast")
+                        .about("Send a transaction file once it holds all of its signatures")
+                        .arg(transaction_file_arg()),
+                ),
+        )
+    }
+}
+
+pub fn parse_transaction_subcommand(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let (subcommand, sub_matches) = matches.subcommand();
+
+    let response = match (subcommand, sub_matches) {
+        ("build", Some(matches)) => {
+            let blockhash_query = BlockhashQuery::new_from_matches(matches);
+            let nonce_account = pubkey_of_signer(matches, NONCE_ARG.name, wallet_manager)?;
+            let (nonce_authority, nonce_authority_pubkey) =
+                signer_of(matches, NONCE_AUTHORITY_ARG.name, wallet_manager)?;
+            let (fee_payer, fee_payer_pubkey) =
+                signer_of(matches, FEE_PAYER_ARG.name, wallet_manager)?;
+
+            let mut bulk_signers = vec![fee_payer];
+            if nonce_account.is_some() {
+                bulk_signers.push(nonce_authority);
+            }
+            let instructions = parse_instructions_file(
+                matches,
+                matches.value_of("instructions_file").unwrap(),
+                wallet_manager,
+                &mut bulk_signers,
+            )?;
+            let signer_info =
+                default_signer.generate_unique_signers(bulk_signers, matches, wallet_manager)?;
+
+            CliCommandInfo {
+                command: CliCommand::Transaction(TransactionCliCommand::Build {
+                    instructions,
+                    fee_payer: signer_info.index_of(fee_payer_pubkey).unwrap(),
+                    blockhash_query,
+                    nonce_account,
+                    nonce_authority: signer_info.index_of(nonce_authority_pubkey).unwrap(),
+                    compute_unit_price: value_of(matches, COMPUTE_UNIT_PRICE_ARG.name),
+                    sign_only: matches.is_present(SIGN_ONLY_ARG.name),
+                    dump_transaction_message: matches.is_present(DUMP_TRANSACTION_MESSAGE.name),
+                    simulate: matches.is_present("simulate"),
+                    output_file: matches.value_of("output_file").map(ToString::to_string),
+                }),
+                signers: signer_info.signers,
+            }
+        }
+        ("create", Some(matches)) => {
+            let message = decode_message(matches.value_of("message").unwrap())?;
+            let presigners = pubkeys_sigs_of(matches, SIGNER_ARG.name).unwrap_or_default();
+
+            CliCommandInfo {
+                command: CliCommand::Transaction(TransactionCliCommand::Create {
+                    message,
+                    presigners,
+                    output_file: matches.value_of("output_file").unwrap().to_string(),
+                }),
+                signers: vec![],
+            }
+        }
+        ("sign", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Transaction(TransactionCliCommand::Sign {
+                transaction_file: matches.value_of("transaction_file").unwrap().to_string(),
+                signer_index: 0,
+                output_file: matches.value_of("output_file").map(ToString::to_string),
+            }),
+            signers: vec![default_signer.signer_from_path(matches, wallet_manager)?],
+        },
+        ("combine", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Transaction(TransactionCliCommand::Combine {
+                transaction_files: matches
+                    .values_of("transaction_files")
+                    .unwrap()
+                    .map(ToString::to_string)
+                    .collect(),
+                output_file: matches.value_of("output_file").unwrap().to_string(),
+            }),
+            signers: vec![],
+        },
+        ("broadcast", Some(matches)) => CliCommandInfo {
+            command: CliCommand::Transaction(TransactionCliCommand::Broadcast {
+                transaction_file: matches.value_of("transaction_file").unwrap().to_string(),
+            }),
+            signers: vec![],
+        },
+        _ => unreachable!(),
+    };
+    Ok(response)
+}
+
+fn decode_message(encoded_message: &str) -> Result<VersionedMessage, CliError> {
+    let message: VersionedMessage = base64::decode(encoded_message)
+        .ok()
+        .and_then(|message_data| bincode::deserialize(&message_data).ok())
+        .ok_or_else(|| CliError::BadParameter("Unable to decode message".to_string()))?;
+    message
+        .sanitize(false)
+        .map_err(|err| CliError::BadParameter(format!("Invalid message: {}", err)))?;
+    Ok(message)
+}
+
+pub fn process_transaction_subcommand(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    subcommand: &TransactionCliCommand,
+) -> ProcessResult {
+    match subcommand {
+        TransactionCliCommand::Build {
+            instructions,
+            fee_payer,
+            blockhash_query,
+            nonce_account,
+            nonce_authority,
+            compute_unit_price,
+            sign_only,
+            dump_transaction_message,
+            simulate,
+            output_file,
+        } => process_build_transaction(
+            rpc_client,
+            config,
+            instructions,
+            *fee_payer,
+            blockhash_query,
+            nonce_account.as_ref(),
+            *nonce_authority,
+            compute_unit_price.as_ref(),
+            *sign_only,
+            *dump_transaction_message,
+            *simulate,
+            output_file.as_deref(),
+        ),
+        TransactionCliCommand::Create {
+            message,
+            presigners,
+            output_file,
+        } => process_create_transaction(config, message, presigners, output_file),
+        TransactionCliCommand::Sign {
+            transaction_file,
+            signer_index,
+            output_file,
+        } => process_sign_transaction(
+            config,
+            transaction_file,
+            *signer_index,
+            output_file.as_deref(),
+        ),
+        TransactionCliCommand::Combine {
+            transaction_files,
+            output_file,
+        } => process_combine_transactions(config, transaction_files, output_file),
+        TransactionCliCommand::Broadcast { transaction_file } => {
+            process_broadcast_transaction(rpc_client, config, transaction_file)
+        }
+    }
+}
+
+fn read_transaction_file(path: &str) -> Result<VersionedTransaction, Box<dyn error::Error>> {
+    let encoded_transaction =
+        fs::read_to_string(path).map_err(|err| format!("Unable to read {}: {}", path, err))?;
+    let transaction = EncodedTransaction::Binary(
+        encoded_transaction.trim().to_string(),
+        TransactionBinaryEncoding::Base64,
+    )
+    .decode()
+    .ok_or_else(|| format!("Unable to decode the transaction in {}", path))?;
+    Ok(transaction)
+}
+
+fn write_transaction_file(
+    path: &str,
+    transaction: &VersionedTransaction,
+) -> Result<(), Box<dyn error::Error>> {
+    let encoded_transaction = base64::encode(bincode::serialize(transaction)?);
+    fs::write(path, encoded_transaction + "\n")
+        .map_err(|err| format!("Unable to write {}: {}", path, err))?;
+    Ok(())
+}
+
+fn signer_position(transaction: &VersionedTransaction, pubkey: &Pubkey) -> Result<usize, CliError> {
+    transaction.message.static_account_keys()[..transaction.signatures.len()]
+        .iter()
+        .position(|key| key == pubkey)
+        .ok_or_else(|| {
+            CliError::BadParameter(format!("{} is not a signer of the transaction", pubkey))
+        })
+}
+
+/// Set the signature of `pubkey`, which must be valid for the message of `transaction`
+fn add_signature(
+    transaction: &mut VersionedTransaction,
+    pubkey: &Pubkey,
+    signature: Signature,
+) -> Result<(), CliError> {
+    let position = signer_position(transaction, pubkey)?;
+    if !signature.verify(pubkey.as_ref(), &transaction.message.serialize()) {
+        return Err(CliError::BadParameter(format!(
+            "Invalid signature for {}",
+            pubkey
+        )));
+    }
+    transaction.signatures[position] = signature;
+    Ok(())
+}
+
+fn transaction_file_output(
+    config: &CliConfig,
+    path: &str,
+    transaction: &VersionedTransaction,
+) -> String {
+    config.output_format.formatted_string(&CliTransactionFile {
+        file: path.to_string(),
+        sign_only_data: return_versioned_signers_data(transaction, &ReturnSignersConfig::default()),
+    })
+}
+
+fn check_signatures(transaction: &VersionedTransaction) -> Result<(), CliError> {
+    let signers_data = return_versioned_signers_data(transaction, &ReturnSignersConfig::default());
+    if !signers_data.absent.is_empty() {
+        return Err(CliError::BadParameter(format!(
+            "Transaction is missing signatures from: {}",
+            signers_data.absent.join(", ")
+        )));
+    }
+    if !signers_data.bad_sig.is_empty() {
+        return Err(CliError::BadParameter(format!(
+            "Transaction holds invalid signatures from: {}",
+            signers_data.bad_sig.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn process_build_transaction(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    instructions: &[Instruction],
+    fee_payer: SignerIndex,
+    blockhash_query: &BlockhashQuery,
+    nonce_account: Option<&Pubkey>,
+    nonce_authority: SignerIndex,
+    compute_unit_price: Option<&u64>,
+    sign_only: bool,
+    dump_transaction_message: bool,
+    simulate: bool,
+    output_file: Option<&str>,
+) -> ProcessResult {
+    let recent_blockhash = blockhash_query.get_blockhash(rpc_client, config.commitment)?;
+    let fee_payer = config.signers[fee_payer];
+    let nonce_authority = config.signers[nonce_authority];
+
+    let instructions = instructions
+        .to_vec()
+        .with_compute_unit_price(compute_unit_price);
+    let message = if let Some(nonce_account) = nonce_account {
+        Message::new_with_nonce(
+            instructions,
+            Some(&fee_payer.pubkey()),
+            nonce_account,
+            &nonce_authority.pubkey(),
+        )
+    } else {
+        Message::new(&instructions, Some(&fee_payer.pubkey()))
+    };
+    let mut transaction = new_unsigned_transaction(rpc_client, config, message)?;
+    transaction.try_partial_sign(&config.signers, recent_blockhash)?;
+
+    if sign_only {
+        return Ok(config
+            .output_format
+            .formatted_string(&return_versioned_signers_data(
+                &transaction,
+                &ReturnSignersConfig {
+                    dump_transaction_message,
+                },
+            )));
+    }
+    if let Some(output_file) = output_file {
+        write_transaction_file(output_file, &transaction)?;
+        return Ok(transaction_file_output(config, output_file, &transaction));
+    }
+    if simulate {
+        let result = rpc_client
+            .simulate_transaction_with_config(
+                &transaction,
+                RpcSimulateTransactionConfig {
+                    commitment: Some(config.commitment),
+                    trace: true,
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )?
+            .value;
+        return Ok(config
+            .output_format
+            .formatted_string(&CliSimulatedTransaction { result }));
+    }
+
+    if let Some(nonce_account) = nonce_account {
+        let nonce_account =
+            nonce_utils::get_account_with_commitment(rpc_client, nonce_account, config.commitment)?;
+        check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
+    }
+    check_signatures(&transaction)?;
+    let signature = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
+        &transaction,
+        config.commitment,
+        config.send_transaction_config,
+    )?;
+    Ok(config.output_format.formatted_string(&CliSignature {
+        signature: signature.to_string(),
+    }))
+}
+
+fn process_create_transaction(
+    config: &CliConfig,
+    message: &VersionedMessage,
+    presigners: &[(Pubkey, Signature)],
+    output_file: &str,
+) -> ProcessResult {
+    let mut transaction = VersionedTransaction {
+        signatures: vec![
+            Signature::default();
+            usize::from(message.header().num_required_signatures)
+        ],
+        message: message.clone(),
+    };
+    for (pubkey, signature) in presigners {
+        add_signature(&mut transaction, pubkey, *signature)?;
+    }
+    write_transaction_file(output_file, &transaction)?;
+    Ok(transaction_file_output(config, output_file, &transaction))
+}
+
+fn process_sign_transaction(
+    config: &CliConfig,
+    transaction_file: &str,
+    signer_index: SignerIndex,
+    output_file: Option<&str>,
+) -> ProcessResult {
+    let mut transaction = read_transaction_file(transaction_file)?;
+    let signer = config.signers[signer_index];
+    let pubkey = signer.try_pubkey()?;
+    // Fail before asking a hardware wallet to sign a transaction it has no part in
+    signer_position(&transaction, &pubkey)?;
+    let signature = signer.try_sign_message(&transaction.message.serialize())?;
+    add_signature(&mut transaction, &pubkey, signature)?;
+
+    let output_file = output_file.unwrap_or(transaction_file);
+    write_transaction_file(output_file, &transaction)?;
+    Ok(transaction_file_output(config, output_file, &transaction))
+}
+
+fn process_combine_transactions(
+    config: &CliConfig,
+    transaction_files: &[String],
+    output_file: &str,
+) -> ProcessResult {
+    let mut combined = read_transaction_file(&transaction_files[0])?;
+    for transaction_file in &transaction_files[1..] {
+        let transaction = read_transaction_file(transaction_file)?;
+        if transaction.message != combined.message {
+            return Err(CliError::BadParameter(format!(
+                "{} holds a different transaction than {}",
+                transaction_file, transaction_files[0]
+            ))
+            .into());
+        }
+        for (signature, pubkey) in transaction
+            .signatures
+            .iter()
+            .zip(transaction.message.static_account_keys())
+            .filter(|(signature, _)| **signature != Signature::default())
+        {
+            add_signature(&mut combined, pubkey, *signature)
+                .map_err(|err| CliError::BadParameter(format!("{}: {}", transaction_file, err)))?;
+        }
+    }
+    write_transaction_file(output_file, &combined)?;
+    Ok(transaction_file_output(config, output_file, &combined))
+}
+
+fn process_broadcast_transaction(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    transaction_file: &str,
+) -> ProcessResult {
+    let transaction = read_transaction_file(transaction_file)?;
+    check_signatures(&transaction)?;
+
+    let signature = rpc_client.send_and_confirm_transaction_with_spinner_and_config(
+        &transaction,
+        config.commitment,
+        config.send_transaction_config,
+    )?;
+    Ok(config.output_format.formatted_string(&CliSignature {
+        signature: signature.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{clap_app::get_clap_app, cli::parse_command},
+        solana_sdk::{
+            hash::Hash,
+            instruction::AccountMeta,
+            signature::{read_keypair_file, write_keypair, Keypair, Signer},
+            system_instruction, system_program,
+        },
+        std::io::Write,
+        tempfile::{NamedTempFile, TempDir},
+    };
+
+    fn make_tmp_file() -> (String, NamedTempFile) {
+        let tmp_file = NamedTempFile::new().unwrap();
+        (String::from(tmp_file.path().to_str().unwrap()), tmp_file)
+    }
+
+    fn create_account_message(payer: &Keypair, new_account: &Keypair) -> VersionedMessage {
+        VersionedMessage::Legacy(Message::new_with_blockhash(
+            &[system_instruction::create_account(
+                &payer.pubkey(),
+                &new_account.pubkey(),
+                1,
+                0,
+                &system_program::id(),
+            )],
+            Some(&payer.pubkey()),
+            &Hash::new_unique(),
+        ))
+    }
+
+    #[test]
+    fn test_parse_command() {
+        let test_commands = get_clap_app("test", "desc", "version");
+        let default_keypair = Keypair::new();
+        let (default_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&default_keypair, tmp_file.as_file_mut()).unwrap();
+        let default_signer = DefaultSigner::new("", &default_keypair_file);
+
+        let message = create_account_message(&default_keypair, &Keypair::new());
+        let encoded_message = base64::encode(message.serialize());
+        let signature = Signature::new(&[1; 64]);
+        let presigner = format!("{}={}", default_keypair.pubkey(), signature);
+        let test_create = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transaction",
+            "create",
+            &encoded_message,
+            "--signer",
+            &presigner,
+            "--output-file",
+            "tx",
+        ]);
+        assert_eq!(
+            parse_command(&test_create, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Transaction(TransactionCliCommand::Create {
+                    message,
+                    presigners: vec![(default_keypair.pubkey(), signature)],
+                    output_file: "tx".to_string(),
+                }),
+                signers: vec![],
+            }
+        );
+        let test_create = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transaction",
+            "create",
+            "garbage",
+            "--output-file",
+            "tx",
+        ]);
+        assert!(parse_command(&test_create, &default_signer, &mut None).is_err());
+
+        let test_sign = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transaction",
+            "sign",
+            "tx",
+            "--keypair",
+            &default_keypair_file,
+        ]);
+        assert_eq!(
+            parse_command(&test_sign, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Transaction(TransactionCliCommand::Sign {
+                    transaction_file: "tx".to_string(),
+                    signer_index: 0,
+                    output_file: None,
+                }),
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
+            }
+        );
+
+        let test_combine = test_commands.clone().get_matches_from(vec![
+            "test",
+            "transaction",
+            "combine",
+            "tx1",
+            "tx2",
+            "--output-file",
+            "tx",
+        ]);
+        assert_eq!(
+            parse_command(&test_combine, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Transaction(TransactionCliCommand::Combine {
+                    transaction_files: vec!["tx1".to_string(), "tx2".to_string()],
+                    output_file: "tx".to_string(),
+                }),
+                signers: vec![],
+            }
+        );
+
+        let test_broadcast =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "transaction", "broadcast", "tx"]);
+        assert_eq!(
+            parse_command(&test_broadcast, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Transaction(TransactionCliCommand::Broadcast {
+                    transaction_file: "tx".to_string(),
+                }),
+                signers: vec![],
+            }
+        );
+
+        let program_id = Pubkey::new_unique();
+        let account_keypair = Keypair::new();
+        let (account_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&account_keypair, tmp_file.as_file_mut()).unwrap();
+        let (instructions_file, mut tmp_file) = make_tmp_file();
+        writeln!(
+            tmp_file,
+            "instructions:
+  - programId: {}
+    accounts:
+      - pubkey: {}
+        isSigner: true
+        isWritable: true
+    data:
+      base58: \"2\"",
+            program_id, account_keypair_file
+        )
+        .unwrap();
+        let test_build = test_commands.get_matches_from(vec![
+            "test",
+            "transaction",
+            "build",
+            &instructions_file,
+            "--with-compute-unit-price",
+            "10",
+            "--simulate",
+        ]);
+        assert_eq!(
+            parse_command(&test_build, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::Transaction(TransactionCliCommand::Build {
+                    instructions: vec![Instruction::new_with_bytes(
+                        program_id,
+                        &[1],
+                        vec![AccountMeta::new(account_keypair.pubkey(), true)],
+                    )],
+                    fee_payer: 0,
+                    blockhash_query: BlockhashQuery::default(),
+                    nonce_account: None,
+                    nonce_authority: 0,
+                    compute_unit_price: Some(10),
+                    sign_only: false,
+                    dump_transaction_message: false,
+                    simulate: true,
+                    output_file: None,
+                }),
+                signers: vec![
+                    read_keypair_file(&default_keypair_file).unwrap().into(),
+                    read_keypair_file(&account_keypair_file).unwrap().into(),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_sign_and_combine_transaction_files() {
+        let payer = Keypair::new();
+        let new_account = Keypair::new();
+        let message = create_account_message(&payer, &new_account);
+        let dir = TempDir::new().unwrap();
+        let path = |name: &str| dir.path().join(name).to_str().unwrap().to_string();
+
+        // A bad presigner signature is rejected
+        let mut config = CliConfig::default();
+        let bad_signature = new_account.sign_message(b"other message");
+        assert!(process_create_transaction(
+            &config,
+            &message,
+            &[(new_account.pubkey(), bad_signature)],
+            &path("unsigned"),
+        )
+        .is_err());
+        process_create_transaction(&config, &message, &[], &path("unsigned")).unwrap();
+
+        config.signers = vec![&payer];
+        process_sign_transaction(&config, &path("unsigned"), 0, Some(&path("payer"))).unwrap();
+        config.signers = vec![&new_account];
+        process_sign_transaction(&config, &path("unsigned"), 0, Some(&path("new_account")))
+            .unwrap();
+        let unsigned = read_transaction_file(&path("unsigned")).unwrap();
+        assert!(unsigned
+            .signatures
+            .iter()
+            .all(|s| *s == Signature::default()));
+
+        // A key that is not a signer of the transaction can't sign it
+        let stranger = Keypair::new();
+        config.signers = vec![&stranger];
+        assert!(process_sign_transaction(&config, &path("unsigned"), 0, None).is_err());
+
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let err = process_broadcast_transaction(&rpc_client, &config, &path("payer")).unwrap_err();
+        assert!(err.to_string().contains(&new_account.pubkey().to_string()));
+
+        process_combine_transactions(
+            &config,
+            &[path("payer"), path("new_account")],
+            &path("combined"),
+        )
+        .unwrap();
+        let combined = read_transaction_file(&path("combined")).unwrap();
+        assert_eq!(combined.message, message);
+        assert!(combined
+            .verify_with_results()
+            .into_iter()
+            .all(|valid| valid));
+
+        // Transaction files of another message can't be combined
+        let other_message = create_account_message(&payer, &new_account);
+        process_create_transaction(&config, &other_message, &[], &path("other")).unwrap();
+        assert!(process_combine_transactions(
+            &config,
+            &[path("combined"), path("other")],
+            &path("combined"),
+        )
+        .is_err());
+    }
+}
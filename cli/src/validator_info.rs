@@ -2,6 +2,7 @@ use {
     crate::{
         cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult},
         spend_utils::{resolve_spend_tx_and_check_account_balance, SpendAmount},
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
     },
     bincode::deserialize,
     clap::{App, AppSettings, Arg, ArgMatches, SubCommand},
@@ -24,7 +25,6 @@ use {
         message::Message,
         pubkey::Pubkey,
         signature::{Keypair, Signer},
-        transaction::Transaction,
     },
     std::{error, sync::Arc},
 };
@@ -358,7 +358,7 @@ pub fn process_set_validator_info(
         build_message,
         config.commitment,
     )?;
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     tx.try_sign(&signers, latest_blockhash)?;
     let signature_str = rpc_client.send_and_confirm_transaction_with_spinner(&tx)?;
 
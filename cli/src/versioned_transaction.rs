@@ -0,0 +1,276 @@
+//! Transactions of CLI commands, compiled with the address lookup tables of `--lookup-table`
+use {
+    crate::{address_lookup_table::get_address_lookup_table_accounts, cli::CliConfig},
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        address_lookup_table_account::AddressLookupTableAccount,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::{v0, Message, VersionedMessage},
+        signature::Signature,
+        signer::{signers::Signers, SignerError},
+        transaction::VersionedTransaction,
+    },
+    std::error,
+};
+
+/// Create an unsigned transaction of `message`. The message is compiled into a version 0 message
+/// that loads its accounts from the lookup tables of the config, if there are any.
+pub fn new_unsigned_transaction(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    message: Message,
+) -> Result<VersionedTransaction, Box<dyn error::Error>> {
+    let lookup_table_accounts =
+        get_address_lookup_table_accounts(rpc_client, &config.lookup_tables, config.commitment)?;
+    compile_unsigned_transaction(message, &lookup_table_accounts)
+}
+
+/// Create an unsigned transaction of `message` with lookup tables that were already fetched,
+/// for commands sending many transactions. The message is compiled into a version 0 message
+/// if there are any lookup tables.
+pub fn compile_unsigned_transaction(
+    message: Message,
+    lookup_table_accounts: &[AddressLookupTableAccount],
+) -> Result<VersionedTransaction, Box<dyn error::Error>> {
+    let message = if lookup_table_accounts.is_empty() {
+        VersionedMessage::Legacy(message)
+    } else {
+        let payer = message
+            .account_keys
+            .first()
+            .filter(|_| message.header.num_required_signatures > 0)
+            .ok_or("Transaction has no fee payer")?;
+        VersionedMessage::V0(v0::Message::try_compile(
+            payer,
+            &decompile_instructions(&message),
+            lookup_table_accounts,
+            message.recent_blockhash,
+        )?)
+    };
+    Ok(VersionedTransaction {
+        signatures: vec![
+            Signature::default();
+            usize::from(message.header().num_required_signatures)
+        ],
+        message,
+    })
+}
+
+/// The instructions of `message`, with the account metas they were compiled from
+fn decompile_instructions(message: &Message) -> Vec<Instruction> {
+    let header = &message.header;
+    // Unlike `Message::is_writable`, writable builtin programs and sysvars are not demoted
+    let is_writable = |i: usize| {
+        if i < usize::from(header.num_required_signatures) {
+            i < usize::from(header.num_required_signatures - header.num_readonly_signed_accounts)
+        } else {
+            i < message.account_keys.len() - usize::from(header.num_readonly_unsigned_accounts)
+        }
+    };
+    message
+        .instructions
+        .iter()
+        .map(|instruction| Instruction {
+            program_id: message.account_keys[usize::from(instruction.program_id_index)],
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|i| {
+                    let i = usize::from(*i);
+                    AccountMeta {
+                        pubkey: message.account_keys[i],
+                        is_signer: message.is_signer(i),
+                        is_writable: is_writable(i),
+                    }
+                })
+                .collect(),
+            data: instruction.data.clone(),
+        })
+        .collect()
+}
+
+/// Signing of versioned transactions, which works like that of legacy `Transaction`s
+pub trait SignVersionedTransaction {
+    /// Set the recent blockhash and sign with all of `signers`, which must be signers of the
+    /// message, failing if any signature of the transaction is still missing
+    fn try_sign<T: Signers>(
+        &mut self,
+        signers: &T,
+        recent_blockhash: Hash,
+    ) -> Result<(), SignerError>;
+
+    /// Set the recent blockhash and sign with all of `signers`, which must be signers of the
+    /// message. Changing the blockhash clears the signatures made before.
+    fn try_partial_sign<T: Signers>(
+        &mut self,
+        signers: &T,
+        recent_blockhash: Hash,
+    ) -> Result<(), SignerError>;
+}
+
+impl SignVersionedTransaction for VersionedTransaction {
+    fn try_sign<T: Signers>(
+        &mut self,
+        signers: &T,
+        recent_blockhash: Hash,
+    ) -> Result<(), SignerError> {
+        self.try_partial_sign(signers, recent_blockhash)?;
+        if self
+            .signatures
+            .iter()
+            .any(|signature| *signature == Signature::default())
+        {
+            return Err(SignerError::NotEnoughSigners);
+        }
+        Ok(())
+    }
+
+    fn try_partial_sign<T: Signers>(
+        &mut self,
+        signers: &T,
+        recent_blockhash: Hash,
+    ) -> Result<(), SignerError> {
+        if *self.message.recent_blockhash() != recent_blockhash {
+            self.message.set_recent_blockhash(recent_blockhash);
+            self.signatures.fill(Signature::default());
+        }
+        let signer_keys = &self.message.static_account_keys()
+            [..usize::from(self.message.header().num_required_signatures)];
+        let positions = signers
+            .try_pubkeys()?
+            .iter()
+            .map(|pubkey| {
+                signer_keys
+                    .iter()
+                    .position(|key| key == pubkey)
+                    .ok_or(SignerError::KeypairPubkeyMismatch)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let signatures = signers.try_sign_message(&self.message.serialize())?;
+        for (position, signature) in positions.into_iter().zip(signatures) {
+            self.signatures[position] = signature;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_account_decoder::{UiAccount, UiAccountEncoding},
+        solana_address_lookup_table_program::state::{AddressLookupTable, LookupTableMeta},
+        solana_client::{
+            rpc_request::RpcRequest,
+            rpc_response::{Response, RpcResponseContext},
+        },
+        solana_sdk::{
+            account::Account,
+            pubkey::Pubkey,
+            signature::{Keypair, Signer},
+            system_instruction,
+        },
+        std::{borrow::Cow, collections::HashMap},
+    };
+
+    #[test]
+    fn test_new_unsigned_transaction() {
+        let payer = Keypair::new();
+        let recipients: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let message = Message::new(
+            &system_instruction::transfer_many(
+                &payer.pubkey(),
+                &recipients
+                    .iter()
+                    .map(|recipient| (*recipient, 1))
+                    .collect::<Vec<_>>(),
+            ),
+            Some(&payer.pubkey()),
+        );
+        let blockhash = Hash::new_unique();
+
+        // Without lookup tables, the transaction is a legacy one
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let mut config = CliConfig::default();
+        let mut transaction =
+            new_unsigned_transaction(&rpc_client, &config, message.clone()).unwrap();
+        assert_eq!(
+            transaction.message,
+            VersionedMessage::Legacy(message.clone())
+        );
+        transaction.try_sign(&[&payer], blockhash).unwrap();
+        assert_eq!(transaction.message.recent_blockhash(), &blockhash);
+        assert!(transaction
+            .verify_with_results()
+            .into_iter()
+            .all(|valid| valid));
+
+        // The recipients are loaded from the lookup table
+        let lookup_table_address = Pubkey::new_unique();
+        let lookup_table = AddressLookupTable {
+            meta: LookupTableMeta::default(),
+            addresses: Cow::Borrowed(&recipients),
+        };
+        let lookup_table_account = Account {
+            lamports: 1,
+            data: lookup_table.serialize_for_tests().unwrap(),
+            owner: solana_address_lookup_table_program::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetAccountInfo,
+            serde_json::to_value(Response {
+                context: RpcResponseContext {
+                    slot: 1,
+                    api_version: None,
+                },
+                value: UiAccount::encode(
+                    &lookup_table_address,
+                    &lookup_table_account,
+                    UiAccountEncoding::Base64,
+                    None,
+                    None,
+                ),
+            })
+            .unwrap(),
+        );
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        config.lookup_tables = vec![lookup_table_address];
+        let mut transaction = new_unsigned_transaction(&rpc_client, &config, message).unwrap();
+        match &transaction.message {
+            VersionedMessage::V0(message) => {
+                assert_eq!(
+                    message.account_keys,
+                    vec![payer.pubkey(), solana_sdk::system_program::id()]
+                );
+                assert_eq!(message.address_table_lookups.len(), 1);
+                assert_eq!(
+                    message.address_table_lookups[0].writable_indexes,
+                    vec![0, 1, 2]
+                );
+            }
+            VersionedMessage::Legacy(_) => panic!("expected a version 0 message"),
+        }
+
+        let other = Keypair::new();
+        assert_eq!(
+            transaction.try_sign(&[&other], blockhash),
+            Err(SignerError::KeypairPubkeyMismatch)
+        );
+        transaction.try_sign(&[&payer], blockhash).unwrap();
+        assert!(transaction
+            .verify_with_results()
+            .into_iter()
+            .all(|valid| valid));
+
+        // A new blockhash clears the signatures
+        assert_eq!(
+            transaction.try_sign(&Vec::<&dyn Signer>::new(), Hash::new_unique()),
+            Err(SignerError::NotEnoughSigners)
+        );
+        assert_eq!(transaction.signatures, vec![Signature::default()]);
+    }
+}
@@ -1,6 +1,6 @@
 use {
     crate::{
-        checks::{check_account_for_fee_with_commitment, check_unique_pubkeys},
+        checks::{check_account_for_versioned_fee_with_commitment, check_unique_pubkeys},
         cli::{
             log_instruction_custom_error, CliCommand, CliCommandInfo, CliConfig, CliError,
             ProcessResult,
@@ -10,6 +10,7 @@ use {
         nonce::check_nonce_account,
         spend_utils::{resolve_spend_tx_and_check_account_balances, SpendAmount},
         stake::check_current_authority,
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
     },
     clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand},
     solana_clap_utils::{
@@ -23,7 +24,7 @@ use {
         offline::*,
     },
     renec_cli_output::{
-        return_signers_with_config, CliEpochVotingHistory, CliLockout, CliVoteAccount,
+        return_versioned_signers_with_config, CliEpochVotingHistory, CliLockout, CliVoteAccount,
         ReturnSignersConfig,
     },
     solana_client::{
@@ -34,7 +35,6 @@ use {
     solana_sdk::{
         account::Account, commitment_config::CommitmentConfig, message::Message,
         native_token::lamports_to_sol, pubkey::Pubkey, system_instruction::SystemError,
-        transaction::Transaction,
     },
     solana_vote_program::{
         vote_error::VoteError,
@@ -882,10 +882,10 @@ pub fn process_create_vote_account(
         }
     }
 
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -996,11 +996,11 @@ pub fn process_vote_authorize(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1017,7 +1017,7 @@ pub fn process_vote_authorize(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
             &config.signers[0].pubkey(),
             &tx.message,
@@ -1072,11 +1072,11 @@ pub fn process_vote_update_validator(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1093,7 +1093,7 @@ pub fn process_vote_update_validator(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
             &config.signers[0].pubkey(),
             &tx.message,
@@ -1142,10 +1142,10 @@ pub fn process_vote_update_commission(
     } else {
         Message::new(&ixs, Some(&fee_payer.pubkey()))
     };
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1162,7 +1162,7 @@ pub fn process_vote_update_commission(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
             &config.signers[0].pubkey(),
             &tx.message,
@@ -1332,11 +1332,11 @@ pub fn process_withdraw_from_vote_account(
         }
     }
 
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -1353,9 +1353,9 @@ pub fn process_withdraw_from_vote_account(
             )?;
             check_nonce_account(&nonce_account, &nonce_authority.pubkey(), &recent_blockhash)?;
         }
-        check_account_for_fee_with_commitment(
+        check_account_for_versioned_fee_with_commitment(
             rpc_client,
-            &tx.message.account_keys[0],
+            &tx.message.static_account_keys()[0],
             &tx.message,
             config.commitment,
         )?;
@@ -1411,11 +1411,11 @@ pub fn process_close_vote_account(
     .with_compute_unit_price(compute_unit_price);
 
     let message = Message::new(&ixs, Some(&fee_payer.pubkey()));
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
     tx.try_sign(&config.signers, latest_blockhash)?;
-    check_account_for_fee_with_commitment(
+    check_account_for_versioned_fee_with_commitment(
         rpc_client,
-        &tx.message.account_keys[0],
+        &tx.message.static_account_keys()[0],
         &tx.message,
         config.commitment,
     )?;
@@ -8,6 +8,7 @@ use {
         memo::WithMemo,
         nonce::check_nonce_account,
         spend_utils::{resolve_spend_tx_and_check_account_balances, SpendAmount},
+        versioned_transaction::{new_unsigned_transaction, SignVersionedTransaction},
     },
     clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand},
     solana_account_decoder::{UiAccount, UiAccountEncoding},
@@ -23,8 +24,9 @@ use {
     },
     renec_cli_output::{
         display::{build_balance_message, BuildBalanceMessageConfig},
-        return_signers_with_config, CliAccount, CliBalance, CliSignatureVerificationStatus,
-        CliTransaction, CliTransactionConfirmation, OutputFormat, ReturnSignersConfig,
+        return_versioned_signers_with_config, CliAccount, CliBalance,
+        CliSignatureVerificationStatus, CliTransaction, CliTransactionConfirmation, OutputFormat,
+        ReturnSignersConfig,
     },
     solana_client::{
         blockhash_query::BlockhashQuery, nonce_utils, rpc_client::RpcClient,
@@ -39,7 +41,7 @@ use {
         stake,
         system_instruction::{self, SystemError},
         system_program,
-        transaction::{Transaction, VersionedTransaction},
+        transaction::VersionedTransaction,
     },
     solana_transaction_status::{
         EncodableWithMeta, EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction,
@@ -222,7 +224,7 @@ impl WalletSubCommands for App<'_, '_> {
                     pubkey!(Arg::with_name("to")
                         .index(1)
                         .value_name("RECIPIENT_ADDRESS")
-                        .required(true),
+                        .required_unless("from_csv"),
                         "The account address of recipient. "),
                 )
                 .arg(
@@ -231,7 +233,7 @@ impl WalletSubCommands for App<'_, '_> {
                         .value_name("AMOUNT")
                         .takes_value(true)
                         .validator(is_amount_or_all)
-                        .required(true)
+                        .required_unless("from_csv")
                         .help("The amount to send, in RENEC; accepts keyword ALL"),
                 )
                 .arg(
@@ -269,6 +271,41 @@ impl WalletSubCommands for App<'_, '_> {
                         .takes_value(false)
                         .help("Complete the transfer even if the recipient address is not funded")
                 )
+                .arg(
+                    Arg::with_name("from_csv")
+                        .long("from-csv")
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .conflicts_with_all(&[
+                            "to",
+                            "amount",
+                            "no_wait",
+                            "derived_address_seed",
+                            "allow_unfunded_recipient",
+                            "sign_only",
+                            "blockhash",
+                            "nonce",
+                        ])
+                        .help("Transfer to each recipient of a CSV file with `recipient,amount` rows, \
+                               amounts in RENEC, packing as many transfers per transaction as fit"),
+                )
+                .arg(
+                    Arg::with_name("state_file")
+                        .long("state-file")
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .requires("from_csv")
+                        .help("File recording the progress of the transfers of --from-csv, \
+                               to resume an interrupted run without transferring twice \
+                               [default: the CSV file path with a .state.json suffix]"),
+                )
+                .arg(
+                    Arg::with_name("dry_run")
+                        .long("dry-run")
+                        .takes_value(false)
+                        .requires("from_csv")
+                        .help("Only show the transactions and total cost of the transfers of --from-csv"),
+                )
                 .offline_args()
                 .nonce_args(false)
                 .arg(memo_arg())
@@ -750,11 +787,11 @@ pub fn process_transfer(
         build_message,
         config.commitment,
     )?;
-    let mut tx = Transaction::new_unsigned(message);
+    let mut tx = new_unsigned_transaction(rpc_client, config, message)?;
 
     if sign_only {
         tx.try_partial_sign(&config.signers, recent_blockhash)?;
-        return_signers_with_config(
+        return_versioned_signers_with_config(
             &tx,
             &config.output_format,
             &ReturnSignersConfig {
@@ -19,18 +19,35 @@ use {
     },
     solana_streamer::socket::SocketAddrSpace,
     solana_test_validator::TestValidator,
-    std::{env, fs::File, io::Read, path::PathBuf, str::FromStr},
+    std::{
+        env,
+        fs::{self, File},
+        io::Read,
+        path::PathBuf,
+        str::FromStr,
+    },
+    tempfile::TempDir,
 };
 
+/// Copy the fixture program `name` to a directory of its own, as the keypair of the buffer
+/// written by a deploy is saved next to the program and must not be shared between tests
+fn program_fixture_path(name: &str) -> (TempDir, PathBuf) {
+    let mut fixture_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    fixture_path.push("tests");
+    fixture_path.push("fixtures");
+    fixture_path.push(name);
+    fixture_path.set_extension("so");
+    let program_dir = tempfile::tempdir().unwrap();
+    let program_path = program_dir.path().join(fixture_path.file_name().unwrap());
+    fs::copy(&fixture_path, &program_path).unwrap();
+    (program_dir, program_path)
+}
+
 #[test]
 fn test_cli_program_deploy_non_upgradeable() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
@@ -147,11 +164,7 @@ fn test_cli_program_deploy_non_upgradeable() {
 fn test_cli_program_deploy_no_authority() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
@@ -199,6 +212,7 @@ fn test_cli_program_deploy_no_authority() {
         is_final: true,
         max_len: None,
         skip_fee_check: false,
+        buffer_only: false,
     });
     config.output_format = OutputFormat::JsonCompact;
     let response = process_command(&config);
@@ -225,6 +239,7 @@ fn test_cli_program_deploy_no_authority() {
         is_final: false,
         max_len: None,
         skip_fee_check: false,
+        buffer_only: false,
     });
     process_command(&config).unwrap_err();
 }
@@ -233,11 +248,7 @@ fn test_cli_program_deploy_no_authority() {
 fn test_cli_program_deploy_with_authority() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
@@ -286,6 +297,7 @@ fn test_cli_program_deploy_with_authority() {
         is_final: false,
         max_len: Some(max_len),
         skip_fee_check: false,
+        buffer_only: false,
     });
     config.output_format = OutputFormat::JsonCompact;
     let response = process_command(&config);
@@ -334,6 +346,7 @@ fn test_cli_program_deploy_with_authority() {
         is_final: false,
         max_len: Some(max_len),
         skip_fee_check: false,
+        buffer_only: false,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -376,6 +389,7 @@ fn test_cli_program_deploy_with_authority() {
         is_final: false,
         max_len: Some(max_len),
         skip_fee_check: false,
+        buffer_only: false,
     });
     process_command(&config).unwrap();
     let program_account = rpc_client.get_account(&program_pubkey).unwrap();
@@ -431,6 +445,7 @@ fn test_cli_program_deploy_with_authority() {
         is_final: false,
         max_len: None,
         skip_fee_check: false,
+        buffer_only: false,
     });
     process_command(&config).unwrap();
     let program_account = rpc_client.get_account(&program_pubkey).unwrap();
@@ -506,6 +521,7 @@ fn test_cli_program_deploy_with_authority() {
         is_final: false,
         max_len: None,
         skip_fee_check: false,
+        buffer_only: false,
     });
     process_command(&config).unwrap_err();
 
@@ -522,6 +538,7 @@ fn test_cli_program_deploy_with_authority() {
         is_final: true,
         max_len: None,
         skip_fee_check: false,
+        buffer_only: false,
     });
     let response = process_command(&config);
     let json: Value = serde_json::from_str(&response.unwrap()).unwrap();
@@ -572,11 +589,7 @@ fn test_cli_program_deploy_with_authority() {
 fn test_cli_program_close_program() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
@@ -625,6 +638,7 @@ fn test_cli_program_close_program() {
         is_final: false,
         max_len: Some(max_len),
         skip_fee_check: false,
+        buffer_only: false,
     });
     config.output_format = OutputFormat::JsonCompact;
     process_command(&config).unwrap();
@@ -672,17 +686,8 @@ fn test_cli_program_close_program() {
 fn test_cli_program_write_buffer() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
-
-    let mut noop_large_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_large_path.push("tests");
-    noop_large_path.push("fixtures");
-    noop_large_path.push("noop_large");
-    noop_large_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
+    let (_noop_large_dir, noop_large_path) = program_fixture_path("noop_large");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
@@ -991,6 +996,7 @@ fn test_cli_program_write_buffer() {
         is_final: true,
         max_len: None,
         skip_fee_check: false,
+        buffer_only: false,
     });
     config.output_format = OutputFormat::JsonCompact;
     let error = process_command(&config).unwrap_err();
@@ -1004,11 +1010,7 @@ fn test_cli_program_write_buffer() {
 fn test_cli_program_set_buffer_authority() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
@@ -1119,11 +1121,7 @@ fn test_cli_program_set_buffer_authority() {
 fn test_cli_program_mismatch_buffer_authority() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
@@ -1188,6 +1186,7 @@ fn test_cli_program_mismatch_buffer_authority() {
         is_final: true,
         max_len: None,
         skip_fee_check: false,
+        buffer_only: false,
     });
     process_command(&config).unwrap_err();
 
@@ -1204,6 +1203,7 @@ fn test_cli_program_mismatch_buffer_authority() {
         is_final: true,
         max_len: None,
         skip_fee_check: false,
+        buffer_only: false,
     });
     process_command(&config).unwrap();
 }
@@ -1212,11 +1212,7 @@ fn test_cli_program_mismatch_buffer_authority() {
 fn test_cli_program_show() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
@@ -1321,6 +1317,7 @@ fn test_cli_program_show() {
         is_final: false,
         max_len: Some(max_len),
         skip_fee_check: false,
+        buffer_only: false,
     });
     config.output_format = OutputFormat::JsonCompact;
     let min_slot = rpc_client.get_slot().unwrap();
@@ -1399,11 +1396,7 @@ fn test_cli_program_show() {
 fn test_cli_program_dump() {
     solana_logger::setup();
 
-    let mut noop_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    noop_path.push("tests");
-    noop_path.push("fixtures");
-    noop_path.push("noop");
-    noop_path.set_extension("so");
+    let (_noop_dir, noop_path) = program_fixture_path("noop");
 
     let mint_keypair = Keypair::new();
     let mint_pubkey = mint_keypair.pubkey();
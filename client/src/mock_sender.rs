@@ -348,8 +348,26 @@ impl RpcSender for MockSender {
                     accounts: None,
                     units_consumed: None,
                     return_data: None,
+                    trace: None,
                 },
             })?,
+            "simulateBundle" => {
+                let transactions = params.as_array().unwrap()[0].as_array().unwrap();
+                serde_json::to_value(Response {
+                    context: RpcResponseContext { slot: 1, api_version: None },
+                    value: vec![
+                        RpcSimulateTransactionResult {
+                            err: None,
+                            logs: None,
+                            accounts: None,
+                            units_consumed: None,
+                            return_data: None,
+                            trace: None,
+                        };
+                        transactions.len()
+                    ],
+                })?
+            }
             "getMinimumBalanceForRentExemption" => json![20],
             "getVersion" => {
                 let version = Version::default();
@@ -1406,6 +1406,158 @@ impl RpcClient {
         .await
     }
 
+    /// Simulate a sequence of transactions, each one seeing the account
+    /// changes made by the ones before it.
+    ///
+    /// This is useful for previewing transactions that depend on each other,
+    /// like creating an account, initializing it and then using it. None of
+    /// the changes are committed to the ledger.
+    ///
+    /// One [`RpcSimulateTransactionResult`] is returned for each transaction
+    /// that was run. The simulation stops at the first transaction that fails,
+    /// in which case its result has the [`err`] field set and the transactions
+    /// after it are not simulated.
+    ///
+    /// [`err`]: crate::rpc_response::RpcSimulateTransactionResult::err
+    ///
+    /// By default, signatures are not verified during simulation. To verify
+    /// signatures, call the [`simulate_bundle_with_config`] method, with the
+    /// [`sig_verify`] field of [`RpcSimulateTransactionConfig`] set to `true`.
+    ///
+    /// [`simulate_bundle_with_config`]: RpcClient::simulate_bundle_with_config
+    /// [`sig_verify`]: crate::rpc_config::RpcSimulateTransactionConfig::sig_verify
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`simulateBundle`] RPC method.
+    ///
+    /// [`simulateBundle`]: https://docs.solana.com/developing/clients/jsonrpc-api#simulatebundle
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     nonblocking::rpc_client::RpcClient,
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// #     system_transaction,
+    /// # };
+    /// # futures::executor::block_on(async {
+    /// #     let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// // Transfer lamports from Alice to Bob, then from Bob to Carol
+    /// #     let alice = Keypair::new();
+    /// #     let bob = Keypair::new();
+    /// #     let carol = Keypair::new();
+    /// #     let lamports = 50;
+    /// let latest_blockhash = rpc_client.get_latest_blockhash().await?;
+    /// let transactions = [
+    ///     system_transaction::transfer(&alice, &bob.pubkey(), lamports, latest_blockhash),
+    ///     system_transaction::transfer(&bob, &carol.pubkey(), lamports, latest_blockhash),
+    /// ];
+    /// let results = rpc_client.simulate_bundle(&transactions).await?;
+    /// assert!(results.value.iter().all(|result| result.err.is_none()));
+    /// #     Ok::<(), ClientError>(())
+    /// # })?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub async fn simulate_bundle(
+        &self,
+        transactions: &[impl SerializableTransaction],
+    ) -> RpcResult<Vec<RpcSimulateTransactionResult>> {
+        self.simulate_bundle_with_config(transactions, RpcSimulateTransactionConfig::default())
+            .await
+    }
+
+    /// Simulate a sequence of transactions, each one seeing the account
+    /// changes made by the ones before it.
+    ///
+    /// Accounts listed in the [`accounts`] field of the
+    /// [`RpcSimulateTransactionConfig`] argument are reported after each
+    /// transaction in the [`accounts`][accounts2] field of its
+    /// [`RpcSimulateTransactionResult`].
+    ///
+    /// [`accounts`]: crate::rpc_config::RpcSimulateTransactionConfig::accounts
+    /// [accounts2]: crate::rpc_response::RpcSimulateTransactionResult::accounts
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`simulateBundle`] RPC method.
+    ///
+    /// [`simulateBundle`]: https://docs.solana.com/developing/clients/jsonrpc-api#simulatebundle
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     nonblocking::rpc_client::RpcClient,
+    /// #     rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// #     system_transaction,
+    /// # };
+    /// # futures::executor::block_on(async {
+    /// #     let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// // Transfer lamports from Alice to Bob, then from Bob to Carol
+    /// #     let alice = Keypair::new();
+    /// #     let bob = Keypair::new();
+    /// #     let carol = Keypair::new();
+    /// #     let lamports = 50;
+    /// let latest_blockhash = rpc_client.get_latest_blockhash().await?;
+    /// let transactions = [
+    ///     system_transaction::transfer(&alice, &bob.pubkey(), lamports, latest_blockhash),
+    ///     system_transaction::transfer(&bob, &carol.pubkey(), lamports, latest_blockhash),
+    /// ];
+    /// let config = RpcSimulateTransactionConfig {
+    ///     sig_verify: true,
+    ///     accounts: Some(RpcSimulateTransactionAccountsConfig {
+    ///         encoding: None,
+    ///         addresses: vec![bob.pubkey().to_string()],
+    ///     }),
+    ///     .. RpcSimulateTransactionConfig::default()
+    /// };
+    /// let results = rpc_client.simulate_bundle_with_config(
+    ///     &transactions,
+    ///     config,
+    /// ).await?;
+    /// assert!(results.value.iter().all(|result| result.err.is_none()));
+    /// #     Ok::<(), ClientError>(())
+    /// # })?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub async fn simulate_bundle_with_config(
+        &self,
+        transactions: &[impl SerializableTransaction],
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<Vec<RpcSimulateTransactionResult>> {
+        let encoding = if let Some(encoding) = config.encoding {
+            encoding
+        } else {
+            self.default_cluster_transaction_encoding().await?
+        };
+        let commitment = config.commitment.unwrap_or_default();
+        let commitment = self.maybe_map_commitment(commitment).await?;
+        let config = RpcSimulateTransactionConfig {
+            encoding: Some(encoding),
+            commitment: Some(commitment),
+            ..config
+        };
+        let serialized_encoded = transactions
+            .iter()
+            .map(|transaction| serialize_and_encode(transaction, encoding))
+            .collect::<ClientResult<Vec<_>>>()?;
+        self.send(
+            RpcRequest::SimulateBundle,
+            json!([serialized_encoded, config]),
+        )
+        .await
+    }
+
     /// Returns the highest slot information that the node has snapshots for.
     ///
     /// This will find the highest full snapshot slot, and the highest incremental snapshot slot
@@ -4272,6 +4424,76 @@ impl RpcClient {
         })
     }
 
+    /// Returns the account information for a list of pubkeys as of a past rooted slot.
+    ///
+    /// The node must have the account history enabled, and `slot` must be
+    /// within its retention window. The commitment of the config does not
+    /// apply, the accounts are returned as of `slot`.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getMultipleAccounts`] RPC method.
+    ///
+    /// [`getMultipleAccounts`]: https://docs.solana.com/developing/clients/jsonrpc-api#getmultipleaccounts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     nonblocking::rpc_client::RpcClient,
+    /// #     rpc_config::RpcAccountInfoConfig,
+    /// #     client_error::ClientError,
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// # };
+    /// # futures::executor::block_on(async {
+    /// #     let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// #     let alice = Keypair::new();
+    /// #     let bob = Keypair::new();
+    /// let pubkeys = vec![alice.pubkey(), bob.pubkey()];
+    /// let slot = 100;
+    /// let accounts = rpc_client.get_multiple_accounts_at_slot(
+    ///     &pubkeys,
+    ///     slot,
+    ///     RpcAccountInfoConfig::default(),
+    /// ).await?;
+    /// #     Ok::<(), ClientError>(())
+    /// # })?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub async fn get_multiple_accounts_at_slot(
+        &self,
+        pubkeys: &[Pubkey],
+        slot: Slot,
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        let config = RpcAccountInfoAtSlotConfig {
+            account_config: RpcAccountInfoConfig {
+                encoding: config.encoding.or(Some(UiAccountEncoding::Base64Zstd)),
+                ..config
+            },
+            slot: Some(slot),
+        };
+        let pubkeys: Vec<_> = pubkeys.iter().map(|pubkey| pubkey.to_string()).collect();
+        let response = self
+            .send(RpcRequest::GetMultipleAccounts, json!([pubkeys, config]))
+            .await?;
+        let Response {
+            context,
+            value: accounts,
+        } = serde_json::from_value::<Response<Vec<Option<UiAccount>>>>(response)?;
+        let accounts: Vec<Option<Account>> = accounts
+            .into_iter()
+            .map(|rpc_account| rpc_account.and_then(|a| a.decode()))
+            .collect();
+        Ok(Response {
+            context,
+            value: accounts,
+        })
+    }
+
     /// Gets the raw data associated with an account.
     ///
     /// This is equivalent to calling [`get_account`] and then accessing the
@@ -4540,6 +4762,7 @@ impl RpcClient {
     ///         min_context_slot: Some(1234),
     ///     },
     ///     with_context: Some(false),
+    ///     ..RpcProgramAccountsConfig::default()
     /// };
     /// let accounts = rpc_client.get_program_accounts_with_config(
     ///     &alice.pubkey(),
@@ -4565,15 +4788,89 @@ impl RpcClient {
         }
 
         let accounts = self
-            .send::<OptionalContext<Vec<RpcKeyedAccount>>>(
+            .send::<OptionalContext<RpcProgramAccounts>>(
                 RpcRequest::GetProgramAccounts,
                 json!([pubkey.to_string(), config]),
             )
             .await?
-            .parse_value();
+            .parse_value()
+            .into_page()
+            .accounts;
         parse_keyed_accounts(accounts, RpcRequest::GetProgramAccounts)
     }
 
+    /// Returns a page of the accounts owned by the provided program pubkey, along with the
+    /// cursor to the next page, or `None` after the last page.
+    ///
+    /// The page holds at most `config.limit` accounts. Pass the returned cursor as
+    /// `config.cursor` to continue from where the page ended; every page is read from the
+    /// same bank as the first one.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getProgramAccounts`] RPC method.
+    ///
+    /// [`getProgramAccounts`]: https://docs.solana.com/developing/clients/jsonrpc-api#getprogramaccounts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     nonblocking::rpc_client::RpcClient,
+    /// #     client_error::ClientError,
+    /// #     rpc_config::RpcProgramAccountsConfig,
+    /// # };
+    /// # use solana_sdk::pubkey::Pubkey;
+    /// # futures::executor::block_on(async {
+    /// #     let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// #     let program_id = Pubkey::new_unique();
+    /// let mut config = RpcProgramAccountsConfig {
+    ///     limit: Some(1_000),
+    ///     ..RpcProgramAccountsConfig::default()
+    /// };
+    /// loop {
+    ///     let (accounts, cursor) = rpc_client
+    ///         .get_program_accounts_page_with_config(&program_id, config.clone())
+    ///         .await?;
+    ///     // process `accounts`
+    ///     if cursor.is_none() {
+    ///         break;
+    ///     }
+    ///     config.cursor = cursor;
+    /// }
+    /// #     Ok::<(), ClientError>(())
+    /// # })?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub async fn get_program_accounts_page_with_config(
+        &self,
+        pubkey: &Pubkey,
+        mut config: RpcProgramAccountsConfig,
+    ) -> ClientResult<(Vec<(Pubkey, Account)>, Option<String>)> {
+        let commitment = config
+            .account_config
+            .commitment
+            .unwrap_or_else(|| self.commitment());
+        let commitment = self.maybe_map_commitment(commitment).await?;
+        config.account_config.commitment = Some(commitment);
+        if let Some(filters) = config.filters {
+            config.filters = Some(self.maybe_map_filters(filters).await?);
+        }
+
+        let RpcKeyedAccountsPage { accounts, cursor } = self
+            .send::<OptionalContext<RpcProgramAccounts>>(
+                RpcRequest::GetProgramAccounts,
+                json!([pubkey.to_string(), config]),
+            )
+            .await?
+            .parse_value()
+            .into_page();
+        Ok((
+            parse_keyed_accounts(accounts, RpcRequest::GetProgramAccounts)?,
+            cursor,
+        ))
+    }
+
     /// Returns the stake minimum delegation, in lamports.
     ///
     /// # RPC Reference
@@ -1151,6 +1151,134 @@ impl RpcClient {
         )
     }
 
+    /// Simulate a sequence of transactions, each one seeing the account
+    /// changes made by the ones before it.
+    ///
+    /// This is useful for previewing transactions that depend on each other,
+    /// like creating an account, initializing it and then using it. None of
+    /// the changes are committed to the ledger.
+    ///
+    /// One [`RpcSimulateTransactionResult`] is returned for each transaction
+    /// that was run. The simulation stops at the first transaction that fails,
+    /// in which case its result has the [`err`] field set and the transactions
+    /// after it are not simulated.
+    ///
+    /// [`err`]: crate::rpc_response::RpcSimulateTransactionResult::err
+    ///
+    /// By default, signatures are not verified during simulation. To verify
+    /// signatures, call the [`simulate_bundle_with_config`] method, with the
+    /// [`sig_verify`] field of [`RpcSimulateTransactionConfig`] set to `true`.
+    ///
+    /// [`simulate_bundle_with_config`]: RpcClient::simulate_bundle_with_config
+    /// [`sig_verify`]: crate::rpc_config::RpcSimulateTransactionConfig::sig_verify
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`simulateBundle`] RPC method.
+    ///
+    /// [`simulateBundle`]: https://docs.solana.com/developing/clients/jsonrpc-api#simulatebundle
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// #     system_transaction,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// // Transfer lamports from Alice to Bob, then from Bob to Carol
+    /// # let alice = Keypair::new();
+    /// # let bob = Keypair::new();
+    /// # let carol = Keypair::new();
+    /// # let lamports = 50;
+    /// let latest_blockhash = rpc_client.get_latest_blockhash()?;
+    /// let transactions = [
+    ///     system_transaction::transfer(&alice, &bob.pubkey(), lamports, latest_blockhash),
+    ///     system_transaction::transfer(&bob, &carol.pubkey(), lamports, latest_blockhash),
+    /// ];
+    /// let results = rpc_client.simulate_bundle(&transactions)?;
+    /// assert!(results.value.iter().all(|result| result.err.is_none()));
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn simulate_bundle(
+        &self,
+        transactions: &[impl SerializableTransaction],
+    ) -> RpcResult<Vec<RpcSimulateTransactionResult>> {
+        self.invoke(self.rpc_client.simulate_bundle(transactions))
+    }
+
+    /// Simulate a sequence of transactions, each one seeing the account
+    /// changes made by the ones before it.
+    ///
+    /// Accounts listed in the [`accounts`] field of the
+    /// [`RpcSimulateTransactionConfig`] argument are reported after each
+    /// transaction in the [`accounts`][accounts2] field of its
+    /// [`RpcSimulateTransactionResult`].
+    ///
+    /// [`accounts`]: crate::rpc_config::RpcSimulateTransactionConfig::accounts
+    /// [accounts2]: crate::rpc_response::RpcSimulateTransactionResult::accounts
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`simulateBundle`] RPC method.
+    ///
+    /// [`simulateBundle`]: https://docs.solana.com/developing/clients/jsonrpc-api#simulatebundle
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     client_error::ClientError,
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// #     system_transaction,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// // Transfer lamports from Alice to Bob, then from Bob to Carol
+    /// # let alice = Keypair::new();
+    /// # let bob = Keypair::new();
+    /// # let carol = Keypair::new();
+    /// # let lamports = 50;
+    /// let latest_blockhash = rpc_client.get_latest_blockhash()?;
+    /// let transactions = [
+    ///     system_transaction::transfer(&alice, &bob.pubkey(), lamports, latest_blockhash),
+    ///     system_transaction::transfer(&bob, &carol.pubkey(), lamports, latest_blockhash),
+    /// ];
+    /// let config = RpcSimulateTransactionConfig {
+    ///     sig_verify: true,
+    ///     accounts: Some(RpcSimulateTransactionAccountsConfig {
+    ///         encoding: None,
+    ///         addresses: vec![bob.pubkey().to_string()],
+    ///     }),
+    ///     .. RpcSimulateTransactionConfig::default()
+    /// };
+    /// let results = rpc_client.simulate_bundle_with_config(
+    ///     &transactions,
+    ///     config,
+    /// )?;
+    /// assert!(results.value.iter().all(|result| result.err.is_none()));
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn simulate_bundle_with_config(
+        &self,
+        transactions: &[impl SerializableTransaction],
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<Vec<RpcSimulateTransactionResult>> {
+        self.invoke(
+            self.rpc_client
+                .simulate_bundle_with_config(transactions, config),
+        )
+    }
+
     /// Returns the highest slot information that the node has snapshots for.
     ///
     /// This will find the highest full snapshot slot, and the highest incremental snapshot slot
@@ -3552,6 +3680,54 @@ impl RpcClient {
         )
     }
 
+    /// Returns the account information for a list of pubkeys as of a past rooted slot.
+    ///
+    /// The node must have the account history enabled, and `slot` must be
+    /// within its retention window. The commitment of the config does not
+    /// apply, the accounts are returned as of `slot`.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getMultipleAccounts`] RPC method.
+    ///
+    /// [`getMultipleAccounts`]: https://docs.solana.com/developing/clients/jsonrpc-api#getmultipleaccounts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     rpc_client::RpcClient,
+    /// #     rpc_config::RpcAccountInfoConfig,
+    /// #     client_error::ClientError,
+    /// # };
+    /// # use solana_sdk::{
+    /// #     signature::Signer,
+    /// #     signer::keypair::Keypair,
+    /// # };
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let alice = Keypair::new();
+    /// # let bob = Keypair::new();
+    /// let pubkeys = vec![alice.pubkey(), bob.pubkey()];
+    /// let slot = 100;
+    /// let accounts = rpc_client.get_multiple_accounts_at_slot(
+    ///     &pubkeys,
+    ///     slot,
+    ///     RpcAccountInfoConfig::default(),
+    /// )?;
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_multiple_accounts_at_slot(
+        &self,
+        pubkeys: &[Pubkey],
+        slot: Slot,
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        self.invoke(
+            self.rpc_client
+                .get_multiple_accounts_at_slot(pubkeys, slot, config),
+        )
+    }
+
     /// Gets the raw data associated with an account.
     ///
     /// This is equivalent to calling [`get_account`] and then accessing the
@@ -3771,6 +3947,7 @@ impl RpcClient {
     ///         min_context_slot: Some(1234),
     ///     },
     ///     with_context: Some(false),
+    ///     ..RpcProgramAccountsConfig::default()
     /// };
     /// let accounts = rpc_client.get_program_accounts_with_config(
     ///     &alice.pubkey(),
@@ -3789,6 +3966,56 @@ impl RpcClient {
         )
     }
 
+    /// Returns a page of the accounts owned by the provided program pubkey, along with the
+    /// cursor to the next page, or `None` after the last page.
+    ///
+    /// The page holds at most `config.limit` accounts. Pass the returned cursor as
+    /// `config.cursor` to continue from where the page ended; every page is read from the
+    /// same bank as the first one.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method is built on the [`getProgramAccounts`] RPC method.
+    ///
+    /// [`getProgramAccounts`]: https://docs.solana.com/developing/clients/jsonrpc-api#getprogramaccounts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_client::{
+    /// #     rpc_client::RpcClient,
+    /// #     client_error::ClientError,
+    /// #     rpc_config::RpcProgramAccountsConfig,
+    /// # };
+    /// # use solana_sdk::pubkey::Pubkey;
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let program_id = Pubkey::new_unique();
+    /// let mut config = RpcProgramAccountsConfig {
+    ///     limit: Some(1_000),
+    ///     ..RpcProgramAccountsConfig::default()
+    /// };
+    /// loop {
+    ///     let (accounts, cursor) =
+    ///         rpc_client.get_program_accounts_page_with_config(&program_id, config.clone())?;
+    ///     // process `accounts`
+    ///     if cursor.is_none() {
+    ///         break;
+    ///     }
+    ///     config.cursor = cursor;
+    /// }
+    /// # Ok::<(), ClientError>(())
+    /// ```
+    pub fn get_program_accounts_page_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<(Vec<(Pubkey, Account)>, Option<String>)> {
+        self.invoke(
+            self.rpc_client
+                .get_program_accounts_page_with_config(pubkey, config),
+        )
+    }
+
     /// Returns the stake minimum delegation, in lamports.
     ///
     /// # RPC Reference
@@ -4507,6 +4734,8 @@ mod tests {
                             min_context_slot: None,
                         },
                         with_context: None,
+                        limit: None,
+                        cursor: None,
                     },
                 )
                 .unwrap();
@@ -4541,10 +4770,71 @@ mod tests {
                             min_context_slot: None,
                         },
                         with_context: Some(true),
+                        limit: None,
+                        cursor: None,
                     },
                 )
                 .unwrap();
             assert_eq!(expected_result, result);
         }
     }
+
+    #[test]
+    fn test_get_program_accounts_page_with_config() {
+        let program_id = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let account = Account {
+            lamports: 1_000_000,
+            data: vec![],
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let keyed_account = RpcKeyedAccount {
+            pubkey: pubkey.to_string(),
+            account: UiAccount::encode(&pubkey, &account, UiAccountEncoding::Base64, None, None),
+        };
+        let config = RpcProgramAccountsConfig {
+            limit: Some(1),
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        // Test: page with a cursor to the next page
+        {
+            let mocks: Mocks = [(
+                RpcRequest::GetProgramAccounts,
+                serde_json::to_value(OptionalContext::NoContext(RpcProgramAccounts::Page(
+                    RpcKeyedAccountsPage {
+                        accounts: vec![keyed_account.clone()],
+                        cursor: Some("next".to_string()),
+                    },
+                )))
+                .unwrap(),
+            )]
+            .into_iter()
+            .collect();
+            let rpc_client = RpcClient::new_mock_with_mocks("mock_client".to_string(), mocks);
+            let (accounts, cursor) = rpc_client
+                .get_program_accounts_page_with_config(&program_id, config.clone())
+                .unwrap();
+            assert_eq!(accounts, vec![(pubkey, account.clone())]);
+            assert_eq!(cursor, Some("next".to_string()));
+        }
+
+        // Test: all accounts from a node that does not paginate
+        {
+            let mocks: Mocks = [(
+                RpcRequest::GetProgramAccounts,
+                serde_json::to_value(OptionalContext::NoContext(vec![keyed_account])).unwrap(),
+            )]
+            .into_iter()
+            .collect();
+            let rpc_client = RpcClient::new_mock_with_mocks("mock_client".to_string(), mocks);
+            let (accounts, cursor) = rpc_client
+                .get_program_accounts_page_with_config(&program_id, config)
+                .unwrap();
+            assert_eq!(accounts, vec![(pubkey, account)]);
+            assert_eq!(cursor, None);
+        }
+    }
 }
@@ -6,6 +6,7 @@ use {
         commitment_config::{CommitmentConfig, CommitmentLevel},
     },
     solana_transaction_status::{TransactionDetails, UiTransactionEncoding},
+    std::collections::HashMap,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,6 +33,17 @@ pub struct RpcSimulateTransactionAccountsConfig {
     pub addresses: Vec<String>,
 }
 
+/// Replaces the state of an account during a simulation. Fields left out keep the value of the
+/// current account, or of an empty system account if it does not exist.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountOverride {
+    pub lamports: Option<u64>,
+    pub owner: Option<String>, // base-58 encoded pubkey
+    pub data: Option<String>,  // base-64 encoded
+    pub executable: Option<bool>,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSimulateTransactionConfig {
@@ -44,6 +56,11 @@ pub struct RpcSimulateTransactionConfig {
     pub encoding: Option<UiTransactionEncoding>,
     pub accounts: Option<RpcSimulateTransactionAccountsConfig>,
     pub min_context_slot: Option<Slot>,
+    /// Account states to simulate against instead of the bank's, keyed by base-58 encoded pubkey
+    pub account_overrides: Option<HashMap<String, RpcAccountOverride>>,
+    /// Return the tree of instructions invoked by the transaction
+    #[serde(default)]
+    pub trace: bool,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -147,6 +164,16 @@ pub struct RpcAccountInfoConfig {
     pub min_context_slot: Option<Slot>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountInfoAtSlotConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Return the state of the accounts as of this rooted slot, requires the account history
+    /// to be enabled on the node
+    pub slot: Option<Slot>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcProgramAccountsConfig {
@@ -154,6 +181,18 @@ pub struct RpcProgramAccountsConfig {
     #[serde(flatten)]
     pub account_config: RpcAccountInfoConfig,
     pub with_context: Option<bool>,
+    /// Return at most this many accounts, along with a cursor to the next page
+    pub limit: Option<usize>,
+    /// The cursor returned with the previous page. Pages are all read from the bank of the first
+    /// one, whatever the commitment of the later requests.
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramAccountsQuery {
+    pub program_id: String,
+    pub filters: Option<Vec<RpcFilterType>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -24,6 +24,9 @@ pub const JSON_RPC_SERVER_ERROR_TRANSACTION_SIGNATURE_LEN_MISMATCH: i64 = -32013
 pub const JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET: i64 = -32014;
 pub const JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION: i64 = -32015;
 pub const JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+pub const JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_AVAILABLE: i64 = -32017;
+pub const JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_SLOT_NOT_AVAILABLE: i64 = -32018;
+pub const JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_RECORDED: i64 = -32019;
 
 #[derive(Error, Debug)]
 pub enum RpcCustomError {
@@ -65,6 +68,16 @@ pub enum RpcCustomError {
     UnsupportedTransactionVersion(u8),
     #[error("MinContextSlotNotReached")]
     MinContextSlotNotReached { context_slot: Slot },
+    #[error("AccountHistoryNotAvailable")]
+    AccountHistoryNotAvailable,
+    #[error("AccountHistorySlotNotAvailable")]
+    AccountHistorySlotNotAvailable {
+        slot: Slot,
+        first_available_slot: Slot,
+        last_available_slot: Slot,
+    },
+    #[error("AccountHistoryNotRecorded")]
+    AccountHistoryNotRecorded { address: String },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -79,6 +92,13 @@ pub struct MinContextSlotNotReachedErrorData {
     pub context_slot: Slot,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountHistorySlotNotAvailableErrorData {
+    pub first_available_slot: Slot,
+    pub last_available_slot: Slot,
+}
+
 impl From<EncodeError> for RpcCustomError {
     fn from(err: EncodeError) -> Self {
         match err {
@@ -210,6 +230,36 @@ impl From<RpcCustomError> for Error {
                     context_slot,
                 })),
             },
+            RpcCustomError::AccountHistoryNotAvailable => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_AVAILABLE),
+                message: "Account history is not available from this node".to_string(),
+                data: None,
+            },
+            RpcCustomError::AccountHistorySlotNotAvailable {
+                slot,
+                first_available_slot,
+                last_available_slot,
+            } => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_SLOT_NOT_AVAILABLE,
+                ),
+                message: format!(
+                    "Account history not available for slot {}. Available slots: {} to {}",
+                    slot, first_available_slot, last_available_slot,
+                ),
+                data: Some(serde_json::json!(AccountHistorySlotNotAvailableErrorData {
+                    first_available_slot,
+                    last_available_slot,
+                })),
+            },
+            RpcCustomError::AccountHistoryNotRecorded { address } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_RECORDED),
+                message: format!(
+                    "Account history is not recorded for {} on this node",
+                    address
+                ),
+                data: None,
+            },
         }
     }
 }
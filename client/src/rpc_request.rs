@@ -72,6 +72,7 @@ pub enum RpcRequest {
     GetMaxShredInsertSlot,
     GetMinimumBalanceForRentExemption,
     GetMultipleAccounts,
+    GetMultipleProgramAccounts,
     GetProgramAccounts,
     #[deprecated(
         since = "1.9.0",
@@ -111,6 +112,7 @@ pub enum RpcRequest {
     RegisterNode,
     RequestAirdrop,
     SendTransaction,
+    SimulateBundle,
     SimulateTransaction,
     SignVote,
 }
@@ -155,6 +157,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetMaxShredInsertSlot => "getMaxShredInsertSlot",
             RpcRequest::GetMinimumBalanceForRentExemption => "getMinimumBalanceForRentExemption",
             RpcRequest::GetMultipleAccounts => "getMultipleAccounts",
+            RpcRequest::GetMultipleProgramAccounts => "getMultipleProgramAccounts",
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
             RpcRequest::GetRecentBlockhash => "getRecentBlockhash",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
@@ -186,6 +189,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::RegisterNode => "registerNode",
             RpcRequest::RequestAirdrop => "requestAirdrop",
             RpcRequest::SendTransaction => "sendTransaction",
+            RpcRequest::SimulateBundle => "simulateBundle",
             RpcRequest::SimulateTransaction => "simulateTransaction",
             RpcRequest::SignVote => "signVote",
         };
@@ -202,7 +206,9 @@ pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
+pub const MAX_GET_MULTIPLE_PROGRAM_ACCOUNTS_PROGRAMS: usize = 8;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
+pub const MAX_SIMULATE_BUNDLE_TRANSACTIONS: usize = 16;
 
 // Validators that are this number of slots behind are considered delinquent
 pub const DELINQUENT_VALIDATOR_SLOT_DISTANCE: u64 = 128;
@@ -194,6 +194,35 @@ pub struct RpcKeyedAccount {
     pub account: UiAccount,
 }
 
+/// A page of the accounts owned by a program
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcKeyedAccountsPage {
+    pub accounts: Vec<RpcKeyedAccount>,
+    /// Where the next page starts, `None` on the last page
+    pub cursor: Option<String>,
+}
+
+/// The accounts returned by `getProgramAccounts`, as a page if the request set a limit or cursor
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum RpcProgramAccounts {
+    All(Vec<RpcKeyedAccount>),
+    Page(RpcKeyedAccountsPage),
+}
+
+impl RpcProgramAccounts {
+    pub fn into_page(self) -> RpcKeyedAccountsPage {
+        match self {
+            Self::All(accounts) => RpcKeyedAccountsPage {
+                accounts,
+                cursor: None,
+            },
+            Self::Page(page) => page,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SlotInfo {
     pub slot: Slot,
@@ -419,6 +448,20 @@ pub struct RpcSimulateTransactionResult {
     pub accounts: Option<Vec<Option<UiAccount>>>,
     pub units_consumed: Option<u64>,
     pub return_data: Option<UiTransactionReturnData>,
+    pub trace: Option<Vec<RpcInstructionTrace>>,
+}
+
+/// An instruction invoked during a simulated transaction, together with the
+/// instructions it invoked in turn
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcInstructionTrace {
+    pub program_id: String,
+    pub accounts: Vec<String>,
+    pub data: String, // base-58 encoded
+    /// Compute units consumed by this instruction, including the instructions it invoked
+    pub compute_units_consumed: u64,
+    pub inner_instructions: Vec<RpcInstructionTrace>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
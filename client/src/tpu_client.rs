@@ -155,7 +155,7 @@ impl TpuClient {
     /// Send a batch of wire transactions to the current and upcoming leader TPUs according to
     /// fanout size
     /// Returns the last error if all sends fail
-    fn try_send_wire_transaction_batch(
+    pub fn try_send_wire_transaction_batch(
         &self,
         wire_transactions: Vec<Vec<u8>>,
     ) -> TransportResult<()> {
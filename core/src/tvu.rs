@@ -33,7 +33,7 @@ use {
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::{
         blockstore::Blockstore, blockstore_processor::TransactionStatusSender,
-        leader_schedule_cache::LeaderScheduleCache,
+        entry_notifier_service::EntryNotifierSender, leader_schedule_cache::LeaderScheduleCache,
     },
     solana_poh::poh_recorder::PohRecorder,
     solana_rpc::{
@@ -111,6 +111,7 @@ impl Tvu {
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
         turbine_disabled: Arc<AtomicBool>,
         transaction_status_sender: Option<TransactionStatusSender>,
+        entry_notification_sender: Option<EntryNotifierSender>,
         rewards_recorder_sender: Option<RewardsRecorderSender>,
         cache_block_meta_sender: Option<CacheBlockMetaSender>,
         vote_tracker: Arc<VoteTracker>,
@@ -231,6 +232,7 @@ impl Tvu {
             accounts_background_request_sender,
             block_commitment_cache,
             transaction_status_sender,
+            entry_notification_sender,
             rewards_recorder_sender,
             cache_block_meta_sender,
             bank_notification_sender,
@@ -439,6 +441,7 @@ pub mod tests {
             None,
             None,
             None,
+            None,
             Arc::<VoteTracker>::default(),
             retransmit_slots_sender,
             gossip_verified_vote_hash_receiver,
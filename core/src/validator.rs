@@ -27,7 +27,10 @@ use {
     rand::{thread_rng, Rng},
     solana_client::connection_cache::ConnectionCache,
     solana_entry::poh::compute_hash_time_ns,
-    solana_geyser_plugin_manager::geyser_plugin_service::GeyserPluginService,
+    solana_geyser_plugin_manager::{
+        geyser_plugin_manager::GeyserPluginManagerRequest,
+        geyser_plugin_service::GeyserPluginService,
+    },
     solana_gossip::{
         cluster_info::{
             ClusterInfo, Node, DEFAULT_CONTACT_DEBUG_INTERVAL_MILLIS,
@@ -44,6 +47,7 @@ use {
         },
         blockstore_options::{BlockstoreOptions, BlockstoreRecoveryMode, LedgerColumnOptions},
         blockstore_processor::{self, TransactionStatusSender},
+        entry_notifier_service::{EntryNotifierSender, EntryNotifierService},
         leader_schedule::FixedSchedule,
         leader_schedule_cache::LeaderScheduleCache,
     },
@@ -54,6 +58,8 @@ use {
         poh_service::{self, PohService},
     },
     solana_rpc::{
+        account_history_service::{AccountHistory, AccountHistoryService},
+        grpc_streaming_service::{GrpcStreamingConfig, GrpcStreamingService},
         max_slots::MaxSlots,
         optimistically_confirmed_bank_tracker::{
             OptimisticallyConfirmedBank, OptimisticallyConfirmedBankTracker,
@@ -127,8 +133,11 @@ pub struct ValidatorConfig {
     pub account_shrink_paths: Option<Vec<PathBuf>>,
     pub rpc_config: JsonRpcConfig,
     pub geyser_plugin_config_files: Option<Vec<PathBuf>>,
+    pub geyser_plugin_always_enabled: bool,
     pub rpc_addrs: Option<(SocketAddr, SocketAddr)>, // (JsonRpc, JsonRpcPubSub)
     pub pubsub_config: PubSubConfig,
+    pub grpc_streaming_addr: Option<SocketAddr>,
+    pub grpc_streaming_config: GrpcStreamingConfig,
     pub snapshot_config: Option<SnapshotConfig>,
     pub max_ledger_shreds: Option<u64>,
     pub broadcast_stage_type: BroadcastStageType,
@@ -191,8 +200,11 @@ impl Default for ValidatorConfig {
             account_shrink_paths: None,
             rpc_config: JsonRpcConfig::default(),
             geyser_plugin_config_files: None,
+            geyser_plugin_always_enabled: false,
             rpc_addrs: None,
             pubsub_config: PubSubConfig::default(),
+            grpc_streaming_addr: None,
+            grpc_streaming_config: GrpcStreamingConfig::default(),
             snapshot_config: None,
             broadcast_stage_type: BroadcastStageType::Standard,
             turbine_disabled: Arc::<AtomicBool>::default(),
@@ -338,6 +350,8 @@ pub struct Validator {
     validator_exit: Arc<RwLock<Exit>>,
     json_rpc_service: Option<JsonRpcService>,
     pubsub_service: Option<PubSubService>,
+    grpc_streaming_service: Option<GrpcStreamingService>,
+    account_history_service: Option<AccountHistoryService>,
     rpc_completed_slots_service: JoinHandle<()>,
     optimistically_confirmed_bank_tracker: Option<OptimisticallyConfirmedBankTracker>,
     transaction_status_service: Option<TransactionStatusService>,
@@ -360,6 +374,7 @@ pub struct Validator {
     pub bank_forks: Arc<RwLock<BankForks>>,
     pub blockstore: Arc<Blockstore>,
     geyser_plugin_service: Option<GeyserPluginService>,
+    entry_notifier_service: Option<EntryNotifierService>,
     ledger_metric_report_service: LedgerMetricReportService,
     accounts_background_service: AccountsBackgroundService,
     accounts_hash_verifier: AccountsHashVerifier,
@@ -395,6 +410,7 @@ impl Validator {
         use_quic: bool,
         tpu_connection_pool_size: usize,
         tpu_enable_udp: bool,
+        geyser_plugin_request_receiver: Option<Receiver<GeyserPluginManagerRequest>>,
     ) -> Self {
         let id = identity_keypair.pubkey();
         assert_eq!(id, node.info.id);
@@ -412,11 +428,18 @@ impl Validator {
         let mut bank_notification_senders = Vec::new();
 
         let geyser_plugin_service =
-            if let Some(geyser_plugin_config_files) = &config.geyser_plugin_config_files {
+            if config.geyser_plugin_config_files.is_some() || config.geyser_plugin_always_enabled {
                 let (confirmed_bank_sender, confirmed_bank_receiver) = unbounded();
                 bank_notification_senders.push(confirmed_bank_sender);
-                let result =
-                    GeyserPluginService::new(confirmed_bank_receiver, geyser_plugin_config_files);
+                let result = GeyserPluginService::new(
+                    confirmed_bank_receiver,
+                    config
+                        .geyser_plugin_config_files
+                        .as_deref()
+                        .unwrap_or_default(),
+                    config.geyser_plugin_always_enabled,
+                    geyser_plugin_request_receiver,
+                );
                 match result {
                     Ok(geyser_plugin_service) => Some(geyser_plugin_service),
                     Err(err) => {
@@ -513,12 +536,23 @@ impl Validator {
             .as_ref()
             .and_then(|geyser_plugin_service| geyser_plugin_service.get_block_metadata_notifier());
 
+        let entry_notifier = geyser_plugin_service
+            .as_ref()
+            .and_then(|geyser_plugin_service| geyser_plugin_service.get_entry_notifier());
+
         info!(
-            "Geyser plugin: accounts_update_notifier: {} transaction_notifier: {}",
+            "Geyser plugin: accounts_update_notifier: {} transaction_notifier: {} entry_notifier: {}",
             accounts_update_notifier.is_some(),
-            transaction_notifier.is_some()
+            transaction_notifier.is_some(),
+            entry_notifier.is_some()
         );
 
+        let entry_notifier_service =
+            entry_notifier.map(|entry_notifier| EntryNotifierService::new(entry_notifier, &exit));
+        let entry_notification_sender = entry_notifier_service
+            .as_ref()
+            .map(|service| service.sender().clone());
+
         let system_monitor_service = Some(SystemMonitorService::new(
             Arc::clone(&exit),
             !config.no_os_memory_stats_reporting,
@@ -697,6 +731,7 @@ impl Validator {
             &leader_schedule_cache,
             &blockstore_process_options,
             transaction_status_sender.as_ref(),
+            entry_notification_sender.as_ref(),
             cache_block_meta_sender.clone(),
             blockstore_root_scan,
             accounts_background_request_sender.clone(),
@@ -779,6 +814,7 @@ impl Validator {
                 &leader_schedule_cache,
                 &poh_config,
                 Some(poh_timing_point_sender),
+                entry_notification_sender.clone(),
                 exit.clone(),
             )
         };
@@ -806,6 +842,8 @@ impl Validator {
         let (
             json_rpc_service,
             pubsub_service,
+            grpc_streaming_service,
+            account_history_service,
             optimistically_confirmed_bank_tracker,
             bank_notification_sender,
         ) = if let Some((rpc_addr, rpc_pubsub_addr)) = config.rpc_addrs {
@@ -821,6 +859,38 @@ impl Validator {
                 ));
             }
 
+            let grpc_streaming_service = config.grpc_streaming_addr.map(|grpc_streaming_addr| {
+                let (confirmed_bank_sender, confirmed_bank_receiver) = unbounded();
+                bank_notification_senders.push(confirmed_bank_sender);
+                GrpcStreamingService::new(
+                    grpc_streaming_addr,
+                    config.grpc_streaming_config.clone(),
+                    bank_forks.clone(),
+                    blockstore.clone(),
+                    confirmed_bank_receiver,
+                    max_complete_transaction_status_slot.clone(),
+                    &exit,
+                )
+            });
+
+            let account_history =
+                config
+                    .rpc_config
+                    .account_history_retention_slots
+                    .map(|retention_slots| {
+                        Arc::new(AccountHistory::new(
+                            blockstore.clone(),
+                            retention_slots,
+                            config.rpc_config.account_history_addresses.clone(),
+                            bank_forks.read().unwrap().root_bank(),
+                        ))
+                    });
+            let account_history_service = account_history.clone().map(|account_history| {
+                let (confirmed_bank_sender, confirmed_bank_receiver) = unbounded();
+                bank_notification_senders.push(confirmed_bank_sender);
+                AccountHistoryService::new(account_history, confirmed_bank_receiver, &exit)
+            });
+
             let (bank_notification_sender, bank_notification_receiver) = unbounded();
             let confirmed_bank_subscribers = if !bank_notification_senders.is_empty() {
                 Some(Arc::new(RwLock::new(bank_notification_senders)))
@@ -851,6 +921,7 @@ impl Validator {
                     max_complete_transaction_status_slot,
                     max_complete_rewards_slot,
                     prioritization_fee_cache.clone(),
+                    account_history,
                 )),
                 if !config.rpc_config.full_api {
                     None
@@ -868,6 +939,8 @@ impl Validator {
 
                     Some(pubsub_service)
                 },
+                grpc_streaming_service,
+                account_history_service,
                 Some(OptimisticallyConfirmedBankTracker::new(
                     bank_notification_receiver,
                     &exit,
@@ -879,7 +952,7 @@ impl Validator {
                 Some(bank_notification_sender),
             )
         } else {
-            (None, None, None, None)
+            (None, None, None, None, None, None)
         };
 
         if config.halt_at_slot.is_some() {
@@ -1001,6 +1074,7 @@ impl Validator {
             block_commitment_cache,
             config.turbine_disabled.clone(),
             transaction_status_sender.clone(),
+            entry_notification_sender.clone(),
             rewards_recorder_sender,
             cache_block_meta_sender,
             vote_tracker.clone(),
@@ -1078,6 +1152,8 @@ impl Validator {
             serve_repair_service,
             json_rpc_service,
             pubsub_service,
+            grpc_streaming_service,
+            account_history_service,
             rpc_completed_slots_service,
             optimistically_confirmed_bank_tracker,
             transaction_status_service,
@@ -1098,6 +1174,7 @@ impl Validator {
             bank_forks,
             blockstore,
             geyser_plugin_service,
+            entry_notifier_service,
             ledger_metric_report_service,
             accounts_background_service,
             accounts_hash_verifier,
@@ -1157,6 +1234,18 @@ impl Validator {
             pubsub_service.join().expect("pubsub_service");
         }
 
+        if let Some(grpc_streaming_service) = self.grpc_streaming_service {
+            grpc_streaming_service
+                .join()
+                .expect("grpc_streaming_service");
+        }
+
+        if let Some(account_history_service) = self.account_history_service {
+            account_history_service
+                .join()
+                .expect("account_history_service");
+        }
+
         self.rpc_completed_slots_service
             .join()
             .expect("rpc_completed_slots_service");
@@ -1228,6 +1317,12 @@ impl Validator {
             ip_echo_server.shutdown_background();
         }
 
+        if let Some(entry_notifier_service) = self.entry_notifier_service {
+            entry_notifier_service
+                .join()
+                .expect("entry_notifier_service");
+        }
+
         if let Some(geyser_plugin_service) = self.geyser_plugin_service {
             geyser_plugin_service.join().expect("geyser_plugin_service");
         }
@@ -1559,6 +1654,7 @@ pub struct ProcessBlockStore<'a> {
     leader_schedule_cache: &'a LeaderScheduleCache,
     process_options: &'a blockstore_processor::ProcessOptions,
     transaction_status_sender: Option<&'a TransactionStatusSender>,
+    entry_notification_sender: Option<&'a EntryNotifierSender>,
     cache_block_meta_sender: Option<CacheBlockMetaSender>,
     blockstore_root_scan: Option<BlockstoreRootScan>,
     accounts_background_request_sender: AbsRequestSender,
@@ -1578,6 +1674,7 @@ impl<'a> ProcessBlockStore<'a> {
         leader_schedule_cache: &'a LeaderScheduleCache,
         process_options: &'a blockstore_processor::ProcessOptions,
         transaction_status_sender: Option<&'a TransactionStatusSender>,
+        entry_notification_sender: Option<&'a EntryNotifierSender>,
         cache_block_meta_sender: Option<CacheBlockMetaSender>,
         blockstore_root_scan: BlockstoreRootScan,
         accounts_background_request_sender: AbsRequestSender,
@@ -1593,6 +1690,7 @@ impl<'a> ProcessBlockStore<'a> {
             leader_schedule_cache,
             process_options,
             transaction_status_sender,
+            entry_notification_sender,
             cache_block_meta_sender,
             blockstore_root_scan: Some(blockstore_root_scan),
             accounts_background_request_sender,
@@ -1630,6 +1728,7 @@ impl<'a> ProcessBlockStore<'a> {
                 self.leader_schedule_cache,
                 self.process_options,
                 self.transaction_status_sender,
+                self.entry_notification_sender,
                 self.cache_block_meta_sender.as_ref(),
                 &self.accounts_background_request_sender,
             )
@@ -2158,6 +2257,7 @@ mod tests {
             DEFAULT_TPU_USE_QUIC,
             DEFAULT_TPU_CONNECTION_POOL_SIZE,
             DEFAULT_TPU_ENABLE_UDP,
+            None,
         );
 
         assert_eq!(
@@ -2252,6 +2352,7 @@ mod tests {
                     DEFAULT_TPU_USE_QUIC,
                     DEFAULT_TPU_CONNECTION_POOL_SIZE,
                     DEFAULT_TPU_ENABLE_UDP,
+                    None,
                 )
             })
             .collect();
@@ -336,6 +336,7 @@ module.exports = {
     "cli/transfer-tokens",
     "cli/delegate-stake",
     "cli/deploy-a-program",
+    "cli/build-a-transaction",
     "offline-signing",
     "offline-signing/durable-nonce",
     "cli/usage",
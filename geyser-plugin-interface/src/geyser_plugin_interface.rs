@@ -3,9 +3,12 @@
 /// In addition, the dynamic library must export a "C" function _create_plugin which
 /// creates the implementation of the plugin.
 use {
-    solana_sdk::{clock::UnixTimestamp, signature::Signature, transaction::SanitizedTransaction},
+    solana_sdk::{
+        clock::UnixTimestamp, pubkey::Pubkey, signature::Signature,
+        transaction::SanitizedTransaction,
+    },
     solana_transaction_status::{Reward, TransactionStatusMeta},
-    std::{any::Any, error, io},
+    std::{any::Any, collections::HashSet, error, io, ops::RangeInclusive},
     thiserror::Error,
 };
 
@@ -80,6 +83,39 @@ pub enum ReplicaAccountInfoVersions<'a> {
     V0_0_2(&'a ReplicaAccountInfoV2<'a>),
 }
 
+/// Describes the accounts a plugin wants to be notified of. It is obtained
+/// from the plugin once, right after it is loaded, and is evaluated by the
+/// validator before any account information is handed over to the plugin.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountsFilter {
+    /// Accounts owned by any of these programs are selected.
+    pub owners: HashSet<Pubkey>,
+
+    /// Accounts with any of these addresses are selected.
+    pub pubkeys: HashSet<Pubkey>,
+
+    /// If not empty, only accounts whose data length falls in one of these
+    /// ranges are selected.
+    pub data_sizes: Vec<RangeInclusive<usize>>,
+}
+
+impl AccountsFilter {
+    /// Check if an account with the given address, owner and data length
+    /// passes the filter. When neither `owners` nor `pubkeys` is set, every
+    /// address is selected and only `data_sizes` applies.
+    pub fn matches(&self, pubkey: &Pubkey, owner: &Pubkey, data_len: usize) -> bool {
+        let address_selected = (self.owners.is_empty() && self.pubkeys.is_empty())
+            || self.pubkeys.contains(pubkey)
+            || self.owners.contains(owner);
+        let size_selected = self.data_sizes.is_empty()
+            || self
+                .data_sizes
+                .iter()
+                .any(|range| range.contains(&data_len));
+        address_selected && size_selected
+    }
+}
+
 /// Information about a transaction
 #[derive(Clone, Debug)]
 pub struct ReplicaTransactionInfo<'a> {
@@ -133,8 +169,46 @@ pub struct ReplicaBlockInfo<'a> {
     pub block_height: Option<u64>,
 }
 
+/// Extending ReplicaBlockInfo by sending the parent slot, parent blockhash
+/// and the number of executed transactions.
+#[derive(Clone, Debug)]
+pub struct ReplicaBlockInfoV2<'a> {
+    pub parent_slot: u64,
+    pub parent_blockhash: &'a str,
+    pub slot: u64,
+    pub blockhash: &'a str,
+    pub rewards: &'a [Reward],
+    pub block_time: Option<UnixTimestamp>,
+    pub block_height: Option<u64>,
+    pub executed_transaction_count: u64,
+}
+
 pub enum ReplicaBlockInfoVersions<'a> {
     V0_0_1(&'a ReplicaBlockInfo<'a>),
+    V0_0_2(&'a ReplicaBlockInfoV2<'a>),
+}
+
+/// Information about a PoH entry
+#[derive(Clone, Debug)]
+pub struct ReplicaEntryInfo<'a> {
+    /// The slot number of the block containing this Entry
+    pub slot: u64,
+    /// The Entry's index in the block
+    pub index: usize,
+    /// The number of hashes since the previous Entry
+    pub num_hashes: u64,
+    /// The Entry's SHA-256 hash, generated from the previous Entry's hash with
+    /// `hashv(&[hash, transaction_hashes])`
+    pub hash: &'a [u8],
+    /// The number of transactions in the Entry
+    pub num_transactions: u64,
+}
+
+/// A wrapper to future-proof ReplicaEntryInfo handling. To make a change to the structure of
+/// ReplicaEntryInfo, add an new enum variant wrapping a newer version, which will force plugin
+/// implementations to handle the change.
+pub enum ReplicaEntryInfoVersions<'a> {
+    V0_0_1(&'a ReplicaEntryInfo<'a>),
 }
 
 /// Errors returned by plugin calls
@@ -254,12 +328,26 @@ pub trait GeyserPlugin: Any + Send + Sync + std::fmt::Debug {
         Ok(())
     }
 
+    /// Called when an entry is executed.
+    #[allow(unused_variables)]
+    fn notify_entry(&mut self, entry: ReplicaEntryInfoVersions) -> Result<()> {
+        Ok(())
+    }
+
     /// Called when block's metadata is updated.
     #[allow(unused_variables)]
     fn notify_block_metadata(&mut self, blockinfo: ReplicaBlockInfoVersions) -> Result<()> {
         Ok(())
     }
 
+    /// The newest `ReplicaBlockInfoVersions` variant the plugin handles in
+    /// `notify_block_metadata`, e.g. 2 for `V0_0_2`.
+    /// Default is 1 -- plugins written before `ReplicaBlockInfoV2` keep
+    /// receiving `V0_0_1`.
+    fn block_metadata_version(&self) -> u32 {
+        1
+    }
+
     /// Check if the plugin is interested in account data
     /// Default is true -- if the plugin is not interested in
     /// account data, please return false.
@@ -273,4 +361,60 @@ pub trait GeyserPlugin: Any + Send + Sync + std::fmt::Debug {
     fn transaction_notifications_enabled(&self) -> bool {
         false
     }
+
+    /// Check if the plugin is interested in entry data
+    /// Default is false -- if the plugin is interested in
+    /// entry data, return true.
+    fn entry_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    /// Get the filter selecting which account updates are sent to the plugin.
+    /// It is queried once after `on_load`; accounts not passing the filter are
+    /// never passed to `update_account`.
+    /// Default is None -- the plugin is notified of every account update.
+    fn account_notifications_filter(&self) -> Option<AccountsFilter> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accounts_filter_matches() {
+        let owner = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        let filter = AccountsFilter::default();
+        assert!(filter.matches(&other, &other, 0));
+
+        let filter = AccountsFilter {
+            owners: HashSet::from([owner]),
+            pubkeys: HashSet::from([pubkey]),
+            ..AccountsFilter::default()
+        };
+        assert!(filter.matches(&other, &owner, 10));
+        assert!(filter.matches(&pubkey, &other, 10));
+        assert!(!filter.matches(&other, &other, 10));
+
+        let filter = AccountsFilter {
+            owners: HashSet::from([owner]),
+            data_sizes: vec![165..=165, 0..=10],
+            ..AccountsFilter::default()
+        };
+        assert!(filter.matches(&other, &owner, 165));
+        assert!(filter.matches(&other, &owner, 3));
+        assert!(!filter.matches(&other, &owner, 82));
+        assert!(!filter.matches(&other, &other, 165));
+
+        let filter = AccountsFilter {
+            data_sizes: vec![82..=82],
+            ..AccountsFilter::default()
+        };
+        assert!(filter.matches(&other, &other, 82));
+        assert!(!filter.matches(&other, &other, 165));
+    }
 }
@@ -14,6 +14,7 @@ use {
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
         clock::Slot,
+        pubkey::Pubkey,
         signature::Signature,
     },
     std::sync::{Arc, RwLock},
@@ -31,18 +32,39 @@ impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
         account: &AccountSharedData,
         txn_signature: &Option<&Signature>,
     ) {
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        if !plugin_manager.account_selected(&meta.pubkey, account.owner(), account.data().len()) {
+            return;
+        }
+
         if let Some(account_info) =
             self.accountinfo_from_shared_account_data(meta, account, txn_signature)
         {
-            self.notify_plugins_of_account_update(account_info, slot, false);
+            Self::notify_plugins_of_account_update(
+                &mut plugin_manager,
+                account_info,
+                &meta.pubkey,
+                account.owner(),
+                slot,
+                false,
+            );
         }
     }
 
     fn notify_account_restore_from_snapshot(&self, slot: Slot, account: &StoredAccountMeta) {
         let mut measure_all = Measure::start("geyser-plugin-notify-account-restore-all");
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        if !plugin_manager.account_selected(
+            &account.meta.pubkey,
+            &account.account_meta.owner,
+            account.data.len(),
+        ) {
+            return;
+        }
+
         let mut measure_copy = Measure::start("geyser-plugin-copy-stored-account-info");
 
-        let account = self.accountinfo_from_stored_account_meta(account);
+        let account_info = self.accountinfo_from_stored_account_meta(account);
         measure_copy.stop();
 
         inc_new_counter_debug!(
@@ -52,8 +74,15 @@ impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
             100000
         );
 
-        if let Some(account_info) = account {
-            self.notify_plugins_of_account_update(account_info, slot, true);
+        if let Some(account_info) = account_info {
+            Self::notify_plugins_of_account_update(
+                &mut plugin_manager,
+                account_info,
+                &account.meta.pubkey,
+                &account.account_meta.owner,
+                slot,
+                true,
+            );
         }
         measure_all.stop();
 
@@ -137,18 +166,25 @@ impl AccountsUpdateNotifierImpl {
     }
 
     fn notify_plugins_of_account_update(
-        &self,
+        plugin_manager: &mut GeyserPluginManager,
         account: ReplicaAccountInfoV2,
+        pubkey: &Pubkey,
+        owner: &Pubkey,
         slot: Slot,
         is_startup: bool,
     ) {
         let mut measure2 = Measure::start("geyser-plugin-notify_plugins_of_account_update");
-        let mut plugin_manager = self.plugin_manager.write().unwrap();
 
         if plugin_manager.plugins.is_empty() {
             return;
         }
         for plugin in plugin_manager.plugins.iter_mut() {
+            if let Some(account_filter) = plugin.account_filter() {
+                if !account_filter.matches(pubkey, owner, account.data.len()) {
+                    continue;
+                }
+            }
+
             let mut measure = Measure::start("geyser-plugin-update-account");
             match plugin.update_account(
                 ReplicaAccountInfoVersions::V0_0_2(&account),
@@ -5,7 +5,7 @@ use {
     },
     log::*,
     solana_geyser_plugin_interface::geyser_plugin_interface::{
-        ReplicaBlockInfo, ReplicaBlockInfoVersions,
+        ReplicaBlockInfo, ReplicaBlockInfoV2, ReplicaBlockInfoVersions,
     },
     solana_measure::measure::Measure,
     solana_metrics::*,
@@ -23,11 +23,14 @@ impl BlockMetadataNotifier for BlockMetadataNotifierImpl {
     /// Notify the block metadata
     fn notify_block_metadata(
         &self,
+        parent_slot: u64,
+        parent_blockhash: &str,
         slot: u64,
         blockhash: &str,
         rewards: &RwLock<Vec<(Pubkey, RewardInfo)>>,
         block_time: Option<UnixTimestamp>,
         block_height: Option<u64>,
+        executed_transaction_count: u64,
     ) {
         let mut plugin_manager = self.plugin_manager.write().unwrap();
         if plugin_manager.plugins.is_empty() {
@@ -37,9 +40,23 @@ impl BlockMetadataNotifier for BlockMetadataNotifierImpl {
 
         for plugin in plugin_manager.plugins.iter_mut() {
             let mut measure = Measure::start("geyser-plugin-update-slot");
-            let block_info =
-                Self::build_replica_block_info(slot, blockhash, &rewards, block_time, block_height);
-            let block_info = ReplicaBlockInfoVersions::V0_0_1(&block_info);
+            let block_info = Self::build_replica_block_info(
+                parent_slot,
+                parent_blockhash,
+                slot,
+                blockhash,
+                &rewards,
+                block_time,
+                block_height,
+                executed_transaction_count,
+            );
+            let block_info_v1;
+            let block_info = if plugin.block_metadata_version() < 2 {
+                block_info_v1 = Self::build_replica_block_info_v1(&block_info);
+                ReplicaBlockInfoVersions::V0_0_1(&block_info_v1)
+            } else {
+                ReplicaBlockInfoVersions::V0_0_2(&block_info)
+            };
             match plugin.notify_block_metadata(block_info) {
                 Err(err) => {
                     error!(
@@ -83,19 +100,38 @@ impl BlockMetadataNotifierImpl {
             .collect()
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_replica_block_info<'a>(
+        parent_slot: u64,
+        parent_blockhash: &'a str,
         slot: u64,
         blockhash: &'a str,
         rewards: &'a [Reward],
         block_time: Option<UnixTimestamp>,
         block_height: Option<u64>,
-    ) -> ReplicaBlockInfo<'a> {
-        ReplicaBlockInfo {
+        executed_transaction_count: u64,
+    ) -> ReplicaBlockInfoV2<'a> {
+        ReplicaBlockInfoV2 {
+            parent_slot,
+            parent_blockhash,
             slot,
             blockhash,
             rewards,
             block_time,
             block_height,
+            executed_transaction_count,
+        }
+    }
+
+    fn build_replica_block_info_v1<'a>(
+        block_info: &ReplicaBlockInfoV2<'a>,
+    ) -> ReplicaBlockInfo<'a> {
+        ReplicaBlockInfo {
+            slot: block_info.slot,
+            blockhash: block_info.blockhash,
+            rewards: block_info.rewards,
+            block_time: block_info.block_time,
+            block_height: block_info.block_height,
         }
     }
 
@@ -1,14 +1,130 @@
 /// Managing the Geyser plugins
 use {
+    crossbeam_channel::Sender,
     libloading::{Library, Symbol},
     log::*,
-    solana_geyser_plugin_interface::geyser_plugin_interface::GeyserPlugin,
-    std::error::Error,
+    solana_geyser_plugin_interface::geyser_plugin_interface::{AccountsFilter, GeyserPlugin},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        error::Error,
+        fs,
+        ops::{Deref, DerefMut},
+        path::Path,
+    },
 };
 
+/// A plugin loaded by the manager, along with what was negotiated with it
+/// when it was loaded.
+#[derive(Debug)]
+pub struct LoadedGeyserPlugin {
+    name: String,
+    config_file: String,
+    account_filter: Option<AccountsFilter>,
+    plugin: Box<dyn GeyserPlugin>,
+}
+
+impl LoadedGeyserPlugin {
+    /// The name reported by the plugin at load time. Unlike
+    /// `GeyserPlugin::name()`, it remains valid after the plugin library
+    /// is unloaded.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The config file the plugin was loaded with
+    pub fn config_file(&self) -> &str {
+        &self.config_file
+    }
+
+    /// The account filter obtained from the plugin at load time.
+    /// `None` selects every account.
+    pub fn account_filter(&self) -> Option<&AccountsFilter> {
+        self.account_filter.as_ref()
+    }
+}
+
+impl Deref for LoadedGeyserPlugin {
+    type Target = Box<dyn GeyserPlugin>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.plugin
+    }
+}
+
+impl DerefMut for LoadedGeyserPlugin {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.plugin
+    }
+}
+
+/// # Safety
+///
+/// The library must export a `_create_plugin` function returning a `GeyserPlugin`.
+unsafe fn load_plugin_from_library(
+    libpath: &str,
+) -> Result<(Box<dyn GeyserPlugin>, Library), Box<dyn Error>> {
+    type PluginConstructor = unsafe fn() -> *mut dyn GeyserPlugin;
+    let lib = load_library_copy(libpath)?;
+    let constructor: Symbol<PluginConstructor> = lib.get(b"_create_plugin")?;
+    let plugin_raw = constructor();
+    Ok((Box::from_raw(plugin_raw), lib))
+}
+
+/// Load the library at `libpath` from a copy with a unique name.
+///
+/// The dynamic loader returns the library already loaded from the same path
+/// or file instead of loading it again, so a library rebuilt while its
+/// previous version is loaded, as when reloading a plugin, would never be
+/// picked up. The copy is removed once loaded, which unix allows; elsewhere
+/// the removal fails and the copy is left in the temporary directory.
+///
+/// # Safety
+///
+/// Like `Library::new`, this runs the initialization routines of the library.
+unsafe fn load_library_copy(libpath: &str) -> Result<Library, Box<dyn Error>> {
+    let path = Path::new(libpath);
+    let file_stem = path.file_stem().unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let copy = tempfile::Builder::new()
+        .prefix(file_stem)
+        .suffix(&extension)
+        .tempfile()?
+        .into_temp_path();
+    fs::copy(path, &copy).map_err(|err| format!("Failed to copy {}: {}", libpath, err))?;
+    let lib = Library::new(&copy)?;
+    drop(copy);
+    Ok(lib)
+}
+
+/// Requests to manage the plugins of a running validator, for example from
+/// the admin RPC service. Each request carries the sender the result is
+/// returned through.
+pub enum GeyserPluginManagerRequest {
+    ListPlugins {
+        response_sender: Sender<Vec<String>>,
+    },
+    LoadPlugin {
+        config_file: String,
+        response_sender: Sender<Result<String, String>>,
+    },
+    UnloadPlugin {
+        name: String,
+        response_sender: Sender<Result<(), String>>,
+    },
+    ReloadPlugin {
+        name: String,
+        config_file: Option<String>,
+        response_sender: Sender<Result<(), String>>,
+    },
+}
+
 #[derive(Default, Debug)]
 pub struct GeyserPluginManager {
-    pub plugins: Vec<Box<dyn GeyserPlugin>>,
+    pub plugins: Vec<LoadedGeyserPlugin>,
+    /// The plugin libraries, in the same order as `plugins`
     libs: Vec<Library>,
 }
 
@@ -28,18 +144,135 @@ impl GeyserPluginManager {
         &mut self,
         libpath: &str,
         config_file: &str,
-    ) -> Result<(), Box<dyn Error>> {
-        type PluginConstructor = unsafe fn() -> *mut dyn GeyserPlugin;
-        let lib = Library::new(libpath)?;
-        let constructor: Symbol<PluginConstructor> = lib.get(b"_create_plugin")?;
-        let plugin_raw = constructor();
-        let mut plugin = Box::from_raw(plugin_raw);
-        plugin.on_load(config_file)?;
+    ) -> Result<String, Box<dyn Error>> {
+        let (plugin, lib) = load_plugin_from_library(libpath)?;
+        self.add_plugin(plugin, lib, config_file)
+    }
+
+    /// Fire the `on_load()` method of a plugin created from `lib`, then
+    /// manage it. Returns the name of the plugin, which must not be the
+    /// name of a plugin already loaded.
+    pub fn add_plugin(
+        &mut self,
+        plugin: Box<dyn GeyserPlugin>,
+        lib: Library,
+        config_file: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let name = plugin.name().to_string();
+        if self.plugins.iter().any(|plugin| plugin.name() == name) {
+            drop(plugin);
+            return Err(format!("A plugin named {:?} is already loaded", name).into());
+        }
+
+        let plugin = Self::on_load(plugin, config_file)?;
         self.plugins.push(plugin);
         self.libs.push(lib);
+        Ok(name)
+    }
+
+    /// # Safety
+    ///
+    /// Like `load_plugin`, this function loads the dynamically linked library specified in the
+    /// path, to replace the plugin with the given name.
+    pub unsafe fn reload_plugin(
+        &mut self,
+        name: &str,
+        libpath: &str,
+        config_file: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let (plugin, lib) = load_plugin_from_library(libpath)?;
+        self.replace_plugin(name, plugin, lib, config_file)
+    }
+
+    /// Replace the plugin with the given name by a plugin created from
+    /// `lib`, which must have the same name. The new plugin is loaded
+    /// first, so that the previous plugin is kept if it fails to load;
+    /// once it is loaded, the `on_unload()` method of the previous plugin
+    /// is fired and its library unloaded.
+    pub fn replace_plugin(
+        &mut self,
+        name: &str,
+        plugin: Box<dyn GeyserPlugin>,
+        lib: Library,
+        config_file: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let index = self
+            .plugins
+            .iter()
+            .position(|plugin| plugin.name() == name)
+            .ok_or_else(|| format!("No plugin named {:?} is loaded", name))?;
+        if plugin.name() != name {
+            return Err(format!(
+                "The new plugin is named {:?} instead of {:?}",
+                plugin.name(),
+                name
+            )
+            .into());
+        }
+
+        let plugin = Self::on_load(plugin, config_file)?;
+        let mut previous_plugin = std::mem::replace(&mut self.plugins[index], plugin);
+        let previous_lib = std::mem::replace(&mut self.libs[index], lib);
+        info!("Unloading plugin for {:?}", previous_plugin.name());
+        previous_plugin.on_unload();
+        drop(previous_plugin);
+        drop(previous_lib);
         Ok(())
     }
 
+    fn on_load(
+        mut plugin: Box<dyn GeyserPlugin>,
+        config_file: &str,
+    ) -> Result<LoadedGeyserPlugin, Box<dyn Error>> {
+        let name = plugin.name().to_string();
+        plugin.on_load(config_file)?;
+        let account_filter = plugin.account_notifications_filter();
+        if let Some(account_filter) = &account_filter {
+            info!(
+                "Plugin {:?} selects accounts by {} owner(s), {} pubkey(s) and {} data size range(s)",
+                name,
+                account_filter.owners.len(),
+                account_filter.pubkeys.len(),
+                account_filter.data_sizes.len()
+            );
+        }
+        Ok(LoadedGeyserPlugin {
+            name,
+            config_file: config_file.to_string(),
+            account_filter,
+            plugin,
+        })
+    }
+
+    /// Unload the plugin with the given name and its library, firing its
+    /// `on_unload()` method first. Returns the config file the plugin was
+    /// loaded with, or `None` if no such plugin is loaded.
+    ///
+    /// Notifications are delivered while holding the manager lock, so by
+    /// the time a caller holding it gets here no notification to the plugin
+    /// is in flight.
+    pub fn unload_plugin(&mut self, name: &str) -> Option<String> {
+        let index = self
+            .plugins
+            .iter()
+            .position(|plugin| plugin.name() == name)?;
+        let mut plugin = self.plugins.remove(index);
+        info!("Unloading plugin for {:?}", plugin.name());
+        plugin.on_unload();
+        let config_file = plugin.config_file.clone();
+        drop(plugin);
+        drop(self.libs.remove(index));
+        Some(config_file)
+    }
+
+    /// The config file the plugin with the given name was loaded with
+    pub fn plugin_config_file(&self, name: &str) -> Option<&str> {
+        self.plugins
+            .iter()
+            .find(|plugin| plugin.name() == name)
+            .map(|plugin| plugin.config_file())
+    }
+
     /// Unload all plugins and loaded plugin libraries, making sure to fire
     /// their `on_plugin_unload()` methods so they can do any necessary cleanup.
     pub fn unload(&mut self) {
@@ -53,6 +286,14 @@ impl GeyserPluginManager {
         }
     }
 
+    /// The names of the loaded plugins
+    pub fn list_plugins(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .map(|plugin| plugin.name().to_string())
+            .collect()
+    }
+
     /// Check if there is any plugin interested in account data
     pub fn account_data_notifications_enabled(&self) -> bool {
         for plugin in &self.plugins {
@@ -63,6 +304,16 @@ impl GeyserPluginManager {
         false
     }
 
+    /// Check if there is any plugin whose account filter selects the account
+    pub fn account_selected(&self, pubkey: &Pubkey, owner: &Pubkey, data_len: usize) -> bool {
+        self.plugins.iter().any(|plugin| {
+            plugin
+                .account_filter()
+                .map(|account_filter| account_filter.matches(pubkey, owner, data_len))
+                .unwrap_or(true)
+        })
+    }
+
     /// Check if there is any plugin interested in transaction data
     pub fn transaction_notifications_enabled(&self) -> bool {
         for plugin in &self.plugins {
@@ -72,4 +323,253 @@ impl GeyserPluginManager {
         }
         false
     }
+
+    /// Check if there is any plugin interested in entry data
+    pub fn entry_notifications_enabled(&self) -> bool {
+        for plugin in &self.plugins {
+            if plugin.entry_notifications_enabled() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::*,
+        solana_geyser_plugin_interface::geyser_plugin_interface::{
+            GeyserPluginError, Result as PluginResult,
+        },
+        std::{
+            process::Command,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+        },
+    };
+
+    /// A plugin built into the test binary, which counts how many times
+    /// it is unloaded
+    #[derive(Debug, Default)]
+    pub(crate) struct TestPlugin {
+        pub(crate) name: &'static str,
+        pub(crate) fail_on_load: bool,
+        pub(crate) unloaded: Arc<AtomicUsize>,
+    }
+
+    impl GeyserPlugin for TestPlugin {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn on_load(&mut self, _config_file: &str) -> PluginResult<()> {
+            if self.fail_on_load {
+                return Err(GeyserPluginError::ConfigFileReadError {
+                    msg: "invalid config".to_string(),
+                });
+            }
+            Ok(())
+        }
+
+        fn on_unload(&mut self) {
+            self.unloaded.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The library of the test binary, standing for the library of a
+    /// `TestPlugin`
+    pub(crate) fn test_library() -> Library {
+        #[cfg(unix)]
+        let library = libloading::os::unix::Library::this();
+        #[cfg(windows)]
+        let library = libloading::os::windows::Library::this().unwrap();
+        Library::from(library)
+    }
+
+    pub(crate) fn add_test_plugin(
+        plugin_manager: &mut GeyserPluginManager,
+        plugin: TestPlugin,
+        config_file: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        plugin_manager.add_plugin(Box::new(plugin), test_library(), config_file)
+    }
+
+    #[test]
+    fn test_add_and_unload_plugins() {
+        let mut plugin_manager = GeyserPluginManager::new();
+        let unloaded = Arc::new(AtomicUsize::default());
+        assert_eq!(
+            add_test_plugin(
+                &mut plugin_manager,
+                TestPlugin {
+                    name: "a",
+                    unloaded: unloaded.clone(),
+                    ..TestPlugin::default()
+                },
+                "a.json",
+            )
+            .unwrap(),
+            "a"
+        );
+        add_test_plugin(
+            &mut plugin_manager,
+            TestPlugin {
+                name: "b",
+                ..TestPlugin::default()
+            },
+            "b.json",
+        )
+        .unwrap();
+        assert_eq!(plugin_manager.list_plugins(), vec!["a", "b"]);
+        assert_eq!(plugin_manager.plugin_config_file("a"), Some("a.json"));
+
+        // Names are unique
+        assert!(add_test_plugin(
+            &mut plugin_manager,
+            TestPlugin {
+                name: "a",
+                ..TestPlugin::default()
+            },
+            "c.json",
+        )
+        .is_err());
+        assert_eq!(plugin_manager.list_plugins(), vec!["a", "b"]);
+
+        // A plugin failing to load is not added
+        assert!(add_test_plugin(
+            &mut plugin_manager,
+            TestPlugin {
+                name: "c",
+                fail_on_load: true,
+                ..TestPlugin::default()
+            },
+            "c.json",
+        )
+        .is_err());
+        assert_eq!(plugin_manager.list_plugins(), vec!["a", "b"]);
+
+        assert_eq!(
+            plugin_manager.unload_plugin("a"),
+            Some("a.json".to_string())
+        );
+        assert_eq!(unloaded.load(Ordering::Relaxed), 1);
+        assert_eq!(plugin_manager.unload_plugin("a"), None);
+        assert_eq!(plugin_manager.unload_plugin("unknown"), None);
+        assert_eq!(plugin_manager.list_plugins(), vec!["b"]);
+        assert_eq!(plugin_manager.libs.len(), 1);
+    }
+
+    #[test]
+    fn test_replace_plugin() {
+        let mut plugin_manager = GeyserPluginManager::new();
+        let unloaded = Arc::new(AtomicUsize::default());
+        add_test_plugin(
+            &mut plugin_manager,
+            TestPlugin {
+                name: "a",
+                unloaded: unloaded.clone(),
+                ..TestPlugin::default()
+            },
+            "a.json",
+        )
+        .unwrap();
+        let replace = |plugin_manager: &mut GeyserPluginManager, name, plugin: TestPlugin| {
+            plugin_manager.replace_plugin(name, Box::new(plugin), test_library(), "new.json")
+        };
+
+        // The previous plugin is kept when the new one fails to load
+        assert!(replace(
+            &mut plugin_manager,
+            "a",
+            TestPlugin {
+                name: "a",
+                fail_on_load: true,
+                ..TestPlugin::default()
+            },
+        )
+        .is_err());
+        assert!(replace(
+            &mut plugin_manager,
+            "a",
+            TestPlugin {
+                name: "b",
+                ..TestPlugin::default()
+            },
+        )
+        .is_err());
+        assert!(replace(
+            &mut plugin_manager,
+            "unknown",
+            TestPlugin {
+                name: "unknown",
+                ..TestPlugin::default()
+            },
+        )
+        .is_err());
+        assert_eq!(unloaded.load(Ordering::Relaxed), 0);
+        assert_eq!(plugin_manager.list_plugins(), vec!["a"]);
+        assert_eq!(plugin_manager.plugin_config_file("a"), Some("a.json"));
+
+        replace(
+            &mut plugin_manager,
+            "a",
+            TestPlugin {
+                name: "a",
+                ..TestPlugin::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(unloaded.load(Ordering::Relaxed), 1);
+        assert_eq!(plugin_manager.list_plugins(), vec!["a"]);
+        assert_eq!(plugin_manager.plugin_config_file("a"), Some("new.json"));
+        assert_eq!(plugin_manager.libs.len(), 1);
+    }
+
+    /// Build a library at `libpath` whose `version` function returns `version`
+    fn build_versioned_library(libpath: &Path, version: u32) {
+        let source = libpath.with_extension("rs");
+        fs::write(
+            &source,
+            format!(
+                "#[no_mangle] pub extern \"C\" fn version() -> u32 {{ {} }}",
+                version
+            ),
+        )
+        .unwrap();
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let status = Command::new(rustc)
+            .args(["--crate-type", "cdylib", "--crate-name", "versioned", "-o"])
+            .arg(libpath)
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_reload_changed_library() {
+        let dir = tempfile::tempdir().unwrap();
+        let libpath = dir.path().join(libloading::library_filename("versioned"));
+        let libpath = libpath.to_str().unwrap();
+        let version = |lib: &Library| unsafe {
+            let version: Symbol<unsafe extern "C" fn() -> u32> = lib.get(b"version").unwrap();
+            version()
+        };
+
+        build_versioned_library(Path::new(libpath), 1);
+        let lib = unsafe { load_library_copy(libpath) }.unwrap();
+        assert_eq!(version(&lib), 1);
+
+        // The library rebuilt while the previous version is still loaded is
+        // picked up
+        build_versioned_library(Path::new(libpath), 2);
+        let reloaded_lib = unsafe { load_library_copy(libpath) }.unwrap();
+        assert_eq!(version(&reloaded_lib), 2);
+        assert_eq!(version(&lib), 1);
+
+        assert!(unsafe { load_library_copy("missing.so") }.is_err());
+    }
 }
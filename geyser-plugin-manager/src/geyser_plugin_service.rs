@@ -3,11 +3,15 @@ use {
         accounts_update_notifier::AccountsUpdateNotifierImpl,
         block_metadata_notifier::BlockMetadataNotifierImpl,
         block_metadata_notifier_interface::BlockMetadataNotifierLock,
-        geyser_plugin_manager::GeyserPluginManager, slot_status_notifier::SlotStatusNotifierImpl,
-        slot_status_observer::SlotStatusObserver, transaction_notifier::TransactionNotifierImpl,
+        entry_notifier::EntryNotifierImpl,
+        geyser_plugin_manager::{GeyserPluginManager, GeyserPluginManagerRequest},
+        slot_status_notifier::SlotStatusNotifierImpl,
+        slot_status_observer::SlotStatusObserver,
+        transaction_notifier::TransactionNotifierImpl,
     },
-    crossbeam_channel::Receiver,
+    crossbeam_channel::{Receiver, RecvTimeoutError},
     log::*,
+    solana_ledger::entry_notifier_interface::EntryNotifierLock,
     solana_rpc::{
         optimistically_confirmed_bank_tracker::BankNotification,
         transaction_notifier_interface::TransactionNotifierLock,
@@ -17,8 +21,12 @@ use {
         fs::File,
         io::Read,
         path::{Path, PathBuf},
-        sync::{Arc, RwLock},
-        thread,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::Duration,
     },
     thiserror::Error,
 };
@@ -42,6 +50,9 @@ pub enum GeyserPluginServiceError {
 
     #[error("Cannot load plugin shared library")]
     PluginLoadError(String),
+
+    #[error("The plugin is not loaded")]
+    PluginNotLoaded(String),
 }
 
 /// The service managing the Geyser plugin workflow.
@@ -51,6 +62,8 @@ pub struct GeyserPluginService {
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
     transaction_notifier: Option<TransactionNotifierLock>,
     block_metadata_notifier: Option<BlockMetadataNotifierLock>,
+    entry_notifier: Option<EntryNotifierLock>,
+    request_handler: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
 }
 
 impl GeyserPluginService {
@@ -66,10 +79,17 @@ impl GeyserPluginService {
     ///    shall create the implementation of `GeyserPlugin` and returns to the caller.
     ///    The rest of the JSON fields' definition is up to to the concrete plugin implementation
     ///    It is usually used to configure the connection information for the external data store.
+    /// * `geyser_plugin_always_enabled` - Set up the notifiers even if none of the plugins
+    ///    loaded at startup asks for notifications, so that plugins loaded later through
+    ///    `request_receiver` receive them.
+    /// * `request_receiver` - The receiver of requests to list, load, unload and reload
+    ///    plugins while the validator is running.
 
     pub fn new(
         confirmed_bank_receiver: Receiver<BankNotification>,
         geyser_plugin_config_files: &[PathBuf],
+        geyser_plugin_always_enabled: bool,
+        request_receiver: Option<Receiver<GeyserPluginManagerRequest>>,
     ) -> Result<Self, GeyserPluginServiceError> {
         info!(
             "Starting GeyserPluginService from config files: {:?}",
@@ -81,8 +101,11 @@ impl GeyserPluginService {
             Self::load_plugin(&mut plugin_manager, geyser_plugin_config_file)?;
         }
         let account_data_notifications_enabled =
-            plugin_manager.account_data_notifications_enabled();
-        let transaction_notifications_enabled = plugin_manager.transaction_notifications_enabled();
+            plugin_manager.account_data_notifications_enabled() || geyser_plugin_always_enabled;
+        let transaction_notifications_enabled =
+            plugin_manager.transaction_notifications_enabled() || geyser_plugin_always_enabled;
+        let entry_notifications_enabled =
+            plugin_manager.entry_notifications_enabled() || geyser_plugin_always_enabled;
 
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
 
@@ -103,6 +126,13 @@ impl GeyserPluginService {
                 None
             };
 
+        let entry_notifier: Option<EntryNotifierLock> = if entry_notifications_enabled {
+            let entry_notifier = EntryNotifierImpl::new(plugin_manager.clone());
+            Some(Arc::new(RwLock::new(entry_notifier)))
+        } else {
+            None
+        };
+
         let (slot_status_observer, block_metadata_notifier): (
             Option<SlotStatusObserver>,
             Option<BlockMetadataNotifierLock>,
@@ -122,6 +152,13 @@ impl GeyserPluginService {
             (None, None)
         };
 
+        let request_handler = request_receiver.map(|request_receiver| {
+            let exit = Arc::new(AtomicBool::new(false));
+            let thread_hdl =
+                Self::start_request_handler(plugin_manager.clone(), request_receiver, exit.clone());
+            (exit, thread_hdl)
+        });
+
         info!("Started GeyserPluginService");
         Ok(GeyserPluginService {
             slot_status_observer,
@@ -129,13 +166,140 @@ impl GeyserPluginService {
             accounts_update_notifier,
             transaction_notifier,
             block_metadata_notifier,
+            entry_notifier,
+            request_handler,
         })
     }
 
+    fn start_request_handler(
+        plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+        request_receiver: Receiver<GeyserPluginManagerRequest>,
+        exit: Arc<AtomicBool>,
+    ) -> JoinHandle<()> {
+        Builder::new()
+            .name("solGeyserPlugin".to_string())
+            .spawn(move || {
+                while !exit.load(Ordering::Relaxed) {
+                    let request = match request_receiver.recv_timeout(Duration::from_millis(100)) {
+                        Ok(request) => request,
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    };
+                    Self::process_request(&plugin_manager, request);
+                }
+            })
+            .unwrap()
+    }
+
+    fn process_request(
+        plugin_manager: &RwLock<GeyserPluginManager>,
+        request: GeyserPluginManagerRequest,
+    ) {
+        // The requester may have given up waiting, so failures to respond are ignored.
+        match request {
+            GeyserPluginManagerRequest::ListPlugins { response_sender } => {
+                let _ = response_sender.send(plugin_manager.read().unwrap().list_plugins());
+            }
+            GeyserPluginManagerRequest::LoadPlugin {
+                config_file,
+                response_sender,
+            } => {
+                let mut plugin_manager = plugin_manager.write().unwrap();
+                let result = Self::load_plugin(&mut plugin_manager, Path::new(&config_file))
+                    .map_err(|err| Self::request_error_message(&err));
+                let _ = response_sender.send(result);
+            }
+            GeyserPluginManagerRequest::UnloadPlugin {
+                name,
+                response_sender,
+            } => {
+                let mut plugin_manager = plugin_manager.write().unwrap();
+                let result = plugin_manager
+                    .unload_plugin(&name)
+                    .map(|_| ())
+                    .ok_or_else(|| {
+                        Self::request_error_message(&GeyserPluginServiceError::PluginNotLoaded(
+                            name,
+                        ))
+                    });
+                let _ = response_sender.send(result);
+            }
+            GeyserPluginManagerRequest::ReloadPlugin {
+                name,
+                config_file,
+                response_sender,
+            } => {
+                let mut plugin_manager = plugin_manager.write().unwrap();
+                let result = Self::reload_plugin(&mut plugin_manager, name, config_file)
+                    .map_err(|err| Self::request_error_message(&err));
+                let _ = response_sender.send(result);
+            }
+        }
+    }
+
+    /// Reload the plugin with the given name from `config_file`, or from
+    /// the config file it was loaded with. The previous plugin is kept when
+    /// the new one fails to load.
+    fn reload_plugin(
+        plugin_manager: &mut GeyserPluginManager,
+        name: String,
+        config_file: Option<String>,
+    ) -> Result<(), GeyserPluginServiceError> {
+        let previous_config_file = match plugin_manager.plugin_config_file(&name) {
+            Some(previous_config_file) => previous_config_file.to_string(),
+            None => return Err(GeyserPluginServiceError::PluginNotLoaded(name)),
+        };
+        let config_file = config_file.unwrap_or(previous_config_file);
+        let (libpath, config_file) = Self::read_plugin_config(Path::new(&config_file))?;
+
+        unsafe {
+            plugin_manager
+                .reload_plugin(&name, libpath.to_str().unwrap(), &config_file)
+                .map_err(|err| {
+                    GeyserPluginServiceError::PluginLoadError(format!(
+                        "Failed to reload the plugin library: {:?}, error: {:?}",
+                        libpath, err
+                    ))
+                })
+        }
+    }
+
+    fn request_error_message(err: &GeyserPluginServiceError) -> String {
+        match err {
+            GeyserPluginServiceError::CannotOpenConfigFile(msg)
+            | GeyserPluginServiceError::CannotReadConfigFile(msg)
+            | GeyserPluginServiceError::InvalidConfigFileFormat(msg)
+            | GeyserPluginServiceError::PluginLoadError(msg) => msg.clone(),
+            GeyserPluginServiceError::PluginNotLoaded(name) => {
+                format!("No plugin named {:?} is loaded", name)
+            }
+            err => err.to_string(),
+        }
+    }
+
     fn load_plugin(
         plugin_manager: &mut GeyserPluginManager,
         geyser_plugin_config_file: &Path,
-    ) -> Result<(), GeyserPluginServiceError> {
+    ) -> Result<String, GeyserPluginServiceError> {
+        let (libpath, config_file) = Self::read_plugin_config(geyser_plugin_config_file)?;
+
+        unsafe {
+            plugin_manager
+                .load_plugin(libpath.to_str().unwrap(), &config_file)
+                .map_err(|err| {
+                    GeyserPluginServiceError::PluginLoadError(format!(
+                        "Failed to load the plugin library: {:?}, error: {:?}",
+                        libpath, err
+                    ))
+                })
+        }
+    }
+
+    /// Returns the path of the plugin library set in the config file, and
+    /// the path of the config file as a string.
+    fn read_plugin_config(
+        geyser_plugin_config_file: &Path,
+    ) -> Result<(PathBuf, String), GeyserPluginServiceError> {
         let mut file = match File::open(geyser_plugin_config_file) {
             Ok(file) => file,
             Err(err) => {
@@ -183,17 +347,7 @@ impl GeyserPluginService {
             .to_str()
             .ok_or(GeyserPluginServiceError::InvalidPluginPath)?;
 
-        unsafe {
-            let result = plugin_manager.load_plugin(libpath.to_str().unwrap(), config_file);
-            if let Err(err) = result {
-                let msg = format!(
-                    "Failed to load the plugin library: {:?}, error: {:?}",
-                    libpath, err
-                );
-                return Err(GeyserPluginServiceError::PluginLoadError(msg));
-            }
-        }
-        Ok(())
+        Ok((libpath, config_file.to_string()))
     }
 
     pub fn get_accounts_update_notifier(&self) -> Option<AccountsUpdateNotifier> {
@@ -208,11 +362,110 @@ impl GeyserPluginService {
         self.block_metadata_notifier.clone()
     }
 
+    pub fn get_entry_notifier(&self) -> Option<EntryNotifierLock> {
+        self.entry_notifier.clone()
+    }
+
     pub fn join(self) -> thread::Result<()> {
         if let Some(mut slot_status_observer) = self.slot_status_observer {
             slot_status_observer.join()?;
         }
+        if let Some((exit, thread_hdl)) = self.request_handler {
+            exit.store(true, Ordering::Relaxed);
+            thread_hdl.join()?;
+        }
         self.plugin_manager.write().unwrap().unload();
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::geyser_plugin_manager::tests::{add_test_plugin, TestPlugin},
+        crossbeam_channel::{bounded, Sender},
+    };
+
+    fn request<T>(
+        plugin_manager: &RwLock<GeyserPluginManager>,
+        make_request: impl FnOnce(Sender<T>) -> GeyserPluginManagerRequest,
+    ) -> T {
+        let (response_sender, response_receiver) = bounded(1);
+        GeyserPluginService::process_request(plugin_manager, make_request(response_sender));
+        response_receiver.try_recv().unwrap()
+    }
+
+    #[test]
+    fn test_process_request() {
+        let mut plugin_manager = GeyserPluginManager::new();
+        add_test_plugin(
+            &mut plugin_manager,
+            TestPlugin {
+                name: "a",
+                ..TestPlugin::default()
+            },
+            "a.json",
+        )
+        .unwrap();
+        let plugin_manager = RwLock::new(plugin_manager);
+        let missing_config_file = "/nonexistent/geyser-plugin.json".to_string();
+
+        assert_eq!(
+            request(&plugin_manager, |response_sender| {
+                GeyserPluginManagerRequest::ListPlugins { response_sender }
+            }),
+            vec!["a"]
+        );
+
+        assert!(request(&plugin_manager, |response_sender| {
+            GeyserPluginManagerRequest::LoadPlugin {
+                config_file: missing_config_file.clone(),
+                response_sender,
+            }
+        })
+        .is_err());
+
+        assert_eq!(
+            request(&plugin_manager, |response_sender| {
+                GeyserPluginManagerRequest::ReloadPlugin {
+                    name: "unknown".to_string(),
+                    config_file: None,
+                    response_sender,
+                }
+            }),
+            Err("No plugin named \"unknown\" is loaded".to_string())
+        );
+
+        // A failed reload keeps the plugin
+        assert!(request(&plugin_manager, |response_sender| {
+            GeyserPluginManagerRequest::ReloadPlugin {
+                name: "a".to_string(),
+                config_file: Some(missing_config_file),
+                response_sender,
+            }
+        })
+        .is_err());
+        assert_eq!(plugin_manager.read().unwrap().list_plugins(), vec!["a"]);
+
+        assert_eq!(
+            request(&plugin_manager, |response_sender| {
+                GeyserPluginManagerRequest::UnloadPlugin {
+                    name: "unknown".to_string(),
+                    response_sender,
+                }
+            }),
+            Err("No plugin named \"unknown\" is loaded".to_string())
+        );
+        assert_eq!(
+            request(&plugin_manager, |response_sender| {
+                GeyserPluginManagerRequest::UnloadPlugin {
+                    name: "a".to_string(),
+                    response_sender,
+                }
+            }),
+            Ok(())
+        );
+        assert!(plugin_manager.read().unwrap().list_plugins().is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+//! Discovery of the used accounts of a hierarchical deterministic wallet.
+
+use {
+    solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
+    solana_clap_v3_utils::keypair::DerivationSource,
+    solana_client::{
+        rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_sdk::{
+        commitment_config::CommitmentConfig, derivation_path::DerivationPath,
+        native_token::lamports_to_sol, pubkey::Pubkey, stake,
+    },
+    std::{collections::HashMap, error},
+};
+
+/// Byte offsets of the authorized staker and withdrawer in a stake account
+const STAKER_OFFSET: usize = 12;
+const WITHDRAWER_OFFSET: usize = 44;
+
+pub struct DiscoveredAccount {
+    pub derivation_path: DerivationPath,
+    pub pubkey: Pubkey,
+    pub usage: Option<AccountUsage>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AccountUsage {
+    pub lamports: u64,
+    pub stake_accounts: usize,
+    pub stake_lamports: u64,
+    pub has_transactions: bool,
+}
+
+impl AccountUsage {
+    pub fn is_used(&self) -> bool {
+        self.lamports > 0 || self.stake_accounts > 0 || self.has_transactions
+    }
+}
+
+/// Derives the accounts at `m/44'/501'/N'/0'` for increasing `N`, until `gap_limit` accounts in a
+/// row are unused.
+///
+/// Without `get_usage`, as when offline, the first `gap_limit` accounts are returned.
+pub fn discover_accounts<F>(
+    derivation_source: &DerivationSource,
+    gap_limit: u32,
+    mut get_usage: Option<F>,
+) -> Result<Vec<DiscoveredAccount>, Box<dyn error::Error>>
+where
+    F: FnMut(&Pubkey) -> Result<AccountUsage, Box<dyn error::Error>>,
+{
+    let mut accounts = vec![];
+    let mut unused = 0;
+    let mut account = 0;
+    while unused < gap_limit {
+        let derivation_path = DerivationPath::new_bip44(Some(account), Some(0));
+        let pubkey = derivation_source.derive_pubkey(&derivation_path, "discover")?;
+        let usage = get_usage
+            .as_mut()
+            .map(|get_usage| get_usage(&pubkey))
+            .transpose()?;
+        if usage.as_ref().map(AccountUsage::is_used).unwrap_or(false) {
+            unused = 0;
+        } else {
+            unused += 1;
+        }
+        accounts.push(DiscoveredAccount {
+            derivation_path,
+            pubkey,
+            usage,
+        });
+        account = account.checked_add(1).ok_or("Ran out of account indexes")?;
+    }
+
+    if get_usage.is_some() {
+        accounts.retain(|account| account.usage.as_ref().unwrap().is_used());
+    }
+    Ok(accounts)
+}
+
+/// Queries the balance, stake accounts and transaction history of `pubkey`
+pub fn get_account_usage(
+    rpc_client: &RpcClient,
+    pubkey: &Pubkey,
+) -> Result<AccountUsage, Box<dyn error::Error>> {
+    let lamports = rpc_client.get_balance(pubkey)?;
+
+    // A stake account may name the address as both staker and withdrawer
+    let mut stake_accounts = HashMap::new();
+    for offset in [STAKER_OFFSET, WITHDRAWER_OFFSET] {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                offset,
+                pubkey.as_ref(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 0,
+                    length: 0,
+                }),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        for (stake_pubkey, account) in
+            rpc_client.get_program_accounts_with_config(&stake::program::id(), config)?
+        {
+            stake_accounts.insert(stake_pubkey, account.lamports);
+        }
+    }
+
+    let has_transactions = !rpc_client
+        .get_signatures_for_address_with_config(
+            pubkey,
+            GetConfirmedSignaturesForAddress2Config {
+                limit: Some(1),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..GetConfirmedSignaturesForAddress2Config::default()
+            },
+        )?
+        .is_empty();
+
+    Ok(AccountUsage {
+        lamports,
+        stake_accounts: stake_accounts.len(),
+        stake_lamports: stake_accounts.values().sum(),
+        has_transactions,
+    })
+}
+
+pub fn print_accounts(accounts: &[DiscoveredAccount], offline: bool) {
+    if offline {
+        println!("{:<24} Address", "Derivation Path");
+        for account in accounts {
+            println!(
+                "{:<24} {}",
+                format!("{:?}", account.derivation_path),
+                account.pubkey
+            );
+        }
+        return;
+    }
+
+    if accounts.is_empty() {
+        println!("No used accounts found");
+        return;
+    }
+    println!(
+        "{:<24} {:<44} {:>20} {:>14} {:>20}",
+        "Derivation Path", "Address", "Balance (RENEC)", "Stake Accounts", "Staked (RENEC)"
+    );
+    for account in accounts {
+        let usage = account.usage.as_ref().unwrap();
+        println!(
+            "{:<24} {:<44} {:>20} {:>14} {:>20}",
+            format!("{:?}", account.derivation_path),
+            account.pubkey,
+            lamports_to_sol(usage.lamports),
+            usage.stake_accounts,
+            lamports_to_sol(usage.stake_lamports),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::signature::Signer};
+
+    #[test]
+    fn test_discover_accounts() {
+        let derivation_source = DerivationSource::Seed(vec![7; 64]);
+        let derive = |account| {
+            let derivation_path = DerivationPath::new_bip44(Some(account), Some(0));
+            derivation_source
+                .derive_pubkey(&derivation_path, "test")
+                .unwrap()
+        };
+        let used = [derive(0), derive(3)];
+
+        // The scan continues past unused account 1 and 2, and stops after 3 unused accounts
+        let mut queried = 0;
+        let accounts = discover_accounts(
+            &derivation_source,
+            3,
+            Some(|pubkey: &Pubkey| {
+                queried += 1;
+                Ok(AccountUsage {
+                    lamports: if used.contains(pubkey) { 1 } else { 0 },
+                    ..AccountUsage::default()
+                })
+            }),
+        )
+        .unwrap();
+        assert_eq!(queried, 7);
+        assert_eq!(
+            accounts
+                .iter()
+                .map(|account| account.pubkey)
+                .collect::<Vec<_>>(),
+            used.to_vec()
+        );
+        assert_eq!(
+            format!("{:?}", accounts[1].derivation_path),
+            "m/44'/501'/3'/0'"
+        );
+        assert_eq!(
+            accounts[0].pubkey,
+            solana_sdk::signature::keypair_from_seed_and_derivation_path(
+                &[7; 64],
+                Some(DerivationPath::new_bip44(Some(0), Some(0)))
+            )
+            .unwrap()
+            .pubkey()
+        );
+
+        // Offline, the first accounts are listed
+        let accounts = discover_accounts(
+            &derivation_source,
+            2,
+            None::<fn(&Pubkey) -> Result<AccountUsage, Box<dyn error::Error>>>,
+        )
+        .unwrap();
+        assert_eq!(
+            accounts
+                .iter()
+                .map(|account| account.pubkey)
+                .collect::<Vec<_>>(),
+            vec![derive(0), derive(1)]
+        );
+    }
+}
@@ -4,24 +4,35 @@ use {
     clap::{crate_description, crate_name, Arg, ArgMatches, Command},
     solana_clap_v3_utils::{
         input_parsers::STDOUT_OUTFILE_TOKEN,
-        input_validators::{is_parsable, is_prompt_signer_source},
+        input_validators::{is_parsable, is_prompt_signer_source, is_pubkey, is_url_or_moniker},
         keypair::{
-            keypair_from_path, keypair_from_seed_phrase, prompt_passphrase, signer_from_path,
+            derivation_source_from_path, keypair_from_keystore_file, keypair_from_path,
+            keypair_from_seed_phrase, prompt_passphrase, pubkey_from_path, signer_from_path,
             SKIP_SEED_PHRASE_VALIDATION_ARG,
         },
         ArgConstant, DisplayError,
     },
-    renec_cli_config::{Config, CONFIG_FILE},
+    renec_cli_config::{Config, ConfigInput, CONFIG_FILE},
+    solana_client::rpc_client::RpcClient,
     solana_remote_wallet::remote_wallet::RemoteWalletManager,
     solana_sdk::{
+        commitment_config::CommitmentConfig,
         instruction::{AccountMeta, Instruction},
         message::Message,
         pubkey::{write_pubkey_file, Pubkey},
-        signature::{keypair_from_seed, write_keypair, write_keypair_file, Keypair, Signer},
+        signature::{
+            keypair_from_seed, read_keypair_file, write_keypair, write_keypair_file, Keypair,
+            Signer,
+        },
+        signer::keystore::{
+            is_keystore_file, write_keystore, write_keystore_file, Keystore,
+            KEYSTORE_PASSPHRASE_ENV,
+        },
     },
     std::{
         collections::HashSet,
-        error,
+        env, error, fs,
+        io::{stderr, stdin, Write},
         path::Path,
         process::exit,
         sync::{
@@ -33,6 +44,9 @@ use {
     },
 };
 
+mod discover;
+mod shamir;
+
 const NO_PASSPHRASE: &str = "";
 
 struct GrindMatch {
@@ -127,21 +141,25 @@ fn check_for_overwrite(outfile: &str, matches: &ArgMatches) {
     }
 }
 
+fn get_keypair_path_from_matches(matches: &ArgMatches, config: Config) -> String {
+    if matches.is_present("keypair") {
+        matches.value_of("keypair").unwrap().to_string()
+    } else if !config.keypair_path.is_empty() {
+        config.keypair_path
+    } else {
+        let mut path = dirs_next::home_dir().expect("home directory");
+        path.extend([".config", "renec", "id.json"]);
+        path.to_str().unwrap().to_string()
+    }
+}
+
 fn get_keypair_from_matches(
     matches: &ArgMatches,
     config: Config,
     wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
 ) -> Result<Box<dyn Signer>, Box<dyn error::Error>> {
-    let mut path = dirs_next::home_dir().expect("home directory");
-    let path = if matches.is_present("keypair") {
-        matches.value_of("keypair").unwrap()
-    } else if !config.keypair_path.is_empty() {
-        &config.keypair_path
-    } else {
-        path.extend([".config", "renec", "id.json"]);
-        path.to_str().unwrap()
-    };
-    signer_from_path(matches, path, "pubkey recovery", wallet_manager)
+    let path = get_keypair_path_from_matches(matches, config);
+    signer_from_path(matches, &path, "pubkey recovery", wallet_manager)
 }
 
 fn output_keypair(
@@ -159,6 +177,44 @@ fn output_keypair(
     Ok(())
 }
 
+/// The passphrase of a new keystore, from the environment or else prompted twice
+fn acquire_keystore_passphrase() -> Result<String, Box<dyn error::Error>> {
+    let passphrase = match env::var(KEYSTORE_PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => prompt_passphrase("Keystore passphrase: ")?,
+    };
+    if passphrase.is_empty() {
+        return Err("A keystore requires a passphrase".into());
+    }
+    Ok(passphrase)
+}
+
+fn output_keystore(
+    keystore: &Keystore,
+    outfile: &str,
+    source: &str,
+) -> Result<(), Box<dyn error::Error>> {
+    if outfile == STDOUT_OUTFILE_TOKEN {
+        let mut stdout = std::io::stdout();
+        write_keystore(keystore, &mut stdout)?;
+    } else {
+        write_keystore_file(keystore, outfile)?;
+        println!("Wrote {} keypair to {}", source, outfile);
+    }
+    Ok(())
+}
+
+/// Replaces `path` without leaving it half written if interrupted
+fn replace_file<F>(path: &str, write: F) -> Result<(), Box<dyn error::Error>>
+where
+    F: FnOnce(&str) -> Result<(), Box<dyn error::Error>>,
+{
+    let tmp_path = format!("{}.tmp", path);
+    write(&tmp_path)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
 fn grind_validator_starts_with(v: &str) -> Result<(), String> {
     if v.matches(':').count() != 1 || (v.starts_with(':') || v.ends_with(':')) {
         return Err(String::from("Expected : between PREFIX and COUNT"));
@@ -209,6 +265,17 @@ fn grind_validator_starts_and_ends_with(v: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn discover_validator_source(v: &str) -> Result<(), String> {
+    let v = v.to_ascii_lowercase();
+    if v == "ask" || v.starts_with("prompt:") || v.starts_with("usb:") {
+        Ok(())
+    } else {
+        Err(String::from(
+            "Expected a `prompt:` URI scheme, `ASK` keyword or `usb://` URL",
+        ))
+    }
+}
+
 fn acquire_language(matches: &ArgMatches) -> Language {
     match matches.value_of(LANGUAGE_ARG.name).unwrap() {
         "english" => Language::English,
@@ -393,6 +460,66 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .arg(no_outfile_arg()
                     .conflicts_with_all(&["outfile", "silent"])
                 )
+                .arg(
+                    Arg::new("encrypt")
+                        .long("encrypt")
+                        .conflicts_with(NO_OUTFILE_ARG.name)
+                        .help("Encrypt the keypair file with a passphrase, read from the RENEC_KEYSTORE_PASSPHRASE environment variable if set"),
+                )
+        )
+        .subcommand(
+            Command::new("encrypt")
+                .about("Encrypt a keypair file with a passphrase")
+                .disable_version_flag(true)
+                .arg(
+                    Arg::new("keypair")
+                        .index(1)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Filepath of the keypair to encrypt"),
+                )
+                .arg(
+                    Arg::new("outfile")
+                        .short('o')
+                        .long("outfile")
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .help("Path of the encrypted keypair file [default: replace KEYPAIR]"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .short('f')
+                        .long("force")
+                        .help("Overwrite the output file if it exists"),
+                )
+        )
+        .subcommand(
+            Command::new("decrypt")
+                .about("Decrypt an encrypted keypair file, back to a plain keypair file")
+                .disable_version_flag(true)
+                .arg(
+                    Arg::new("keypair")
+                        .index(1)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Filepath of the encrypted keypair"),
+                )
+                .arg(
+                    Arg::new("outfile")
+                        .short('o')
+                        .long("outfile")
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .help("Path of the decrypted keypair file [default: replace KEYPAIR]"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .short('f')
+                        .long("force")
+                        .help("Overwrite the output file if it exists"),
+                )
         )
         .subcommand(
             Command::new("grind")
@@ -522,6 +649,112 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 ),
 
         )
+        .subcommand(
+            Command::new("discover")
+                .about("Discover the used accounts derived from a seed phrase or hardware wallet")
+                .disable_version_flag(true)
+                .arg(
+                    Arg::new("keypair")
+                        .index(1)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .default_value("prompt:")
+                        .validator(discover_validator_source)
+                        .help("`prompt:` URI scheme or `ASK` keyword for a seed phrase, or `usb://` URL of a hardware wallet"),
+                )
+                .arg(
+                    Arg::new("gap_limit")
+                        .long("gap-limit")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .validator(is_parsable::<u32>)
+                        .default_value("20")
+                        .help("Stop after this many unused accounts in a row"),
+                )
+                .arg(
+                    Arg::new("json_rpc_url")
+                        .short('u')
+                        .long("url")
+                        .value_name("URL_OR_MONIKER")
+                        .takes_value(true)
+                        .validator(|s| is_url_or_moniker(s))
+                        .help("URL for RENEC's JSON RPC or moniker (or their first letter): \
+                               [mainnet-beta, testnet, devnet, localhost]"),
+                )
+                .arg(
+                    Arg::new("offline")
+                        .long("offline")
+                        .conflicts_with("json_rpc_url")
+                        .help("Only derive addresses, without querying their balances. Lists as many addresses as the gap limit"),
+                )
+                .arg(
+                    Arg::new(SKIP_SEED_PHRASE_VALIDATION_ARG.name)
+                        .long(SKIP_SEED_PHRASE_VALIDATION_ARG.long)
+                        .help(SKIP_SEED_PHRASE_VALIDATION_ARG.help),
+                ),
+        )
+        .subcommand(
+            Command::new("split")
+                .about("Split a keypair into mnemonic shares, a threshold of which recover it")
+                .disable_version_flag(true)
+                .arg(
+                    Arg::new("keypair")
+                        .index(1)
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .help("Filepath or URL to a keypair [default: client keypair]"),
+                )
+                .arg(
+                    Arg::new("threshold")
+                        .long("threshold")
+                        .value_name("M")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_parsable::<u8>)
+                        .help("Number of shares needed to recover the keypair"),
+                )
+                .arg(
+                    Arg::new("shares")
+                        .long("shares")
+                        .value_name("N")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_parsable::<u8>)
+                        .help("Number of shares to create, at most 16"),
+                )
+                .arg(
+                    Arg::new(SKIP_SEED_PHRASE_VALIDATION_ARG.name)
+                        .long(SKIP_SEED_PHRASE_VALIDATION_ARG.long)
+                        .help(SKIP_SEED_PHRASE_VALIDATION_ARG.help),
+                ),
+        )
+        .subcommand(
+            Command::new("combine")
+                .about("Recover a keypair from the mnemonic shares of `split`")
+                .disable_version_flag(true)
+                .arg(
+                    Arg::new("pubkey")
+                        .long("pubkey")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(is_pubkey)
+                        .help("Expected pubkey of the recovered keypair [default: confirm the recovered pubkey at a prompt]"),
+                )
+                .arg(
+                    Arg::new("outfile")
+                        .short('o')
+                        .long("outfile")
+                        .value_name("FILEPATH")
+                        .takes_value(true)
+                        .help("Path to generated file"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .short('f')
+                        .long("force")
+                        .help("Overwrite the output file if it exists"),
+                ),
+        )
         .get_matches();
 
     do_main(&matches).map_err(|err| DisplayError::new_as_boxed(err).into())
@@ -540,8 +773,12 @@ fn do_main(matches: &ArgMatches) -> Result<(), Box<dyn error::Error>> {
 
     match subcommand {
         ("pubkey", matches) => {
-            let pubkey =
-                get_keypair_from_matches(matches, config, &mut wallet_manager)?.try_pubkey()?;
+            let pubkey = pubkey_from_path(
+                matches,
+                &get_keypair_path_from_matches(matches, config),
+                "pubkey recovery",
+                &mut wallet_manager,
+            )?;
 
             if matches.is_present("outfile") {
                 let outfile = matches.value_of("outfile").unwrap();
@@ -583,8 +820,14 @@ fn do_main(matches: &ArgMatches) -> Result<(), Box<dyn error::Error>> {
             let keypair = keypair_from_seed(seed.as_bytes())?;
 
             if let Some(outfile) = outfile {
-                output_keypair(&keypair, outfile, "new")
-                    .map_err(|err| format!("Unable to write {}: {}", outfile, err))?;
+                if matches.is_present("encrypt") {
+                    Keystore::encrypt(&keypair, &acquire_keystore_passphrase()?)
+                        .map_err(|err| err.into())
+                        .and_then(|keystore| output_keystore(&keystore, outfile, "encrypted new"))
+                } else {
+                    output_keypair(&keypair, outfile, "new")
+                }
+                .map_err(|err| format!("Unable to write {}: {}", outfile, err))?;
             }
 
             if !silent {
@@ -618,6 +861,48 @@ fn do_main(matches: &ArgMatches) -> Result<(), Box<dyn error::Error>> {
             };
             output_keypair(&keypair, outfile, "recovered")?;
         }
+        ("encrypt", matches) => {
+            let keypair_path = matches.value_of("keypair").unwrap();
+            if is_keystore_file(keypair_path) {
+                return Err(format!("{} is already encrypted", keypair_path).into());
+            }
+            let keypair = read_keypair_file(keypair_path)
+                .map_err(|err| format!("Unable to read {}: {}", keypair_path, err))?;
+            let outfile = matches.value_of("outfile").unwrap_or(keypair_path);
+            if outfile != keypair_path && outfile != STDOUT_OUTFILE_TOKEN {
+                check_for_overwrite(outfile, matches);
+            }
+
+            let keystore = Keystore::encrypt(&keypair, &acquire_keystore_passphrase()?)?;
+            if outfile == keypair_path {
+                replace_file(keypair_path, |tmp_path| {
+                    write_keystore_file(&keystore, tmp_path).map(|_| ())
+                })?;
+                println!("Encrypted {}", keypair_path);
+            } else {
+                output_keystore(&keystore, outfile, "encrypted")?;
+            }
+        }
+        ("decrypt", matches) => {
+            let keypair_path = matches.value_of("keypair").unwrap();
+            if !is_keystore_file(keypair_path) {
+                return Err(format!("{} is not an encrypted keypair file", keypair_path).into());
+            }
+            let outfile = matches.value_of("outfile").unwrap_or(keypair_path);
+            if outfile != keypair_path && outfile != STDOUT_OUTFILE_TOKEN {
+                check_for_overwrite(outfile, matches);
+            }
+
+            let keypair = keypair_from_keystore_file(keypair_path, "decrypt")?;
+            if outfile == keypair_path {
+                replace_file(keypair_path, |tmp_path| {
+                    write_keypair_file(&keypair, tmp_path).map(|_| ())
+                })?;
+                println!("Decrypted {}", keypair_path);
+            } else {
+                output_keypair(&keypair, outfile, "decrypted")?;
+            }
+        }
         ("grind", matches) => {
             let ignore_case = matches.is_present("ignore_case");
 
@@ -773,6 +1058,137 @@ fn do_main(matches: &ArgMatches) -> Result<(), Box<dyn error::Error>> {
                 thread_handle.join().unwrap();
             }
         }
+        ("discover", matches) => {
+            let gap_limit: u32 = matches.value_of_t_or_exit("gap_limit");
+            let derivation_source = derivation_source_from_path(
+                matches,
+                matches.value_of("keypair").unwrap(),
+                "discover",
+                &mut wallet_manager,
+            )?;
+
+            let offline = matches.is_present("offline");
+            let accounts = if offline {
+                discover::discover_accounts(&derivation_source, gap_limit, None::<fn(&_) -> _>)?
+            } else {
+                let (_, json_rpc_url) = ConfigInput::compute_json_rpc_url_setting(
+                    matches.value_of("json_rpc_url").unwrap_or(""),
+                    &config.json_rpc_url,
+                );
+                let rpc_client =
+                    RpcClient::new_with_commitment(json_rpc_url, CommitmentConfig::confirmed());
+                discover::discover_accounts(
+                    &derivation_source,
+                    gap_limit,
+                    Some(|pubkey: &Pubkey| {
+                        // Each account takes a few queries, which add up over the gap limit
+                        eprintln!("Checking {}", pubkey);
+                        discover::get_account_usage(&rpc_client, pubkey)
+                    }),
+                )?
+            };
+            discover::print_accounts(&accounts, offline);
+        }
+        ("split", matches) => {
+            let threshold: u8 = matches.value_of_t_or_exit("threshold");
+            let share_count: u8 = matches.value_of_t_or_exit("shares");
+            let keypair = keypair_from_path(
+                matches,
+                &get_keypair_path_from_matches(matches, config),
+                "split",
+                false,
+            )?;
+            let shares = shamir::split_keypair(&keypair, threshold, share_count)?;
+
+            let mnemonics: Vec<String> = shares.iter().map(shamir::Share::to_mnemonic).collect();
+            let divider = "=".repeat(mnemonics.iter().map(String::len).max().unwrap_or(0));
+            println!(
+                "{}\npubkey: {}\n{}\nAny {} of these {} shares recover the keypair. Store each one separately:",
+                &divider,
+                keypair.pubkey(),
+                &divider,
+                threshold,
+                share_count
+            );
+            for (share, mnemonic) in shares.iter().zip(&mnemonics) {
+                println!("Share {}:\n{}\n{}", share.index, mnemonic, &divider);
+            }
+        }
+        ("combine", matches) => {
+            let mut path = dirs_next::home_dir().expect("home directory");
+            let outfile = if matches.is_present("outfile") {
+                matches.value_of("outfile").unwrap()
+            } else {
+                path.extend([".config", "renec", "id.json"]);
+                path.to_str().unwrap()
+            };
+
+            if outfile != STDOUT_OUTFILE_TOKEN {
+                check_for_overwrite(outfile, matches);
+            }
+
+            // The first share tells how many are needed
+            let mut shares: Vec<shamir::Share> = vec![];
+            loop {
+                let mnemonic = rpassword::prompt_password(format!(
+                    "[combine] share {}{}: ",
+                    shares.len() + 1,
+                    shares
+                        .first()
+                        .map(|share| format!(" of {}", share.threshold))
+                        .unwrap_or_default()
+                ))?;
+                // A mistyped share is entered again, keeping the shares entered so far
+                let share = match shamir::Share::from_mnemonic(&mnemonic) {
+                    Ok(share) => share,
+                    Err(err) => {
+                        eprintln!("{}. Enter the share again", err);
+                        continue;
+                    }
+                };
+                if let Some(first) = shares.first() {
+                    if share.identifier != first.identifier || share.threshold != first.threshold {
+                        eprintln!("The share is not of the same keypair as the first one");
+                        continue;
+                    }
+                    if shares.iter().any(|entered| entered.index == share.index) {
+                        eprintln!("Share {} was already entered", share.index);
+                        continue;
+                    }
+                }
+                shares.push(share);
+                if shares.len() >= shares[0].threshold as usize {
+                    break;
+                }
+            }
+
+            let keypair = shamir::combine_shares(&shares)?;
+            if let Some(pubkey) = matches.value_of("pubkey") {
+                if keypair.pubkey().to_string() != pubkey {
+                    return Err(format!(
+                        "The recovered keypair {} does not match the expected pubkey {}",
+                        keypair.pubkey(),
+                        pubkey
+                    )
+                    .into());
+                }
+            } else {
+                // The shares only check the first bytes of the pubkey. The prompt goes to
+                // stderr, as the keypair may be written to stdout
+                eprint!("Recovered pubkey `{}`. Continue? (y/n): ", keypair.pubkey());
+                let _ignored = stderr().flush();
+                let mut input = String::new();
+                stdin().read_line(&mut input)?;
+                if input.to_lowercase().trim() != "y" {
+                    eprintln!("Exiting");
+                    exit(1);
+                }
+            }
+            if outfile != STDOUT_OUTFILE_TOKEN {
+                println!("Recovered keypair {}", keypair.pubkey());
+            }
+            output_keypair(&keypair, outfile, "recovered")?;
+        }
         ("verify", matches) => {
             let keypair = get_keypair_from_matches(matches, config, &mut wallet_manager)?;
             let simple_message = Message::new(
@@ -0,0 +1,345 @@
+//! Shamir secret sharing of keypairs, with mnemonic shares.
+//!
+//! The 32-byte secret key of a keypair is split over GF(256), so that any
+//! `threshold` of the shares recover it. The shares have their own format,
+//! which is not compatible with SLIP-39. Each share is encoded as 30 words of
+//! the BIP39 English wordlist, holding:
+//!
+//! - an identifier, the first two bytes of the pubkey, which tells apart the
+//!   shares of different keypairs and checks the recovered keypair
+//! - the threshold, and the index of the share
+//! - the share value
+//! - a checksum, the first four bytes of the SHA-256 hash of the above
+
+use {
+    bip39::Language,
+    rand::{rngs::OsRng, RngCore},
+    solana_sdk::{
+        hash::hash,
+        signature::{keypair_from_seed, Keypair, Signer},
+    },
+    std::{collections::HashSet, error},
+};
+
+pub const MAX_SHARE_COUNT: u8 = 16;
+
+const SECRET_LEN: usize = 32;
+const HEADER_LEN: usize = 4;
+const CHECKSUM_LEN: usize = 4;
+const PAYLOAD_LEN: usize = HEADER_LEN + SECRET_LEN + CHECKSUM_LEN;
+const BITS_PER_WORD: usize = 11;
+pub const SHARE_WORD_COUNT: usize = (PAYLOAD_LEN * 8 + BITS_PER_WORD - 1) / BITS_PER_WORD;
+
+/// Log and exp tables of GF(256) with the polynomial of AES, x^8 + x^4 + x^3 + x + 1
+struct Gf256 {
+    log: [u8; 256],
+    exp: [u8; 255],
+}
+
+impl Gf256 {
+    fn new() -> Self {
+        let mut log = [0u8; 256];
+        let mut exp = [0u8; 255];
+        let mut value: u16 = 1;
+        for (power, exp) in exp.iter_mut().enumerate() {
+            *exp = value as u8;
+            log[value as usize] = power as u8;
+            // Multiply by the generator 3
+            value ^= value << 1;
+            if value & 0x100 != 0 {
+                value ^= 0x11b;
+            }
+        }
+        Self { log, exp }
+    }
+
+    fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            let power = (self.log[a as usize] as usize + self.log[b as usize] as usize) % 255;
+            self.exp[power]
+        }
+    }
+
+    fn div(&self, a: u8, b: u8) -> u8 {
+        assert_ne!(b, 0);
+        if a == 0 {
+            0
+        } else {
+            let power = (self.log[a as usize] as usize + 255 - self.log[b as usize] as usize) % 255;
+            self.exp[power]
+        }
+    }
+
+    /// Evaluates at 0 the polynomial through `points`, by Lagrange interpolation
+    fn interpolate_at_zero(&self, points: &[(u8, u8)]) -> u8 {
+        points.iter().fold(0, |sum, (x_i, y_i)| {
+            let basis = points
+                .iter()
+                .filter(|(x_j, _)| x_j != x_i)
+                .fold(1, |product, (x_j, _)| {
+                    self.mul(product, self.div(*x_j, x_j ^ x_i))
+                });
+            sum ^ self.mul(*y_i, basis)
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub identifier: u16,
+    pub threshold: u8,
+    pub index: u8,
+    pub value: [u8; SECRET_LEN],
+}
+
+impl Share {
+    fn header(&self) -> [u8; HEADER_LEN] {
+        let identifier = self.identifier.to_be_bytes();
+        [identifier[0], identifier[1], self.threshold, self.index]
+    }
+
+    fn checksum(header: &[u8], value: &[u8]) -> [u8; CHECKSUM_LEN] {
+        let mut checksum = [0u8; CHECKSUM_LEN];
+        checksum.copy_from_slice(&hash(&[header, value].concat()).as_ref()[..CHECKSUM_LEN]);
+        checksum
+    }
+
+    pub fn to_mnemonic(&self) -> String {
+        let header = self.header();
+        let payload = [
+            &header[..],
+            &self.value,
+            &Self::checksum(&header, &self.value),
+        ]
+        .concat();
+
+        let wordlist = Language::English.wordlist();
+        (0..SHARE_WORD_COUNT)
+            .map(|word| {
+                let bits = (0..BITS_PER_WORD).fold(0u16, |bits, bit| {
+                    let position = word * BITS_PER_WORD + bit;
+                    let bit = payload
+                        .get(position / 8)
+                        .map(|byte| (byte >> (7 - position % 8)) & 1)
+                        .unwrap_or(0);
+                    bits << 1 | bit as u16
+                });
+                wordlist.get_word(bits.into())
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Result<Self, Box<dyn error::Error>> {
+        let words: Vec<&str> = mnemonic.split_whitespace().collect();
+        if words.len() != SHARE_WORD_COUNT {
+            return Err(format!(
+                "A share has {} words, not {}",
+                SHARE_WORD_COUNT,
+                words.len()
+            )
+            .into());
+        }
+
+        let wordmap = Language::English.wordmap();
+        let mut payload = vec![0u8; (SHARE_WORD_COUNT * BITS_PER_WORD + 7) / 8];
+        for (word_index, word) in words.iter().enumerate() {
+            let bits = u16::from(
+                wordmap
+                    .get_bits(&word.to_lowercase())
+                    .map_err(|_| format!("Unknown share word \"{}\"", word))?,
+            );
+            for bit in 0..BITS_PER_WORD {
+                if bits >> (BITS_PER_WORD - 1 - bit) & 1 == 1 {
+                    let position = word_index * BITS_PER_WORD + bit;
+                    payload[position / 8] |= 1 << (7 - position % 8);
+                }
+            }
+        }
+        if payload[PAYLOAD_LEN..].iter().any(|byte| *byte != 0) {
+            return Err("Invalid share padding".into());
+        }
+
+        let (header, rest) = payload.split_at(HEADER_LEN);
+        let (value, rest) = rest.split_at(SECRET_LEN);
+        if Self::checksum(header, value)[..] != rest[..CHECKSUM_LEN] {
+            return Err("Invalid share checksum, check the words of the share".into());
+        }
+        let share = Self {
+            identifier: u16::from_be_bytes([header[0], header[1]]),
+            threshold: header[2],
+            index: header[3],
+            value: value.try_into().unwrap(),
+        };
+        if share.threshold == 0 || share.index == 0 {
+            return Err("Invalid share".into());
+        }
+        Ok(share)
+    }
+}
+
+fn identifier_of(keypair: &Keypair) -> u16 {
+    let pubkey = keypair.pubkey();
+    u16::from_be_bytes([pubkey.as_ref()[0], pubkey.as_ref()[1]])
+}
+
+/// Splits the secret key of `keypair` into `share_count` shares, any `threshold` of which
+/// recover it
+pub fn split_keypair(
+    keypair: &Keypair,
+    threshold: u8,
+    share_count: u8,
+) -> Result<Vec<Share>, Box<dyn error::Error>> {
+    if threshold == 0 || threshold > share_count || share_count > MAX_SHARE_COUNT {
+        return Err(format!(
+            "Expected a threshold of at least 1, and no more than the count of shares, \
+             which is at most {}",
+            MAX_SHARE_COUNT
+        )
+        .into());
+    }
+
+    let gf256 = Gf256::new();
+    let secret = &keypair.to_bytes()[..SECRET_LEN];
+    // For each byte of the secret, a random polynomial of degree `threshold - 1`
+    // which is the secret byte at 0
+    let coefficients: Vec<Vec<u8>> = secret
+        .iter()
+        .map(|secret_byte| {
+            let mut coefficients = vec![0u8; threshold as usize];
+            OsRng.fill_bytes(&mut coefficients);
+            coefficients[0] = *secret_byte;
+            coefficients
+        })
+        .collect();
+
+    Ok((1..=share_count)
+        .map(|index| {
+            let mut value = [0u8; SECRET_LEN];
+            for (value, coefficients) in value.iter_mut().zip(&coefficients) {
+                *value = coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |sum, coefficient| gf256.mul(sum, index) ^ coefficient);
+            }
+            Share {
+                identifier: identifier_of(keypair),
+                threshold,
+                index,
+                value,
+            }
+        })
+        .collect())
+}
+
+/// Recovers a keypair from `threshold` of its shares
+pub fn combine_shares(shares: &[Share]) -> Result<Keypair, Box<dyn error::Error>> {
+    let first = shares.first().ok_or("No shares")?;
+    if shares
+        .iter()
+        .any(|share| share.identifier != first.identifier || share.threshold != first.threshold)
+    {
+        return Err("The shares are not of the same keypair".into());
+    }
+    let indexes: HashSet<u8> = shares.iter().map(|share| share.index).collect();
+    if indexes.len() != shares.len() {
+        return Err("The same share was given twice".into());
+    }
+    if shares.len() < first.threshold as usize {
+        return Err(format!(
+            "{} shares are needed, but only {} were given",
+            first.threshold,
+            shares.len()
+        )
+        .into());
+    }
+
+    let gf256 = Gf256::new();
+    let shares = &shares[..first.threshold as usize];
+    let mut secret = [0u8; SECRET_LEN];
+    for (byte_index, secret_byte) in secret.iter_mut().enumerate() {
+        let points: Vec<(u8, u8)> = shares
+            .iter()
+            .map(|share| (share.index, share.value[byte_index]))
+            .collect();
+        *secret_byte = gf256.interpolate_at_zero(&points);
+    }
+
+    let keypair = keypair_from_seed(&secret)?;
+    if identifier_of(&keypair) != first.identifier {
+        return Err("The recovered keypair does not match the identifier of the shares".into());
+    }
+    Ok(keypair)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf256() {
+        let gf256 = Gf256::new();
+        // Multiplication example of FIPS-197
+        assert_eq!(gf256.mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(gf256.div(gf256.mul(a, 0x53), 0x53), a);
+        }
+    }
+
+    #[test]
+    fn test_split_and_combine() {
+        let keypair = Keypair::new();
+        let shares = split_keypair(&keypair, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for (a, b, c) in [(0, 1, 2), (4, 2, 0), (1, 3, 4)] {
+            let subset = [shares[a].clone(), shares[b].clone(), shares[c].clone()];
+            assert_eq!(
+                combine_shares(&subset).unwrap().to_bytes(),
+                keypair.to_bytes()
+            );
+        }
+
+        assert!(combine_shares(&shares[..2]).is_err());
+        assert!(
+            combine_shares(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err()
+        );
+
+        let other_shares = split_keypair(&Keypair::new(), 3, 5).unwrap();
+        assert!(combine_shares(&[
+            shares[0].clone(),
+            shares[1].clone(),
+            other_shares[2].clone()
+        ])
+        .is_err());
+
+        assert!(split_keypair(&keypair, 0, 5).is_err());
+        assert!(split_keypair(&keypair, 4, 3).is_err());
+        assert!(split_keypair(&keypair, 2, MAX_SHARE_COUNT + 1).is_err());
+    }
+
+    #[test]
+    fn test_share_mnemonic() {
+        let keypair = Keypair::new();
+        let share = split_keypair(&keypair, 2, 3).unwrap().remove(1);
+        let mnemonic = share.to_mnemonic();
+        assert_eq!(mnemonic.split(' ').count(), SHARE_WORD_COUNT);
+        assert_eq!(Share::from_mnemonic(&mnemonic).unwrap(), share);
+        assert_eq!(
+            Share::from_mnemonic(&format!("  {}\n", mnemonic.to_uppercase())).unwrap(),
+            share
+        );
+
+        // A mistyped word fails the checksum
+        let mut words: Vec<&str> = mnemonic.split(' ').collect();
+        words[5] = if words[5] == "abandon" {
+            "ability"
+        } else {
+            "abandon"
+        };
+        assert!(Share::from_mnemonic(&words.join(" ")).is_err());
+        assert!(Share::from_mnemonic(&words[1..].join(" ")).is_err());
+    }
+}
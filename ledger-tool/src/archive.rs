@@ -0,0 +1,179 @@
+//! The `archive` subcommand
+use {
+    crate::ledger_path::canonicalize_ledger_path,
+    clap::{
+        value_t, value_t_or_exit, values_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand,
+    },
+    solana_clap_utils::input_validators::{is_parsable, is_slot},
+    solana_ledger::{
+        block_archive::{
+            export_block_archives, import_block_archive, read_block_archive, BlockArchiveError,
+            DEFAULT_SLOTS_PER_ARCHIVE,
+        },
+        blockstore_options::{AccessType, ShredStorageType},
+    },
+    solana_sdk::clock::Slot,
+    std::{
+        fs,
+        path::{Path, PathBuf},
+        process::exit,
+    },
+};
+
+pub trait ArchiveSubCommand {
+    fn archive_subcommand(self) -> Self;
+}
+
+impl ArchiveSubCommand for App<'_, '_> {
+    fn archive_subcommand(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("archive")
+                .about("Rooted blocks in portable archive files")
+                .setting(AppSettings::InferSubcommands)
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("export")
+                        .about("Export rooted blocks of the ledger into archive files")
+                        .arg(
+                            Arg::with_name("starting_slot")
+                                .long("starting-slot")
+                                .validator(is_slot)
+                                .value_name("START_SLOT")
+                                .takes_value(true)
+                                .help("Start exporting at this slot [default: first root]"),
+                        )
+                        .arg(
+                            Arg::with_name("ending_slot")
+                                .long("ending-slot")
+                                .validator(is_slot)
+                                .value_name("END_SLOT")
+                                .takes_value(true)
+                                .help("Stop exporting at this slot [default: last root]"),
+                        )
+                        .arg(
+                            Arg::with_name("slots_per_archive")
+                                .long("slots-per-archive")
+                                .validator(is_parsable::<u64>)
+                                .value_name("NUM_SLOTS")
+                                .takes_value(true)
+                                .help(
+                                    "Number of slots covered by each archive file [default: 1000]",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("output_dir")
+                                .long("output-dir")
+                                .value_name("DIR")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Directory to write the archive files to"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("import")
+                        .about("Verify archive files and write their blocks into the ledger")
+                        .arg(
+                            Arg::with_name("files")
+                                .index(1)
+                                .value_name("FILES")
+                                .takes_value(true)
+                                .multiple(true)
+                                .required(true)
+                                .help("Archive files to import, in slot order"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about("Verify archive files against their manifests and PoH entries")
+                        .arg(
+                            Arg::with_name("files")
+                                .index(1)
+                                .value_name("FILES")
+                                .takes_value(true)
+                                .multiple(true)
+                                .required(true)
+                                .help("Archive files to verify"),
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn archive_process_command(
+    ledger_path: &Path,
+    matches: &ArgMatches<'_>,
+    shred_storage_type: &ShredStorageType,
+) {
+    let result = match matches.subcommand() {
+        ("export", Some(arg_matches)) => {
+            let starting_slot = value_t!(arg_matches, "starting_slot", Slot).unwrap_or(0);
+            let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
+            let slots_per_archive = value_t!(arg_matches, "slots_per_archive", u64)
+                .unwrap_or(DEFAULT_SLOTS_PER_ARCHIVE);
+            let output_dir = PathBuf::from(value_t_or_exit!(arg_matches, "output_dir", String));
+            let blockstore = crate::open_blockstore(
+                &canonicalize_ledger_path(ledger_path),
+                AccessType::Secondary,
+                None,
+                shred_storage_type,
+            );
+            export_block_archives(
+                &blockstore,
+                starting_slot,
+                ending_slot,
+                slots_per_archive,
+                &output_dir,
+            )
+            .map(|paths| {
+                for path in paths {
+                    println!("{}", path.display());
+                }
+            })
+        }
+        ("import", Some(arg_matches)) => {
+            let files = values_t_or_exit!(arg_matches, "files", PathBuf);
+            // The blocks may be imported into a new ledger
+            fs::create_dir_all(ledger_path).unwrap_or_else(|err| {
+                eprintln!("Unable to create ledger directory: {}", err);
+                exit(1);
+            });
+            let blockstore = crate::open_blockstore(
+                &canonicalize_ledger_path(ledger_path),
+                AccessType::Primary,
+                None,
+                shred_storage_type,
+            );
+            files.iter().try_for_each(|file| {
+                let manifest = import_block_archive(&blockstore, file)?;
+                println!(
+                    "Imported {} blocks of slots {} to {} from {}",
+                    manifest.blocks.len(),
+                    manifest.first_slot,
+                    manifest.last_slot,
+                    file.display()
+                );
+                Ok::<(), BlockArchiveError>(())
+            })
+        }
+        ("verify", Some(arg_matches)) => {
+            let files = values_t_or_exit!(arg_matches, "files", PathBuf);
+            files.iter().try_for_each(|file| {
+                let (manifest, _blocks) = read_block_archive(file)?;
+                println!(
+                    "{}: {} blocks of slots {} to {} verified",
+                    file.display(),
+                    manifest.blocks.len(),
+                    manifest.first_slot,
+                    manifest.last_slot
+                );
+                Ok(())
+            })
+        }
+        _ => unreachable!(),
+    };
+
+    result.unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    });
+}
@@ -28,7 +28,7 @@ use {
     std::{
         cmp::min,
         collections::HashSet,
-        path::Path,
+        path::{Path, PathBuf},
         process::exit,
         result::Result,
         str::FromStr,
@@ -365,6 +365,14 @@ impl BigTableSubCommand for App<'_, '_> {
                         .default_value(solana_storage_bigtable::DEFAULT_APP_PROFILE_ID)
                         .help("Bigtable application profile id to use in requests")
                 )
+                .arg(
+                    Arg::with_name("local_storage_path")
+                        .global(true)
+                        .long("local-storage-path")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .help("Use a local database in this directory instead of a Bigtable instance")
+                )
                 .subcommand(
                     SubCommand::with_name("upload")
                         .about("Upload the ledger to BigTable")
@@ -639,6 +647,10 @@ pub fn bigtable_process_command(
         "rpc_bigtable_app_profile_id",
         solana_storage_bigtable::DEFAULT_APP_PROFILE_ID,
     );
+    let local_path = sub_matches
+        .and_then(|sub_matches| sub_matches.value_of("local_storage_path"))
+        .or_else(|| matches.value_of("local_storage_path"))
+        .map(PathBuf::from);
 
     let future = match (subcommand, sub_matches) {
         ("upload", Some(arg_matches)) => {
@@ -655,6 +667,7 @@ pub fn bigtable_process_command(
                 read_only: false,
                 instance_name,
                 app_profile_id,
+                local_path,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(upload(
@@ -671,6 +684,7 @@ pub fn bigtable_process_command(
                 read_only: !arg_matches.is_present("force"),
                 instance_name,
                 app_profile_id,
+                local_path,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(delete_slots(slots, config))
@@ -680,6 +694,7 @@ pub fn bigtable_process_command(
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                local_path,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(first_available_block(config))
@@ -690,6 +705,7 @@ pub fn bigtable_process_command(
                 read_only: false,
                 instance_name,
                 app_profile_id,
+                local_path,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(block(slot, output_format, config))
@@ -701,6 +717,7 @@ pub fn bigtable_process_command(
                 read_only: false,
                 instance_name,
                 app_profile_id,
+                local_path,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -713,6 +730,7 @@ pub fn bigtable_process_command(
                 read_only: false,
                 instance_name,
                 app_profile_id,
+                local_path,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -746,6 +764,7 @@ pub fn bigtable_process_command(
                 read_only: false,
                 instance_name,
                 app_profile_id,
+                local_path,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -766,6 +785,7 @@ pub fn bigtable_process_command(
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                local_path,
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -1,6 +1,6 @@
 #![allow(clippy::integer_arithmetic)]
 use {
-    crate::{bigtable::*, ledger_path::*},
+    crate::{archive::*, bigtable::*, ledger_path::*},
     chrono::{DateTime, Utc},
     clap::{
         crate_description, crate_name, value_t, value_t_or_exit, values_t_or_exit, App,
@@ -94,6 +94,7 @@ use {
     },
 };
 
+mod archive;
 mod bigtable;
 mod ledger_path;
 
@@ -792,6 +793,7 @@ fn analyze_storage(database: &Database) {
     analyze_column::<BlockHeight>(database, "BlockHeight");
     analyze_column::<ProgramCosts>(database, "ProgramCosts");
     analyze_column::<OptimisticSlots>(database, "OptimisticSlots");
+    analyze_column::<AccountHistory>(database, "AccountHistory");
 }
 
 fn open_blockstore(
@@ -953,6 +955,7 @@ fn load_bank_forks(
         &process_options,
         None,
         None,
+        None,
         &AbsRequestSender::default(),
     )
     .map(|_| (bank_forks, starting_snapshot_hashes));
@@ -1349,6 +1352,7 @@ fn main() {
                 .takes_value(false)
                 .help("Show additional information where supported"),
         )
+        .archive_subcommand()
         .bigtable_subcommand()
         .subcommand(
             SubCommand::with_name("print")
@@ -2024,7 +2028,9 @@ fn main() {
         }
     };
 
-    if let ("bigtable", Some(arg_matches)) = matches.subcommand() {
+    if let ("archive", Some(arg_matches)) = matches.subcommand() {
+        archive_process_command(&ledger_path, arg_matches, &shred_storage_type)
+    } else if let ("bigtable", Some(arg_matches)) = matches.subcommand() {
         bigtable_process_command(&ledger_path, arg_matches, &shred_storage_type)
     } else {
         let ledger_path = canonicalize_ledger_path(&ledger_path);
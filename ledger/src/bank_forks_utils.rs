@@ -68,6 +68,7 @@ pub fn load(
         &leader_schedule_cache,
         &process_options,
         transaction_status_sender,
+        None,
         cache_block_meta_sender,
         &AbsRequestSender::default(),
     )
@@ -0,0 +1,547 @@
+//! Export of rooted blocks to self-contained archive files, and their import into a `Blockstore`
+//!
+//! An archive holds the blocks of a range of slots along with their PoH entries, so that each
+//! block can be checked against its blockhash and the blockhash of its parent without access to
+//! the cluster. A manifest lists the blockhash, bank hash and content hash of every block.
+
+use {
+    crate::{
+        blockstore::Blockstore,
+        blockstore_db::BlockstoreError,
+        shred::{ProcessShredsStats, ReedSolomonCache, Shredder},
+    },
+    log::*,
+    prost::Message,
+    solana_entry::entry::{Entry, EntrySlice, EntrySummary},
+    solana_sdk::{
+        clock::Slot,
+        hash::{hash, Hash},
+        pubkey::Pubkey,
+        signature::Keypair,
+    },
+    solana_storage_proto::convert::block_archive,
+    solana_transaction_status::{
+        ConfirmedBlock, TransactionWithStatusMeta, VersionedConfirmedBlock,
+    },
+    std::{
+        convert::TryFrom,
+        fs,
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+    thiserror::Error,
+};
+
+pub const DEFAULT_SLOTS_PER_ARCHIVE: u64 = 1_000;
+
+#[derive(Error, Debug)]
+pub enum BlockArchiveError {
+    #[error("blockstore error: {0}")]
+    Blockstore(#[from] BlockstoreError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to decode archive: {0}")]
+    Decode(#[from] prost::DecodeError),
+
+    #[error("invalid archive: {0}")]
+    InvalidArchive(String),
+
+    #[error("invalid block in slot {0}: {1}")]
+    InvalidBlock(Slot, String),
+}
+
+pub type Result<T> = std::result::Result<T, BlockArchiveError>;
+
+/// A block read back from an archive, after verification
+pub struct VerifiedBlock {
+    pub slot: Slot,
+    pub block: VersionedConfirmedBlock,
+    pub entries: Vec<Entry>,
+    pub bank_hash: Option<Hash>,
+}
+
+pub fn archive_file_name(first_slot: Slot, last_slot: Slot) -> String {
+    format!("block-archive-{}-{}.pb", first_slot, last_slot)
+}
+
+/// Export the rooted blocks from `starting_slot` to `ending_slot` into archive files of
+/// `slots_per_archive` slots each, written to `output_dir`. The range is limited to the rooted
+/// slots of the ledger. Returns the paths of the written files.
+pub fn export_block_archives(
+    blockstore: &Blockstore,
+    starting_slot: Slot,
+    ending_slot: Slot,
+    slots_per_archive: u64,
+    output_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let first_root = match blockstore.rooted_slot_iterator(0)?.next() {
+        Some(first_root) => first_root,
+        None => return Ok(vec![]),
+    };
+    let starting_slot = starting_slot.max(first_root);
+    let ending_slot = ending_slot.min(blockstore.max_root());
+    if starting_slot > ending_slot || slots_per_archive == 0 {
+        return Ok(vec![]);
+    }
+    fs::create_dir_all(output_dir)?;
+
+    let mut rooted_slots = blockstore
+        .rooted_slot_iterator(starting_slot)?
+        .take_while(|slot| *slot <= ending_slot)
+        .peekable();
+    let mut paths = vec![];
+    let mut first_slot = starting_slot;
+    loop {
+        let last_slot = first_slot
+            .saturating_add(slots_per_archive - 1)
+            .min(ending_slot);
+        let mut manifest = block_archive::Manifest {
+            first_slot,
+            last_slot,
+            blocks: vec![],
+        };
+        let mut blocks = vec![];
+        while let Some(slot) = rooted_slots.next_if(|slot| *slot <= last_slot) {
+            let (archived_block, manifest_entry) = archive_block(blockstore, slot)?;
+            blocks.push(archived_block);
+            manifest.blocks.push(manifest_entry);
+        }
+
+        let num_blocks = blocks.len();
+        let archive = block_archive::BlockArchive {
+            manifest: Some(manifest),
+            blocks,
+        };
+        let path = output_dir.join(archive_file_name(first_slot, last_slot));
+        fs::write(&path, archive.encode_to_vec())?;
+        info!(
+            "Exported {} blocks of slots {} to {} into {}",
+            num_blocks,
+            first_slot,
+            last_slot,
+            path.display()
+        );
+        paths.push(path);
+
+        if last_slot == ending_slot {
+            return Ok(paths);
+        }
+        first_slot = last_slot + 1;
+    }
+}
+
+fn archive_block(
+    blockstore: &Blockstore,
+    slot: Slot,
+) -> Result<(block_archive::ArchivedBlock, block_archive::ManifestEntry)> {
+    let block = blockstore.get_rooted_block(slot, false)?;
+    let entries = blockstore.get_slot_entries(slot, 0)?;
+    let blockhash = block.blockhash.clone();
+    let archived_block = block_archive::ArchivedBlock {
+        slot,
+        block: Some(block.into()),
+        entries: entries
+            .iter()
+            .map(|entry| {
+                let EntrySummary {
+                    num_hashes,
+                    hash,
+                    num_transactions,
+                } = EntrySummary::from(entry);
+                block_archive::EntrySummary {
+                    num_hashes,
+                    hash: hash.to_bytes().to_vec(),
+                    num_transactions,
+                }
+            })
+            .collect(),
+    };
+    let manifest_entry = block_archive::ManifestEntry {
+        slot,
+        blockhash,
+        bank_hash: blockstore
+            .get_bank_hash(slot)
+            .map(|bank_hash| bank_hash.to_string())
+            .unwrap_or_default(),
+        content_hash: content_hash(&archived_block).to_bytes().to_vec(),
+    };
+    Ok((archived_block, manifest_entry))
+}
+
+fn content_hash(archived_block: &block_archive::ArchivedBlock) -> Hash {
+    hash(&archived_block.encode_to_vec())
+}
+
+/// Read the archive at `path`, checking every block against the manifest and its PoH entries,
+/// and the chaining of consecutive blocks
+pub fn read_block_archive(path: &Path) -> Result<(block_archive::Manifest, Vec<VerifiedBlock>)> {
+    let archive = block_archive::BlockArchive::decode(&fs::read(path)?[..])?;
+    let manifest = archive
+        .manifest
+        .ok_or_else(|| BlockArchiveError::InvalidArchive("missing manifest".to_string()))?;
+    if manifest.blocks.len() != archive.blocks.len() {
+        return Err(BlockArchiveError::InvalidArchive(format!(
+            "manifest lists {} blocks, archive holds {}",
+            manifest.blocks.len(),
+            archive.blocks.len()
+        )));
+    }
+
+    let mut verified_blocks: Vec<VerifiedBlock> = Vec::with_capacity(archive.blocks.len());
+    for (manifest_entry, archived_block) in manifest.blocks.iter().zip(archive.blocks) {
+        let slot = archived_block.slot;
+        if slot != manifest_entry.slot || slot < manifest.first_slot || slot > manifest.last_slot {
+            return Err(BlockArchiveError::InvalidArchive(format!(
+                "block of slot {} does not match the manifest",
+                slot
+            )));
+        }
+        if content_hash(&archived_block).as_ref() != manifest_entry.content_hash.as_slice() {
+            return Err(invalid_block(slot, "content hash mismatch"));
+        }
+        let verified_block = verify_block(archived_block, manifest_entry)?;
+        if let Some(previous) = verified_blocks.last() {
+            if verified_block.block.parent_slot != previous.slot
+                || verified_block.block.previous_blockhash != previous.block.blockhash
+            {
+                return Err(invalid_block(slot, "does not chain to the previous block"));
+            }
+        }
+        verified_blocks.push(verified_block);
+    }
+    Ok((manifest, verified_blocks))
+}
+
+fn invalid_block(slot: Slot, reason: &str) -> BlockArchiveError {
+    BlockArchiveError::InvalidBlock(slot, reason.to_string())
+}
+
+fn parse_hash(slot: Slot, hash: &str) -> Result<Hash> {
+    Hash::from_str(hash).map_err(|_| invalid_block(slot, &format!("invalid hash {}", hash)))
+}
+
+fn verify_block(
+    archived_block: block_archive::ArchivedBlock,
+    manifest_entry: &block_archive::ManifestEntry,
+) -> Result<VerifiedBlock> {
+    let slot = archived_block.slot;
+    let block = archived_block
+        .block
+        .ok_or_else(|| invalid_block(slot, "missing block"))
+        .and_then(|block| {
+            ConfirmedBlock::try_from(block)
+                .map_err(|err| invalid_block(slot, &format!("failed to decode block: {}", err)))
+        })?;
+    let transactions = block
+        .transactions
+        .into_iter()
+        .map(|transaction| match transaction {
+            // The first signature identifies the transaction in the ledger
+            TransactionWithStatusMeta::Complete(transaction)
+                if transaction.transaction.signatures.is_empty() =>
+            {
+                Err(invalid_block(slot, "transaction without signatures"))
+            }
+            TransactionWithStatusMeta::Complete(transaction) => Ok(transaction),
+            TransactionWithStatusMeta::MissingMetadata(_) => {
+                Err(invalid_block(slot, "transaction without status metadata"))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let block = VersionedConfirmedBlock {
+        previous_blockhash: block.previous_blockhash,
+        blockhash: block.blockhash,
+        parent_slot: block.parent_slot,
+        transactions,
+        rewards: block.rewards,
+        block_time: block.block_time,
+        block_height: block.block_height,
+    };
+    if block.parent_slot >= slot {
+        return Err(invalid_block(slot, "parent slot is not older"));
+    }
+    if block.blockhash != manifest_entry.blockhash {
+        return Err(invalid_block(slot, "blockhash does not match the manifest"));
+    }
+
+    let mut transactions = block
+        .transactions
+        .iter()
+        .map(|transaction| transaction.transaction.clone());
+    let mut entries = Vec::with_capacity(archived_block.entries.len());
+    for entry in archived_block.entries {
+        let hash = <[u8; 32]>::try_from(entry.hash.as_slice())
+            .map_err(|_| invalid_block(slot, "invalid entry hash"))?;
+        let entry_transactions: Vec<_> = transactions
+            .by_ref()
+            .take(entry.num_transactions as usize)
+            .collect();
+        if entry_transactions.len() as u64 != entry.num_transactions {
+            return Err(invalid_block(
+                slot,
+                "entries hold more transactions than the block",
+            ));
+        }
+        entries.push(Entry {
+            num_hashes: entry.num_hashes,
+            hash: Hash::new_from_array(hash),
+            transactions: entry_transactions,
+        });
+    }
+    if transactions.next().is_some() {
+        return Err(invalid_block(
+            slot,
+            "block holds transactions outside its entries",
+        ));
+    }
+
+    let blockhash = parse_hash(slot, &block.blockhash)?;
+    if entries.last().map(|entry| entry.hash) != Some(blockhash) {
+        return Err(invalid_block(slot, "last entry hash is not the blockhash"));
+    }
+    // The previous blockhash is unknown when the exporting ledger lacked the parent block, and
+    // the first entry can then only be taken as is
+    let previous_blockhash = parse_hash(slot, &block.previous_blockhash)?;
+    let verified = if previous_blockhash == Hash::default() {
+        entries[1..].verify(&entries[0].hash)
+    } else {
+        entries.verify(&previous_blockhash)
+    };
+    if !verified {
+        return Err(invalid_block(slot, "PoH verification failed"));
+    }
+
+    let bank_hash = if manifest_entry.bank_hash.is_empty() {
+        None
+    } else {
+        Some(parse_hash(slot, &manifest_entry.bank_hash)?)
+    };
+    Ok(VerifiedBlock {
+        slot,
+        block,
+        entries,
+        bank_hash,
+    })
+}
+
+/// Verify the archive at `path` and write its blocks to `blockstore` as rooted slots, with their
+/// transaction statuses, rewards, block times and heights, and bank hashes. Returns the manifest
+/// of the archive.
+///
+/// The shreds of the imported blocks are re-created, and not signed by the slot leaders.
+pub fn import_block_archive(
+    blockstore: &Blockstore,
+    path: &Path,
+) -> Result<block_archive::Manifest> {
+    let (manifest, verified_blocks) = read_block_archive(path)?;
+    let reed_solomon_cache = ReedSolomonCache::default();
+    let keypair = Keypair::new();
+    for VerifiedBlock {
+        slot,
+        block,
+        entries,
+        bank_hash,
+    } in &verified_blocks
+    {
+        let (data_shreds, _coding_shreds) = Shredder::new(*slot, block.parent_slot, 0, 0)
+            .map_err(|err| invalid_block(*slot, &err.to_string()))?
+            .entries_to_shreds(
+                &keypair,
+                entries,
+                true, // is_last_in_slot
+                0,    // next_shred_index
+                0,    // next_code_index
+                true, // merkle_variant
+                &reed_solomon_cache,
+                &mut ProcessShredsStats::default(),
+            );
+        blockstore.insert_shreds(data_shreds, None, false)?;
+
+        for transaction_with_meta in &block.transactions {
+            let message = &transaction_with_meta.transaction.message;
+            let (writable_keys, readonly_keys): (Vec<(usize, &Pubkey)>, _) = transaction_with_meta
+                .account_keys()
+                .iter()
+                .enumerate()
+                .partition(|(index, _)| message.is_maybe_writable(*index));
+            blockstore.write_transaction_status(
+                *slot,
+                transaction_with_meta.transaction.signatures[0],
+                writable_keys.into_iter().map(|(_, key)| key).collect(),
+                readonly_keys.into_iter().map(|(_, key)| key).collect(),
+                transaction_with_meta.meta.clone(),
+            )?;
+        }
+        blockstore.write_rewards(*slot, block.rewards.clone())?;
+        if let Some(block_time) = block.block_time {
+            blockstore.cache_block_time(*slot, block_time)?;
+        }
+        if let Some(block_height) = block.block_height {
+            blockstore.cache_block_height(*slot, block_height)?;
+        }
+        if let Some(bank_hash) = bank_hash {
+            blockstore.insert_bank_hash(*slot, *bank_hash, false);
+        }
+    }
+    blockstore.set_roots(
+        verified_blocks
+            .iter()
+            .map(|verified_block| &verified_block.slot),
+    )?;
+    info!(
+        "Imported {} blocks of slots {} to {} from {}",
+        verified_blocks.len(),
+        manifest.first_slot,
+        manifest.last_slot,
+        path.display()
+    );
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            blockstore::{entries_to_test_shreds, tests::make_slot_entries_with_transactions},
+            get_tmp_ledger_path_auto_delete,
+        },
+        solana_entry::entry::next_hash,
+        solana_sdk::{message::v0::LoadedAddresses, transaction_context::TransactionReturnData},
+        solana_transaction_status::TransactionStatusMeta,
+    };
+
+    fn status_meta(num_accounts: usize) -> TransactionStatusMeta {
+        TransactionStatusMeta {
+            status: Ok(()),
+            fee: 42,
+            pre_balances: vec![11; num_accounts],
+            post_balances: vec![10; num_accounts],
+            inner_instructions: Some(vec![]),
+            log_messages: Some(vec![]),
+            pre_token_balances: Some(vec![]),
+            post_token_balances: Some(vec![]),
+            rewards: Some(vec![]),
+            loaded_addresses: LoadedAddresses::default(),
+            return_data: Some(TransactionReturnData::default()),
+            compute_units_consumed: Some(1234),
+        }
+    }
+
+    /// Insert rooted slots `1..=num_slots` whose entries chain from slot 0
+    fn insert_rooted_slots(blockstore: &Blockstore, num_slots: Slot) {
+        let mut last_hash = Hash::default();
+        for slot in 0..=num_slots {
+            let mut entries = make_slot_entries_with_transactions(3);
+            // Chain the entries of the slot to the previous slot
+            let mut hash = last_hash;
+            for entry in entries.iter_mut() {
+                entry.num_hashes = 1;
+                entry.hash = next_hash(&hash, 1, &entry.transactions);
+                hash = entry.hash;
+            }
+            last_hash = hash;
+            for transaction in entries.iter().flat_map(|entry| &entry.transactions) {
+                let num_accounts = transaction.message.static_account_keys().len();
+                blockstore
+                    .write_transaction_status(
+                        slot,
+                        transaction.signatures[0],
+                        vec![],
+                        vec![],
+                        status_meta(num_accounts),
+                    )
+                    .unwrap();
+            }
+            let shreds =
+                entries_to_test_shreds(&entries, slot, slot.saturating_sub(1), true, 0, true);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+            blockstore.cache_block_time(slot, slot as i64 * 10).unwrap();
+            blockstore.insert_bank_hash(slot, hash, false);
+        }
+        blockstore
+            .set_roots((0..=num_slots).collect::<Vec<_>>().iter())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_block_archive_export_import() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        insert_rooted_slots(&blockstore, 5);
+
+        let archive_dir = tempfile::tempdir().unwrap();
+        let paths = export_block_archives(&blockstore, 1, 100, 3, archive_dir.path()).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                archive_dir.path().join(archive_file_name(1, 3)),
+                archive_dir.path().join(archive_file_name(4, 5)),
+            ]
+        );
+
+        let import_path = get_tmp_ledger_path_auto_delete!();
+        let imported = Blockstore::open(import_path.path()).unwrap();
+        for path in &paths {
+            import_block_archive(&imported, path).unwrap();
+        }
+        for slot in 1..=5 {
+            let expected = blockstore.get_rooted_block(slot, false).unwrap();
+            let mut block = imported.get_rooted_block(slot, false).unwrap();
+            if slot == 1 {
+                // The parent of the first imported block is not in the new ledger
+                assert_eq!(block.previous_blockhash, Hash::default().to_string());
+                block.previous_blockhash = expected.previous_blockhash.clone();
+            }
+            assert_eq!(block, expected);
+            assert_eq!(
+                imported.get_slot_entries(slot, 0).unwrap(),
+                blockstore.get_slot_entries(slot, 0).unwrap()
+            );
+            assert_eq!(imported.get_bank_hash(slot), blockstore.get_bank_hash(slot));
+        }
+
+        // Tampering with a block is caught by the manifest
+        let mut archive =
+            block_archive::BlockArchive::decode(&fs::read(&paths[0]).unwrap()[..]).unwrap();
+        archive.blocks[1].block.as_mut().unwrap().block_time = None;
+        fs::write(&paths[0], archive.encode_to_vec()).unwrap();
+        assert!(matches!(
+            read_block_archive(&paths[0]),
+            Err(BlockArchiveError::InvalidBlock(2, _))
+        ));
+
+        // As is a block not matching its entries, even with a matching manifest
+        let mut archive =
+            block_archive::BlockArchive::decode(&fs::read(&paths[1]).unwrap()[..]).unwrap();
+        archive.blocks[0].entries[0].num_hashes += 1;
+        let content_hash = content_hash(&archive.blocks[0]).to_bytes().to_vec();
+        archive.manifest.as_mut().unwrap().blocks[0].content_hash = content_hash;
+        fs::write(&paths[1], archive.encode_to_vec()).unwrap();
+        assert!(matches!(
+            read_block_archive(&paths[1]),
+            Err(BlockArchiveError::InvalidBlock(4, _))
+        ));
+
+        // Or a transaction without signatures
+        let paths = export_block_archives(&blockstore, 1, 100, 3, archive_dir.path()).unwrap();
+        let mut archive =
+            block_archive::BlockArchive::decode(&fs::read(&paths[1]).unwrap()[..]).unwrap();
+        archive.blocks[0].block.as_mut().unwrap().transactions[0]
+            .transaction
+            .as_mut()
+            .unwrap()
+            .signatures
+            .clear();
+        let content_hash = content_hash(&archive.blocks[0]).to_bytes().to_vec();
+        archive.manifest.as_mut().unwrap().blocks[0].content_hash = content_hash;
+        fs::write(&paths[1], archive.encode_to_vec()).unwrap();
+        assert!(matches!(
+            read_block_archive(&paths[1]),
+            Err(BlockArchiveError::InvalidBlock(4, reason))
+                if reason == "transaction without signatures"
+        ));
+    }
+}
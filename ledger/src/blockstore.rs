@@ -41,6 +41,7 @@ use {
     solana_rayon_threadlimit::get_max_thread_count,
     solana_runtime::hardened_unpack::{unpack_genesis_archive, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
     solana_sdk::{
+        account::Account,
         clock::{Slot, UnixTimestamp, DEFAULT_TICKS_PER_SECOND, MS_PER_TICK},
         genesis_config::{GenesisConfig, DEFAULT_GENESIS_ARCHIVE, DEFAULT_GENESIS_FILE},
         hash::Hash,
@@ -171,6 +172,7 @@ pub struct Blockstore {
     program_costs_cf: LedgerColumn<cf::ProgramCosts>,
     bank_hash_cf: LedgerColumn<cf::BankHash>,
     optimistic_slots_cf: LedgerColumn<cf::OptimisticSlots>,
+    account_history_cf: LedgerColumn<cf::AccountHistory>,
     last_root: RwLock<Slot>,
     insert_shreds_lock: Mutex<()>,
     new_shreds_signals: Mutex<Vec<Sender<bool>>>,
@@ -277,6 +279,7 @@ impl Blockstore {
         let program_costs_cf = db.column();
         let bank_hash_cf = db.column();
         let optimistic_slots_cf = db.column();
+        let account_history_cf = db.column();
 
         let db = Arc::new(db);
 
@@ -329,6 +332,7 @@ impl Blockstore {
             program_costs_cf,
             bank_hash_cf,
             optimistic_slots_cf,
+            account_history_cf,
             new_shreds_signals: Mutex::default(),
             completed_slots_senders: Mutex::default(),
             shred_timing_point_sender: None,
@@ -702,6 +706,7 @@ impl Blockstore {
         self.program_costs_cf.submit_rocksdb_cf_metrics();
         self.bank_hash_cf.submit_rocksdb_cf_metrics();
         self.optimistic_slots_cf.submit_rocksdb_cf_metrics();
+        self.account_history_cf.submit_rocksdb_cf_metrics();
     }
 
     fn try_shred_recovery(
@@ -3063,6 +3068,45 @@ impl Blockstore {
             .collect())
     }
 
+    /// Records the state of the accounts written in `slot` as it was before the slot
+    pub fn write_account_history(
+        &self,
+        slot: Slot,
+        prior_states: impl Iterator<Item = (Pubkey, Option<Account>)>,
+    ) -> Result<()> {
+        let mut write_batch = self.db.batch()?;
+        for (pubkey, prior_state) in prior_states {
+            write_batch
+                .put::<cf::AccountHistory>((pubkey, slot), &AccountHistoryEntry { prior_state })?;
+        }
+        self.db.write(write_batch)
+    }
+
+    /// Returns the first rooted slot in `(after_slot, up_to_slot]` that wrote the account, along
+    /// with the state of the account before that slot. This is the state of the account as of
+    /// `after_slot` when the history of the range has been recorded.
+    pub fn get_account_history_prior_state(
+        &self,
+        pubkey: &Pubkey,
+        after_slot: Slot,
+        up_to_slot: Slot,
+    ) -> Result<Option<(Slot, Option<Account>)>> {
+        let index_iterator = self.account_history_cf.iter(IteratorMode::From(
+            (*pubkey, after_slot.saturating_add(1)),
+            IteratorDirection::Forward,
+        ))?;
+        for ((address, slot), data) in index_iterator {
+            if address != *pubkey || slot > up_to_slot {
+                break;
+            }
+            if self.is_root(slot) {
+                let entry: AccountHistoryEntry = deserialize(&data)?;
+                return Ok(Some((slot, entry.prior_state)));
+            }
+        }
+        Ok(None)
+    }
+
     pub fn set_duplicate_confirmed_slots_and_hashes(
         &self,
         duplicate_confirmed_slot_hashes: impl Iterator<Item = (Slot, Hash)>,
@@ -9627,6 +9671,65 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_account_history() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let pubkey = Pubkey::new_unique();
+        let other_pubkey = Pubkey::new_unique();
+        let account = |lamports| Account {
+            lamports,
+            ..Account::default()
+        };
+
+        // Slot 3 creates the account, slot 5 is not rooted, slot 7 updates it
+        blockstore
+            .write_account_history(3, vec![(pubkey, None)].into_iter())
+            .unwrap();
+        blockstore
+            .write_account_history(
+                5,
+                vec![(pubkey, Some(account(10))), (other_pubkey, None)].into_iter(),
+            )
+            .unwrap();
+        blockstore
+            .write_account_history(7, vec![(pubkey, Some(account(10)))].into_iter())
+            .unwrap();
+        blockstore.set_roots(vec![1, 3, 7].iter()).unwrap();
+
+        assert_eq!(
+            blockstore
+                .get_account_history_prior_state(&pubkey, 1, 10)
+                .unwrap(),
+            Some((3, None))
+        );
+        assert_eq!(
+            blockstore
+                .get_account_history_prior_state(&pubkey, 3, 10)
+                .unwrap(),
+            Some((7, Some(account(10))))
+        );
+        assert_eq!(
+            blockstore
+                .get_account_history_prior_state(&pubkey, 3, 6)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            blockstore
+                .get_account_history_prior_state(&pubkey, 7, 10)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            blockstore
+                .get_account_history_prior_state(&other_pubkey, 1, 10)
+                .unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_delete_old_records_from_cost_table() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -93,6 +93,8 @@ const BLOCK_HEIGHT_CF: &str = "block_height";
 const PROGRAM_COSTS_CF: &str = "program_costs";
 /// Column family for optimistic slots
 const OPTIMISTIC_SLOTS_CF: &str = "optimistic_slots";
+/// Column family for account history
+const ACCOUNT_HISTORY_CF: &str = "account_history";
 
 // 1 day is chosen for the same reasoning of DEFAULT_COMPACTION_SLOT_INTERVAL
 const PERIODIC_COMPACTION_SECONDS: u64 = 60 * 60 * 24;
@@ -215,6 +217,10 @@ pub mod columns {
     /// The optimistic slot column
     pub struct OptimisticSlots;
 
+    #[derive(Debug)]
+    /// The account history column, only purged by the compaction filter
+    pub struct AccountHistory;
+
     // When adding a new column ...
     // - Add struct below and implement `Column` and `ColumnName` traits
     // - Add descriptor in Rocks::cf_descriptors() and name in Rocks::columns()
@@ -398,6 +404,7 @@ impl Rocks {
             new_cf_descriptor::<BlockHeight>(options, oldest_slot),
             new_cf_descriptor::<ProgramCosts>(options, oldest_slot),
             new_cf_descriptor::<OptimisticSlots>(options, oldest_slot),
+            new_cf_descriptor::<AccountHistory>(options, oldest_slot),
         ]
     }
 
@@ -425,6 +432,7 @@ impl Rocks {
             BlockHeight::NAME,
             ProgramCosts::NAME,
             OptimisticSlots::NAME,
+            AccountHistory::NAME,
         ]
     }
 
@@ -949,6 +957,42 @@ impl TypedColumn for columns::OptimisticSlots {
     type Type = blockstore_meta::OptimisticSlotMetaVersioned;
 }
 
+impl Column for columns::AccountHistory {
+    type Index = (Pubkey, Slot);
+
+    fn key((pubkey, slot): (Pubkey, Slot)) -> Vec<u8> {
+        let mut key = vec![0; 32 + 8]; // size_of Pubkey + size_of Slot
+        key[0..32].clone_from_slice(&pubkey.as_ref()[0..32]);
+        BigEndian::write_u64(&mut key[32..40], slot);
+        key
+    }
+
+    fn index(key: &[u8]) -> (Pubkey, Slot) {
+        let pubkey = Pubkey::try_from(&key[0..32]).unwrap();
+        let slot = BigEndian::read_u64(&key[32..40]);
+        (pubkey, slot)
+    }
+
+    fn primary_index(_index: Self::Index) -> u64 {
+        unimplemented!()
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.1
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn as_index(_index: u64) -> Self::Index {
+        (Pubkey::default(), 0)
+    }
+}
+impl ColumnName for columns::AccountHistory {
+    const NAME: &'static str = ACCOUNT_HISTORY_CF;
+}
+impl TypedColumn for columns::AccountHistory {
+    type Type = blockstore_meta::AccountHistoryEntry;
+}
+
 #[derive(Debug)]
 pub struct Database {
     backend: Arc<Rocks>,
@@ -3,6 +3,7 @@ use {
     bitflags::bitflags,
     serde::{Deserialize, Deserializer, Serialize, Serializer},
     solana_sdk::{
+        account::Account,
         clock::{Slot, UnixTimestamp},
         hash::Hash,
     },
@@ -414,6 +415,13 @@ impl OptimisticSlotMetaVersioned {
         }
     }
 }
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AccountHistoryEntry {
+    /// The state of the account before the writes of the slot, `None` if the
+    /// account did not exist
+    pub prior_state: Option<Account>,
+}
 #[cfg(test)]
 mod test {
     use {
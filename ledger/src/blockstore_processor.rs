@@ -1,7 +1,11 @@
 use {
     crate::{
-        block_error::BlockError, blockstore::Blockstore, blockstore_db::BlockstoreError,
-        blockstore_meta::SlotMeta, leader_schedule_cache::LeaderScheduleCache,
+        block_error::BlockError,
+        blockstore::Blockstore,
+        blockstore_db::BlockstoreError,
+        blockstore_meta::SlotMeta,
+        entry_notifier_service::{EntryNotification, EntryNotifierSender},
+        leader_schedule_cache::LeaderScheduleCache,
         token_balances::collect_token_balances,
     },
     chrono_humanize::{Accuracy, HumanTime, Tense},
@@ -11,7 +15,8 @@ use {
     rand::{seq::SliceRandom, thread_rng},
     rayon::{prelude::*, ThreadPool},
     solana_entry::entry::{
-        self, create_ticks, Entry, EntrySlice, EntryType, EntryVerificationStatus, VerifyRecyclers,
+        self, create_ticks, Entry, EntrySlice, EntrySummary, EntryType, EntryVerificationStatus,
+        VerifyRecyclers,
     },
     solana_measure::{measure, measure::Measure},
     solana_metrics::{datapoint_error, inc_new_counter_debug},
@@ -650,6 +655,7 @@ pub fn test_process_blockstore(
         opts,
         None,
         None,
+        None,
         &AbsRequestSender::default(),
     )
     .unwrap();
@@ -699,6 +705,7 @@ pub fn process_blockstore_from_root(
     leader_schedule_cache: &LeaderScheduleCache,
     opts: &ProcessOptions,
     transaction_status_sender: Option<&TransactionStatusSender>,
+    entry_notification_sender: Option<&EntryNotifierSender>,
     cache_block_meta_sender: Option<&CacheBlockMetaSender>,
     accounts_background_request_sender: &AbsRequestSender,
 ) -> result::Result<(), BlockstoreProcessorError> {
@@ -746,6 +753,7 @@ pub fn process_blockstore_from_root(
             leader_schedule_cache,
             opts,
             transaction_status_sender,
+            entry_notification_sender,
             cache_block_meta_sender,
             &mut timing,
             accounts_background_request_sender,
@@ -850,6 +858,7 @@ fn confirm_full_slot(
     recyclers: &VerifyRecyclers,
     progress: &mut ConfirmationProgress,
     transaction_status_sender: Option<&TransactionStatusSender>,
+    entry_notification_sender: Option<&EntryNotifierSender>,
     replay_vote_sender: Option<&ReplayVoteSender>,
     timing: &mut ExecuteTimings,
 ) -> result::Result<(), BlockstoreProcessorError> {
@@ -864,6 +873,7 @@ fn confirm_full_slot(
         progress,
         skip_verification,
         transaction_status_sender,
+        entry_notification_sender,
         replay_vote_sender,
         opts.entry_callback.as_ref(),
         recyclers,
@@ -991,6 +1001,7 @@ pub fn confirm_slot(
     progress: &mut ConfirmationProgress,
     skip_verification: bool,
     transaction_status_sender: Option<&TransactionStatusSender>,
+    entry_notification_sender: Option<&EntryNotifierSender>,
     replay_vote_sender: Option<&ReplayVoteSender>,
     entry_callback: Option<&ProcessCallback>,
     recyclers: &VerifyRecyclers,
@@ -1021,6 +1032,7 @@ pub fn confirm_slot(
         progress,
         skip_verification,
         transaction_status_sender,
+        entry_notification_sender,
         replay_vote_sender,
         entry_callback,
         recyclers,
@@ -1037,6 +1049,7 @@ fn confirm_slot_entries(
     progress: &mut ConfirmationProgress,
     skip_verification: bool,
     transaction_status_sender: Option<&TransactionStatusSender>,
+    entry_notification_sender: Option<&EntryNotifierSender>,
     replay_vote_sender: Option<&ReplayVoteSender>,
     entry_callback: Option<&ProcessCallback>,
     recyclers: &VerifyRecyclers,
@@ -1066,6 +1079,8 @@ fn confirm_slot_entries(
         num_txs,
         slot_full,
     );
+    let entry_summaries: Option<Vec<EntrySummary>> =
+        entry_notification_sender.map(|_| entries.iter().map(EntrySummary::from).collect());
 
     if !skip_verification {
         let tick_hash_count = &mut progress.tick_hash_count;
@@ -1172,6 +1187,23 @@ fn confirm_slot_entries(
 
             process_result?;
 
+            if let (Some(entry_notification_sender), Some(entry_summaries)) =
+                (entry_notification_sender, entry_summaries)
+            {
+                for (i, entry) in entry_summaries.into_iter().enumerate() {
+                    if let Err(err) = entry_notification_sender.send(EntryNotification {
+                        slot,
+                        index: progress.num_entries.saturating_add(i),
+                        entry,
+                    }) {
+                        warn!(
+                            "Slot {}, entry {} entry_notification_sender send failed: {:?}",
+                            slot, i, err
+                        );
+                    }
+                }
+            }
+
             progress.num_shreds += num_shreds;
             progress.num_entries += num_entries;
             progress.num_txs += num_txs;
@@ -1206,6 +1238,7 @@ fn process_bank_0(
         &mut progress,
         None,
         None,
+        None,
         &mut ExecuteTimings::default(),
     )
     .expect("Failed to process bank 0 from ledger. Did you forget to provide a snapshot?");
@@ -1282,6 +1315,7 @@ fn load_frozen_forks(
     leader_schedule_cache: &LeaderScheduleCache,
     opts: &ProcessOptions,
     transaction_status_sender: Option<&TransactionStatusSender>,
+    entry_notification_sender: Option<&EntryNotifierSender>,
     cache_block_meta_sender: Option<&CacheBlockMetaSender>,
     timing: &mut ExecuteTimings,
     accounts_background_request_sender: &AbsRequestSender,
@@ -1344,6 +1378,7 @@ fn load_frozen_forks(
                 &recyclers,
                 &mut progress,
                 transaction_status_sender,
+                entry_notification_sender,
                 cache_block_meta_sender,
                 None,
                 timing,
@@ -1544,6 +1579,7 @@ fn process_single_slot(
     recyclers: &VerifyRecyclers,
     progress: &mut ConfirmationProgress,
     transaction_status_sender: Option<&TransactionStatusSender>,
+    entry_notification_sender: Option<&EntryNotifierSender>,
     cache_block_meta_sender: Option<&CacheBlockMetaSender>,
     replay_vote_sender: Option<&ReplayVoteSender>,
     timing: &mut ExecuteTimings,
@@ -1557,6 +1593,7 @@ fn process_single_slot(
         recyclers,
         progress,
         transaction_status_sender,
+        entry_notification_sender,
         replay_vote_sender,
         timing,
     )
@@ -3402,6 +3439,7 @@ pub mod tests {
             &mut ConfirmationProgress::new(bank0.last_blockhash()),
             None,
             None,
+            None,
             &mut ExecuteTimings::default(),
         )
         .unwrap();
@@ -3422,6 +3460,7 @@ pub mod tests {
             &opts,
             None,
             None,
+            None,
             &AbsRequestSender::default(),
         )
         .unwrap();
@@ -4049,6 +4088,7 @@ pub mod tests {
             None,
             None,
             None,
+            None,
             &VerifyRecyclers::default(),
             None,
             &PrioritizationFeeCache::new(0u64),
@@ -4193,6 +4233,7 @@ pub mod tests {
             Some(&transaction_status_sender),
             None,
             None,
+            None,
             &VerifyRecyclers::default(),
             None,
             &PrioritizationFeeCache::new(0u64),
@@ -4239,6 +4280,7 @@ pub mod tests {
             Some(&transaction_status_sender),
             None,
             None,
+            None,
             &VerifyRecyclers::default(),
             None,
             &PrioritizationFeeCache::new(0u64),
@@ -4258,6 +4300,78 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_confirm_slot_entries_entry_notifications() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(100 * LAMPORTS_PER_SOL);
+        let genesis_hash = genesis_config.hash();
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let mut timing = ConfirmationTiming::default();
+        let mut progress = ConfirmationProgress::new(genesis_hash);
+        let amount = genesis_config.rent.minimum_balance(0);
+        let (entry_notification_sender, entry_notification_receiver) =
+            crossbeam_channel::unbounded();
+
+        let tx1 = system_transaction::transfer(
+            &mint_keypair,
+            &Pubkey::new_unique(),
+            amount,
+            genesis_hash,
+        );
+        let tx2 = system_transaction::transfer(
+            &mint_keypair,
+            &Pubkey::new_unique(),
+            amount,
+            genesis_hash,
+        );
+        let entry1 = next_entry(&genesis_hash, 1, vec![tx1]);
+        let entry2 = next_entry(&entry1.hash, 1, vec![tx2]);
+        let entries = vec![entry1, entry2];
+
+        confirm_slot_entries(
+            &bank,
+            (entries[..1].to_vec(), 0, false),
+            &mut timing,
+            &mut progress,
+            false,
+            None,
+            Some(&entry_notification_sender),
+            None,
+            None,
+            &VerifyRecyclers::default(),
+            None,
+            &PrioritizationFeeCache::new(0u64),
+        )
+        .unwrap();
+        confirm_slot_entries(
+            &bank,
+            (entries[1..].to_vec(), 0, false),
+            &mut timing,
+            &mut progress,
+            false,
+            None,
+            Some(&entry_notification_sender),
+            None,
+            None,
+            &VerifyRecyclers::default(),
+            None,
+            &PrioritizationFeeCache::new(0u64),
+        )
+        .unwrap();
+
+        let notifications: Vec<_> = entry_notification_receiver.try_iter().collect();
+        assert_eq!(notifications.len(), entries.len());
+        for (index, (notification, entry)) in notifications.iter().zip(&entries).enumerate() {
+            assert_eq!(notification.slot, bank.slot());
+            assert_eq!(notification.index, index);
+            assert_eq!(notification.entry, EntrySummary::from(entry));
+            assert_eq!(notification.entry.num_transactions, 1);
+        }
+    }
+
     #[test]
     fn test_rebatch_transactions() {
         let dummy_leader_pubkey = solana_sdk::pubkey::new_rand();
@@ -7,6 +7,7 @@ pub mod bank_forks_utils;
 pub mod bigtable_delete;
 pub mod bigtable_upload;
 pub mod bigtable_upload_service;
+pub mod block_archive;
 pub mod block_error;
 #[macro_use]
 pub mod blockstore;
@@ -17,6 +18,8 @@ pub mod blockstore_metrics;
 pub mod blockstore_options;
 pub mod blockstore_processor;
 pub mod builtins;
+pub mod entry_notifier_interface;
+pub mod entry_notifier_service;
 pub mod genesis_utils;
 pub mod leader_schedule;
 pub mod leader_schedule_cache;
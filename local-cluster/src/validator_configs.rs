@@ -15,8 +15,11 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         account_shrink_paths: config.account_shrink_paths.clone(),
         rpc_config: config.rpc_config.clone(),
         geyser_plugin_config_files: config.geyser_plugin_config_files.clone(),
+        geyser_plugin_always_enabled: config.geyser_plugin_always_enabled,
         rpc_addrs: config.rpc_addrs,
         pubsub_config: config.pubsub_config.clone(),
+        grpc_streaming_addr: config.grpc_streaming_addr,
+        grpc_streaming_config: config.grpc_streaming_config.clone(),
         snapshot_config: config.snapshot_config.clone(),
         max_ledger_shreds: config.max_ledger_shreds,
         broadcast_stage_type: config.broadcast_stage_type.clone(),
@@ -1,7 +1,11 @@
 //! The `metrics` module enables sending measurements to an `InfluxDB` instance
 
 use {
-    crate::{counter::CounterPoint, datapoint::DataPoint},
+    crate::{
+        counter::CounterPoint,
+        datapoint::DataPoint,
+        prometheus::{self, PrometheusRegistry},
+    },
     crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender},
     gethostname::gethostname,
     lazy_static::lazy_static,
@@ -171,12 +175,34 @@ impl MetricsAgent {
         writer: Arc<dyn MetricsWriter + Send + Sync>,
         write_frequency: Duration,
         max_points_per_sec: usize,
+    ) -> Self {
+        Self::new_with_prometheus(
+            writer,
+            write_frequency,
+            max_points_per_sec,
+            prometheus::REGISTRY.clone(),
+        )
+    }
+
+    fn new_with_prometheus(
+        writer: Arc<dyn MetricsWriter + Send + Sync>,
+        write_frequency: Duration,
+        max_points_per_sec: usize,
+        prometheus: Arc<PrometheusRegistry>,
     ) -> Self {
         let (sender, receiver) = unbounded::<MetricsCommand>();
 
         thread::Builder::new()
             .name("solMetricsAgent".into())
-            .spawn(move || Self::run(&receiver, &writer, write_frequency, max_points_per_sec))
+            .spawn(move || {
+                Self::run(
+                    &receiver,
+                    &writer,
+                    write_frequency,
+                    max_points_per_sec,
+                    &prometheus,
+                )
+            })
             .unwrap();
 
         Self { sender }
@@ -234,6 +260,7 @@ impl MetricsAgent {
         writer: &Arc<dyn MetricsWriter + Send + Sync>,
         write_frequency: Duration,
         max_points_per_sec: usize,
+        prometheus: &PrometheusRegistry,
     ) {
         trace!("run: enter");
         let mut last_write_time = Instant::now();
@@ -260,10 +287,12 @@ impl MetricsAgent {
                     }
                     MetricsCommand::Submit(point, level) => {
                         log!(level, "{}", point);
+                        prometheus.record_point(&point);
                         points.push(point);
                     }
                     MetricsCommand::SubmitCounter(counter, _level, bucket) => {
                         debug!("{:?}", counter);
+                        prometheus.record_counter(&counter);
                         let key = (counter.name, bucket);
                         if let Some(value) = counters.get_mut(&key) {
                             value.count += counter.count;
@@ -588,6 +617,43 @@ mod test {
         assert_eq!(writer.points_written(), 11);
     }
 
+    #[test]
+    fn test_submit_prometheus() {
+        let writer = Arc::new(MockMetricsWriter::new());
+        let prometheus = Arc::new(PrometheusRegistry::default());
+        prometheus.enable();
+        let agent = MetricsAgent::new_with_prometheus(
+            writer.clone(),
+            Duration::from_secs(10),
+            1000,
+            prometheus.clone(),
+        );
+
+        agent.submit(
+            DataPoint::new("measurement")
+                .add_field_i64("i", 42)
+                .to_owned(),
+            Level::Info,
+        );
+        for i in 0..10 {
+            agent.submit_counter(
+                CounterPoint {
+                    name: "counter",
+                    count: 1,
+                    timestamp: UNIX_EPOCH,
+                },
+                Level::Info,
+                i,
+            );
+        }
+
+        agent.flush();
+        assert_eq!(writer.points_written(), 12);
+        let text = prometheus.render();
+        assert!(text.contains("renec_measurement_i 42\n"));
+        assert!(text.contains("renec_counter_total 10\n"));
+    }
+
     #[test]
     fn test_submit_with_delay() {
         let writer = Arc::new(MockMetricsWriter::new());
@@ -0,0 +1,417 @@
+//! The `prometheus` module exposes the submitted metrics on an HTTP `/metrics`
+//! endpoint, in the OpenMetrics text format.
+//!
+//! Every point and counter submitted to the `MetricsAgent` is recorded here
+//! once the exporter is started, alongside being sent to `InfluxDB`:
+//!
+//! - each numeric field of a datapoint is a gauge named
+//!   `renec_<datapoint>_<field>`, holding the latest value, and labelled by the
+//!   tags of the datapoint
+//! - each counter is a counter named `renec_<counter>`, summing all its counts
+//!
+//! Most datapoints report values accumulated over their reporting interval,
+//! which their gauges hold until the next report.
+
+use {
+    crate::{counter::CounterPoint, datapoint::DataPoint},
+    lazy_static::lazy_static,
+    log::*,
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt::Write as _,
+        io::{self, Read, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, JoinHandle},
+        time::Duration,
+    },
+};
+
+const METRIC_PREFIX: &str = "renec_";
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Bound on the number of series, as tags with many values would otherwise
+/// grow the registry without limit
+const MAX_SERIES: usize = 50_000;
+
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    pub(crate) static ref REGISTRY: Arc<PrometheusRegistry> =
+        Arc::new(PrometheusRegistry::default());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Value {
+    Int(i64),
+    Float(f64),
+}
+
+impl Value {
+    /// Parses a field value of a `DataPoint`, which are formatted for the
+    /// `InfluxDB` line protocol. String fields have no numeric value.
+    fn parse(value: &str) -> Option<Self> {
+        if let Some(int) = value.strip_suffix('i') {
+            return int.parse().ok().map(Value::Int);
+        }
+        match value {
+            "true" => Some(Value::Int(1)),
+            "false" => Some(Value::Int(0)),
+            _ if value.starts_with('"') => None,
+            _ => value.parse().ok().map(Value::Float),
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            Value::Int(value) => value.to_string(),
+            Value::Float(value) if value.is_nan() => "NaN".to_string(),
+            Value::Float(value) if value.is_infinite() => if value.is_sign_positive() {
+                "+Inf"
+            } else {
+                "-Inf"
+            }
+            .to_string(),
+            Value::Float(value) => value.to_string(),
+        }
+    }
+}
+
+/// Metric name, and rendered labels
+type SeriesKey = (String, String);
+
+#[derive(Default)]
+struct Series {
+    gauges: BTreeMap<SeriesKey, Value>,
+    counters: BTreeMap<SeriesKey, i64>,
+}
+
+impl Series {
+    fn len(&self) -> usize {
+        self.gauges.len() + self.counters.len()
+    }
+}
+
+/// The latest values of the submitted metrics
+#[derive(Default)]
+pub struct PrometheusRegistry {
+    enabled: AtomicBool,
+    series: Mutex<Series>,
+    series_dropped: AtomicBool,
+    name_collided: AtomicBool,
+}
+
+impl PrometheusRegistry {
+    pub(crate) fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    fn has_room(&self, series: &Series) -> bool {
+        if series.len() < MAX_SERIES {
+            return true;
+        }
+        if !self.series_dropped.swap(true, Ordering::Relaxed) {
+            warn!(
+                "prometheus: more than {} series, dropping new ones",
+                MAX_SERIES
+            );
+        }
+        false
+    }
+
+    pub(crate) fn record_point(&self, point: &DataPoint) {
+        if !self.is_enabled() {
+            return;
+        }
+        let labels = render_labels(&point.tags);
+        let mut series = self.series.lock().unwrap();
+        for (field, value) in &point.fields {
+            if let Some(value) = Value::parse(value) {
+                let key = (metric_name(&[point.name, field]), labels.clone());
+                if series.gauges.contains_key(&key) || self.has_room(&series) {
+                    series.gauges.insert(key, value);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn record_counter(&self, counter: &CounterPoint) {
+        if !self.is_enabled() {
+            return;
+        }
+        let key = (metric_name(&[counter.name]), String::new());
+        let mut series = self.series.lock().unwrap();
+        if let Some(total) = series.counters.get_mut(&key) {
+            *total = total.saturating_add(counter.count);
+        } else if self.has_room(&series) {
+            series.counters.insert(key, counter.count);
+        }
+    }
+
+    /// Renders all series in the OpenMetrics text format
+    pub fn render(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let mut text = String::new();
+        let mut family = "";
+        for ((name, labels), value) in &series.gauges {
+            if name != family {
+                let _ = writeln!(text, "# TYPE {} gauge", name);
+                family = name;
+            }
+            let _ = writeln!(text, "{}{} {}", name, labels, value.render());
+        }
+        // The samples of a counter family are named with the suffixes `_total` and `_created`,
+        // and a family name can only be declared once
+        let gauge_names: BTreeSet<&str> = series
+            .gauges
+            .keys()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        for ((name, labels), total) in &series.counters {
+            if ["", "_total", "_created"]
+                .iter()
+                .any(|suffix| gauge_names.contains(format!("{}{}", name, suffix).as_str()))
+            {
+                if !self.name_collided.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "prometheus: counter {} collides with a datapoint field, skipping it",
+                        name
+                    );
+                }
+                continue;
+            }
+            let _ = writeln!(text, "# TYPE {} counter", name);
+            let _ = writeln!(text, "{}_total{} {}", name, labels, total);
+        }
+        text.push_str("# EOF\n");
+        text
+    }
+}
+
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn metric_name(parts: &[&str]) -> String {
+    let mut name = METRIC_PREFIX.to_string();
+    name.push_str(
+        &parts
+            .iter()
+            .map(|part| sanitize_name(part))
+            .collect::<Vec<_>>()
+            .join("_"),
+    );
+    name
+}
+
+fn render_labels(tags: &[(&'static str, String)]) -> String {
+    if tags.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<String> = tags
+        .iter()
+        .map(|(name, value)| {
+            let mut name = sanitize_name(name);
+            if name.starts_with(|c: char| c.is_ascii_digit()) {
+                name.insert(0, '_');
+            }
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+fn handle_request(mut stream: TcpStream, registry: &PrometheusRegistry) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut request = vec![];
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let len = stream.read(&mut buf)?;
+        if len == 0 || request.len() > MAX_REQUEST_LEN {
+            break;
+        }
+        request.extend_from_slice(&buf[..len]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default();
+    let path = request_line.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+
+    let (status, content_type, body) = if method != "GET" {
+        ("405 Method Not Allowed", "text/plain", String::new())
+    } else if path == "/metrics" {
+        ("200 OK", CONTENT_TYPE, registry.render())
+    } else {
+        ("404 Not Found", "text/plain", String::new())
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn serve(listener: TcpListener, registry: Arc<PrometheusRegistry>) -> io::Result<JoinHandle<()>> {
+    registry.enable();
+    thread::Builder::new()
+        .name("solMetricsProm".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(err) = handle_request(stream, &registry) {
+                            debug!("prometheus: request failed: {}", err);
+                        }
+                    }
+                    Err(err) => warn!("prometheus: accept failed: {}", err),
+                }
+            }
+        })
+}
+
+/// Starts recording the submitted metrics, and serving them on
+/// `http://<address>/metrics`
+pub fn start_exporter(address: SocketAddr) -> io::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(address)?;
+    info!("prometheus: serving metrics on http://{}/metrics", address);
+    serve(listener, REGISTRY.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, std::time::UNIX_EPOCH};
+
+    #[test]
+    fn test_render() {
+        let registry = PrometheusRegistry::default();
+        let point = DataPoint::new("replay-slot-stats")
+            .add_tag("op", "a \"quoted\" value")
+            .add_field_i64("total_entries", 12)
+            .add_field_f64("ratio", 0.5)
+            .add_field_bool("is_full", true)
+            .add_field_str("message", "not a number")
+            .to_owned();
+        let counter = CounterPoint {
+            name: "bank-process_transactions-txs",
+            count: 3,
+            timestamp: UNIX_EPOCH,
+        };
+
+        // Nothing is recorded until the exporter is enabled
+        registry.record_point(&point);
+        registry.record_counter(&counter);
+        assert_eq!(registry.render(), "# EOF\n");
+
+        registry.enable();
+        registry.record_point(&point);
+        registry.record_point(
+            &DataPoint::new("replay-slot-stats")
+                .add_tag("op", "a \"quoted\" value")
+                .add_field_i64("total_entries", 15)
+                .to_owned(),
+        );
+        registry.record_counter(&counter);
+        registry.record_counter(&counter);
+        assert_eq!(
+            registry.render(),
+            "# TYPE renec_replay_slot_stats_is_full gauge\n\
+             renec_replay_slot_stats_is_full{op=\"a \\\"quoted\\\" value\"} 1\n\
+             # TYPE renec_replay_slot_stats_ratio gauge\n\
+             renec_replay_slot_stats_ratio{op=\"a \\\"quoted\\\" value\"} 0.5\n\
+             # TYPE renec_replay_slot_stats_total_entries gauge\n\
+             renec_replay_slot_stats_total_entries{op=\"a \\\"quoted\\\" value\"} 15\n\
+             # TYPE renec_bank_process_transactions_txs counter\n\
+             renec_bank_process_transactions_txs_total 6\n\
+             # EOF\n"
+        );
+    }
+
+    #[test]
+    fn test_render_special_values() {
+        let registry = PrometheusRegistry::default();
+        registry.enable();
+        registry.record_point(
+            &DataPoint::new("point")
+                .add_field_f64("inf", f64::INFINITY)
+                .add_field_f64("neg_inf", f64::NEG_INFINITY)
+                .add_field_f64("nan", f64::NAN)
+                .to_owned(),
+        );
+        // The field `bank-txs` of `point`, and the counter `point-bank_txs`, both sanitize to
+        // `renec_point_bank_txs`
+        registry.record_point(
+            &DataPoint::new("point")
+                .add_field_i64("bank-txs", 2)
+                .to_owned(),
+        );
+        registry.record_counter(&CounterPoint {
+            name: "point-bank_txs",
+            count: 3,
+            timestamp: UNIX_EPOCH,
+        });
+        registry.record_point(&DataPoint::new("other").add_field_i64("total", 4).to_owned());
+        registry.record_counter(&CounterPoint {
+            name: "other",
+            count: 5,
+            timestamp: UNIX_EPOCH,
+        });
+        assert_eq!(
+            registry.render(),
+            "# TYPE renec_other_total gauge\n\
+             renec_other_total 4\n\
+             # TYPE renec_point_bank_txs gauge\n\
+             renec_point_bank_txs 2\n\
+             # TYPE renec_point_inf gauge\n\
+             renec_point_inf +Inf\n\
+             # TYPE renec_point_nan gauge\n\
+             renec_point_nan NaN\n\
+             # TYPE renec_point_neg_inf gauge\n\
+             renec_point_neg_inf -Inf\n\
+             # EOF\n"
+        );
+    }
+
+    #[test]
+    fn test_serve() {
+        let registry = Arc::new(PrometheusRegistry::default());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        serve(listener, registry.clone()).unwrap();
+        registry.record_point(&DataPoint::new("point").add_field_i64("value", 1).to_owned());
+
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.ends_with("renec_point_value 1\n# EOF\n"));
+        assert!(get("/").starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
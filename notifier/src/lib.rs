@@ -17,24 +17,72 @@
 /// ```bash
 /// export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'
 /// ```
+///
+/// Incidents, which are triggered and then resolved, are sent to PagerDuty and Opsgenie with:
+/// ```bash
+/// export PAGERDUTY_ROUTING_KEY=...
+/// export OPSGENIE_API_KEY=...
+/// ```
+///
+/// A generic JSON webhook is sent with the following variables, the template being optional:
+/// ```bash
+/// export WEBHOOK_URL=...
+/// export WEBHOOK_TEMPLATE='{"text": "{{message}}", "event": "{{event}}", "key": "{{key}}"}'
+/// ```
+///
+/// Alternatively, a `NotifierConfig` defines the notifications without environment variables.
 use log::*;
 use {
     reqwest::{blocking::Client, StatusCode},
+    serde_derive::Deserialize,
     serde_json::json,
     std::{env, str::FromStr, thread::sleep, time::Duration},
 };
 
-struct TelegramWebHook {
-    bot_token: String,
-    chat_id: String,
+mod opsgenie;
+mod pagerduty;
+mod webhook;
+
+pub use {
+    opsgenie::{OpsgenieConfig, OPSGENIE_API_URL},
+    pagerduty::{PagerDutyConfig, PAGERDUTY_EVENTS_URL},
+    webhook::{WebhookConfig, DEFAULT_WEBHOOK_TEMPLATE},
+};
+
+/// An incident, identified by a key, which notifications trigger and then resolve
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Incident<'a> {
+    Trigger(&'a str),
+    Resolve(&'a str),
+}
+
+pub struct Notification<'a> {
+    pub message: &'a str,
+    pub incident: Option<Incident<'a>>,
+}
+
+/// A destination of notifications
+pub trait NotifierBackend: Send + Sync {
+    /// The name of the backend, for logs
+    fn name(&self) -> &str;
+
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String>;
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TelegramWebHook {
+    pub bot_token: String,
+    pub chat_id: String,
 }
 
-#[derive(Debug, Default)]
-struct TwilioWebHook {
-    account: String,
-    token: String,
-    to: String,
-    from: String,
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TwilioWebHook {
+    pub account: String,
+    pub token: String,
+    pub to: String,
+    pub from: String,
 }
 
 impl TwilioWebHook {
@@ -77,17 +125,193 @@ fn get_twilio_config() -> Result<Option<TwilioWebHook>, String> {
     Ok(Some(config))
 }
 
-enum NotificationType {
-    Discord(String),
-    Slack(String),
-    Telegram(TelegramWebHook),
-    Twilio(TwilioWebHook),
-    Log(Level),
+/// The notifications of a `Notifier`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct NotifierConfig {
+    pub discord_webhook: Option<String>,
+    pub slack_webhook: Option<String>,
+    pub telegram: Option<TelegramWebHook>,
+    pub twilio: Option<TwilioWebHook>,
+    pub webhook: Option<WebhookConfig>,
+    pub pagerduty: Option<PagerDutyConfig>,
+    pub opsgenie: Option<OpsgenieConfig>,
+    /// Log the notifications at this level
+    pub log_level: Option<String>,
+}
+
+impl NotifierConfig {
+    /// Reads the configuration from the environment variables, prefixed by `env_prefix`
+    pub fn from_env(env_prefix: &str) -> Self {
+        let var = |name| env::var(format!("{}{}", env_prefix, name)).ok();
+
+        let telegram = if let (Some(bot_token), Some(chat_id)) =
+            (var("TELEGRAM_BOT_TOKEN"), var("TELEGRAM_CHAT_ID"))
+        {
+            Some(TelegramWebHook { bot_token, chat_id })
+        } else {
+            None
+        };
+
+        let log_level =
+            var("LOG_NOTIFIER_LEVEL").filter(|log_level| match Level::from_str(log_level) {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!(
+                        "could not parse specified log notifier level string ({}): {}",
+                        log_level, e
+                    );
+                    false
+                }
+            });
+
+        Self {
+            discord_webhook: var("DISCORD_WEBHOOK"),
+            slack_webhook: var("SLACK_WEBHOOK"),
+            telegram,
+            twilio: get_twilio_config().ok().flatten(),
+            webhook: var("WEBHOOK_URL").map(|url| WebhookConfig {
+                url,
+                template: var("WEBHOOK_TEMPLATE"),
+            }),
+            pagerduty: var("PAGERDUTY_ROUTING_KEY").map(|routing_key| PagerDutyConfig {
+                routing_key,
+                ..PagerDutyConfig::default()
+            }),
+            opsgenie: var("OPSGENIE_API_KEY").map(|api_key| OpsgenieConfig {
+                api_key,
+                ..OpsgenieConfig::default()
+            }),
+            log_level,
+        }
+    }
+}
+
+struct DiscordWebHook(String);
+
+impl NotifierBackend for DiscordWebHook {
+    fn name(&self) -> &str {
+        "Discord"
+    }
+
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        for line in notification.message.split('\n') {
+            // Discord rate limiting is aggressive, limit to 1 message a second
+            sleep(Duration::from_millis(1000));
+
+            info!("Sending {}", line);
+            let data = json!({ "content": line });
+
+            loop {
+                let response = client.post(&self.0).json(&data).send();
+
+                // A line that fails to send is logged, and the next lines are still sent
+                if let Err(err) = response {
+                    warn!("Failed to send Discord message: \"{}\": {:?}", line, err);
+                    break;
+                } else if let Ok(response) = response {
+                    info!("response status: {}", response.status());
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        warn!("rate limited!...");
+                        warn!("response text: {:?}", response.text());
+                        sleep(Duration::from_secs(2));
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct SlackWebHook(String);
+
+impl NotifierBackend for SlackWebHook {
+    fn name(&self) -> &str {
+        "Slack"
+    }
+
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let data = json!({ "text": notification.message });
+        client
+            .post(&self.0)
+            .json(&data)
+            .send()
+            .map(|_| ())
+            .map_err(|err| format!("{:?}", err))
+    }
+}
+
+impl NotifierBackend for TelegramWebHook {
+    fn name(&self) -> &str {
+        "Telegram"
+    }
+
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let data = json!({ "chat_id": self.chat_id, "text": notification.message });
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        client
+            .post(&url)
+            .json(&data)
+            .send()
+            .map(|_| ())
+            .map_err(|err| format!("{:?}", err))
+    }
+}
+
+impl NotifierBackend for TwilioWebHook {
+    fn name(&self) -> &str {
+        "Twilio"
+    }
+
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let url = format!(
+            "https://{}:{}@api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+            self.account, self.token, self.account
+        );
+        let params = [
+            ("To", &self.to),
+            ("From", &self.from),
+            ("Body", &notification.message.to_string()),
+        ];
+        client
+            .post(&url)
+            .form(&params)
+            .send()
+            .map(|_| ())
+            .map_err(|err| format!("{:?}", err))
+    }
+}
+
+struct LogNotifier(Level);
+
+impl NotifierBackend for LogNotifier {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    fn send(&self, _client: &Client, notification: &Notification) -> Result<(), String> {
+        log!(self.0, "{}", notification.message);
+        Ok(())
+    }
+}
+
+/// Truncates `message` to at most `max_len` bytes, on a character boundary
+pub(crate) fn truncate(message: &str, max_len: usize) -> &str {
+    if message.len() <= max_len {
+        return message;
+    }
+    let mut len = max_len;
+    while !message.is_char_boundary(len) {
+        len -= 1;
+    }
+    &message[..len]
 }
 
 pub struct Notifier {
     client: Client,
-    notifiers: Vec<NotificationType>,
+    backends: Vec<Box<dyn NotifierBackend>>,
 }
 
 impl Notifier {
@@ -98,116 +322,332 @@ impl Notifier {
     pub fn new(env_prefix: &str) -> Self {
         info!("Initializing {}Notifier", env_prefix);
 
-        let mut notifiers = vec![];
+        Self::from_config(&NotifierConfig::from_env(env_prefix)).unwrap_or_else(|err| {
+            warn!("{}", err);
+            Notifier {
+                client: Client::new(),
+                backends: vec![],
+            }
+        })
+    }
+
+    pub fn from_config(config: &NotifierConfig) -> Result<Self, String> {
+        let mut backends: Vec<Box<dyn NotifierBackend>> = vec![];
 
-        if let Ok(webhook) = env::var(format!("{}DISCORD_WEBHOOK", env_prefix)) {
-            notifiers.push(NotificationType::Discord(webhook));
+        if let Some(webhook) = &config.discord_webhook {
+            backends.push(Box::new(DiscordWebHook(webhook.clone())));
         }
-        if let Ok(webhook) = env::var(format!("{}SLACK_WEBHOOK", env_prefix)) {
-            notifiers.push(NotificationType::Slack(webhook));
+        if let Some(webhook) = &config.slack_webhook {
+            backends.push(Box::new(SlackWebHook(webhook.clone())));
         }
-
-        if let (Ok(bot_token), Ok(chat_id)) = (
-            env::var(format!("{}TELEGRAM_BOT_TOKEN", env_prefix)),
-            env::var(format!("{}TELEGRAM_CHAT_ID", env_prefix)),
-        ) {
-            notifiers.push(NotificationType::Telegram(TelegramWebHook {
-                bot_token,
-                chat_id,
-            }));
+        if let Some(telegram) = &config.telegram {
+            backends.push(Box::new(telegram.clone()));
         }
-
-        if let Ok(Some(webhook)) = get_twilio_config() {
-            notifiers.push(NotificationType::Twilio(webhook));
+        if let Some(twilio) = &config.twilio {
+            if !twilio.complete() {
+                return Err("Twilio configuration is incomplete".to_string());
+            }
+            backends.push(Box::new(twilio.clone()));
         }
-
-        if let Ok(log_level) = env::var(format!("{}LOG_NOTIFIER_LEVEL", env_prefix)) {
-            match Level::from_str(&log_level) {
-                Ok(level) => notifiers.push(NotificationType::Log(level)),
-                Err(e) => warn!(
+        if let Some(webhook) = &config.webhook {
+            backends.push(Box::new(webhook::Webhook::new(webhook)?));
+        }
+        if let Some(pagerduty) = &config.pagerduty {
+            backends.push(Box::new(pagerduty::PagerDuty::new(pagerduty)?));
+        }
+        if let Some(opsgenie) = &config.opsgenie {
+            backends.push(Box::new(opsgenie::Opsgenie::new(opsgenie)?));
+        }
+        if let Some(log_level) = &config.log_level {
+            let level = Level::from_str(log_level).map_err(|e| {
+                format!(
                     "could not parse specified log notifier level string ({}): {}",
                     log_level, e
-                ),
-            }
+                )
+            })?;
+            backends.push(Box::new(LogNotifier(level)));
         }
 
-        info!("{} notifiers", notifiers.len());
+        info!("{} notifiers", backends.len());
 
-        Notifier {
+        Ok(Notifier {
             client: Client::new(),
-            notifiers,
-        }
+            backends,
+        })
+    }
+
+    /// Adds a destination of the notifications
+    pub fn add_backend(&mut self, backend: Box<dyn NotifierBackend>) {
+        self.backends.push(backend);
     }
 
     pub fn is_empty(&self) -> bool {
-        self.notifiers.is_empty()
+        self.backends.is_empty()
     }
 
     pub fn send(&self, msg: &str) {
-        for notifier in &self.notifiers {
-            match notifier {
-                NotificationType::Discord(webhook) => {
-                    for line in msg.split('\n') {
-                        // Discord rate limiting is aggressive, limit to 1 message a second
-                        sleep(Duration::from_millis(1000));
-
-                        info!("Sending {}", line);
-                        let data = json!({ "content": line });
-
-                        loop {
-                            let response = self.client.post(webhook).json(&data).send();
-
-                            if let Err(err) = response {
-                                warn!("Failed to send Discord message: \"{}\": {:?}", line, err);
-                                break;
-                            } else if let Ok(response) = response {
-                                info!("response status: {}", response.status());
-                                if response.status() == StatusCode::TOO_MANY_REQUESTS {
-                                    warn!("rate limited!...");
-                                    warn!("response text: {:?}", response.text());
-                                    sleep(Duration::from_secs(2));
-                                } else {
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-                NotificationType::Slack(webhook) => {
-                    let data = json!({ "text": msg });
-                    if let Err(err) = self.client.post(webhook).json(&data).send() {
-                        warn!("Failed to send Slack message: {:?}", err);
-                    }
-                }
+        self.notify(&Notification {
+            message: msg,
+            incident: None,
+        });
+    }
 
-                NotificationType::Telegram(TelegramWebHook { chat_id, bot_token }) => {
-                    let data = json!({ "chat_id": chat_id, "text": msg });
-                    let url = format!("https://api.telegram.org/bot{}/sendMessage", bot_token);
+    /// Sends `msg` as the trigger of the incident `key`, or as a plain message to the backends
+    /// without incidents
+    pub fn trigger(&self, key: &str, msg: &str) {
+        self.notify(&Notification {
+            message: msg,
+            incident: Some(Incident::Trigger(key)),
+        });
+    }
 
-                    if let Err(err) = self.client.post(&url).json(&data).send() {
-                        warn!("Failed to send Telegram message: {:?}", err);
-                    }
-                }
+    /// Sends `msg` as the resolution of the incident `key`, or as a plain message to the
+    /// backends without incidents
+    pub fn resolve(&self, key: &str, msg: &str) {
+        self.notify(&Notification {
+            message: msg,
+            incident: Some(Incident::Resolve(key)),
+        });
+    }
 
-                NotificationType::Twilio(TwilioWebHook {
-                    account,
-                    token,
-                    to,
-                    from,
-                }) => {
-                    let url = format!(
-                        "https://{}:{}@api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
-                        account, token, account
-                    );
-                    let params = [("To", to), ("From", from), ("Body", &msg.to_string())];
-                    if let Err(err) = self.client.post(&url).form(&params).send() {
-                        warn!("Failed to send Twilio message: {:?}", err);
-                    }
-                }
-                NotificationType::Log(level) => {
-                    log!(*level, "{}", msg)
-                }
+    pub fn notify(&self, notification: &Notification) {
+        for backend in &self.backends {
+            if let Err(err) = backend.send(&self.client, notification) {
+                warn!("Failed to send {} message: {}", backend.name(), err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        serde_json::Value,
+        std::{
+            io::{BufRead, BufReader, Read, Write},
+            net::{TcpListener, TcpStream},
+            sync::mpsc::{channel, Receiver},
+            thread,
+        },
+    };
+
+    struct Request {
+        method: String,
+        path: String,
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl Request {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        }
+
+        fn json(&self) -> Value {
+            serde_json::from_str(&self.body).unwrap()
+        }
+    }
+
+    fn read_request(stream: &TcpStream) -> Request {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap().to_string();
+        let path = parts.next().unwrap().to_string();
+
+        let mut headers = vec![];
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
             }
+            let (name, value) = line.split_once(':').unwrap();
+            headers.push((name.to_string(), value.trim().to_string()));
         }
+
+        let mut request = Request {
+            method,
+            path,
+            headers,
+            body: String::new(),
+        };
+        let content_length = request
+            .header("content-length")
+            .map(|len| len.parse().unwrap())
+            .unwrap_or(0);
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        request.body = String::from_utf8(body).unwrap();
+        request
+    }
+
+    /// Serves HTTP requests on a local port, replying `202 Accepted`
+    fn mock_server() -> (String, Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let request = read_request(&stream);
+                stream
+                    .write_all(b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\n\r\n")
+                    .unwrap();
+                if sender.send(request).is_err() {
+                    break;
+                }
+            }
+        });
+        (url, receiver)
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("message", 10), "message");
+        assert_eq!(truncate("message", 3), "mes");
+        assert_eq!(truncate("é", 1), "");
+    }
+
+    #[test]
+    fn test_webhook() {
+        let (url, requests) = mock_server();
+        let notifier = Notifier::from_config(&NotifierConfig {
+            webhook: Some(WebhookConfig {
+                url: format!("{}/hook", url),
+                template: Some(r#"{"text": "{{message}}", "state": "{{event}}"}"#.to_string()),
+            }),
+            ..NotifierConfig::default()
+        })
+        .unwrap();
+
+        notifier.send("a \"quoted\"\nmessage");
+        let request = requests.recv().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/hook");
+        assert_eq!(request.header("content-type"), Some("application/json"));
+        assert_eq!(
+            request.json(),
+            json!({ "text": "a \"quoted\"\nmessage", "state": "notify" })
+        );
+
+        notifier.resolve("key", "resolved");
+        assert_eq!(
+            requests.recv().unwrap().json(),
+            json!({ "text": "resolved", "state": "resolve" })
+        );
+
+        assert!(Notifier::from_config(&NotifierConfig {
+            webhook: Some(WebhookConfig {
+                url,
+                template: Some("{{message}}".to_string()),
+            }),
+            ..NotifierConfig::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_pagerduty() {
+        let (url, requests) = mock_server();
+        let notifier = Notifier::from_config(&NotifierConfig {
+            pagerduty: Some(PagerDutyConfig {
+                routing_key: "routing-key".to_string(),
+                severity: Some("warning".to_string()),
+                url: Some(format!("{}/v2/enqueue", url)),
+                ..PagerDutyConfig::default()
+            }),
+            ..NotifierConfig::default()
+        })
+        .unwrap();
+
+        // Plain messages are not incidents
+        notifier.send("message");
+        notifier.trigger("key", "triggered");
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/v2/enqueue");
+        assert_eq!(
+            request.json(),
+            json!({
+                "routing_key": "routing-key",
+                "event_action": "trigger",
+                "dedup_key": "key",
+                "payload": {
+                    "summary": "triggered",
+                    "source": "solana-notifier",
+                    "severity": "warning",
+                },
+            })
+        );
+
+        notifier.resolve("key", "resolved");
+        assert_eq!(
+            requests.recv().unwrap().json(),
+            json!({
+                "routing_key": "routing-key",
+                "event_action": "resolve",
+                "dedup_key": "key",
+            })
+        );
+
+        // Keys are capped at the 255 characters PagerDuty accepts
+        let long_key = "k".repeat(300);
+        notifier.resolve(&long_key, "resolved");
+        assert_eq!(
+            requests.recv().unwrap().json()["dedup_key"],
+            json!(&long_key[..255])
+        );
+
+        assert!(Notifier::from_config(&NotifierConfig {
+            pagerduty: Some(PagerDutyConfig {
+                severity: Some("fatal".to_string()),
+                ..PagerDutyConfig::default()
+            }),
+            ..NotifierConfig::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_opsgenie() {
+        let (url, requests) = mock_server();
+        let notifier = Notifier::from_config(&NotifierConfig {
+            opsgenie: Some(OpsgenieConfig {
+                api_key: "api-key".to_string(),
+                priority: Some("P2".to_string()),
+                url: Some(url),
+            }),
+            ..NotifierConfig::default()
+        })
+        .unwrap();
+
+        notifier.send("message");
+        notifier.trigger("group/check", "triggered");
+        let request = requests.recv().unwrap();
+        assert_eq!(request.path, "/v2/alerts");
+        assert_eq!(request.header("authorization"), Some("GenieKey api-key"));
+        assert_eq!(
+            request.json(),
+            json!({
+                "message": "triggered",
+                "alias": "group/check",
+                "description": "triggered",
+                "priority": "P2",
+            })
+        );
+
+        notifier.resolve("group/check", "resolved");
+        let request = requests.recv().unwrap();
+        assert_eq!(
+            request.path,
+            "/v2/alerts/group%2Fcheck/close?identifierType=alias"
+        );
+        assert_eq!(request.header("authorization"), Some("GenieKey api-key"));
+        assert_eq!(request.json(), json!({ "note": "resolved" }));
     }
 }
@@ -0,0 +1,97 @@
+//! Opsgenie Alert API, which creates and closes alerts
+
+use {
+    crate::{truncate, Incident, Notification, NotifierBackend},
+    log::*,
+    reqwest::{blocking::Client, header::AUTHORIZATION, Url},
+    serde_derive::Deserialize,
+    serde_json::json,
+};
+
+pub const OPSGENIE_API_URL: &str = "https://api.opsgenie.com";
+
+const PRIORITIES: [&str; 5] = ["P1", "P2", "P3", "P4", "P5"];
+const MAX_MESSAGE_LEN: usize = 130;
+const MAX_ALIAS_LEN: usize = 512;
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct OpsgenieConfig {
+    pub api_key: String,
+    /// [default: `P1`]
+    pub priority: Option<String>,
+    /// [default: `OPSGENIE_API_URL`, or `https://api.eu.opsgenie.com` for the EU instance]
+    pub url: Option<String>,
+}
+
+pub(crate) struct Opsgenie {
+    api_key: String,
+    priority: String,
+    url: Url,
+}
+
+impl Opsgenie {
+    pub(crate) fn new(config: &OpsgenieConfig) -> Result<Self, String> {
+        let priority = config.priority.clone().unwrap_or_else(|| "P1".to_string());
+        if !PRIORITIES.contains(&priority.as_str()) {
+            return Err(format!(
+                "Opsgenie priority must be one of {}",
+                PRIORITIES.join(", ")
+            ));
+        }
+        let url = config.url.as_deref().unwrap_or(OPSGENIE_API_URL);
+        let url = Url::parse(url).map_err(|err| format!("Opsgenie url {}: {}", url, err))?;
+        if url.cannot_be_a_base() {
+            return Err(format!("Opsgenie url {} is not a base URL", url));
+        }
+        Ok(Self {
+            api_key: config.api_key.clone(),
+            priority,
+            url,
+        })
+    }
+
+    fn alerts_url(&self, path: &[&str]) -> Url {
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .unwrap()
+            .pop_if_empty()
+            .extend(["v2", "alerts"])
+            .extend(path);
+        url
+    }
+}
+
+impl NotifierBackend for Opsgenie {
+    fn name(&self) -> &str {
+        "Opsgenie"
+    }
+
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let request = match notification.incident {
+            Some(Incident::Trigger(key)) => client.post(self.alerts_url(&[])).json(&json!({
+                "message": truncate(notification.message, MAX_MESSAGE_LEN),
+                "alias": truncate(key, MAX_ALIAS_LEN),
+                "description": notification.message,
+                "priority": self.priority,
+            })),
+            Some(Incident::Resolve(key)) => {
+                let mut url = self.alerts_url(&[truncate(key, MAX_ALIAS_LEN), "close"]);
+                url.query_pairs_mut().append_pair("identifierType", "alias");
+                client
+                    .post(url)
+                    .json(&json!({ "note": notification.message }))
+            }
+            None => {
+                debug!("Opsgenie only receives incidents, skipping notification");
+                return Ok(());
+            }
+        };
+        request
+            .header(AUTHORIZATION, format!("GenieKey {}", self.api_key))
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
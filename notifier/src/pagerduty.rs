@@ -0,0 +1,99 @@
+//! PagerDuty Events API v2, which triggers and resolves incidents
+
+use {
+    crate::{truncate, Incident, Notification, NotifierBackend},
+    log::*,
+    reqwest::blocking::Client,
+    serde_derive::Deserialize,
+    serde_json::json,
+};
+
+pub const PAGERDUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+const SEVERITIES: [&str; 4] = ["critical", "error", "warning", "info"];
+const MAX_SUMMARY_LEN: usize = 1024;
+const MAX_DEDUP_KEY_LEN: usize = 255;
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PagerDutyConfig {
+    /// Integration key of the service
+    pub routing_key: String,
+    /// [default: `critical`]
+    pub severity: Option<String>,
+    /// [default: `solana-notifier`]
+    pub source: Option<String>,
+    /// [default: `PAGERDUTY_EVENTS_URL`]
+    pub url: Option<String>,
+}
+
+pub(crate) struct PagerDuty {
+    routing_key: String,
+    severity: String,
+    source: String,
+    url: String,
+}
+
+impl PagerDuty {
+    pub(crate) fn new(config: &PagerDutyConfig) -> Result<Self, String> {
+        let severity = config
+            .severity
+            .clone()
+            .unwrap_or_else(|| "critical".to_string());
+        if !SEVERITIES.contains(&severity.as_str()) {
+            return Err(format!(
+                "PagerDuty severity must be one of {}",
+                SEVERITIES.join(", ")
+            ));
+        }
+        Ok(Self {
+            routing_key: config.routing_key.clone(),
+            severity,
+            source: config
+                .source
+                .clone()
+                .unwrap_or_else(|| "solana-notifier".to_string()),
+            url: config
+                .url
+                .clone()
+                .unwrap_or_else(|| PAGERDUTY_EVENTS_URL.to_string()),
+        })
+    }
+}
+
+impl NotifierBackend for PagerDuty {
+    fn name(&self) -> &str {
+        "PagerDuty"
+    }
+
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        let event = match notification.incident {
+            Some(Incident::Trigger(key)) => json!({
+                "routing_key": self.routing_key,
+                "event_action": "trigger",
+                "dedup_key": truncate(key, MAX_DEDUP_KEY_LEN),
+                "payload": {
+                    "summary": truncate(notification.message, MAX_SUMMARY_LEN),
+                    "source": self.source,
+                    "severity": self.severity,
+                },
+            }),
+            Some(Incident::Resolve(key)) => json!({
+                "routing_key": self.routing_key,
+                "event_action": "resolve",
+                "dedup_key": truncate(key, MAX_DEDUP_KEY_LEN),
+            }),
+            None => {
+                debug!("PagerDuty only receives incidents, skipping notification");
+                return Ok(());
+            }
+        };
+        client
+            .post(&self.url)
+            .json(&event)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
@@ -0,0 +1,80 @@
+//! Generic JSON webhook, with a body rendered from a template
+
+use {
+    crate::{Incident, Notification, NotifierBackend},
+    reqwest::{blocking::Client, header::CONTENT_TYPE},
+    serde_derive::Deserialize,
+};
+
+/// The body of the webhook, when the configuration has no template
+pub const DEFAULT_WEBHOOK_TEMPLATE: &str =
+    r#"{"message": "{{message}}", "event": "{{event}}", "key": "{{key}}"}"#;
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// JSON body, in which `{{message}}`, `{{event}}` (`trigger`, `resolve` or `notify`) and
+    /// `{{key}}` (the incident key, or empty) are replaced by their values, escaped for a JSON
+    /// string
+    pub template: Option<String>,
+}
+
+pub(crate) struct Webhook {
+    url: String,
+    template: String,
+}
+
+impl Webhook {
+    pub(crate) fn new(config: &WebhookConfig) -> Result<Self, String> {
+        let webhook = Self {
+            url: config.url.clone(),
+            template: config
+                .template
+                .clone()
+                .unwrap_or_else(|| DEFAULT_WEBHOOK_TEMPLATE.to_string()),
+        };
+        let sample = webhook.render(&Notification {
+            message: "message",
+            incident: None,
+        });
+        serde_json::from_str::<serde_json::Value>(&sample)
+            .map_err(|err| format!("webhook template is not JSON: {}", err))?;
+        Ok(webhook)
+    }
+
+    fn render(&self, notification: &Notification) -> String {
+        let (event, key) = match notification.incident {
+            Some(Incident::Trigger(key)) => ("trigger", key),
+            Some(Incident::Resolve(key)) => ("resolve", key),
+            None => ("notify", ""),
+        };
+        self.template
+            .replace("{{message}}", &escape_json_string(notification.message))
+            .replace("{{event}}", event)
+            .replace("{{key}}", &escape_json_string(key))
+    }
+}
+
+/// Escapes `value` for the inside of a JSON string
+fn escape_json_string(value: &str) -> String {
+    let quoted = serde_json::Value::from(value).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+impl NotifierBackend for Webhook {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn send(&self, client: &Client, notification: &Notification) -> Result<(), String> {
+        client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(self.render(notification))
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|err| err.to_string())
+    }
+}
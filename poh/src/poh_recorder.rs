@@ -15,9 +15,13 @@ use {
     crate::poh_service::PohService,
     crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, SendError, Sender, TrySendError},
     log::*,
-    solana_entry::{entry::Entry, poh::Poh},
+    solana_entry::{
+        entry::{Entry, EntrySummary},
+        poh::Poh,
+    },
     solana_ledger::{
         blockstore::Blockstore,
+        entry_notifier_service::{EntryNotification, EntryNotifierSender},
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
         leader_schedule_cache::LeaderScheduleCache,
     },
@@ -197,6 +201,8 @@ pub struct WorkingBank {
     pub min_tick_height: u64,
     pub max_tick_height: u64,
     pub transaction_index: Option<usize>,
+    /// The index in the slot of the next entry sent for the bank
+    pub entry_index: usize,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -215,6 +221,7 @@ pub struct PohRecorder {
     working_bank: Option<WorkingBank>,
     sender: Sender<WorkingBankEntry>,
     poh_timing_point_sender: Option<PohTimingSender>,
+    entry_notification_sender: Option<EntryNotifierSender>,
     leader_first_tick_height_including_grace_ticks: Option<u64>,
     leader_last_tick_height: u64, // zero if none
     grace_ticks: u64,
@@ -506,6 +513,7 @@ impl PohRecorder {
             min_tick_height: bank.tick_height(),
             max_tick_height: bank.max_tick_height(),
             transaction_index: track_transaction_indexes.then(|| 0),
+            entry_index: 0,
         };
         trace!("new working bank");
         assert_eq!(working_bank.bank.ticks_per_slot(), self.ticks_per_slot());
@@ -539,7 +547,7 @@ impl PohRecorder {
         // will fail instead of broadcasting any ticks
         let working_bank = self
             .working_bank
-            .as_ref()
+            .as_mut()
             .ok_or(PohRecorderError::MaxHeightReached)?;
         if self.tick_height < working_bank.min_tick_height {
             return Err(PohRecorderError::MinHeightNotReached);
@@ -570,6 +578,11 @@ impl PohRecorder {
                 if send_result.is_err() {
                     break;
                 }
+                Self::notify_entry(
+                    &self.entry_notification_sender,
+                    working_bank,
+                    EntrySummary::from(&tick.0),
+                );
             }
         }
         if self.tick_height >= working_bank.max_tick_height {
@@ -595,6 +608,28 @@ impl PohRecorder {
         Ok(())
     }
 
+    // Sends the entry to the entry notifier, if any, and advances the index of
+    // the next entry in the working bank's slot
+    fn notify_entry(
+        entry_notification_sender: &Option<EntryNotifierSender>,
+        working_bank: &mut WorkingBank,
+        entry: EntrySummary,
+    ) {
+        let slot = working_bank.bank.slot();
+        let index = working_bank.entry_index;
+        working_bank.entry_index += 1;
+        if let Some(entry_notification_sender) = entry_notification_sender {
+            if let Err(err) =
+                entry_notification_sender.send(EntryNotification { slot, index, entry })
+            {
+                warn!(
+                    "Slot {}, entry {} entry_notification_sender send failed: {:?}",
+                    slot, index, err
+                );
+            }
+        }
+    }
+
     fn report_poh_timing_point_by_tick(&self) {
         match self.tick_height % self.ticks_per_slot {
             // reaching the end of the slot
@@ -795,6 +830,15 @@ impl PohRecorder {
                 );
                 self.send_entry_us += send_entry_time.as_us();
                 send_entry_res?;
+                Self::notify_entry(
+                    &self.entry_notification_sender,
+                    working_bank,
+                    EntrySummary {
+                        num_hashes: poh_entry.num_hashes,
+                        hash: poh_entry.hash,
+                        num_transactions: num_transactions as u64,
+                    },
+                );
                 let starting_transaction_index =
                     working_bank.transaction_index.map(|transaction_index| {
                         let next_starting_transaction_index =
@@ -825,6 +869,7 @@ impl PohRecorder {
         leader_schedule_cache: &Arc<LeaderScheduleCache>,
         poh_config: &Arc<PohConfig>,
         poh_timing_point_sender: Option<PohTimingSender>,
+        entry_notification_sender: Option<EntryNotifierSender>,
         is_exited: Arc<AtomicBool>,
     ) -> (Self, Receiver<WorkingBankEntry>, Receiver<Record>) {
         let tick_number = 0;
@@ -851,6 +896,7 @@ impl PohRecorder {
                 working_bank: None,
                 sender,
                 poh_timing_point_sender,
+                entry_notification_sender,
                 clear_bank_signal,
                 start_bank,
                 start_tick_height: tick_height + 1,
@@ -909,6 +955,7 @@ impl PohRecorder {
             leader_schedule_cache,
             poh_config,
             None,
+            None,
             is_exited,
         )
     }
@@ -1408,6 +1455,54 @@ mod tests {
         Blockstore::destroy(&ledger_path).unwrap();
     }
 
+    #[test]
+    fn test_poh_recorder_entry_notifications() {
+        let ledger_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&ledger_path)
+                .expect("Expected to be able to open database ledger");
+            let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(2);
+            let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+            let prev_hash = bank.last_blockhash();
+            let (entry_notification_sender, entry_notification_receiver) = unbounded();
+            let (mut poh_recorder, _entry_receiver, _record_receiver) =
+                PohRecorder::new_with_clear_signal(
+                    0,
+                    prev_hash,
+                    bank.clone(),
+                    Some((4, 4)),
+                    bank.ticks_per_slot(),
+                    &Pubkey::default(),
+                    &Arc::new(blockstore),
+                    None,
+                    &Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+                    &Arc::new(PohConfig::default()),
+                    None,
+                    Some(entry_notification_sender),
+                    Arc::new(AtomicBool::default()),
+                );
+
+            poh_recorder.set_bank(&bank, false);
+            poh_recorder.tick();
+            let h1 = hash(b"hello world!");
+            poh_recorder
+                .record(bank.slot(), h1, vec![test_tx().into(), test_tx().into()])
+                .unwrap();
+
+            let notifications: Vec<_> = entry_notification_receiver.try_iter().collect();
+            assert_eq!(notifications.len(), 2);
+            assert!(notifications
+                .iter()
+                .all(|notification| notification.slot == bank.slot()));
+            assert_eq!(notifications[0].index, 0);
+            assert_eq!(notifications[0].entry.num_transactions, 0);
+            assert_eq!(notifications[1].index, 1);
+            assert_eq!(notifications[1].entry.num_transactions, 2);
+            assert_eq!(poh_recorder.working_bank.as_ref().unwrap().entry_index, 2);
+        }
+        Blockstore::destroy(&ledger_path).unwrap();
+    }
+
     #[test]
     fn test_poh_recorder_record_transaction_index() {
         let ledger_path = get_tmp_ledger_path!();
@@ -1682,6 +1777,7 @@ mod tests {
                     &Arc::new(LeaderScheduleCache::default()),
                     &Arc::new(PohConfig::default()),
                     None,
+                    None,
                     Arc::new(AtomicBool::default()),
                 );
             poh_recorder.set_bank(&bank, false);
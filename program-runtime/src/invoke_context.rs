@@ -839,6 +839,8 @@ impl<'a> InvokeContext<'a> {
                 };
                 let post_remaining_units = self.compute_meter.borrow().get_remaining();
                 *compute_units_consumed = pre_remaining_units.saturating_sub(post_remaining_units);
+                self.transaction_context
+                    .set_current_instruction_compute_units_consumed(*compute_units_consumed);
 
                 process_executable_chain_time.stop();
                 saturating_add_assign!(
@@ -1424,6 +1426,18 @@ mod tests {
             assert_eq!(compute_units_consumed, compute_units_to_consume);
             assert_eq!(result, expected_result);
 
+            // The consumed units are recorded in the instruction trace
+            let inner_instruction_context = invoke_context
+                .transaction_context
+                .get_instruction_trace()
+                .last()
+                .and_then(|instruction_trace| instruction_trace.last())
+                .unwrap();
+            assert_eq!(
+                inner_instruction_context.get_compute_units_consumed(),
+                compute_units_to_consume
+            );
+
             invoke_context.pop().unwrap();
         }
     }
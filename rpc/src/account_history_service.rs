@@ -0,0 +1,403 @@
+//! The `account_history_service` module implements a threaded service recording the state of the
+//! accounts written in each slot before the slot's writes, so that `getAccountInfo` and
+//! `getMultipleAccounts` can serve the state of an account as of an older rooted slot. The
+//! history can be limited to some accounts.
+//!
+//! The state of an account as of slot `S` is the state recorded for the first rooted slot after
+//! `S` that wrote the account, or the current state in the latest root when no such slot exists.
+
+use {
+    crate::optimistically_confirmed_bank_tracker::{BankNotification, BankNotificationReceiver},
+    crossbeam_channel::RecvTimeoutError,
+    solana_ledger::{blockstore::Blockstore, blockstore_db::Result as BlockstoreResult},
+    solana_runtime::bank::Bank,
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        clock::Slot,
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::{BTreeSet, HashSet},
+        ops::RangeInclusive,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+pub const DEFAULT_ACCOUNT_HISTORY_RETENTION_SLOTS: Slot = 216_000;
+
+struct AccountHistoryState {
+    /// The history is complete for the slots starting at this one
+    first_complete_slot: Slot,
+    /// The latest root processed by the service, which history lookups fall back to
+    root_bank: Arc<Bank>,
+}
+
+pub struct AccountHistory {
+    blockstore: Arc<Blockstore>,
+    retention_slots: Slot,
+    /// Only the history of these accounts is recorded, or of every account if `None`
+    addresses: Option<HashSet<Pubkey>>,
+    state: RwLock<AccountHistoryState>,
+}
+
+impl AccountHistory {
+    /// History starts at `root_bank`, the history of older slots is not available
+    pub fn new(
+        blockstore: Arc<Blockstore>,
+        retention_slots: Slot,
+        addresses: Option<HashSet<Pubkey>>,
+        root_bank: Arc<Bank>,
+    ) -> Self {
+        Self {
+            blockstore,
+            retention_slots,
+            addresses,
+            state: RwLock::new(AccountHistoryState {
+                first_complete_slot: root_bank.slot(),
+                root_bank,
+            }),
+        }
+    }
+
+    /// The latest processed root along with the slots the history can be looked up at
+    pub fn root_bank_and_available_slots(&self) -> (Arc<Bank>, RangeInclusive<Slot>) {
+        let state = self.state.read().unwrap();
+        let last_available_slot = state.root_bank.slot();
+        let first_available_slot = state
+            .first_complete_slot
+            .max(last_available_slot.saturating_sub(self.retention_slots))
+            .max(self.blockstore.lowest_cleanup_slot());
+        (
+            state.root_bank.clone(),
+            first_available_slot..=last_available_slot,
+        )
+    }
+
+    /// Whether the history of the account is recorded
+    pub fn records(&self, pubkey: &Pubkey) -> bool {
+        self.addresses
+            .as_ref()
+            .map_or(true, |addresses| addresses.contains(pubkey))
+    }
+
+    /// Returns the state of the account as of `slot`, which must be one of the available slots
+    /// of `root_bank`. The account must be one whose history is recorded.
+    pub fn get_account(
+        &self,
+        pubkey: &Pubkey,
+        slot: Slot,
+        root_bank: &Bank,
+    ) -> BlockstoreResult<Option<AccountSharedData>> {
+        match self
+            .blockstore
+            .get_account_history_prior_state(pubkey, slot, root_bank.slot())?
+        {
+            Some((_, prior_state)) => Ok(prior_state.map(AccountSharedData::from)),
+            None => Ok(root_bank.get_account(pubkey)),
+        }
+    }
+
+    fn record_frozen_bank(&self, bank: &Bank) -> BlockstoreResult<bool> {
+        let parent = match bank.parent() {
+            Some(parent) => parent,
+            None => return Ok(false),
+        };
+        let prior_states = match &self.addresses {
+            // Only the configured accounts are looked up, a deleted account is one the parent
+            // has but the bank does not
+            Some(addresses) => addresses
+                .iter()
+                .filter_map(|pubkey| {
+                    let prior_state = parent.get_account(pubkey);
+                    let is_written = match bank.get_account_modified_slot(pubkey) {
+                        Some((_, slot)) => slot == bank.slot(),
+                        None => prior_state.is_some(),
+                    };
+                    is_written.then(|| (*pubkey, prior_state.map(Account::from)))
+                })
+                .collect::<Vec<_>>(),
+            None => bank
+                .get_all_accounts_modified_since_parent()
+                .into_iter()
+                .map(|(pubkey, _)| (pubkey, parent.get_account(&pubkey).map(Account::from)))
+                .collect(),
+        };
+        self.blockstore
+            .write_account_history(bank.slot(), prior_states.into_iter())?;
+        Ok(true)
+    }
+
+    /// Moves the history to the new root. Rooted slots that were not recorded leave a gap, and
+    /// the history is only complete after the last of them.
+    fn set_root(&self, root_bank: Arc<Bank>, recorded_slots: &BTreeSet<Slot>) {
+        let mut state = self.state.write().unwrap();
+        let last_root = state.root_bank.slot();
+        let root = root_bank.slot();
+        if root <= last_root {
+            return;
+        }
+        match self.blockstore.rooted_slot_iterator(last_root + 1) {
+            Ok(rooted_slots) => {
+                for slot in rooted_slots.take_while(|slot| *slot <= root) {
+                    if !recorded_slots.contains(&slot) {
+                        warn!("account history missing for rooted slot {}", slot);
+                        state.first_complete_slot = state.first_complete_slot.max(slot);
+                    }
+                }
+            }
+            Err(err) => {
+                warn!("account history failed to read roots: {:?}", err);
+                state.first_complete_slot = root;
+            }
+        }
+        state.root_bank = root_bank;
+    }
+}
+
+pub struct AccountHistoryService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl AccountHistoryService {
+    pub fn new(
+        account_history: Arc<AccountHistory>,
+        bank_notification_receiver: BankNotificationReceiver,
+        exit: &Arc<AtomicBool>,
+    ) -> Self {
+        let exit = exit.clone();
+        let thread_hdl = Builder::new()
+            .name("solRpcAcctHist".to_string())
+            .spawn(move || {
+                let mut recorded_slots = BTreeSet::new();
+                loop {
+                    if exit.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    match bank_notification_receiver.recv_timeout(Duration::from_secs(1)) {
+                        Ok(notification) => Self::process_notification(
+                            &account_history,
+                            notification,
+                            &mut recorded_slots,
+                        ),
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            })
+            .unwrap();
+        Self { thread_hdl }
+    }
+
+    fn process_notification(
+        account_history: &AccountHistory,
+        notification: BankNotification,
+        recorded_slots: &mut BTreeSet<Slot>,
+    ) {
+        match notification {
+            BankNotification::Frozen(bank) => match account_history.record_frozen_bank(&bank) {
+                Ok(true) => {
+                    recorded_slots.insert(bank.slot());
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    warn!(
+                        "account history failed to record slot {}: {:?}",
+                        bank.slot(),
+                        err
+                    );
+                }
+            },
+            BankNotification::Root(bank) => {
+                let root = bank.slot();
+                account_history.set_root(bank, recorded_slots);
+                *recorded_slots = recorded_slots.split_off(&(root + 1));
+            }
+            BankNotification::OptimisticallyConfirmed(_) => {}
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_ledger::get_tmp_ledger_path_auto_delete,
+        solana_runtime::genesis_utils::{create_genesis_config, GenesisConfigInfo},
+        solana_sdk::{account::ReadableAccount, signature::Signer},
+    };
+
+    #[test]
+    fn test_account_history() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1_000_000_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let pubkey = Pubkey::new_unique();
+        let account_history = AccountHistory::new(
+            blockstore.clone(),
+            100,
+            Some(HashSet::from([pubkey, mint_keypair.pubkey()])),
+            bank0.clone(),
+        );
+        let mut recorded_slots = BTreeSet::new();
+        let other_pubkey = Pubkey::new_unique();
+        let rent_exempt_lamports = bank0.get_minimum_balance_for_rent_exemption(0);
+
+        // Slot 1 creates the account, slot 2 is not recorded, slot 3 credits the account
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank1
+            .transfer(rent_exempt_lamports, &mint_keypair, &pubkey)
+            .unwrap();
+        bank1
+            .transfer(rent_exempt_lamports, &mint_keypair, &other_pubkey)
+            .unwrap();
+        bank1.freeze();
+        let bank2 = Arc::new(Bank::new_from_parent(&bank1, &Pubkey::default(), 2));
+        bank2.freeze();
+        let bank3 = Arc::new(Bank::new_from_parent(&bank2, &Pubkey::default(), 3));
+        bank3.transfer(5, &mint_keypair, &pubkey).unwrap();
+        bank3.freeze();
+
+        for bank in [&bank1, &bank3] {
+            AccountHistoryService::process_notification(
+                &account_history,
+                BankNotification::Frozen(bank.clone()),
+                &mut recorded_slots,
+            );
+        }
+        blockstore.set_roots(vec![1].iter()).unwrap();
+        AccountHistoryService::process_notification(
+            &account_history,
+            BankNotification::Root(bank1),
+            &mut recorded_slots,
+        );
+        let (root_bank, available_slots) = account_history.root_bank_and_available_slots();
+        assert_eq!(root_bank.slot(), 1);
+        assert_eq!(available_slots, 0..=1);
+        assert_eq!(
+            account_history.get_account(&pubkey, 0, &root_bank).unwrap(),
+            None
+        );
+
+        // Accounts that are not configured are not recorded
+        assert!(account_history.records(&pubkey));
+        assert!(!account_history.records(&other_pubkey));
+        assert_eq!(
+            blockstore
+                .get_account_history_prior_state(&other_pubkey, 0, 1)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            account_history
+                .get_account(&pubkey, 1, &root_bank)
+                .unwrap()
+                .map(|account| account.lamports()),
+            Some(rent_exempt_lamports)
+        );
+
+        // Slot 2 was never recorded, so the history of the older slots is lost
+        blockstore.set_roots(vec![2, 3].iter()).unwrap();
+        AccountHistoryService::process_notification(
+            &account_history,
+            BankNotification::Root(bank3),
+            &mut recorded_slots,
+        );
+        assert!(recorded_slots.is_empty());
+        let (root_bank, available_slots) = account_history.root_bank_and_available_slots();
+        assert_eq!(root_bank.slot(), 3);
+        assert_eq!(available_slots, 2..=3);
+        assert_eq!(
+            account_history
+                .get_account(&pubkey, 2, &root_bank)
+                .unwrap()
+                .map(|account| account.lamports()),
+            Some(rent_exempt_lamports)
+        );
+        assert_eq!(
+            account_history
+                .get_account(&pubkey, 3, &root_bank)
+                .unwrap()
+                .map(|account| account.lamports()),
+            Some(rent_exempt_lamports + 5)
+        );
+        assert_eq!(
+            account_history
+                .get_account(&mint_keypair.pubkey(), 2, &root_bank)
+                .unwrap()
+                .map(|account| account.lamports()),
+            bank2
+                .get_account(&mint_keypair.pubkey())
+                .map(|account| account.lamports())
+        );
+    }
+
+    #[test]
+    fn test_account_history_of_every_account() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1_000_000_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let account_history = AccountHistory::new(blockstore.clone(), 100, None, bank0.clone());
+        let mut recorded_slots = BTreeSet::new();
+        let pubkey = Pubkey::new_unique();
+        let rent_exempt_lamports = bank0.get_minimum_balance_for_rent_exemption(0);
+
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        bank1
+            .transfer(rent_exempt_lamports, &mint_keypair, &pubkey)
+            .unwrap();
+        bank1.freeze();
+        AccountHistoryService::process_notification(
+            &account_history,
+            BankNotification::Frozen(bank1.clone()),
+            &mut recorded_slots,
+        );
+        blockstore.set_roots(vec![1].iter()).unwrap();
+        AccountHistoryService::process_notification(
+            &account_history,
+            BankNotification::Root(bank1),
+            &mut recorded_slots,
+        );
+
+        let (root_bank, available_slots) = account_history.root_bank_and_available_slots();
+        assert_eq!(available_slots, 0..=1);
+        assert!(account_history.records(&pubkey));
+        assert_eq!(
+            blockstore
+                .get_account_history_prior_state(&pubkey, 0, 1)
+                .unwrap(),
+            Some((1, None))
+        );
+        assert_eq!(
+            account_history.get_account(&pubkey, 0, &root_bank).unwrap(),
+            None
+        );
+        assert_eq!(
+            account_history
+                .get_account(&mint_keypair.pubkey(), 0, &root_bank)
+                .unwrap()
+                .map(|account| account.lamports()),
+            bank0
+                .get_account(&mint_keypair.pubkey())
+                .map(|account| account.lamports())
+        );
+    }
+}
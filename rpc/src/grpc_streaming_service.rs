@@ -0,0 +1,1054 @@
+//! The `grpc_streaming_service` module implements a gRPC endpoint streaming account, slot,
+//! transaction and block updates. It covers the data of the `accountSubscribe`,
+//! `programSubscribe`, `slotsUpdatesSubscribe` and `blockSubscribe` websocket subscriptions,
+//! but sends protobuf messages and applies the filters of each subscription server side.
+//!
+//! Updates are taken from the bank notifications of the `OptimisticallyConfirmedBankTracker`,
+//! so slot updates are limited to frozen, optimistically confirmed and rooted slots. The
+//! `FirstShredReceived`, `Completed`, `CreatedBank` and `Dead` updates of
+//! `slotsUpdatesSubscribe` are not streamed.
+
+use {
+    crate::optimistically_confirmed_bank_tracker::{BankNotification, BankNotificationReceiver},
+    crossbeam_channel::RecvTimeoutError,
+    solana_client::rpc_filter::{Memcmp, RpcFilterType},
+    solana_ledger::blockstore::Blockstore,
+    solana_runtime::{bank::Bank, bank_forks::BankForks},
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        clock::Slot,
+        pubkey::Pubkey,
+        transaction_context::TransactionAccount,
+    },
+    solana_storage_proto::convert::{
+        generated,
+        streaming::{
+            self, account_data_filter, streaming_service_server, subscribe_update, AccountUpdate,
+            BlockUpdate, CommitmentLevel, ParentSlot, SlotStatus, SlotUpdate, SubscribeRequest,
+            SubscribeUpdate, TransactionUpdate,
+        },
+    },
+    solana_transaction_status::{VersionedConfirmedBlock, VersionedTransactionWithStatusMeta},
+    std::{
+        collections::{BTreeSet, HashSet},
+        convert::TryFrom,
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+            Arc, RwLock,
+        },
+        thread::{self, Builder, JoinHandle},
+        time::Duration,
+    },
+    tokio::sync::{broadcast, mpsc},
+    tokio_stream::wrappers::ReceiverStream,
+    tonic::{Request, Response, Status},
+};
+
+pub const DEFAULT_GRPC_QUEUE_CAPACITY_ITEMS: usize = 100_000;
+pub const DEFAULT_GRPC_MAX_REPLAY_SLOTS: u64 = 10_000;
+pub const DEFAULT_GRPC_WORKER_THREADS: usize = 4;
+
+// Number of events buffered for the subscriptions, each event holding up to a whole bank
+// worth of accounts or a whole block
+const EVENT_CHANNEL_CAPACITY: usize = 1_024;
+// Blocks whose transaction statuses are not written yet are retried on later notifications,
+// this bounds how many of them are kept around
+const MAX_PENDING_BLOCKS: usize = 1_024;
+const NUM_COMMITMENT_LEVELS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct GrpcStreamingConfig {
+    /// Stream blocks and transactions. Requires the transaction history to be enabled.
+    pub enable_block_subscription: bool,
+    /// Number of updates queued for a subscription before it is dropped as too slow
+    pub queue_capacity_items: usize,
+    /// How far behind the highest root a subscription may ask to replay from
+    pub max_replay_slots: u64,
+    pub worker_threads: usize,
+}
+
+impl Default for GrpcStreamingConfig {
+    fn default() -> Self {
+        Self {
+            enable_block_subscription: false,
+            queue_capacity_items: DEFAULT_GRPC_QUEUE_CAPACITY_ITEMS,
+            max_replay_slots: DEFAULT_GRPC_MAX_REPLAY_SLOTS,
+            worker_threads: DEFAULT_GRPC_WORKER_THREADS,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum StreamEvent {
+    Slot {
+        slot: Slot,
+        parent: Option<Slot>,
+        status: SlotStatus,
+    },
+    Accounts {
+        slot: Slot,
+        commitment: CommitmentLevel,
+        accounts: Arc<Vec<TransactionAccount>>,
+    },
+    Block {
+        slot: Slot,
+        commitment: CommitmentLevel,
+        block: Arc<VersionedConfirmedBlock>,
+    },
+}
+
+/// Number of live subscriptions wanting accounts and blocks at each commitment level, so
+/// that the notifier only collects what some subscription is going to use
+#[derive(Default)]
+struct SubscriberCounts {
+    accounts: [AtomicUsize; NUM_COMMITMENT_LEVELS],
+    blocks: [AtomicUsize; NUM_COMMITMENT_LEVELS],
+}
+
+impl SubscriberCounts {
+    fn accounts(&self, commitment: CommitmentLevel) -> usize {
+        self.accounts[commitment as usize].load(Ordering::Relaxed)
+    }
+
+    fn blocks(&self, commitment: CommitmentLevel) -> usize {
+        self.blocks[commitment as usize].load(Ordering::Relaxed)
+    }
+}
+
+/// Registers a subscription in `SubscriberCounts` for as long as it is alive
+struct SubscriptionGuard {
+    counts: Arc<SubscriberCounts>,
+    commitment: CommitmentLevel,
+    accounts: bool,
+    blocks: bool,
+}
+
+impl SubscriptionGuard {
+    fn new(counts: Arc<SubscriberCounts>, filter: &SubscriptionFilter) -> Self {
+        let guard = Self {
+            counts,
+            commitment: filter.commitment,
+            accounts: !filter.accounts.is_empty(),
+            blocks: filter.wants_blocks(),
+        };
+        let index = guard.commitment as usize;
+        if guard.accounts {
+            guard.counts.accounts[index].fetch_add(1, Ordering::Relaxed);
+        }
+        if guard.blocks {
+            guard.counts.blocks[index].fetch_add(1, Ordering::Relaxed);
+        }
+        guard
+    }
+}
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        let index = self.commitment as usize;
+        if self.accounts {
+            self.counts.accounts[index].fetch_sub(1, Ordering::Relaxed);
+        }
+        if self.blocks {
+            self.counts.blocks[index].fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct AccountsSelector {
+    pubkeys: HashSet<Pubkey>,
+    owners: HashSet<Pubkey>,
+    filters: Vec<RpcFilterType>,
+}
+
+impl AccountsSelector {
+    fn matches(&self, pubkey: &Pubkey, account: &AccountSharedData) -> bool {
+        let selected = (self.pubkeys.is_empty() && self.owners.is_empty())
+            || self.pubkeys.contains(pubkey)
+            || self.owners.contains(account.owner());
+        selected && self.filters.iter().all(|filter| filter.allows(account))
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct TransactionsSelector {
+    include_votes: bool,
+    include_failed: bool,
+    account_include: HashSet<Pubkey>,
+}
+
+impl TransactionsSelector {
+    fn matches(&self, transaction: &VersionedTransactionWithStatusMeta) -> bool {
+        if !self.include_votes && is_vote_transaction(transaction) {
+            return false;
+        }
+        if !self.include_failed && transaction.meta.status.is_err() {
+            return false;
+        }
+        self.account_include.is_empty()
+            || transaction
+                .account_keys()
+                .iter()
+                .any(|key| self.account_include.contains(key))
+    }
+}
+
+fn is_vote_transaction(transaction: &VersionedTransactionWithStatusMeta) -> bool {
+    let message = &transaction.transaction.message;
+    let account_keys = message.static_account_keys();
+    let instructions = message.instructions();
+    !instructions.is_empty()
+        && instructions
+            .iter()
+            .all(|instruction| instruction.program_id(account_keys) == &solana_vote_program::id())
+}
+
+/// The validated form of a `SubscribeRequest`
+#[derive(Debug, PartialEq)]
+struct SubscriptionFilter {
+    accounts: Vec<AccountsSelector>,
+    slots: bool,
+    transactions: Option<TransactionsSelector>,
+    /// `Some(include_transactions)` if blocks are streamed
+    blocks: Option<bool>,
+    commitment: CommitmentLevel,
+    from_slot: Option<Slot>,
+}
+
+fn parse_pubkeys(keys: &[Vec<u8>]) -> Result<HashSet<Pubkey>, Status> {
+    keys.iter()
+        .map(|key| {
+            Pubkey::try_from(key.as_slice())
+                .map_err(|_| Status::invalid_argument(format!("invalid pubkey: {:?}", key)))
+        })
+        .collect()
+}
+
+impl TryFrom<SubscribeRequest> for SubscriptionFilter {
+    type Error = Status;
+
+    fn try_from(request: SubscribeRequest) -> Result<Self, Self::Error> {
+        let accounts = request
+            .accounts
+            .iter()
+            .map(|accounts| {
+                let filters = accounts
+                    .filters
+                    .iter()
+                    .map(|filter| {
+                        let filter = match &filter.filter {
+                            Some(account_data_filter::Filter::DataSize(data_size)) => {
+                                RpcFilterType::DataSize(*data_size)
+                            }
+                            Some(account_data_filter::Filter::Memcmp(memcmp)) => {
+                                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                                    memcmp.offset as usize,
+                                    memcmp.bytes.clone(),
+                                ))
+                            }
+                            Some(account_data_filter::Filter::TokenAccountState(_)) => {
+                                RpcFilterType::TokenAccountState
+                            }
+                            None => {
+                                return Err(Status::invalid_argument("empty account data filter"))
+                            }
+                        };
+                        filter
+                            .verify()
+                            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+                        Ok(filter)
+                    })
+                    .collect::<Result<Vec<_>, Status>>()?;
+                Ok(AccountsSelector {
+                    pubkeys: parse_pubkeys(&accounts.pubkeys)?,
+                    owners: parse_pubkeys(&accounts.owners)?,
+                    filters,
+                })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let transactions = request
+            .transactions
+            .filter(|transactions| transactions.enabled)
+            .map(|transactions| {
+                Ok::<_, Status>(TransactionsSelector {
+                    include_votes: transactions.include_votes,
+                    include_failed: transactions.include_failed,
+                    account_include: parse_pubkeys(&transactions.account_include)?,
+                })
+            })
+            .transpose()?;
+
+        let commitment = CommitmentLevel::from_i32(request.commitment)
+            .ok_or_else(|| Status::invalid_argument("invalid commitment level"))?;
+
+        Ok(Self {
+            accounts,
+            slots: request.slots,
+            transactions,
+            blocks: request
+                .blocks
+                .filter(|blocks| blocks.enabled)
+                .map(|blocks| blocks.include_transactions),
+            commitment,
+            from_slot: request.from_slot.map(|from_slot| from_slot.slot),
+        })
+    }
+}
+
+impl SubscriptionFilter {
+    fn wants_blocks(&self) -> bool {
+        self.blocks.is_some() || self.transactions.is_some()
+    }
+
+    fn slot_update(&self, slot: Slot, parent: Option<Slot>, status: SlotStatus) -> SubscribeUpdate {
+        SubscribeUpdate {
+            update: Some(subscribe_update::Update::Slot(SlotUpdate {
+                slot,
+                parent: parent.map(|slot| ParentSlot { slot }),
+                status: status as i32,
+            })),
+        }
+    }
+
+    fn block_updates(&self, slot: Slot, block: &VersionedConfirmedBlock) -> Vec<SubscribeUpdate> {
+        let mut updates = vec![];
+        if let Some(transactions) = &self.transactions {
+            updates.extend(
+                block
+                    .transactions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, transaction)| transactions.matches(transaction))
+                    .map(|(index, transaction)| SubscribeUpdate {
+                        update: Some(subscribe_update::Update::Transaction(TransactionUpdate {
+                            slot,
+                            signature: transaction.transaction.signatures[0].as_ref().to_vec(),
+                            is_vote: is_vote_transaction(transaction),
+                            index: index as u64,
+                            transaction: Some(generated::ConfirmedTransaction::from(
+                                transaction.clone(),
+                            )),
+                        })),
+                    }),
+            );
+        }
+        if let Some(include_transactions) = self.blocks {
+            let block = VersionedConfirmedBlock {
+                previous_blockhash: block.previous_blockhash.clone(),
+                blockhash: block.blockhash.clone(),
+                parent_slot: block.parent_slot,
+                transactions: if include_transactions {
+                    block.transactions.clone()
+                } else {
+                    vec![]
+                },
+                rewards: block.rewards.clone(),
+                block_time: block.block_time,
+                block_height: block.block_height,
+            };
+            updates.push(SubscribeUpdate {
+                update: Some(subscribe_update::Update::Block(BlockUpdate {
+                    slot,
+                    block: Some(generated::ConfirmedBlock::from(block)),
+                })),
+            });
+        }
+        updates
+    }
+
+    /// The updates this subscription receives for the event
+    fn updates(&self, event: &StreamEvent) -> Vec<SubscribeUpdate> {
+        match event {
+            StreamEvent::Slot {
+                slot,
+                parent,
+                status,
+            } => {
+                if self.slots {
+                    vec![self.slot_update(*slot, *parent, *status)]
+                } else {
+                    vec![]
+                }
+            }
+            StreamEvent::Accounts {
+                slot,
+                commitment,
+                accounts,
+            } => {
+                if *commitment != self.commitment || self.accounts.is_empty() {
+                    return vec![];
+                }
+                accounts
+                    .iter()
+                    .filter(|(pubkey, account)| {
+                        self.accounts
+                            .iter()
+                            .any(|selector| selector.matches(pubkey, account))
+                    })
+                    .map(|(pubkey, account)| SubscribeUpdate {
+                        update: Some(subscribe_update::Update::Account(AccountUpdate {
+                            slot: *slot,
+                            pubkey: pubkey.to_bytes().to_vec(),
+                            lamports: account.lamports(),
+                            owner: account.owner().to_bytes().to_vec(),
+                            executable: account.executable(),
+                            rent_epoch: account.rent_epoch(),
+                            data: account.data().to_vec(),
+                        })),
+                    })
+                    .collect()
+            }
+            StreamEvent::Block {
+                slot,
+                commitment,
+                block,
+            } => {
+                if *commitment != self.commitment {
+                    return vec![];
+                }
+                self.block_updates(*slot, block)
+            }
+        }
+    }
+}
+
+/// Turns the bank notifications of the `OptimisticallyConfirmedBankTracker` into the events
+/// the subscriptions select their updates from
+struct StreamNotifier {
+    bank_forks: Arc<RwLock<BankForks>>,
+    blockstore: Arc<Blockstore>,
+    max_complete_transaction_status_slot: Arc<AtomicU64>,
+    enable_block_subscription: bool,
+    event_sender: broadcast::Sender<StreamEvent>,
+    counts: Arc<SubscriberCounts>,
+    last_notified_slots: [Option<Slot>; NUM_COMMITMENT_LEVELS],
+    pending_blocks: BTreeSet<(Slot, i32)>,
+}
+
+impl StreamNotifier {
+    fn send(&self, event: StreamEvent) {
+        // Fails only when there are no subscriptions
+        let _ = self.event_sender.send(event);
+    }
+
+    fn process_notification(&mut self, notification: BankNotification) {
+        match notification {
+            BankNotification::Frozen(bank) => {
+                self.send(StreamEvent::Slot {
+                    slot: bank.slot(),
+                    parent: Some(bank.parent_slot()),
+                    status: SlotStatus::Frozen,
+                });
+                self.notify_banks(vec![bank], CommitmentLevel::Processed);
+            }
+            BankNotification::OptimisticallyConfirmed(slot) => {
+                let bank = self.bank_forks.read().unwrap().get(slot);
+                self.send(StreamEvent::Slot {
+                    slot,
+                    parent: bank.as_ref().map(|bank| bank.parent_slot()),
+                    status: SlotStatus::OptimisticConfirmation,
+                });
+                if let Some(bank) = bank {
+                    let banks = self.unnotified_banks(bank, CommitmentLevel::Confirmed);
+                    self.notify_banks(banks, CommitmentLevel::Confirmed);
+                }
+            }
+            BankNotification::Root(bank) => {
+                self.send(StreamEvent::Slot {
+                    slot: bank.slot(),
+                    parent: Some(bank.parent_slot()),
+                    status: SlotStatus::Root,
+                });
+                let banks = self.unnotified_banks(bank, CommitmentLevel::Finalized);
+                self.notify_banks(banks, CommitmentLevel::Finalized);
+            }
+        }
+    }
+
+    /// The bank along with the ancestors not notified yet at the commitment level, oldest
+    /// first. Notifications may skip slots, for example when several slots are rooted at once.
+    fn unnotified_banks(&self, bank: Arc<Bank>, commitment: CommitmentLevel) -> Vec<Arc<Bank>> {
+        let last_notified_slot = self.last_notified_slots[commitment as usize];
+        let mut banks: Vec<_> = match last_notified_slot {
+            Some(last_notified_slot) if bank.slot() > last_notified_slot => bank
+                .parents()
+                .into_iter()
+                .take_while(|parent| parent.slot() > last_notified_slot)
+                .collect(),
+            _ => vec![],
+        };
+        banks.reverse();
+        banks.push(bank);
+        banks
+    }
+
+    fn notify_banks(&mut self, banks: Vec<Arc<Bank>>, commitment: CommitmentLevel) {
+        for bank in banks {
+            if self.counts.accounts(commitment) > 0 {
+                self.send(StreamEvent::Accounts {
+                    slot: bank.slot(),
+                    commitment,
+                    accounts: Arc::new(bank.get_all_accounts_modified_since_parent()),
+                });
+            }
+            if self.enable_block_subscription && self.counts.blocks(commitment) > 0 {
+                self.pending_blocks.insert((bank.slot(), commitment as i32));
+            }
+            self.last_notified_slots[commitment as usize] = Some(bank.slot());
+        }
+        self.notify_pending_blocks();
+    }
+
+    /// Send the blocks whose transaction statuses have been written to the blockstore
+    fn notify_pending_blocks(&mut self) {
+        let max_complete_transaction_status_slot = self
+            .max_complete_transaction_status_slot
+            .load(Ordering::SeqCst);
+        while let Some((slot, commitment)) = self.pending_blocks.iter().next().copied() {
+            if slot > max_complete_transaction_status_slot {
+                break;
+            }
+            self.pending_blocks.remove(&(slot, commitment));
+            match self.blockstore.get_complete_block(slot, false) {
+                Ok(block) => self.send(StreamEvent::Block {
+                    slot,
+                    commitment: CommitmentLevel::from_i32(commitment).unwrap(),
+                    block: Arc::new(block),
+                }),
+                Err(err) => warn!(
+                    "grpc streaming: get_complete_block({}) error: {}",
+                    slot, err
+                ),
+            }
+        }
+        while self.pending_blocks.len() > MAX_PENDING_BLOCKS {
+            let oldest = self.pending_blocks.iter().next().copied().unwrap();
+            warn!(
+                "grpc streaming: dropping block {} still missing transaction statuses",
+                oldest.0
+            );
+            self.pending_blocks.remove(&oldest);
+        }
+    }
+}
+
+struct StreamingServer {
+    config: GrpcStreamingConfig,
+    blockstore: Arc<Blockstore>,
+    event_sender: broadcast::Sender<StreamEvent>,
+    counts: Arc<SubscriberCounts>,
+}
+
+#[tonic::async_trait]
+impl streaming_service_server::StreamingService for StreamingServer {
+    type SubscribeStream = ReceiverStream<Result<SubscribeUpdate, Status>>;
+
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let filter = SubscriptionFilter::try_from(request.into_inner())?;
+        if filter.wants_blocks() && !self.config.enable_block_subscription {
+            return Err(Status::failed_precondition(
+                "block and transaction streaming is not enabled on this node",
+            ));
+        }
+        if let Some(from_slot) = filter.from_slot {
+            let max_root = self.blockstore.max_root();
+            if max_root.saturating_sub(from_slot) > self.config.max_replay_slots {
+                return Err(Status::out_of_range(format!(
+                    "cannot replay more than {} slots behind root {}",
+                    self.config.max_replay_slots, max_root
+                )));
+            }
+        }
+
+        // Subscribe to live events before replaying, so that none are missed in between
+        let event_receiver = self.event_sender.subscribe();
+        let guard = SubscriptionGuard::new(self.counts.clone(), &filter);
+        let (update_sender, update_receiver) = mpsc::channel(self.config.queue_capacity_items);
+        let blockstore = self.blockstore.clone();
+        tokio::spawn(async move {
+            let _guard = guard;
+            let filter = Arc::new(filter);
+            let mut replayed_slot = None;
+            if filter.from_slot.is_some() {
+                let (filter, update_sender) = (filter.clone(), update_sender.clone());
+                match tokio::task::spawn_blocking(move || {
+                    replay_from_blockstore(&blockstore, &filter, &update_sender)
+                })
+                .await
+                {
+                    Ok(Some(slot)) => replayed_slot = Some(slot),
+                    Ok(None) => {}
+                    Err(err) => {
+                        warn!("grpc streaming: replay failed: {}", err);
+                        return;
+                    }
+                }
+            }
+            stream_updates(&filter, replayed_slot, event_receiver, update_sender).await;
+        });
+
+        Ok(Response::new(ReceiverStream::new(update_receiver)))
+    }
+}
+
+/// Send the rooted slots, blocks and transactions from `filter.from_slot` on, returning the
+/// last slot replayed
+fn replay_from_blockstore(
+    blockstore: &Blockstore,
+    filter: &SubscriptionFilter,
+    update_sender: &mpsc::Sender<Result<SubscribeUpdate, Status>>,
+) -> Option<Slot> {
+    let from_slot = filter.from_slot?;
+    let max_root = blockstore.max_root();
+    let rooted_slots = match blockstore.rooted_slot_iterator(from_slot) {
+        Ok(rooted_slots) => rooted_slots,
+        Err(err) => {
+            let _ = update_sender.blocking_send(Err(Status::internal(err.to_string())));
+            return None;
+        }
+    };
+
+    let mut replayed_slot = None;
+    for slot in rooted_slots.take_while(|slot| *slot <= max_root) {
+        let mut updates = vec![];
+        if filter.slots {
+            let parent = blockstore
+                .meta(slot)
+                .ok()
+                .flatten()
+                .and_then(|meta| meta.parent_slot);
+            updates.push(filter.slot_update(slot, parent, SlotStatus::Root));
+        }
+        if filter.wants_blocks() {
+            match blockstore.get_rooted_block(slot, false) {
+                Ok(block) => updates.extend(filter.block_updates(slot, &block)),
+                Err(err) => debug!("grpc streaming: no block to replay at {}: {}", slot, err),
+            }
+        }
+        for update in updates {
+            if update_sender.blocking_send(Ok(update)).is_err() {
+                return None;
+            }
+        }
+        replayed_slot = Some(slot);
+    }
+    replayed_slot
+}
+
+async fn stream_updates(
+    filter: &SubscriptionFilter,
+    replayed_slot: Option<Slot>,
+    mut event_receiver: broadcast::Receiver<StreamEvent>,
+    update_sender: mpsc::Sender<Result<SubscribeUpdate, Status>>,
+) {
+    loop {
+        let event = match event_receiver.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                let _ = update_sender
+                    .send(Err(Status::data_loss(format!(
+                        "subscription fell behind, {} events skipped",
+                        skipped
+                    ))))
+                    .await;
+                return;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        // Skip what the replay already sent
+        match (&event, replayed_slot) {
+            (StreamEvent::Block { slot, .. }, Some(replayed_slot))
+            | (
+                StreamEvent::Slot {
+                    slot,
+                    status: SlotStatus::Root,
+                    ..
+                },
+                Some(replayed_slot),
+            ) if *slot <= replayed_slot => continue,
+            _ => {}
+        }
+        for update in filter.updates(&event) {
+            match update_sender.try_send(Ok(update)) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!("grpc streaming: dropping subscription whose queue is full");
+                    return;
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => return,
+            }
+        }
+    }
+}
+
+pub struct GrpcStreamingService {
+    notifier_thread_hdl: JoinHandle<()>,
+    server_thread_hdl: JoinHandle<()>,
+}
+
+impl GrpcStreamingService {
+    pub fn new(
+        grpc_addr: SocketAddr,
+        config: GrpcStreamingConfig,
+        bank_forks: Arc<RwLock<BankForks>>,
+        blockstore: Arc<Blockstore>,
+        bank_notification_receiver: BankNotificationReceiver,
+        max_complete_transaction_status_slot: Arc<AtomicU64>,
+        exit: &Arc<AtomicBool>,
+    ) -> Self {
+        info!("grpc streaming bound to {:?}", grpc_addr);
+        let (event_sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let counts = Arc::new(SubscriberCounts::default());
+
+        let mut notifier = StreamNotifier {
+            bank_forks,
+            blockstore: blockstore.clone(),
+            max_complete_transaction_status_slot,
+            enable_block_subscription: config.enable_block_subscription,
+            event_sender: event_sender.clone(),
+            counts: counts.clone(),
+            last_notified_slots: [None; NUM_COMMITMENT_LEVELS],
+            pending_blocks: BTreeSet::new(),
+        };
+        let exit_ = exit.clone();
+        let notifier_thread_hdl = Builder::new()
+            .name("solRpcGrpcNotif".to_string())
+            .spawn(move || loop {
+                if exit_.load(Ordering::Relaxed) {
+                    break;
+                }
+                match bank_notification_receiver.recv_timeout(Duration::from_millis(100)) {
+                    Ok(notification) => notifier.process_notification(notification),
+                    Err(RecvTimeoutError::Timeout) => notifier.notify_pending_blocks(),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            })
+            .unwrap();
+
+        let exit = exit.clone();
+        let server_thread_hdl = Builder::new()
+            .name("solRpcGrpc".to_string())
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(config.worker_threads)
+                    .enable_all()
+                    .build()
+                    .expect("runtime creation failed");
+                let server = StreamingServer {
+                    config,
+                    blockstore,
+                    event_sender,
+                    counts,
+                };
+                let shutdown = async move {
+                    while !exit.load(Ordering::Relaxed) {
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                };
+                if let Err(err) = runtime.block_on(
+                    tonic::transport::Server::builder()
+                        .add_service(streaming_service_server::StreamingServiceServer::new(
+                            server,
+                        ))
+                        .serve_with_shutdown(grpc_addr, shutdown),
+                ) {
+                    error!("grpc streaming service failed: {}", err);
+                }
+            })
+            .unwrap();
+
+        Self {
+            notifier_thread_hdl,
+            server_thread_hdl,
+        }
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.notifier_thread_hdl.join()?;
+        self.server_thread_hdl.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            hash::Hash,
+            message::Message,
+            signature::{Keypair, Signer},
+            system_instruction,
+            transaction::{Transaction, TransactionError, VersionedTransaction},
+        },
+        solana_transaction_status::TransactionStatusMeta,
+        solana_vote_program::{vote_instruction, vote_state::Vote},
+        streaming::{
+            AccountDataFilter, AccountsFilter, BlocksFilter, FromSlot, TransactionsFilter,
+        },
+    };
+
+    fn transaction_with_meta(
+        transaction: Transaction,
+        status: Result<(), TransactionError>,
+    ) -> VersionedTransactionWithStatusMeta {
+        VersionedTransactionWithStatusMeta {
+            transaction: VersionedTransaction::from(transaction),
+            meta: TransactionStatusMeta {
+                status,
+                ..TransactionStatusMeta::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_subscription_filter_from_request() {
+        let owner = Pubkey::new_unique();
+        let request = SubscribeRequest {
+            accounts: vec![AccountsFilter {
+                pubkeys: vec![],
+                owners: vec![owner.to_bytes().to_vec()],
+                filters: vec![
+                    AccountDataFilter {
+                        filter: Some(account_data_filter::Filter::DataSize(165)),
+                    },
+                    AccountDataFilter {
+                        filter: Some(account_data_filter::Filter::Memcmp(streaming::Memcmp {
+                            offset: 32,
+                            bytes: vec![1, 2, 3],
+                        })),
+                    },
+                ],
+            }],
+            slots: true,
+            transactions: Some(TransactionsFilter {
+                enabled: false,
+                ..TransactionsFilter::default()
+            }),
+            blocks: Some(BlocksFilter {
+                enabled: true,
+                include_transactions: false,
+            }),
+            commitment: CommitmentLevel::Confirmed as i32,
+            from_slot: Some(FromSlot { slot: 42 }),
+        };
+        assert_eq!(
+            SubscriptionFilter::try_from(request).unwrap(),
+            SubscriptionFilter {
+                accounts: vec![AccountsSelector {
+                    pubkeys: HashSet::new(),
+                    owners: HashSet::from([owner]),
+                    filters: vec![
+                        RpcFilterType::DataSize(165),
+                        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(32, vec![1, 2, 3])),
+                    ],
+                }],
+                slots: true,
+                transactions: None,
+                blocks: Some(false),
+                commitment: CommitmentLevel::Confirmed,
+                from_slot: Some(42),
+            }
+        );
+
+        let request = SubscribeRequest {
+            accounts: vec![AccountsFilter {
+                pubkeys: vec![vec![0; 31]],
+                ..AccountsFilter::default()
+            }],
+            ..SubscribeRequest::default()
+        };
+        assert!(SubscriptionFilter::try_from(request).is_err());
+
+        let request = SubscribeRequest {
+            accounts: vec![AccountsFilter {
+                filters: vec![AccountDataFilter {
+                    filter: Some(account_data_filter::Filter::Memcmp(streaming::Memcmp {
+                        offset: 0,
+                        bytes: vec![0; 129],
+                    })),
+                }],
+                ..AccountsFilter::default()
+            }],
+            ..SubscribeRequest::default()
+        };
+        assert!(SubscriptionFilter::try_from(request).is_err());
+
+        let request = SubscribeRequest {
+            commitment: 3,
+            ..SubscribeRequest::default()
+        };
+        assert!(SubscriptionFilter::try_from(request).is_err());
+    }
+
+    #[test]
+    fn test_account_updates() {
+        let owner = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let filter = SubscriptionFilter {
+            accounts: vec![
+                AccountsSelector {
+                    owners: HashSet::from([owner]),
+                    filters: vec![RpcFilterType::DataSize(3)],
+                    ..AccountsSelector::default()
+                },
+                AccountsSelector {
+                    pubkeys: HashSet::from([pubkey]),
+                    ..AccountsSelector::default()
+                },
+            ],
+            slots: false,
+            transactions: None,
+            blocks: None,
+            commitment: CommitmentLevel::Processed,
+            from_slot: None,
+        };
+
+        let owned = (Pubkey::new_unique(), AccountSharedData::new(1, 3, &owner));
+        let owned_wrong_size = (Pubkey::new_unique(), AccountSharedData::new(1, 4, &owner));
+        let selected = (pubkey, AccountSharedData::new(2, 0, &Pubkey::new_unique()));
+        let other = (
+            Pubkey::new_unique(),
+            AccountSharedData::new(1, 3, &Pubkey::new_unique()),
+        );
+        let accounts = Arc::new(vec![
+            owned.clone(),
+            owned_wrong_size,
+            selected.clone(),
+            other,
+        ]);
+
+        let updates = filter.updates(&StreamEvent::Accounts {
+            slot: 7,
+            commitment: CommitmentLevel::Processed,
+            accounts: accounts.clone(),
+        });
+        let pubkeys: Vec<_> = updates
+            .iter()
+            .map(|update| match &update.update {
+                Some(subscribe_update::Update::Account(account)) => {
+                    assert_eq!(account.slot, 7);
+                    Pubkey::try_from(account.pubkey.as_slice()).unwrap()
+                }
+                _ => panic!("unexpected update"),
+            })
+            .collect();
+        assert_eq!(pubkeys, vec![owned.0, selected.0]);
+
+        assert!(filter
+            .updates(&StreamEvent::Accounts {
+                slot: 7,
+                commitment: CommitmentLevel::Finalized,
+                accounts,
+            })
+            .is_empty());
+        assert!(filter
+            .updates(&StreamEvent::Slot {
+                slot: 7,
+                parent: Some(6),
+                status: SlotStatus::Frozen,
+            })
+            .is_empty());
+    }
+
+    #[test]
+    fn test_block_updates() {
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let transfer = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(&payer.pubkey(), &recipient, 1)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        );
+        let failed_transfer = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &payer.pubkey(),
+                &Pubkey::new_unique(),
+                1,
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        );
+        let vote_pubkey = Pubkey::new_unique();
+        let vote = Transaction::new_unsigned(Message::new(
+            &[vote_instruction::vote(
+                &vote_pubkey,
+                &payer.pubkey(),
+                Vote::new(vec![1], Hash::default()),
+            )],
+            Some(&payer.pubkey()),
+        ));
+        let block = VersionedConfirmedBlock {
+            previous_blockhash: Hash::default().to_string(),
+            blockhash: Hash::new_unique().to_string(),
+            parent_slot: 1,
+            transactions: vec![
+                transaction_with_meta(vote, Ok(())),
+                transaction_with_meta(transfer, Ok(())),
+                transaction_with_meta(
+                    failed_transfer,
+                    Err(TransactionError::InsufficientFundsForFee),
+                ),
+            ],
+            rewards: vec![],
+            block_time: None,
+            block_height: Some(2),
+        };
+        let event = StreamEvent::Block {
+            slot: 2,
+            commitment: CommitmentLevel::Confirmed,
+            block: Arc::new(block),
+        };
+
+        let mut filter = SubscriptionFilter {
+            accounts: vec![],
+            slots: false,
+            transactions: Some(TransactionsSelector::default()),
+            blocks: Some(false),
+            commitment: CommitmentLevel::Confirmed,
+            from_slot: None,
+        };
+        let updates = filter.updates(&event);
+        assert_eq!(updates.len(), 2);
+        match &updates[0].update {
+            Some(subscribe_update::Update::Transaction(transaction)) => {
+                assert_eq!(transaction.slot, 2);
+                assert_eq!(transaction.index, 1);
+                assert!(!transaction.is_vote);
+            }
+            _ => panic!("unexpected update"),
+        }
+        match &updates[1].update {
+            Some(subscribe_update::Update::Block(block)) => {
+                assert_eq!(block.slot, 2);
+                assert!(block.block.as_ref().unwrap().transactions.is_empty());
+            }
+            _ => panic!("unexpected update"),
+        }
+
+        filter.transactions = Some(TransactionsSelector {
+            include_votes: true,
+            include_failed: true,
+            account_include: HashSet::from([vote_pubkey]),
+        });
+        filter.blocks = None;
+        let updates = filter.updates(&event);
+        assert_eq!(updates.len(), 1);
+        match &updates[0].update {
+            Some(subscribe_update::Update::Transaction(transaction)) => {
+                assert_eq!(transaction.index, 0);
+                assert!(transaction.is_vote);
+            }
+            _ => panic!("unexpected update"),
+        }
+
+        filter.commitment = CommitmentLevel::Finalized;
+        assert!(filter.updates(&event).is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 #![allow(clippy::integer_arithmetic)]
+pub mod account_history_service;
 mod cluster_tpu_info;
+pub mod grpc_streaming_service;
 pub mod max_slots;
 pub mod optimistically_confirmed_bank_tracker;
 pub mod parsed_token_accounts;
@@ -2,7 +2,8 @@
 
 use {
     crate::{
-        max_slots::MaxSlots, optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
+        account_history_service::AccountHistory, max_slots::MaxSlots,
+        optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
         parsed_token_accounts::*, rpc_health::*,
     },
     bincode::{config::Options, serialize},
@@ -23,9 +24,10 @@ use {
         rpc_request::{
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
             MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
-            MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
+            MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE,
+            MAX_GET_MULTIPLE_PROGRAM_ACCOUNTS_PROGRAMS, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
             MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
-            NUM_LARGEST_ACCOUNTS,
+            MAX_SIMULATE_BUNDLE_TRANSACTIONS, NUM_LARGEST_ACCOUNTS,
         },
         rpc_response::{Response as RpcResponse, *},
     },
@@ -43,9 +45,10 @@ use {
     solana_metrics::inc_new_counter_info,
     solana_perf::packet::PACKET_DATA_SIZE,
     solana_runtime::{
+        account_overrides::AccountOverrides,
         accounts::AccountAddressFilter,
         accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey, ScanConfig},
-        bank::{Bank, TransactionSimulationResult},
+        bank::{Bank, TracedInstruction, TracedInstructionsList, TransactionSimulationResult},
         bank_forks::BankForks,
         commitment::{BlockCommitmentArray, BlockCommitmentCache, CommitmentSlots},
         inline_spl_token::{SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
@@ -56,7 +59,7 @@ use {
         snapshot_utils,
     },
     solana_sdk::{
-        account::{AccountSharedData, ReadableAccount},
+        account::{AccountSharedData, ReadableAccount, WritableAccount},
         account_utils::StateMut,
         clock::{Slot, UnixTimestamp, MAX_RECENT_BLOCKHASHES},
         commitment_config::{CommitmentConfig, CommitmentLevel},
@@ -71,7 +74,7 @@ use {
         signature::{Keypair, Signature, Signer},
         stake::state::{StakeActivationStatus, StakeState},
         stake_history::StakeHistory,
-        system_instruction,
+        system_instruction, system_program,
         sysvar::stake_history,
         transaction::{
             self, AddressLoader, MessageHash, SanitizedTransaction, TransactionError,
@@ -103,12 +106,13 @@ use {
         collections::{HashMap, HashSet},
         convert::TryFrom,
         net::SocketAddr,
+        path::PathBuf,
         str::FromStr,
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
             Arc, Mutex, RwLock,
         },
-        time::Duration,
+        time::{Duration, Instant},
     },
 };
 
@@ -152,6 +156,8 @@ pub struct JsonRpcConfig {
     pub full_api: bool,
     pub obsolete_v1_7_api: bool,
     pub rpc_scan_and_fix_roots: bool,
+    pub account_history_retention_slots: Option<Slot>,
+    pub account_history_addresses: Option<HashSet<Pubkey>>,
 }
 
 impl JsonRpcConfig {
@@ -169,6 +175,8 @@ pub struct RpcBigtableConfig {
     pub bigtable_instance_name: String,
     pub bigtable_app_profile_id: String,
     pub timeout: Option<Duration>,
+    /// Serve the ledger history from a local database under this path instead of BigTable
+    pub local_storage_path: Option<PathBuf>,
 }
 
 impl Default for RpcBigtableConfig {
@@ -180,6 +188,7 @@ impl Default for RpcBigtableConfig {
             bigtable_instance_name,
             bigtable_app_profile_id,
             timeout: None,
+            local_storage_path: None,
         }
     }
 }
@@ -205,6 +214,8 @@ pub struct JsonRpcRequestProcessor {
     max_complete_transaction_status_slot: Arc<AtomicU64>,
     max_complete_rewards_slot: Arc<AtomicU64>,
     prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    account_history: Option<Arc<AccountHistory>>,
+    program_accounts_cursor_banks: Arc<RwLock<ProgramAccountsCursorBanks>>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -312,6 +323,7 @@ impl JsonRpcRequestProcessor {
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         max_complete_rewards_slot: Arc<AtomicU64>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        account_history: Option<Arc<AccountHistory>>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (sender, receiver) = unbounded();
         (
@@ -334,6 +346,8 @@ impl JsonRpcRequestProcessor {
                 max_complete_transaction_status_slot,
                 max_complete_rewards_slot,
                 prioritization_fee_cache,
+                account_history,
+                program_accounts_cursor_banks: Arc::default(),
             },
             receiver,
         )
@@ -400,6 +414,8 @@ impl JsonRpcRequestProcessor {
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
             max_complete_rewards_slot: Arc::new(AtomicU64::default()),
             prioritization_fee_cache: Arc::new(PrioritizationFeeCache::default()),
+            account_history: None,
+            program_accounts_cursor_banks: Arc::default(),
         }
     }
 
@@ -450,6 +466,82 @@ impl JsonRpcRequestProcessor {
         Ok(new_response(&bank, accounts))
     }
 
+    pub fn get_account_info_at_slot(
+        &self,
+        pubkey: &Pubkey,
+        slot: Slot,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<RpcResponse<Option<UiAccount>>> {
+        let mut config = config.unwrap_or_default();
+        config.encoding = config.encoding.or(Some(UiAccountEncoding::Binary));
+        let RpcResponse { context, value } =
+            self.get_multiple_accounts_at_slot(vec![*pubkey], slot, Some(config))?;
+        Ok(RpcResponse {
+            context,
+            value: value.into_iter().next().flatten(),
+        })
+    }
+
+    /// Returns the state of the accounts as of a rooted slot within the retention window of the
+    /// account history, which must record all of them. The commitment and minimum context slot of
+    /// the config do not apply.
+    pub fn get_multiple_accounts_at_slot(
+        &self,
+        pubkeys: Vec<Pubkey>,
+        slot: Slot,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<RpcResponse<Vec<Option<UiAccount>>>> {
+        let account_history = self
+            .account_history
+            .as_ref()
+            .ok_or(RpcCustomError::AccountHistoryNotAvailable)?;
+        let RpcAccountInfoConfig {
+            encoding,
+            data_slice,
+            ..
+        } = config.unwrap_or_default();
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Base64);
+        check_slice_and_encoding(&encoding, data_slice.is_some())?;
+        if let Some(pubkey) = pubkeys
+            .iter()
+            .find(|pubkey| !account_history.records(pubkey))
+        {
+            return Err(RpcCustomError::AccountHistoryNotRecorded {
+                address: pubkey.to_string(),
+            }
+            .into());
+        }
+
+        let (root_bank, available_slots) = account_history.root_bank_and_available_slots();
+        if !available_slots.contains(&slot) {
+            return Err(RpcCustomError::AccountHistorySlotNotAvailable {
+                slot,
+                first_available_slot: *available_slots.start(),
+                last_available_slot: *available_slots.end(),
+            }
+            .into());
+        }
+        let accounts = pubkeys
+            .into_iter()
+            .map(|pubkey| {
+                account_history
+                    .get_account(&pubkey, slot, &root_bank)
+                    .map_err(|err| {
+                        warn!("account history lookup failed for {}: {:?}", pubkey, err);
+                        Error::internal_error()
+                    })?
+                    .map(|account| {
+                        encode_bank_account(&root_bank, &pubkey, account, encoding, data_slice)
+                    })
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(RpcResponse {
+            context: RpcResponseContext::new(slot),
+            value: accounts,
+        })
+    }
+
     pub fn get_minimum_balance_for_rent_exemption(
         &self,
         data_len: usize,
@@ -465,43 +557,84 @@ impl JsonRpcRequestProcessor {
         config: Option<RpcAccountInfoConfig>,
         mut filters: Vec<RpcFilterType>,
         with_context: bool,
-    ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
+        limit: Option<usize>,
+        cursor: Option<ProgramAccountsCursor>,
+    ) -> Result<OptionalContext<RpcProgramAccounts>> {
         let RpcAccountInfoConfig {
             encoding,
             data_slice: data_slice_config,
             commitment,
             min_context_slot,
         } = config.unwrap_or_default();
-        let bank = self.get_bank_with_config(RpcContextConfig {
-            commitment,
-            min_context_slot,
-        })?;
+        let bank = match cursor {
+            // Later pages are read from the bank of the first one, which is kept for them even
+            // once it is no longer in `BankForks`
+            Some(cursor) => self
+                .program_accounts_cursor_banks
+                .read()
+                .unwrap()
+                .get(cursor.slot)
+                .or_else(|| self.bank_forks.read().unwrap().get(cursor.slot))
+                .ok_or_else(|| {
+                    Error::invalid_params(format!(
+                        "Cursor expired: slot {} is no longer available",
+                        cursor.slot
+                    ))
+                })?,
+            None => self.get_bank_with_config(RpcContextConfig {
+                commitment,
+                min_context_slot,
+            })?,
+        };
         let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
         check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
         optimize_filters(&mut filters);
-        let keyed_accounts = {
-            if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_owner(&bank, program_id, &owner, filters)?
-            } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_mint(&bank, program_id, &mint, filters)?
-            } else {
-                self.get_filtered_program_accounts(&bank, program_id, filters)?
+        let accounts = if limit.is_some() || cursor.is_some() {
+            // One more account than the limit tells whether there is a next page
+            let keyed_accounts = self.get_filtered_program_accounts_page(
+                &bank,
+                program_id,
+                filters,
+                cursor.as_ref().map(|cursor| &cursor.last_pubkey),
+                limit.map_or(usize::MAX, |limit| limit.saturating_add(1)),
+            )?;
+            let (keyed_accounts, next_cursor) =
+                paginate_keyed_accounts(keyed_accounts, bank.slot(), limit);
+            if next_cursor.is_some() {
+                self.program_accounts_cursor_banks
+                    .write()
+                    .unwrap()
+                    .insert(bank.clone());
             }
-        };
-        let accounts = if is_known_spl_token_id(program_id)
-            && encoding == UiAccountEncoding::JsonParsed
-        {
-            get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect()
+            RpcProgramAccounts::Page(RpcKeyedAccountsPage {
+                accounts: encode_keyed_accounts(
+                    &bank,
+                    program_id,
+                    keyed_accounts,
+                    encoding,
+                    data_slice_config,
+                )?,
+                cursor: next_cursor.map(|cursor| cursor.encode()),
+            })
         } else {
-            keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| {
-                    Ok(RpcKeyedAccount {
-                        pubkey: pubkey.to_string(),
-                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?
+            let keyed_accounts = {
+                if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
+                    self.get_filtered_spl_token_accounts_by_owner(
+                        &bank, program_id, &owner, filters,
+                    )?
+                } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters) {
+                    self.get_filtered_spl_token_accounts_by_mint(&bank, program_id, &mint, filters)?
+                } else {
+                    self.get_filtered_program_accounts(&bank, program_id, filters)?
+                }
+            };
+            RpcProgramAccounts::All(encode_keyed_accounts(
+                &bank,
+                program_id,
+                keyed_accounts,
+                encoding,
+                data_slice_config,
+            )?)
         };
         Ok(match with_context {
             true => OptionalContext::Context(new_response(&bank, accounts)),
@@ -509,6 +642,97 @@ impl JsonRpcRequestProcessor {
         })
     }
 
+    pub fn get_multiple_program_accounts(
+        &self,
+        program_queries: Vec<(Pubkey, Vec<RpcFilterType>)>,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<RpcResponse<HashMap<String, Vec<RpcKeyedAccount>>>> {
+        let RpcAccountInfoConfig {
+            encoding,
+            data_slice: data_slice_config,
+            commitment,
+            min_context_slot,
+        } = config.unwrap_or_default();
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment,
+            min_context_slot,
+        })?;
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
+        check_slice_and_encoding(&encoding, data_slice_config.is_some())?;
+
+        // Programs whose filters can be served by a secondary index are looked up there, all
+        // the others share a single scan of the accounts
+        let account_indexes = &self.config.account_indexes;
+        let mut keyed_accounts_by_program = HashMap::new();
+        let mut scanned_programs = HashMap::new();
+        for (program_id, mut filters) in program_queries {
+            optimize_filters(&mut filters);
+            let keyed_accounts = if let Some(owner) =
+                get_spl_token_owner_filter(&program_id, &filters)
+                    .filter(|_| account_indexes.contains(&AccountIndex::SplTokenOwner))
+            {
+                self.get_filtered_spl_token_accounts_by_owner(&bank, &program_id, &owner, filters)?
+            } else if let Some(mint) = get_spl_token_mint_filter(&program_id, &filters)
+                .filter(|_| account_indexes.contains(&AccountIndex::SplTokenMint))
+            {
+                self.get_filtered_spl_token_accounts_by_mint(&bank, &program_id, &mint, filters)?
+            } else if account_indexes.contains(&AccountIndex::ProgramId) {
+                self.get_filtered_program_accounts(&bank, &program_id, filters)?
+            } else {
+                scanned_programs.insert(program_id, filters);
+                continue;
+            };
+            keyed_accounts_by_program.insert(program_id, keyed_accounts);
+        }
+        if !scanned_programs.is_empty() {
+            let program_ids: HashSet<_> = scanned_programs.keys().copied().collect();
+            let keyed_accounts = bank
+                .get_filtered_multiple_program_accounts(
+                    &program_ids,
+                    |account| {
+                        scanned_programs
+                            .get(account.owner())
+                            .map(|filters| {
+                                filters
+                                    .iter()
+                                    .all(|filter_type| filter_type.allows(account))
+                            })
+                            .unwrap_or_default()
+                    },
+                    &ScanConfig::default(),
+                )
+                .map_err(|e| RpcCustomError::ScanError {
+                    message: e.to_string(),
+                })?;
+            for program_id in program_ids {
+                keyed_accounts_by_program.insert(program_id, vec![]);
+            }
+            for (pubkey, account) in keyed_accounts {
+                keyed_accounts_by_program
+                    .entry(*account.owner())
+                    .or_default()
+                    .push((pubkey, account));
+            }
+        }
+
+        let accounts = keyed_accounts_by_program
+            .into_iter()
+            .map(|(program_id, keyed_accounts)| {
+                Ok((
+                    program_id.to_string(),
+                    encode_keyed_accounts(
+                        &bank,
+                        &program_id,
+                        keyed_accounts,
+                        encoding,
+                        data_slice_config,
+                    )?,
+                ))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        Ok(new_response(&bank, accounts))
+    }
+
     pub async fn get_inflation_reward(
         &self,
         addresses: Vec<Pubkey>,
@@ -2036,6 +2260,68 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    /// Get the accounts of the program after `start_after`, in address order, up to `limit` of
+    /// them. The scan starts at `start_after` and stops at the last account returned, going
+    /// through the same secondary index as an unpaginated `getProgramAccounts` would.
+    fn get_filtered_program_accounts_page(
+        &self,
+        bank: &Arc<Bank>,
+        program_id: &Pubkey,
+        mut filters: Vec<RpcFilterType>,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+    ) -> RpcCustomResult<Vec<(Pubkey, AccountSharedData)>> {
+        let account_indexes = &self.config.account_indexes;
+        let index_key = if let Some(owner) = get_spl_token_owner_filter(program_id, &filters)
+            .filter(|_| account_indexes.contains(&AccountIndex::SplTokenOwner))
+        {
+            // Redundant filters, as in `get_filtered_spl_token_accounts_by_owner`
+            filters.push(RpcFilterType::TokenAccountState);
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                SPL_TOKEN_ACCOUNT_OWNER_OFFSET,
+                owner.to_bytes().into(),
+            )));
+            Some((IndexKey::SplTokenOwner(owner), owner))
+        } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters)
+            .filter(|_| account_indexes.contains(&AccountIndex::SplTokenMint))
+        {
+            // Redundant filters, as in `get_filtered_spl_token_accounts_by_mint`
+            filters.push(RpcFilterType::TokenAccountState);
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                SPL_TOKEN_ACCOUNT_MINT_OFFSET,
+                mint.to_bytes().into(),
+            )));
+            Some((IndexKey::SplTokenMint(mint), mint))
+        } else if account_indexes.contains(&AccountIndex::ProgramId) {
+            Some((IndexKey::ProgramId(*program_id), *program_id))
+        } else {
+            None
+        };
+        if let Some((_, key)) = index_key {
+            if !account_indexes.include_key(&key) {
+                return Err(RpcCustomError::KeyExcludedFromSecondaryIndex {
+                    index_key: key.to_string(),
+                });
+            }
+        }
+
+        bank.get_filtered_program_accounts_page(
+            program_id,
+            index_key.map(|(index_key, _)| index_key),
+            |account| {
+                filters
+                    .iter()
+                    .all(|filter_type| filter_type.allows(account))
+            },
+            start_after,
+            limit,
+            &ScanConfig::default(),
+        )
+        .map_err(|e| RpcCustomError::ScanError {
+            message: e.to_string(),
+        })
+    }
+
     /// Get an iterator of spl-token accounts by owner address
     fn get_filtered_spl_token_accounts_by_owner(
         &self,
@@ -2185,6 +2471,109 @@ impl JsonRpcRequestProcessor {
     }
 }
 
+/// Where a page of `getProgramAccounts` ends: the slot of the bank all the pages are read from,
+/// and the last account returned so far. Pages are ordered by account address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramAccountsCursor {
+    slot: Slot,
+    last_pubkey: Pubkey,
+}
+
+impl ProgramAccountsCursor {
+    fn encode(&self) -> String {
+        bs58::encode(serialize(self).unwrap()).into_string()
+    }
+}
+
+/// How long the bank of a `getProgramAccounts` cursor is kept after the last page read from it
+const PROGRAM_ACCOUNTS_CURSOR_BANK_TTL: Duration = Duration::from_secs(60);
+/// How many banks are kept for `getProgramAccounts` cursors at most
+const MAX_PROGRAM_ACCOUNTS_CURSOR_BANKS: usize = 16;
+
+/// The banks `getProgramAccounts` cursors read their pages from. `BankForks` drops a bank soon
+/// after a new root is set, well before a scan of a large program is done paging, so each bank
+/// is kept here by slot until no page was read from it for `PROGRAM_ACCOUNTS_CURSOR_BANK_TTL`.
+#[derive(Default)]
+struct ProgramAccountsCursorBanks {
+    banks: HashMap<Slot, (Arc<Bank>, Instant)>,
+}
+
+impl ProgramAccountsCursorBanks {
+    fn get(&self, slot: Slot) -> Option<Arc<Bank>> {
+        self.banks
+            .get(&slot)
+            .filter(|(_, last_used)| last_used.elapsed() < PROGRAM_ACCOUNTS_CURSOR_BANK_TTL)
+            .map(|(bank, _)| bank.clone())
+    }
+
+    /// Keep `bank` for the cursors to its next pages, dropping the expired banks and, when
+    /// full, the least recently used one
+    fn insert(&mut self, bank: Arc<Bank>) {
+        self.banks
+            .retain(|_, (_, last_used)| last_used.elapsed() < PROGRAM_ACCOUNTS_CURSOR_BANK_TTL);
+        if !self.banks.contains_key(&bank.slot())
+            && self.banks.len() >= MAX_PROGRAM_ACCOUNTS_CURSOR_BANKS
+        {
+            let least_recently_used_slot = self
+                .banks
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(slot, _)| *slot);
+            if let Some(slot) = least_recently_used_slot {
+                self.banks.remove(&slot);
+            }
+        }
+        self.banks.insert(bank.slot(), (bank, Instant::now()));
+    }
+}
+
+/// Keep the first `limit` of the accounts of a page, which are in address order and scanned up
+/// to one past the limit. Also returns the cursor to the next page if some accounts remain.
+fn paginate_keyed_accounts(
+    mut keyed_accounts: Vec<(Pubkey, AccountSharedData)>,
+    slot: Slot,
+    limit: Option<usize>,
+) -> (
+    Vec<(Pubkey, AccountSharedData)>,
+    Option<ProgramAccountsCursor>,
+) {
+    let next_cursor = match limit {
+        Some(limit) if keyed_accounts.len() > limit => {
+            keyed_accounts.truncate(limit);
+            keyed_accounts
+                .last()
+                .map(|(last_pubkey, _)| ProgramAccountsCursor {
+                    slot,
+                    last_pubkey: *last_pubkey,
+                })
+        }
+        _ => None,
+    };
+    (keyed_accounts, next_cursor)
+}
+
+fn encode_keyed_accounts(
+    bank: &Arc<Bank>,
+    program_id: &Pubkey,
+    keyed_accounts: Vec<(Pubkey, AccountSharedData)>,
+    encoding: UiAccountEncoding,
+    data_slice_config: Option<UiDataSliceConfig>,
+) -> Result<Vec<RpcKeyedAccount>> {
+    if is_known_spl_token_id(program_id) && encoding == UiAccountEncoding::JsonParsed {
+        Ok(get_parsed_token_accounts(bank.clone(), keyed_accounts.into_iter()).collect())
+    } else {
+        keyed_accounts
+            .into_iter()
+            .map(|(pubkey, account)| {
+                Ok(RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
+                })
+            })
+            .collect()
+    }
+}
+
 fn optimize_filters(filters: &mut [RpcFilterType]) {
     filters.iter_mut().for_each(|filter_type| {
         if let RpcFilterType::Memcmp(compare) = filter_type {
@@ -2229,6 +2618,14 @@ fn verify_filter(input: &RpcFilterType) -> Result<()> {
         .map_err(|e| Error::invalid_params(format!("Invalid param: {:?}", e)))
 }
 
+fn verify_program_accounts_cursor(input: &str) -> Result<ProgramAccountsCursor> {
+    bs58::decode(input)
+        .into_vec()
+        .ok()
+        .and_then(|bytes| bincode::deserialize(&bytes).ok())
+        .ok_or_else(|| Error::invalid_params(format!("Invalid cursor: {}", input)))
+}
+
 fn verify_pubkey(input: &str) -> Result<Pubkey> {
     input
         .parse()
@@ -2322,18 +2719,22 @@ fn get_encoded_account(
     encoding: UiAccountEncoding,
     data_slice: Option<UiDataSliceConfig>,
 ) -> Result<Option<UiAccount>> {
-    match bank.get_account(pubkey) {
-        Some(account) => {
-            let response = if is_known_spl_token_id(account.owner())
-                && encoding == UiAccountEncoding::JsonParsed
-            {
-                get_parsed_token_account(bank.clone(), pubkey, account)
-            } else {
-                encode_account(&account, pubkey, encoding, data_slice)?
-            };
-            Ok(Some(response))
-        }
-        None => Ok(None),
+    bank.get_account(pubkey)
+        .map(|account| encode_bank_account(bank, pubkey, account, encoding, data_slice))
+        .transpose()
+}
+
+fn encode_bank_account(
+    bank: &Arc<Bank>,
+    pubkey: &Pubkey,
+    account: AccountSharedData,
+    encoding: UiAccountEncoding,
+    data_slice: Option<UiDataSliceConfig>,
+) -> Result<UiAccount> {
+    if is_known_spl_token_id(account.owner()) && encoding == UiAccountEncoding::JsonParsed {
+        Ok(get_parsed_token_account(bank.clone(), pubkey, account))
+    } else {
+        encode_account(&account, pubkey, encoding, data_slice)
     }
 }
 
@@ -2993,7 +3394,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountInfoAtSlotConfig>,
         ) -> Result<RpcResponse<Option<UiAccount>>>;
 
         #[rpc(meta, name = "getMultipleAccounts")]
@@ -3001,7 +3402,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_strs: Vec<String>,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountInfoAtSlotConfig>,
         ) -> Result<RpcResponse<Vec<Option<UiAccount>>>>;
 
         #[rpc(meta, name = "getProgramAccounts")]
@@ -3010,7 +3411,15 @@ pub mod rpc_accounts {
             meta: Self::Metadata,
             program_id_str: String,
             config: Option<RpcProgramAccountsConfig>,
-        ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>>;
+        ) -> Result<OptionalContext<RpcProgramAccounts>>;
+
+        #[rpc(meta, name = "getMultipleProgramAccounts")]
+        fn get_multiple_program_accounts(
+            &self,
+            meta: Self::Metadata,
+            program_queries: Vec<RpcProgramAccountsQuery>,
+            config: Option<RpcAccountInfoConfig>,
+        ) -> Result<RpcResponse<HashMap<String, Vec<RpcKeyedAccount>>>>;
 
         #[rpc(meta, name = "getBlockCommitment")]
         fn get_block_commitment(
@@ -3096,18 +3505,25 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountInfoAtSlotConfig>,
         ) -> Result<RpcResponse<Option<UiAccount>>> {
             debug!("get_account_info rpc request received: {:?}", pubkey_str);
             let pubkey = verify_pubkey(&pubkey_str)?;
-            meta.get_account_info(&pubkey, config)
+            let RpcAccountInfoAtSlotConfig {
+                account_config,
+                slot,
+            } = config.unwrap_or_default();
+            match slot {
+                Some(slot) => meta.get_account_info_at_slot(&pubkey, slot, Some(account_config)),
+                None => meta.get_account_info(&pubkey, Some(account_config)),
+            }
         }
 
         fn get_multiple_accounts(
             &self,
             meta: Self::Metadata,
             pubkey_strs: Vec<String>,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountInfoAtSlotConfig>,
         ) -> Result<RpcResponse<Vec<Option<UiAccount>>>> {
             debug!(
                 "get_multiple_accounts rpc request received: {:?}",
@@ -3128,7 +3544,16 @@ pub mod rpc_accounts {
                 .into_iter()
                 .map(|pubkey_str| verify_pubkey(&pubkey_str))
                 .collect::<Result<Vec<_>>>()?;
-            meta.get_multiple_accounts(pubkeys, config)
+            let RpcAccountInfoAtSlotConfig {
+                account_config,
+                slot,
+            } = config.unwrap_or_default();
+            match slot {
+                Some(slot) => {
+                    meta.get_multiple_accounts_at_slot(pubkeys, slot, Some(account_config))
+                }
+                None => meta.get_multiple_accounts(pubkeys, Some(account_config)),
+            }
         }
 
         fn get_program_accounts(
@@ -3136,21 +3561,29 @@ pub mod rpc_accounts {
             meta: Self::Metadata,
             program_id_str: String,
             config: Option<RpcProgramAccountsConfig>,
-        ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
+        ) -> Result<OptionalContext<RpcProgramAccounts>> {
             debug!(
                 "get_program_accounts rpc request received: {:?}",
                 program_id_str
             );
             let program_id = verify_pubkey(&program_id_str)?;
-            let (config, filters, with_context) = if let Some(config) = config {
+            let (config, filters, with_context, limit, cursor) = if let Some(config) = config {
                 (
                     Some(config.account_config),
                     config.filters.unwrap_or_default(),
                     config.with_context.unwrap_or_default(),
+                    config.limit,
+                    config.cursor,
                 )
             } else {
-                (None, vec![], false)
+                (None, vec![], false, None, None)
             };
+            if limit == Some(0) {
+                return Err(Error::invalid_params("Limit must be greater than 0"));
+            }
+            let cursor = cursor
+                .map(|cursor| verify_program_accounts_cursor(&cursor))
+                .transpose()?;
             if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
                 return Err(Error::invalid_params(format!(
                     "Too many filters provided; max {}",
@@ -3160,7 +3593,50 @@ pub mod rpc_accounts {
             for filter in &filters {
                 verify_filter(filter)?;
             }
-            meta.get_program_accounts(&program_id, config, filters, with_context)
+            meta.get_program_accounts(&program_id, config, filters, with_context, limit, cursor)
+        }
+
+        fn get_multiple_program_accounts(
+            &self,
+            meta: Self::Metadata,
+            program_queries: Vec<RpcProgramAccountsQuery>,
+            config: Option<RpcAccountInfoConfig>,
+        ) -> Result<RpcResponse<HashMap<String, Vec<RpcKeyedAccount>>>> {
+            debug!(
+                "get_multiple_program_accounts rpc request received: {:?}",
+                program_queries.len()
+            );
+            if program_queries.len() > MAX_GET_MULTIPLE_PROGRAM_ACCOUNTS_PROGRAMS {
+                return Err(Error::invalid_params(format!(
+                    "Too many programs provided; max {}",
+                    MAX_GET_MULTIPLE_PROGRAM_ACCOUNTS_PROGRAMS
+                )));
+            }
+            let mut program_ids = HashSet::new();
+            let program_queries = program_queries
+                .into_iter()
+                .map(|query| {
+                    let program_id = verify_pubkey(&query.program_id)?;
+                    if !program_ids.insert(program_id) {
+                        return Err(Error::invalid_params(format!(
+                            "Duplicate program provided: {}",
+                            program_id
+                        )));
+                    }
+                    let filters = query.filters.unwrap_or_default();
+                    if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
+                        return Err(Error::invalid_params(format!(
+                            "Too many filters provided; max {}",
+                            MAX_GET_PROGRAM_ACCOUNT_FILTERS
+                        )));
+                    }
+                    for filter in &filters {
+                        verify_filter(filter)?;
+                    }
+                    Ok((program_id, filters))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            meta.get_multiple_program_accounts(program_queries, config)
         }
 
         fn get_block_commitment(
@@ -3345,6 +3821,14 @@ pub mod rpc_full {
             config: Option<RpcSimulateTransactionConfig>,
         ) -> Result<RpcResponse<RpcSimulateTransactionResult>>;
 
+        #[rpc(meta, name = "simulateBundle")]
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateTransactionConfig>,
+        ) -> Result<RpcResponse<Vec<RpcSimulateTransactionResult>>>;
+
         #[rpc(meta, name = "minimumLedgerSlot")]
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot>;
 
@@ -3693,6 +4177,7 @@ pub mod rpc_full {
                     post_simulation_accounts: _,
                     units_consumed,
                     return_data,
+                    instruction_trace: _,
                 } = preflight_bank.simulate_transaction(transaction)
                 {
                     match err {
@@ -3711,6 +4196,7 @@ pub mod rpc_full {
                             accounts: None,
                             units_consumed: Some(units_consumed),
                             return_data: return_data.map(|return_data| return_data.into()),
+                            trace: None,
                         },
                     }
                     .into());
@@ -3741,37 +4227,26 @@ pub mod rpc_full {
                 encoding,
                 accounts: config_accounts,
                 min_context_slot,
+                account_overrides,
+                trace,
             } = config.unwrap_or_default();
-            let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
-            let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
-                Error::invalid_params(format!(
-                    "unsupported encoding: {}. Supported encodings: base58, base64",
-                    tx_encoding
-                ))
-            })?;
-            let (_, mut unsanitized_tx) =
-                decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)?;
+            let binary_encoding = get_simulation_binary_encoding(encoding)?;
 
             let bank = &*meta.get_bank_with_config(RpcContextConfig {
                 commitment,
                 min_context_slot,
             })?;
-            if replace_recent_blockhash {
-                if sig_verify {
-                    return Err(Error::invalid_params(
-                        "sigVerify may not be used with replaceRecentBlockhash",
-                    ));
-                }
-                unsanitized_tx
-                    .message
-                    .set_recent_blockhash(bank.last_blockhash());
-            }
-
-            let transaction = sanitize_transaction(unsanitized_tx, bank)?;
-            if sig_verify {
-                verify_transaction(&transaction, &bank.feature_set)?;
-            }
+            let transaction = sanitize_simulation_transaction(
+                data,
+                binary_encoding,
+                bank,
+                replace_recent_blockhash,
+                sig_verify,
+            )?;
             let number_of_accounts = transaction.message().account_keys().len();
+            let account_keys = trace.then(|| get_account_keys(&transaction));
+            let account_overrides =
+                get_simulation_account_overrides(bank, account_overrides.unwrap_or_default())?;
 
             let TransactionSimulationResult {
                 result,
@@ -3779,18 +4254,15 @@ pub mod rpc_full {
                 post_simulation_accounts,
                 units_consumed,
                 return_data,
-            } = bank.simulate_transaction(transaction);
+                instruction_trace,
+            } = bank.simulate_transaction_with_account_overrides(
+                transaction,
+                account_overrides,
+                trace,
+            );
 
             let accounts = if let Some(config_accounts) = config_accounts {
-                let accounts_encoding = config_accounts
-                    .encoding
-                    .unwrap_or(UiAccountEncoding::Base64);
-
-                if accounts_encoding == UiAccountEncoding::Binary
-                    || accounts_encoding == UiAccountEncoding::Base58
-                {
-                    return Err(Error::invalid_params("base58 encoding not supported"));
-                }
+                let accounts_encoding = get_simulation_accounts_encoding(&config_accounts)?;
 
                 if config_accounts.addresses.len() > number_of_accounts {
                     return Err(Error::invalid_params(format!(
@@ -3831,10 +4303,155 @@ pub mod rpc_full {
                     accounts,
                     units_consumed: Some(units_consumed),
                     return_data: return_data.map(|return_data| return_data.into()),
+                    trace: account_keys.map(|account_keys| {
+                        build_instruction_trace(&account_keys, instruction_trace)
+                    }),
                 },
             ))
         }
 
+        fn simulate_bundle(
+            &self,
+            meta: Self::Metadata,
+            data: Vec<String>,
+            config: Option<RpcSimulateTransactionConfig>,
+        ) -> Result<RpcResponse<Vec<RpcSimulateTransactionResult>>> {
+            debug!(
+                "simulate_bundle rpc request received: {} transactions",
+                data.len()
+            );
+            let RpcSimulateTransactionConfig {
+                sig_verify,
+                replace_recent_blockhash,
+                commitment,
+                encoding,
+                accounts: config_accounts,
+                min_context_slot,
+                account_overrides,
+                trace,
+            } = config.unwrap_or_default();
+            if data.is_empty() {
+                return Err(Error::invalid_params("No transactions provided"));
+            }
+            if data.len() > MAX_SIMULATE_BUNDLE_TRANSACTIONS {
+                return Err(Error::invalid_params(format!(
+                    "Too many transactions provided; max {}",
+                    MAX_SIMULATE_BUNDLE_TRANSACTIONS
+                )));
+            }
+            let binary_encoding = get_simulation_binary_encoding(encoding)?;
+            let config_accounts = config_accounts
+                .map(|config_accounts| {
+                    let accounts_encoding = get_simulation_accounts_encoding(&config_accounts)?;
+                    if config_accounts.addresses.len() > MAX_MULTIPLE_ACCOUNTS {
+                        return Err(Error::invalid_params(format!(
+                            "Too many accounts provided; max {}",
+                            MAX_MULTIPLE_ACCOUNTS
+                        )));
+                    }
+                    let addresses = config_accounts
+                        .addresses
+                        .iter()
+                        .map(|address| verify_pubkey(address))
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok((addresses, accounts_encoding))
+                })
+                .transpose()?;
+
+            let bank = meta.get_bank_with_config(RpcContextConfig {
+                commitment,
+                min_context_slot,
+            })?;
+            let transactions = data
+                .into_iter()
+                .map(|data| {
+                    sanitize_simulation_transaction(
+                        data,
+                        binary_encoding,
+                        &bank,
+                        replace_recent_blockhash,
+                        sig_verify,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut transactions_account_keys = trace.then(|| {
+                transactions
+                    .iter()
+                    .map(get_account_keys)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            });
+            let account_overrides =
+                get_simulation_account_overrides(&bank, account_overrides.unwrap_or_default())?;
+
+            // The requested accounts are reported as they stand after each transaction, so keep
+            // track of their latest state starting from the overrides and the bank
+            let mut requested_accounts = config_accounts.as_ref().map(|(addresses, _)| {
+                addresses
+                    .iter()
+                    .map(|address| {
+                        account_overrides
+                            .get(address)
+                            .cloned()
+                            .or_else(|| bank.get_account(address))
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            let mut results = vec![];
+            for TransactionSimulationResult {
+                result,
+                logs,
+                post_simulation_accounts,
+                units_consumed,
+                return_data,
+                instruction_trace,
+            } in bank.simulate_bundle(transactions, account_overrides, trace)
+            {
+                let account_keys = transactions_account_keys
+                    .as_mut()
+                    .and_then(|account_keys| account_keys.next());
+                let accounts = match (&config_accounts, &mut requested_accounts) {
+                    (Some((addresses, _)), _) if result.is_err() => {
+                        Some(vec![None; addresses.len()])
+                    }
+                    (Some((addresses, accounts_encoding)), Some(requested_accounts)) => {
+                        let mut accounts = Vec::with_capacity(addresses.len());
+                        for (address, account) in addresses.iter().zip(requested_accounts) {
+                            if let Some((_, post_account)) = post_simulation_accounts
+                                .iter()
+                                .find(|(key, _account)| key == address)
+                            {
+                                *account = Some(post_account.clone());
+                            }
+                            accounts.push(
+                                account
+                                    .as_ref()
+                                    .map(|account| {
+                                        encode_account(account, address, *accounts_encoding, None)
+                                    })
+                                    .transpose()?,
+                            );
+                        }
+                        Some(accounts)
+                    }
+                    _ => None,
+                };
+                results.push(RpcSimulateTransactionResult {
+                    err: result.err(),
+                    logs: Some(logs),
+                    accounts,
+                    units_consumed: Some(units_consumed),
+                    return_data: return_data.map(|return_data| return_data.into()),
+                    trace: account_keys.map(|account_keys| {
+                        build_instruction_trace(&account_keys, instruction_trace)
+                    }),
+                });
+            }
+
+            Ok(new_response(&bank, results))
+        }
+
         fn minimum_ledger_slot(&self, meta: Self::Metadata) -> Result<Slot> {
             debug!("minimum_ledger_slot rpc request received");
             meta.minimum_ledger_slot()
@@ -4485,27 +5102,187 @@ where
             }
             base64::decode(encoded).map_err(|e| Error::invalid_params(format!("{:?}", e)))?
         }
-    };
-    if wire_output.len() > PACKET_DATA_SIZE {
-        let err = format!(
-            "encoded {} too large: {} bytes (max: {} bytes)",
-            type_name::<T>(),
-            wire_output.len(),
-            PACKET_DATA_SIZE
-        );
-        info!("{}", err);
-        return Err(Error::invalid_params(&err));
+    };
+    if wire_output.len() > PACKET_DATA_SIZE {
+        let err = format!(
+            "encoded {} too large: {} bytes (max: {} bytes)",
+            type_name::<T>(),
+            wire_output.len(),
+            PACKET_DATA_SIZE
+        );
+        info!("{}", err);
+        return Err(Error::invalid_params(&err));
+    }
+    bincode::options()
+        .with_limit(PACKET_DATA_SIZE as u64)
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .deserialize_from(&wire_output[..])
+        .map_err(|err| {
+            info!("deserialize error: {}", err);
+            Error::invalid_params(&err.to_string())
+        })
+        .map(|output| (wire_output, output))
+}
+
+/// Apply the requested overrides on top of the current state of the accounts
+fn get_simulation_binary_encoding(
+    encoding: Option<UiTransactionEncoding>,
+) -> Result<TransactionBinaryEncoding> {
+    let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
+    tx_encoding.into_binary_encoding().ok_or_else(|| {
+        Error::invalid_params(format!(
+            "unsupported encoding: {}. Supported encodings: base58, base64",
+            tx_encoding
+        ))
+    })
+}
+
+fn get_simulation_accounts_encoding(
+    config_accounts: &RpcSimulateTransactionAccountsConfig,
+) -> Result<UiAccountEncoding> {
+    let accounts_encoding = config_accounts
+        .encoding
+        .unwrap_or(UiAccountEncoding::Base64);
+    if accounts_encoding == UiAccountEncoding::Binary
+        || accounts_encoding == UiAccountEncoding::Base58
+    {
+        return Err(Error::invalid_params("base58 encoding not supported"));
+    }
+    Ok(accounts_encoding)
+}
+
+/// Decode a transaction to simulate against `bank`, replacing its recent blockhash or verifying
+/// its signatures if requested
+fn sanitize_simulation_transaction(
+    data: String,
+    binary_encoding: TransactionBinaryEncoding,
+    bank: &Bank,
+    replace_recent_blockhash: bool,
+    sig_verify: bool,
+) -> Result<SanitizedTransaction> {
+    let (_, mut unsanitized_tx) =
+        decode_and_deserialize::<VersionedTransaction>(data, binary_encoding)?;
+    if replace_recent_blockhash {
+        if sig_verify {
+            return Err(Error::invalid_params(
+                "sigVerify may not be used with replaceRecentBlockhash",
+            ));
+        }
+        unsanitized_tx
+            .message
+            .set_recent_blockhash(bank.last_blockhash());
+    }
+
+    let transaction = sanitize_transaction(unsanitized_tx, bank)?;
+    if sig_verify {
+        verify_transaction(&transaction, &bank.feature_set)?;
+    }
+    Ok(transaction)
+}
+
+fn get_simulation_account_overrides(
+    bank: &Bank,
+    account_overrides: HashMap<String, RpcAccountOverride>,
+) -> Result<AccountOverrides> {
+    let mut overrides = AccountOverrides::default();
+    for (address, account_override) in account_overrides {
+        let pubkey = verify_pubkey(&address)?;
+        let RpcAccountOverride {
+            lamports,
+            owner,
+            data,
+            executable,
+        } = account_override;
+        let mut account = bank
+            .get_account(&pubkey)
+            .unwrap_or_else(|| AccountSharedData::new(0, 0, &system_program::id()));
+        if let Some(lamports) = lamports {
+            account.set_lamports(lamports);
+        }
+        if let Some(owner) = owner {
+            account.set_owner(verify_pubkey(&owner)?);
+        }
+        if let Some(data) = data {
+            account.set_data(base64::decode(data).map_err(|e| {
+                Error::invalid_params(format!("invalid data for account {}: {}", pubkey, e))
+            })?);
+        }
+        if let Some(executable) = executable {
+            account.set_executable(executable);
+        }
+        overrides.set_account(&pubkey, Some(account));
+    }
+    Ok(overrides)
+}
+
+fn get_account_keys(transaction: &SanitizedTransaction) -> Vec<Pubkey> {
+    transaction
+        .message()
+        .account_keys()
+        .iter()
+        .copied()
+        .collect()
+}
+
+/// Turn the instructions invoked during each transaction instruction into their call trees
+fn build_instruction_trace(
+    account_keys: &[Pubkey],
+    instruction_trace: TracedInstructionsList,
+) -> Vec<RpcInstructionTrace> {
+    instruction_trace
+        .into_iter()
+        .filter_map(|traced_instructions| build_call_tree(account_keys, traced_instructions))
+        .collect()
+}
+
+/// Turn the instructions invoked during one transaction instruction, listed in invocation order,
+/// into their call tree
+fn build_call_tree(
+    account_keys: &[Pubkey],
+    traced_instructions: Vec<TracedInstruction>,
+) -> Option<RpcInstructionTrace> {
+    let key_at = |index: u8| {
+        account_keys
+            .get(index as usize)
+            .map(|pubkey| pubkey.to_string())
+            .unwrap_or_default()
+    };
+    // Invocations whose inner instructions are still being collected, from the outermost one
+    let mut invocations: Vec<RpcInstructionTrace> = vec![];
+    let fold_innermost = |invocations: &mut Vec<RpcInstructionTrace>| {
+        let innermost = invocations.pop().unwrap();
+        invocations
+            .last_mut()
+            .unwrap()
+            .inner_instructions
+            .push(innermost);
+    };
+    for TracedInstruction {
+        instruction,
+        stack_height,
+        compute_units_consumed,
+    } in traced_instructions
+    {
+        while invocations.len() > 1 && invocations.len() >= stack_height {
+            fold_innermost(&mut invocations);
+        }
+        invocations.push(RpcInstructionTrace {
+            program_id: key_at(instruction.program_id_index),
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|index| key_at(*index))
+                .collect(),
+            data: bs58::encode(instruction.data).into_string(),
+            compute_units_consumed,
+            inner_instructions: vec![],
+        });
     }
-    bincode::options()
-        .with_limit(PACKET_DATA_SIZE as u64)
-        .with_fixint_encoding()
-        .allow_trailing_bytes()
-        .deserialize_from(&wire_output[..])
-        .map_err(|err| {
-            info!("deserialize error: {}", err);
-            Error::invalid_params(&err.to_string())
-        })
-        .map(|output| (wire_output, output))
+    while invocations.len() > 1 {
+        fold_innermost(&mut invocations);
+    }
+    invocations.pop()
 }
 
 fn sanitize_transaction(
@@ -4634,6 +5411,9 @@ pub mod tests {
         solana_address_lookup_table_program::state::{AddressLookupTable, LookupTableMeta},
         solana_client::{
             rpc_custom_error::{
+                JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_AVAILABLE,
+                JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_RECORDED,
+                JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_SLOT_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION,
@@ -4657,7 +5437,7 @@ pub mod tests {
             compute_budget::ComputeBudgetInstruction,
             fee_calculator::{FeeRateGovernor, DEFAULT_BURN_PERCENT},
             hash::{hash, Hash},
-            instruction::InstructionError,
+            instruction::{CompiledInstruction, InstructionError},
             message::{
                 v0::{self, MessageAddressTableLookup},
                 Message, MessageHeader, VersionedMessage,
@@ -4794,6 +5574,7 @@ pub mod tests {
                 max_complete_transaction_status_slot.clone(),
                 max_complete_rewards_slot,
                 Arc::new(PrioritizationFeeCache::default()),
+                None,
             )
             .0;
 
@@ -5631,6 +6412,79 @@ pub mod tests {
         assert_eq!(response, expected);
     }
 
+    #[test]
+    fn test_rpc_get_accounts_at_slot() {
+        let mut rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let pubkey = Pubkey::new_unique();
+        let address = pubkey.to_string();
+        let data = vec![1, 2, 3, 4, 5];
+        let account = AccountSharedData::create(42, data.clone(), Pubkey::default(), false, 0);
+        bank.store_account(&pubkey, &account);
+
+        let request = create_test_request(
+            "getAccountInfo",
+            Some(json!([address, {"slot": bank.slot()}])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_AVAILABLE);
+
+        let other_address = Pubkey::new_unique().to_string();
+        rpc.meta.account_history = Some(Arc::new(AccountHistory::new(
+            rpc.blockstore.clone(),
+            100,
+            Some(HashSet::from([
+                pubkey,
+                verify_pubkey(&other_address).unwrap(),
+            ])),
+            bank.clone(),
+        )));
+
+        let request = create_test_request(
+            "getAccountInfo",
+            Some(json!([address, {"encoding": "base64", "slot": bank.slot()}])),
+        );
+        let result: Value = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result["context"]["slot"], bank.slot());
+        assert_eq!(result["value"]["lamports"], 42);
+        assert_eq!(
+            result["value"]["data"],
+            json!([base64::encode(&data), "base64"])
+        );
+
+        let request = create_test_request(
+            "getMultipleAccounts",
+            Some(json!([
+                [address, other_address],
+                {"slot": bank.slot()},
+            ])),
+        );
+        let result: RpcResponse<Value> = parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.context.slot, bank.slot());
+        assert_eq!(result.value[0]["lamports"], 42);
+        assert_eq!(result.value[1], Value::Null);
+
+        let request = create_test_request(
+            "getMultipleAccounts",
+            Some(json!([[address], {"slot": bank.slot() + 1}])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            code,
+            JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_SLOT_NOT_AVAILABLE
+        );
+
+        let request = create_test_request(
+            "getMultipleAccounts",
+            Some(json!([
+                [address, Pubkey::new_unique().to_string()],
+                {"slot": bank.slot()},
+            ])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, JSON_RPC_SERVER_ERROR_ACCOUNT_HISTORY_NOT_RECORDED);
+    }
+
     #[test]
     fn test_rpc_get_program_accounts() {
         let rpc = RpcHandler::start();
@@ -5785,6 +6639,195 @@ pub mod tests {
         assert_eq!(result.len(), 0);
     }
 
+    #[test]
+    fn test_rpc_get_program_accounts_paginated() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+
+        let program_id = Pubkey::new_unique();
+        let mut pubkeys = (0..5)
+            .map(|_| {
+                let pubkey = Pubkey::new_unique();
+                bank.store_account(&pubkey, &AccountSharedData::new(42, 0, &program_id));
+                pubkey
+            })
+            .collect::<Vec<_>>();
+        pubkeys.sort();
+
+        let mut cursor: Option<String> = None;
+        let mut paged_pubkeys = vec![];
+        let mut num_pages = 0;
+        loop {
+            let request = create_test_request(
+                "getProgramAccounts",
+                Some(json!([
+                    program_id.to_string(),
+                    {"limit": 2, "cursor": cursor},
+                ])),
+            );
+            let page: RpcKeyedAccountsPage = parse_success_result(rpc.handle_request_sync(request));
+            assert!(page.accounts.len() <= 2);
+            paged_pubkeys.extend(page.accounts.into_iter().map(|account| account.pubkey));
+            num_pages += 1;
+            if page.cursor.is_none() {
+                break;
+            }
+            cursor = page.cursor;
+
+            // Accounts created after the first page are not visible from the cursor's bank
+            if num_pages == 1 {
+                let new_bank = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+                new_bank.store_account(
+                    &Pubkey::new_unique(),
+                    &AccountSharedData::new(42, 0, &program_id),
+                );
+                rpc.bank_forks.write().unwrap().insert(new_bank);
+            }
+            // The cursor's bank is still available once `BankForks` has dropped it
+            if num_pages == 2 {
+                let new_bank = Bank::new_from_parent(&bank, &Pubkey::default(), 2);
+                let mut bank_forks = rpc.bank_forks.write().unwrap();
+                bank_forks.insert(new_bank);
+                bank_forks.set_root(2, &AbsRequestSender::default(), None);
+                assert!(bank_forks.get(0).is_none());
+            }
+        }
+        assert_eq!(num_pages, 3);
+        let expected_pubkeys = pubkeys
+            .iter()
+            .map(|pubkey| pubkey.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(paged_pubkeys, expected_pubkeys);
+
+        // A cursor alone returns the rest of the accounts
+        let cursor = ProgramAccountsCursor {
+            slot: 0,
+            last_pubkey: pubkeys[2],
+        };
+        let request = create_test_request(
+            "getProgramAccounts",
+            Some(json!([
+                program_id.to_string(),
+                {"cursor": cursor.encode(), "withContext": true},
+            ])),
+        );
+        let result: RpcResponse<RpcKeyedAccountsPage> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.context.slot, 0);
+        assert_eq!(
+            result
+                .value
+                .accounts
+                .into_iter()
+                .map(|account| account.pubkey)
+                .collect::<Vec<_>>(),
+            expected_pubkeys[3..].to_vec()
+        );
+        assert_eq!(result.value.cursor, None);
+
+        // Test invalid parameters
+        let request = create_test_request(
+            "getProgramAccounts",
+            Some(json!([program_id.to_string(), {"limit": 0}])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+        let request = create_test_request(
+            "getProgramAccounts",
+            Some(json!([program_id.to_string(), {"cursor": "not a cursor"}])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+        let cursor = ProgramAccountsCursor {
+            slot: 42,
+            last_pubkey: pubkeys[0],
+        };
+        let request = create_test_request(
+            "getProgramAccounts",
+            Some(json!([program_id.to_string(), {"cursor": cursor.encode()}])),
+        );
+        let (code, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+        assert_eq!(message, "Cursor expired: slot 42 is no longer available");
+    }
+
+    #[test]
+    fn test_rpc_get_multiple_program_accounts() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+
+        let program_ids = (0..2).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let mut keyed_accounts = vec![];
+        for program_id in &program_ids {
+            for data_len in 1..3 {
+                let pubkey = Pubkey::new_unique();
+                let account = AccountSharedData::new(42, data_len, program_id);
+                bank.store_account(&pubkey, &account);
+                keyed_accounts.push((pubkey, account));
+            }
+        }
+        let idle_program_id = Pubkey::new_unique();
+
+        let request = create_test_request(
+            "getMultipleProgramAccounts",
+            Some(json!([
+                [
+                    {"programId": program_ids[0].to_string()},
+                    {"programId": program_ids[1].to_string(), "filters": [{"dataSize": 2}]},
+                    {"programId": idle_program_id.to_string()},
+                ],
+                {"encoding": "base64"},
+            ])),
+        );
+        let result: RpcResponse<HashMap<String, Vec<RpcKeyedAccount>>> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.context.slot, 0);
+        let encode = |(pubkey, account): &(Pubkey, AccountSharedData)| RpcKeyedAccount {
+            pubkey: pubkey.to_string(),
+            account: UiAccount::encode(pubkey, account, UiAccountEncoding::Base64, None, None),
+        };
+        let mut expected_value = HashMap::new();
+        expected_value.insert(
+            program_ids[0].to_string(),
+            keyed_accounts[..2].iter().map(encode).collect::<Vec<_>>(),
+        );
+        expected_value.insert(program_ids[1].to_string(), vec![encode(&keyed_accounts[3])]);
+        expected_value.insert(idle_program_id.to_string(), vec![]);
+        let mut value = result.value;
+        for accounts in value.values_mut() {
+            accounts.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+        }
+        for accounts in expected_value.values_mut() {
+            accounts.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+        }
+        assert_eq!(value, expected_value);
+
+        // Test duplicate programs
+        let request = create_test_request(
+            "getMultipleProgramAccounts",
+            Some(json!([[
+                {"programId": program_ids[0].to_string()},
+                {"programId": program_ids[0].to_string()},
+            ]])),
+        );
+        let (code, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+        assert_eq!(
+            message,
+            format!("Duplicate program provided: {}", program_ids[0])
+        );
+
+        // Test too many programs
+        let request = create_test_request(
+            "getMultipleProgramAccounts",
+            Some(json!([(0..=MAX_GET_MULTIPLE_PROGRAM_ACCOUNTS_PROGRAMS)
+                .map(|_| json!({"programId": Pubkey::new_unique().to_string()}))
+                .collect::<Vec<_>>()])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     fn test_rpc_simulate_transaction() {
         let rpc = RpcHandler::start();
@@ -6062,6 +7105,268 @@ pub mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_rpc_simulate_transaction_with_account_overrides() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        bank.freeze();
+
+        let payer = Keypair::new();
+        let bob_pubkey = Pubkey::new_unique();
+        let tx =
+            system_transaction::transfer(&payer, &bob_pubkey, rent_exempt_amount, recent_blockhash);
+        let tx_serialized_encoded = base64::encode(serialize(&tx).unwrap());
+        let accounts_config = json!({
+            "encoding": "base64",
+            "addresses": [payer.pubkey().to_string()],
+        });
+
+        // The payer does not exist
+        let request = create_test_request(
+            "simulateTransaction",
+            Some(json!([
+                tx_serialized_encoded,
+                {"encoding": "base64", "accounts": accounts_config},
+            ])),
+        );
+        let result: RpcResponse<RpcSimulateTransactionResult> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value.err, Some(TransactionError::AccountNotFound));
+
+        // Fund the payer for the simulation only
+        let lamports = 10 * rent_exempt_amount;
+        let request = create_test_request(
+            "simulateTransaction",
+            Some(json!([
+                tx_serialized_encoded,
+                {
+                    "encoding": "base64",
+                    "accounts": accounts_config,
+                    "accountOverrides": {
+                        payer.pubkey().to_string(): {"lamports": lamports},
+                    },
+                },
+            ])),
+        );
+        let result: RpcResponse<RpcSimulateTransactionResult> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value.err, None);
+        let payer_account: AccountSharedData = result.value.accounts.unwrap()[0]
+            .as_ref()
+            .unwrap()
+            .decode()
+            .unwrap();
+        assert!(payer_account.lamports() <= lamports - rent_exempt_amount);
+        assert_eq!(payer_account.owner(), &system_program::id());
+        assert!(bank.get_account(&payer.pubkey()).is_none());
+
+        // Test invalid overrides
+        let request = create_test_request(
+            "simulateTransaction",
+            Some(json!([
+                tx_serialized_encoded,
+                {
+                    "encoding": "base64",
+                    "accountOverrides": {
+                        payer.pubkey().to_string(): {"data": "not base64!"},
+                    },
+                },
+            ])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+        let request = create_test_request(
+            "simulateTransaction",
+            Some(json!([
+                tx_serialized_encoded,
+                {
+                    "encoding": "base64",
+                    "accountOverrides": {
+                        payer.pubkey().to_string(): {"owner": "not a pubkey"},
+                    },
+                },
+            ])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn test_rpc_simulate_bundle() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        bank.freeze();
+
+        // Alice can only pay Bob with the lamports the first transaction gives her
+        let alice = Keypair::new();
+        let bob_pubkey = Pubkey::new_unique();
+        let transactions = [
+            system_transaction::transfer(
+                &rpc.mint_keypair,
+                &alice.pubkey(),
+                10 * rent_exempt_amount,
+                recent_blockhash,
+            ),
+            system_transaction::transfer(&alice, &bob_pubkey, rent_exempt_amount, recent_blockhash),
+            system_transaction::transfer(
+                &alice,
+                &bob_pubkey,
+                100 * rent_exempt_amount,
+                recent_blockhash,
+            ),
+            system_transaction::transfer(&alice, &bob_pubkey, rent_exempt_amount, recent_blockhash),
+        ]
+        .iter()
+        .map(|tx| base64::encode(serialize(tx).unwrap()))
+        .collect::<Vec<_>>();
+        let config = json!({
+            "encoding": "base64",
+            "accounts": {
+                "encoding": "base64",
+                "addresses": [bob_pubkey.to_string()],
+            },
+        });
+
+        let request =
+            create_test_request("simulateBundle", Some(json!([transactions[..2], config])));
+        let result: RpcResponse<Vec<RpcSimulateTransactionResult>> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value.len(), 2);
+        assert!(result.value.iter().all(|result| result.err.is_none()));
+        let bob_account = |result: &RpcSimulateTransactionResult| {
+            result.accounts.as_ref().unwrap()[0]
+                .as_ref()
+                .map(|account| account.decode::<AccountSharedData>().unwrap())
+        };
+        assert_eq!(bob_account(&result.value[0]), None);
+        assert_eq!(
+            bob_account(&result.value[1]).unwrap().lamports(),
+            rent_exempt_amount
+        );
+        assert!(bank.get_account(&alice.pubkey()).is_none());
+        assert!(bank.get_account(&bob_pubkey).is_none());
+
+        // The simulation stops at the first failed transaction
+        let request = create_test_request("simulateBundle", Some(json!([transactions, config])));
+        let result: RpcResponse<Vec<RpcSimulateTransactionResult>> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value.len(), 3);
+        assert!(result.value[2].err.is_some());
+        assert_eq!(result.value[2].accounts, Some(vec![None]));
+
+        // Each transaction is simulated on its own without the ones before it
+        let request =
+            create_test_request("simulateBundle", Some(json!([transactions[1..2], config])));
+        let result: RpcResponse<Vec<RpcSimulateTransactionResult>> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value[0].err, Some(TransactionError::AccountNotFound));
+
+        // Test invalid bundles
+        let request = create_test_request("simulateBundle", Some(json!([[], config])));
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+        let request = create_test_request(
+            "simulateBundle",
+            Some(json!([
+                vec![transactions[0].clone(); MAX_SIMULATE_BUNDLE_TRANSACTIONS + 1],
+                config,
+            ])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(code, ErrorCode::InvalidParams.code());
+    }
+
+    #[test]
+    fn test_rpc_simulate_transaction_with_trace() {
+        let rpc = RpcHandler::start();
+        let bank = rpc.working_bank();
+        let recent_blockhash = bank.confirmed_last_blockhash();
+        bank.freeze();
+
+        let bob_pubkey = Pubkey::new_unique();
+        let tx =
+            system_transaction::transfer(&rpc.mint_keypair, &bob_pubkey, 1234, recent_blockhash);
+        let tx_serialized_encoded = base64::encode(serialize(&tx).unwrap());
+
+        let request = create_test_request(
+            "simulateTransaction",
+            Some(json!([tx_serialized_encoded, {"encoding": "base64"}])),
+        );
+        let result: RpcResponse<RpcSimulateTransactionResult> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value.trace, None);
+
+        let request = create_test_request(
+            "simulateTransaction",
+            Some(json!([tx_serialized_encoded, {"encoding": "base64", "trace": true}])),
+        );
+        let result: RpcResponse<RpcSimulateTransactionResult> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value.err, None);
+        assert_eq!(
+            result.value.trace,
+            Some(vec![RpcInstructionTrace {
+                program_id: system_program::id().to_string(),
+                accounts: vec![
+                    rpc.mint_keypair.pubkey().to_string(),
+                    bob_pubkey.to_string()
+                ],
+                data: bs58::encode(&tx.message.instructions[0].data).into_string(),
+                compute_units_consumed: result.value.units_consumed.unwrap(),
+                inner_instructions: vec![],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_build_instruction_trace() {
+        let account_keys = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let traced_instruction =
+            |data: u8, stack_height, compute_units_consumed| TracedInstruction {
+                instruction: CompiledInstruction::new_from_raw_parts(1, vec![data], vec![0]),
+                stack_height,
+                compute_units_consumed,
+            };
+        let rpc_instruction_trace =
+            |data: u8, compute_units_consumed, inner_instructions| RpcInstructionTrace {
+                program_id: account_keys[1].to_string(),
+                accounts: vec![account_keys[0].to_string()],
+                data: bs58::encode([data]).into_string(),
+                compute_units_consumed,
+                inner_instructions,
+            };
+
+        // The second instruction invokes 2, which invokes 3, and then invokes 4
+        let instruction_trace = vec![
+            vec![traced_instruction(0, 1, 100)],
+            vec![
+                traced_instruction(1, 1, 1000),
+                traced_instruction(2, 2, 500),
+                traced_instruction(3, 3, 200),
+                traced_instruction(4, 2, 300),
+            ],
+            vec![],
+        ];
+        assert_eq!(
+            build_instruction_trace(&account_keys, instruction_trace),
+            vec![
+                rpc_instruction_trace(0, 100, vec![]),
+                rpc_instruction_trace(
+                    1,
+                    1000,
+                    vec![
+                        rpc_instruction_trace(2, 500, vec![rpc_instruction_trace(3, 200, vec![])]),
+                        rpc_instruction_trace(4, 300, vec![]),
+                    ]
+                ),
+            ]
+        );
+    }
+
     #[test]
     #[should_panic(expected = "simulation bank must be frozen")]
     fn test_rpc_simulate_transaction_panic_on_unfrozen_bank() {
@@ -6388,6 +7693,7 @@ pub mod tests {
             Arc::new(AtomicU64::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            None,
         );
         let connection_cache = Arc::new(ConnectionCache::default());
         SendTransactionService::new::<NullTpuInfo>(
@@ -6660,6 +7966,7 @@ pub mod tests {
             Arc::new(AtomicU64::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            None,
         );
         let connection_cache = Arc::new(ConnectionCache::default());
         SendTransactionService::new::<NullTpuInfo>(
@@ -8295,6 +9602,7 @@ pub mod tests {
             max_complete_transaction_status_slot,
             max_complete_rewards_slot,
             Arc::new(PrioritizationFeeCache::default()),
+            None,
         );
 
         let mut io = MetaIoHandler::default();
@@ -369,6 +369,7 @@ pub(crate) mod tests {
             status: Ok(()),
             log_messages: None,
             inner_instructions: None,
+            instruction_trace: None,
             durable_nonce_fee: Some(DurableNonceFee::from(
                 &NonceFull::from_partial(
                     rollback_partial,
@@ -62,7 +62,7 @@ use {
     std::{
         cmp::Reverse,
         collections::{hash_map, BinaryHeap, HashMap, HashSet},
-        ops::RangeBounds,
+        ops::{Bound, RangeBounds},
         path::PathBuf,
         sync::{
             atomic::{AtomicUsize, Ordering},
@@ -941,6 +941,69 @@ impl Accounts {
         )
     }
 
+    /// Like `load_by_program_with_filter`, but collects the accounts of several programs in a
+    /// single scan
+    pub fn load_by_programs_with_filter<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        program_ids: &HashSet<Pubkey>,
+        filter: F,
+        config: &ScanConfig,
+    ) -> ScanResult<Vec<TransactionAccount>> {
+        self.accounts_db.scan_accounts(
+            ancestors,
+            bank_id,
+            |collector: &mut Vec<TransactionAccount>, some_account_tuple| {
+                Self::load_while_filtering(collector, some_account_tuple, |account| {
+                    program_ids.contains(account.owner()) && filter(account)
+                })
+            },
+            config,
+        )
+    }
+
+    /// Like `load_by_program_with_filter`, but only collects the accounts after `start_after`,
+    /// in pubkey order, and stops the scan once `limit` of them are collected. The secondary
+    /// index of `index_key` is used when given.
+    #[allow(clippy::too_many_arguments)]
+    pub fn load_page_by_program_with_filter<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        program_id: &Pubkey,
+        index_key: Option<IndexKey>,
+        filter: F,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+        config: &ScanConfig,
+    ) -> ScanResult<Vec<TransactionAccount>> {
+        let config = config.recreate_with_abort();
+        let start_bound = match start_after {
+            Some(start_after) => Bound::Excluded(*start_after),
+            None => Bound::Unbounded,
+        };
+        self.accounts_db.scan_accounts_in_range(
+            ancestors,
+            bank_id,
+            index_key,
+            (start_bound, Bound::Unbounded),
+            |collector: &mut Vec<TransactionAccount>, some_account_tuple| {
+                if collector.len() >= limit {
+                    return;
+                }
+                Self::load_while_filtering(collector, some_account_tuple, |account| {
+                    account.owner() == program_id && filter(account)
+                });
+                if collector.len() >= limit {
+                    // the page is full, so end the scan early
+                    config.abort();
+                }
+            },
+            &config,
+        )
+    }
+
     fn calc_scan_result_size(account: &AccountSharedData) -> usize {
         account.data().len()
             + std::mem::size_of::<AccountSharedData>()
@@ -1488,6 +1551,7 @@ mod tests {
                 status,
                 log_messages: None,
                 inner_instructions: None,
+                instruction_trace: None,
                 durable_nonce_fee: nonce.map(DurableNonceFee::from),
                 return_data: None,
                 executed_units: 0,
@@ -2242,6 +2306,47 @@ mod tests {
         assert_eq!(loaded, vec![]);
     }
 
+    #[test]
+    fn test_load_by_programs_with_filter() {
+        let accounts = Accounts::new_with_config_for_tests(
+            Vec::new(),
+            &ClusterType::Development,
+            AccountSecondaryIndexes::default(),
+            false,
+            AccountShrinkThreshold::default(),
+        );
+
+        // Load accounts owned by various programs into AccountsDb
+        let pubkey0 = solana_sdk::pubkey::new_rand();
+        let account0 = AccountSharedData::new(1, 0, &Pubkey::from([2; 32]));
+        accounts.store_slow_uncached(0, &pubkey0, &account0);
+        let pubkey1 = solana_sdk::pubkey::new_rand();
+        let account1 = AccountSharedData::new(1, 1, &Pubkey::from([2; 32]));
+        accounts.store_slow_uncached(0, &pubkey1, &account1);
+        let pubkey2 = solana_sdk::pubkey::new_rand();
+        let account2 = AccountSharedData::new(1, 0, &Pubkey::from([3; 32]));
+        accounts.store_slow_uncached(0, &pubkey2, &account2);
+        let pubkey3 = solana_sdk::pubkey::new_rand();
+        let account3 = AccountSharedData::new(1, 0, &Pubkey::from([4; 32]));
+        accounts.store_slow_uncached(0, &pubkey3, &account3);
+
+        let ancestors = vec![(0, 0)].into_iter().collect();
+        let program_ids = HashSet::from([Pubkey::from([2; 32]), Pubkey::from([3; 32])]);
+        let mut loaded = accounts
+            .load_by_programs_with_filter(
+                &ancestors,
+                0,
+                &program_ids,
+                |account| account.data().is_empty(),
+                &ScanConfig::default(),
+            )
+            .unwrap();
+        loaded.sort_by_key(|(pubkey, _)| *pubkey);
+        let mut expected = vec![(pubkey0, account0), (pubkey2, account2)];
+        expected.sort_by_key(|(pubkey, _)| *pubkey);
+        assert_eq!(loaded, expected);
+    }
+
     #[test]
     fn test_load_accounts_executable_with_write_lock() {
         let mut accounts: Vec<TransactionAccount> = Vec::new();
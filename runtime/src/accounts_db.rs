@@ -4118,6 +4118,48 @@ impl AccountsDb {
         Ok(collector)
     }
 
+    /// Like `scan_accounts`, but only over the accounts in `range`, in pubkey order. The
+    /// secondary index of `index_key` is used when it includes the key.
+    pub fn scan_accounts_in_range<F, A, R>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: Option<IndexKey>,
+        range: R,
+        scan_func: F,
+        config: &ScanConfig,
+    ) -> ScanResult<A>
+    where
+        F: Fn(&mut A, Option<(&Pubkey, AccountSharedData, Slot)>),
+        A: Default,
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        let index_key = index_key.filter(|index_key| {
+            let key = match index_key {
+                IndexKey::ProgramId(key) => key,
+                IndexKey::SplTokenMint(key) => key,
+                IndexKey::SplTokenOwner(key) => key,
+            };
+            self.account_indexes.include_key(key)
+        });
+        let mut collector = A::default();
+        self.accounts_index.scan_accounts_in_range(
+            ancestors,
+            bank_id,
+            index_key,
+            range,
+            |pubkey, (account_info, slot)| {
+                let account_slot = self
+                    .get_account_accessor(slot, pubkey, &account_info.storage_location())
+                    .get_loaded_account()
+                    .map(|loaded_account| (pubkey, loaded_account.take_account(), slot));
+                scan_func(&mut collector, account_slot)
+            },
+            config,
+        )?;
+        Ok(collector)
+    }
+
     pub fn unchecked_scan_accounts<F, A>(
         &self,
         metric_name: &'static str,
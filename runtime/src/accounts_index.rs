@@ -148,7 +148,8 @@ pub enum ScanError {
 
 enum ScanTypes<R: RangeBounds<Pubkey>> {
     Unindexed(Option<R>),
-    Indexed(IndexKey),
+    /// With a range, the keys of the secondary index are visited in order
+    Indexed(IndexKey, Option<R>),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -942,32 +943,35 @@ impl<T: IndexValue> AccountsIndex<T> {
                 // Pass "" not to log metrics, so RPC doesn't get spammy
                 self.do_scan_accounts(metric_name, ancestors, func, range, Some(max_root), config);
             }
-            ScanTypes::Indexed(IndexKey::ProgramId(program_id)) => {
+            ScanTypes::Indexed(IndexKey::ProgramId(program_id), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.program_id_index,
                     &program_id,
+                    range,
                     Some(max_root),
                     config,
                 );
             }
-            ScanTypes::Indexed(IndexKey::SplTokenMint(mint_key)) => {
+            ScanTypes::Indexed(IndexKey::SplTokenMint(mint_key), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.spl_token_mint_index,
                     &mint_key,
+                    range,
                     Some(max_root),
                     config,
                 );
             }
-            ScanTypes::Indexed(IndexKey::SplTokenOwner(owner_key)) => {
+            ScanTypes::Indexed(IndexKey::SplTokenOwner(owner_key), range) => {
                 self.do_scan_secondary_index(
                     ancestors,
                     func,
                     &self.spl_token_owner_index,
                     &owner_key,
+                    range,
                     Some(max_root),
                     config,
                 );
@@ -1079,8 +1083,10 @@ impl<T: IndexValue> AccountsIndex<T> {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn do_scan_secondary_index<
         F,
+        R,
         SecondaryIndexEntryType: SecondaryIndexEntry + Default + Sync + Send,
     >(
         &self,
@@ -1088,12 +1094,19 @@ impl<T: IndexValue> AccountsIndex<T> {
         mut func: F,
         index: &SecondaryIndex<SecondaryIndexEntryType>,
         index_key: &Pubkey,
+        range: Option<R>,
         max_root: Option<Slot>,
         config: &ScanConfig,
     ) where
         F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey>,
     {
-        for pubkey in index.get(index_key) {
+        let mut pubkeys = index.get(index_key);
+        if let Some(range) = range {
+            pubkeys.retain(|pubkey| range.contains(pubkey));
+            pubkeys.sort_unstable();
+        }
+        for pubkey in pubkeys {
             // Maybe these reads from the AccountsIndex can be batched every time it
             // grabs the read lock as well...
             if let AccountIndexGetResult::Found(list_r, index) =
@@ -1177,6 +1190,35 @@ impl<T: IndexValue> AccountsIndex<T> {
         )
     }
 
+    /// call func with every pubkey in `range` and its index visible from a given set of
+    /// ancestors, in pubkey order. With an `index_key`, only the pubkeys of the secondary index
+    /// are visited.
+    pub(crate) fn scan_accounts_in_range<F, R>(
+        &self,
+        ancestors: &Ancestors,
+        scan_bank_id: BankId,
+        index_key: Option<IndexKey>,
+        range: R,
+        func: F,
+        config: &ScanConfig,
+    ) -> Result<(), ScanError>
+    where
+        F: FnMut(&Pubkey, (&T, Slot)),
+        R: RangeBounds<Pubkey> + std::fmt::Debug,
+    {
+        let scan_type = match index_key {
+            Some(index_key) => ScanTypes::Indexed(index_key, Some(range)),
+            None => ScanTypes::Unindexed(Some(range)),
+        };
+        // The bins of the index are only visited in order when sorted
+        let config = ScanConfig {
+            abort: config.abort.clone(),
+            collect_all_unsorted: false,
+        };
+        // Pass "" not to log metrics, so RPC doesn't get spammy
+        self.do_checked_scan_accounts("", ancestors, scan_bank_id, func, scan_type, &config)
+    }
+
     pub(crate) fn unchecked_scan_accounts<F>(
         &self,
         metric_name: &'static str,
@@ -1230,7 +1272,7 @@ impl<T: IndexValue> AccountsIndex<T> {
             ancestors,
             scan_bank_id,
             func,
-            ScanTypes::<Range<Pubkey>>::Indexed(index_key),
+            ScanTypes::<Range<Pubkey>>::Indexed(index_key, None),
             config,
         )
     }
@@ -140,8 +140,8 @@ use {
             TransactionVerificationMode, VersionedTransaction, MAX_TX_ACCOUNT_LOCKS,
         },
         transaction_context::{
-            ExecutionRecord, InstructionTrace, TransactionAccount, TransactionContext,
-            TransactionReturnData,
+            ExecutionRecord, InstructionContext, InstructionTrace, TransactionAccount,
+            TransactionContext, TransactionReturnData,
         },
     },
     solana_stake_program::stake_state::{
@@ -366,6 +366,7 @@ pub struct TransactionExecutionDetails {
     pub status: Result<()>,
     pub log_messages: Option<Vec<String>>,
     pub inner_instructions: Option<InnerInstructionsList>,
+    pub instruction_trace: Option<TracedInstructionsList>,
     pub durable_nonce_fee: Option<DurableNonceFee>,
     pub return_data: Option<TransactionReturnData>,
     pub executed_units: u64,
@@ -468,6 +469,7 @@ pub struct TransactionSimulationResult {
     pub post_simulation_accounts: Vec<TransactionAccount>,
     pub units_consumed: u64,
     pub return_data: Option<TransactionReturnData>,
+    pub instruction_trace: TracedInstructionsList,
 }
 pub struct TransactionBalancesSet {
     pub pre_balances: TransactionBalances,
@@ -493,6 +495,41 @@ pub type InnerInstructions = Vec<CompiledInstruction>;
 /// a transaction
 pub type InnerInstructionsList = Vec<InnerInstructions>;
 
+/// An instruction invoked during a transaction, with its depth in the call
+/// tree and the compute units consumed by it and the instructions it invoked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TracedInstruction {
+    pub instruction: CompiledInstruction,
+    pub stack_height: usize,
+    pub compute_units_consumed: u64,
+}
+
+/// The instructions invoked during each instruction of a transaction, in
+/// invocation order and starting with the transaction instruction itself
+pub type TracedInstructionsList = Vec<Vec<TracedInstruction>>;
+
+fn compiled_instruction_from_instruction_context(
+    instruction_context: &InstructionContext,
+) -> CompiledInstruction {
+    CompiledInstruction::new_from_raw_parts(
+        instruction_context
+            .get_index_of_program_account_in_transaction(
+                instruction_context
+                    .get_number_of_program_accounts()
+                    .saturating_sub(1),
+            )
+            .unwrap_or_default() as u8,
+        instruction_context.get_instruction_data().to_vec(),
+        (0..instruction_context.get_number_of_instruction_accounts())
+            .map(|instruction_account_index| {
+                instruction_context
+                    .get_index_of_instruction_account_in_transaction(instruction_account_index)
+                    .unwrap_or_default() as u8
+            })
+            .collect(),
+    )
+}
+
 /// Convert from an InstructionTrace to InnerInstructionsList
 pub fn inner_instructions_list_from_instruction_trace(
     instruction_trace: &InstructionTrace,
@@ -503,26 +540,25 @@ pub fn inner_instructions_list_from_instruction_trace(
             inner_instructions_trace
                 .iter()
                 .skip(1)
-                .map(|instruction_context| {
-                    CompiledInstruction::new_from_raw_parts(
-                        instruction_context
-                            .get_index_of_program_account_in_transaction(
-                                instruction_context
-                                    .get_number_of_program_accounts()
-                                    .saturating_sub(1),
-                            )
-                            .unwrap_or_default() as u8,
-                        instruction_context.get_instruction_data().to_vec(),
-                        (0..instruction_context.get_number_of_instruction_accounts())
-                            .map(|instruction_account_index| {
-                                instruction_context
-                                    .get_index_of_instruction_account_in_transaction(
-                                        instruction_account_index,
-                                    )
-                                    .unwrap_or_default() as u8
-                            })
-                            .collect(),
-                    )
+                .map(compiled_instruction_from_instruction_context)
+                .collect()
+        })
+        .collect()
+}
+
+/// Convert from an InstructionTrace to TracedInstructionsList
+pub fn traced_instructions_list_from_instruction_trace(
+    instruction_trace: &InstructionTrace,
+) -> TracedInstructionsList {
+    instruction_trace
+        .iter()
+        .map(|inner_instructions_trace| {
+            inner_instructions_trace
+                .iter()
+                .map(|instruction_context| TracedInstruction {
+                    instruction: compiled_instruction_from_instruction_context(instruction_context),
+                    stack_height: instruction_context.get_stack_height(),
+                    compute_units_consumed: instruction_context.get_compute_units_consumed(),
                 })
                 .collect()
         })
@@ -785,6 +821,7 @@ impl PartialEq for Bank {
             parent_slot,
             hard_forks,
             transaction_count,
+            executed_transaction_count: _,
             transaction_error_count: _,
             transaction_entries_count: _,
             transactions_per_entry_max: _,
@@ -949,6 +986,9 @@ pub struct Bank {
     /// The number of transactions processed without error
     transaction_count: AtomicU64,
 
+    /// The number of transactions executed in this slot, with or without error
+    executed_transaction_count: AtomicU64,
+
     /// The number of transaction errors in this slot
     transaction_error_count: AtomicU64,
 
@@ -1229,6 +1269,7 @@ impl Bank {
             parent_slot: Slot::default(),
             hard_forks: Arc::<RwLock<HardForks>>::default(),
             transaction_count: AtomicU64::default(),
+            executed_transaction_count: AtomicU64::default(),
             transaction_error_count: AtomicU64::default(),
             transaction_entries_count: AtomicU64::default(),
             transactions_per_entry_max: AtomicU64::default(),
@@ -1552,6 +1593,7 @@ impl Bank {
             vote_only_bank,
             inflation: parent.inflation.clone(),
             transaction_count: AtomicU64::new(parent.transaction_count()),
+            executed_transaction_count: AtomicU64::new(0),
             transaction_error_count: AtomicU64::new(0),
             transaction_entries_count: AtomicU64::new(0),
             transactions_per_entry_max: AtomicU64::new(0),
@@ -1898,6 +1940,7 @@ impl Bank {
             parent_slot: fields.parent_slot,
             hard_forks: Arc::new(RwLock::new(fields.hard_forks)),
             transaction_count: AtomicU64::new(fields.transaction_count),
+            executed_transaction_count: new(),
             transaction_error_count: new(),
             transaction_entries_count: new(),
             transactions_per_entry_max: new(),
@@ -3751,15 +3794,99 @@ impl Bank {
         self.simulate_transaction_unchecked(transaction)
     }
 
+    /// Run transactions against a frozen bank without committing the results, with some accounts
+    /// replaced by the given overrides. The instruction trace of the result is only filled in if
+    /// `enable_cpi_recording` is set.
+    pub fn simulate_transaction_with_account_overrides(
+        &self,
+        transaction: SanitizedTransaction,
+        mut account_overrides: AccountOverrides,
+        enable_cpi_recording: bool,
+    ) -> TransactionSimulationResult {
+        assert!(self.is_frozen(), "simulation bank must be frozen");
+
+        self.simulate_transaction_with_account_overrides_unchecked(
+            transaction,
+            &mut account_overrides,
+            enable_cpi_recording,
+        )
+    }
+
+    /// Run a sequence of transactions against a frozen bank without committing the results. Each
+    /// transaction sees the accounts written by the ones before it, which are carried forward as
+    /// overrides so that nothing reaches the accounts cache or the status cache. A transaction
+    /// repeated within the bundle fails as already processed. The simulation stops at the first
+    /// transaction that fails, so fewer results than transactions may be returned.
+    pub fn simulate_bundle(
+        &self,
+        transactions: Vec<SanitizedTransaction>,
+        mut account_overrides: AccountOverrides,
+        enable_cpi_recording: bool,
+    ) -> Vec<TransactionSimulationResult> {
+        assert!(self.is_frozen(), "simulation bank must be frozen");
+
+        let mut message_hashes = HashSet::with_capacity(transactions.len());
+        let mut results = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            if !message_hashes.insert(*transaction.message_hash()) {
+                results.push(TransactionSimulationResult {
+                    result: Err(TransactionError::AlreadyProcessed),
+                    logs: vec![],
+                    post_simulation_accounts: vec![],
+                    units_consumed: 0,
+                    return_data: None,
+                    instruction_trace: vec![],
+                });
+                break;
+            }
+            let writable_accounts = (0..transaction.message().account_keys().len())
+                .map(|index| transaction.message().is_writable(index))
+                .collect::<Vec<_>>();
+            let result = self.simulate_transaction_with_account_overrides_unchecked(
+                transaction,
+                &mut account_overrides,
+                enable_cpi_recording,
+            );
+            if result.result.is_err() {
+                results.push(result);
+                break;
+            }
+            for ((pubkey, account), is_writable) in result
+                .post_simulation_accounts
+                .iter()
+                .zip(writable_accounts)
+            {
+                if is_writable {
+                    account_overrides.set_account(pubkey, Some(account.clone()));
+                }
+            }
+            results.push(result);
+        }
+        results
+    }
+
     /// Run transactions against a bank without committing the results; does not check if the bank
     /// is frozen, enabling use in single-Bank test frameworks
     pub fn simulate_transaction_unchecked(
         &self,
         transaction: SanitizedTransaction,
+    ) -> TransactionSimulationResult {
+        self.simulate_transaction_with_account_overrides_unchecked(
+            transaction,
+            &mut AccountOverrides::default(),
+            false,
+        )
+    }
+
+    fn simulate_transaction_with_account_overrides_unchecked(
+        &self,
+        transaction: SanitizedTransaction,
+        account_overrides: &mut AccountOverrides,
+        enable_cpi_recording: bool,
     ) -> TransactionSimulationResult {
         let account_keys = transaction.message().account_keys();
         let number_of_accounts = account_keys.len();
-        let account_overrides = self.get_account_overrides_for_simulation(&account_keys);
+        self.get_account_overrides_for_simulation(&account_keys, account_overrides);
         let batch = self.prepare_simulation_batch(transaction);
         let mut timings = ExecuteTimings::default();
 
@@ -3773,11 +3900,11 @@ impl Bank {
             // for processing. During forwarding, the transaction could expire if the
             // delay is not accounted for.
             MAX_PROCESSING_AGE - MAX_TRANSACTION_FORWARDING_DELAY,
-            false,
+            enable_cpi_recording,
             true,
             true,
             &mut timings,
-            Some(&account_overrides),
+            Some(account_overrides),
             None,
         );
 
@@ -3808,13 +3935,16 @@ impl Bank {
 
         let execution_result = execution_results.pop().unwrap();
         let flattened_result = execution_result.flattened_result();
-        let (logs, return_data) = match execution_result {
-            TransactionExecutionResult::Executed { details, .. } => {
-                (details.log_messages, details.return_data)
-            }
-            TransactionExecutionResult::NotExecuted(_) => (None, None),
+        let (logs, return_data, instruction_trace) = match execution_result {
+            TransactionExecutionResult::Executed { details, .. } => (
+                details.log_messages,
+                details.return_data,
+                details.instruction_trace,
+            ),
+            TransactionExecutionResult::NotExecuted(_) => (None, None, None),
         };
         let logs = logs.unwrap_or_default();
+        let instruction_trace = instruction_trace.unwrap_or_default();
 
         TransactionSimulationResult {
             result: flattened_result,
@@ -3822,13 +3952,19 @@ impl Bank {
             post_simulation_accounts,
             units_consumed,
             return_data,
+            instruction_trace,
         }
     }
 
-    fn get_account_overrides_for_simulation(&self, account_keys: &AccountKeys) -> AccountOverrides {
-        let mut account_overrides = AccountOverrides::default();
+    fn get_account_overrides_for_simulation(
+        &self,
+        account_keys: &AccountKeys,
+        account_overrides: &mut AccountOverrides,
+    ) {
         let slot_history_id = sysvar::slot_history::id();
-        if account_keys.iter().any(|pubkey| *pubkey == slot_history_id) {
+        if account_overrides.get(&slot_history_id).is_none()
+            && account_keys.iter().any(|pubkey| *pubkey == slot_history_id)
+        {
             let current_account = self.get_account_with_fixed_root(&slot_history_id);
             let slot_history = current_account
                 .as_ref()
@@ -3843,7 +3979,6 @@ impl Bank {
                 }
             }
         }
-        account_overrides
     }
 
     pub fn unlock_accounts(&self, batch: &mut TransactionBatch) {
@@ -4220,12 +4355,17 @@ impl Bank {
             accounts_data_len_delta = status.as_ref().map_or(0, |_| accounts_resize_delta);
         }
 
-        let inner_instructions = if enable_cpi_recording {
-            Some(inner_instructions_list_from_instruction_trace(
-                &instruction_trace,
-            ))
+        let (inner_instructions, instruction_trace) = if enable_cpi_recording {
+            (
+                Some(inner_instructions_list_from_instruction_trace(
+                    &instruction_trace,
+                )),
+                Some(traced_instructions_list_from_instruction_trace(
+                    &instruction_trace,
+                )),
+            )
         } else {
-            None
+            (None, None)
         };
 
         let return_data = if enable_return_data_recording {
@@ -4245,6 +4385,7 @@ impl Bank {
                 status,
                 log_messages,
                 inner_instructions,
+                instruction_trace,
                 durable_nonce_fee,
                 return_data,
                 executed_units,
@@ -4795,6 +4936,8 @@ impl Bank {
         };
 
         self.increment_transaction_count(tx_count);
+        self.executed_transaction_count
+            .fetch_add(committed_transactions_count, Relaxed);
         self.increment_signature_count(signature_count);
 
         inc_new_counter_info!(
@@ -6461,6 +6604,44 @@ impl Bank {
         )
     }
 
+    /// Returns up to `limit` accounts of the program after `start_after`, in pubkey order,
+    /// without scanning the accounts past the last one returned
+    pub fn get_filtered_program_accounts_page<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        program_id: &Pubkey,
+        index_key: Option<IndexKey>,
+        filter: F,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+        config: &ScanConfig,
+    ) -> ScanResult<Vec<TransactionAccount>> {
+        self.rc.accounts.load_page_by_program_with_filter(
+            &self.ancestors,
+            self.bank_id,
+            program_id,
+            index_key,
+            filter,
+            start_after,
+            limit,
+            config,
+        )
+    }
+
+    pub fn get_filtered_multiple_program_accounts<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        program_ids: &HashSet<Pubkey>,
+        filter: F,
+        config: &ScanConfig,
+    ) -> ScanResult<Vec<TransactionAccount>> {
+        self.rc.accounts.load_by_programs_with_filter(
+            &self.ancestors,
+            self.bank_id,
+            program_ids,
+            filter,
+            config,
+        )
+    }
+
     pub fn get_filtered_indexed_accounts<F: Fn(&AccountSharedData) -> bool>(
         &self,
         index_key: &IndexKey,
@@ -6542,6 +6723,12 @@ impl Bank {
         self.transaction_count.load(Relaxed)
     }
 
+    /// Returns the number of transactions executed in this slot, including
+    /// the ones that failed
+    pub fn executed_transaction_count(&self) -> u64 {
+        self.executed_transaction_count.load(Relaxed)
+    }
+
     pub fn transaction_error_count(&self) -> u64 {
         self.transaction_error_count.load(Relaxed)
     }
@@ -7911,6 +8098,7 @@ pub(crate) mod tests {
                 status,
                 log_messages: None,
                 inner_instructions: None,
+                instruction_trace: None,
                 durable_nonce_fee: nonce.map(DurableNonceFee::from),
                 return_data: None,
                 executed_units: 0,
@@ -10348,6 +10536,33 @@ pub(crate) mod tests {
         assert_eq!(bank.get_signature_status(&t2.signatures[0]), None);
     }
 
+    #[test]
+    fn test_executed_transaction_count() {
+        let (genesis_config, mint_keypair) = create_genesis_config(sol_to_lamports(1.));
+        let key1 = solana_sdk::pubkey::new_rand();
+        let key2 = solana_sdk::pubkey::new_rand();
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let amount = genesis_config.rent.minimum_balance(0);
+
+        let t1 = system_transaction::transfer(&mint_keypair, &key1, amount, genesis_config.hash());
+        assert_eq!(bank.process_transaction(&t1), Ok(()));
+        let t2 = system_transaction::transfer(
+            &mint_keypair,
+            &key2,
+            sol_to_lamports(2.),
+            genesis_config.hash(),
+        );
+        assert!(matches!(
+            bank.process_transaction(&t2),
+            Err(TransactionError::InstructionError(_, _))
+        ));
+        assert_eq!(bank.executed_transaction_count(), 2);
+        assert_eq!(bank.transaction_error_count(), 1);
+
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+        assert_eq!(bank.executed_transaction_count(), 0);
+    }
+
     #[test]
     fn test_one_tx_two_out_atomic_fail() {
         let amount = sol_to_lamports(1.);
@@ -12477,6 +12692,69 @@ pub(crate) mod tests {
         assert_eq!(indexed_accounts[0], (address, new_account));
     }
 
+    #[test]
+    fn test_get_filtered_program_accounts_page() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(500);
+        let mut account_indexes = AccountSecondaryIndexes::default();
+        account_indexes.indexes.insert(AccountIndex::ProgramId);
+        let bank = Arc::new(Bank::new_with_config_for_tests(
+            &genesis_config,
+            account_indexes,
+            false,
+            AccountShrinkThreshold::default(),
+        ));
+
+        let program_id = Pubkey::new_unique();
+        let mut addresses: Vec<_> = (0..5).map(|_| solana_sdk::pubkey::new_rand()).collect();
+        for (lamports, address) in addresses.iter().enumerate() {
+            bank.store_account(
+                address,
+                &AccountSharedData::new(lamports as u64 + 1, 0, &program_id),
+            );
+        }
+        bank.store_account(
+            &solana_sdk::pubkey::new_rand(),
+            &AccountSharedData::new(1, 0, &Pubkey::new_unique()),
+        );
+        addresses.sort();
+
+        for index_key in [None, Some(IndexKey::ProgramId(program_id))] {
+            let page = |start_after: Option<&Pubkey>, limit| {
+                bank.get_filtered_program_accounts_page(
+                    &program_id,
+                    index_key,
+                    |_| true,
+                    start_after,
+                    limit,
+                    &ScanConfig::default(),
+                )
+                .unwrap()
+                .into_iter()
+                .map(|(address, _)| address)
+                .collect::<Vec<_>>()
+            };
+            assert_eq!(page(None, 2), addresses[..2]);
+            assert_eq!(page(Some(&addresses[1]), 2), addresses[2..4]);
+            assert_eq!(page(Some(&addresses[3]), 2), addresses[4..]);
+            assert_eq!(page(Some(&addresses[4]), 2), vec![]);
+            assert_eq!(page(None, usize::MAX), addresses);
+        }
+
+        // The filter applies before the limit
+        let accounts = bank
+            .get_filtered_program_accounts_page(
+                &program_id,
+                None,
+                |account| account.lamports() % 2 == 0,
+                None,
+                1,
+                &ScanConfig::default(),
+            )
+            .unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].1.lamports() % 2, 0);
+    }
+
     #[test]
     fn test_status_cache_ancestors() {
         solana_logger::setup();
@@ -18569,6 +18847,41 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn test_traced_instructions_list_from_instruction_trace() {
+        let instruction_trace = vec![
+            vec![
+                InstructionContext::new(0, 0, &[], &[], &[1]),
+                InstructionContext::new(1, 0, &[], &[], &[2]),
+            ],
+            vec![
+                InstructionContext::new(0, 0, &[], &[], &[3]),
+                InstructionContext::new(1, 0, &[], &[], &[4]),
+                InstructionContext::new(2, 0, &[], &[], &[5]),
+            ],
+        ];
+
+        let traced_instructions =
+            traced_instructions_list_from_instruction_trace(&instruction_trace);
+
+        let traced_instruction = |data, stack_height| TracedInstruction {
+            instruction: CompiledInstruction::new_from_raw_parts(0, vec![data], vec![]),
+            stack_height,
+            compute_units_consumed: 0,
+        };
+        assert_eq!(
+            traced_instructions,
+            vec![
+                vec![traced_instruction(1, 1), traced_instruction(2, 2)],
+                vec![
+                    traced_instruction(3, 1),
+                    traced_instruction(4, 2),
+                    traced_instruction(5, 3)
+                ]
+            ]
+        );
+    }
+
     #[derive(Serialize, Deserialize)]
     enum MockReallocInstruction {
         Realloc(usize, u64, Pubkey),
@@ -19242,4 +19555,73 @@ pub(crate) mod tests {
             signature_fee
         );
     }
+
+    #[test]
+    fn test_simulate_bundle() {
+        let (genesis_config, mint_keypair) = create_genesis_config(sol_to_lamports(1.));
+        let bank = Bank::new_for_tests(&genesis_config);
+        bank.freeze();
+        let key1 = Keypair::new();
+        let key2 = Pubkey::new_unique();
+        let blockhash = bank.last_blockhash();
+
+        let fund_key1 =
+            system_transaction::transfer(&mint_keypair, &key1.pubkey(), 10_000_000, blockhash);
+        let transactions = vec![
+            fund_key1.clone(),
+            system_transaction::transfer(&key1, &key2, 2_000_000, blockhash),
+            system_transaction::transfer(&key1, &key2, 20_000_000, blockhash),
+            system_transaction::transfer(&mint_keypair, &key2, 1_000_000, blockhash),
+        ]
+        .into_iter()
+        .map(SanitizedTransaction::from_transaction_for_tests)
+        .collect();
+
+        // The second transfer spends lamports the first one credited, the third one fails and the
+        // fourth one is never run
+        let results = bank.simulate_bundle(transactions, AccountOverrides::default(), false);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].result, Ok(()));
+        assert_eq!(results[1].result, Ok(()));
+        assert!(results[1]
+            .post_simulation_accounts
+            .iter()
+            .any(|(pubkey, account)| *pubkey == key2 && account.lamports() == 2_000_000));
+        assert_eq!(
+            results[2].result,
+            Err(TransactionError::InstructionError(
+                0,
+                SystemError::ResultWithNegativeLamports.into()
+            ))
+        );
+
+        // Nothing was committed to the bank
+        assert_eq!(bank.get_balance(&key1.pubkey()), 0);
+        assert_eq!(bank.get_balance(&key2), 0);
+        assert_eq!(bank.transaction_count(), 0);
+        assert_eq!(bank.get_signature_status(&fund_key1.signatures[0]), None);
+
+        // A transaction repeated within the bundle is rejected as it would be once landed
+        let transactions = vec![fund_key1.clone(), fund_key1]
+            .into_iter()
+            .map(SanitizedTransaction::from_transaction_for_tests)
+            .collect();
+        let results = bank.simulate_bundle(transactions, AccountOverrides::default(), false);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].result, Ok(()));
+        assert_eq!(results[1].result, Err(TransactionError::AlreadyProcessed));
+
+        // Overridden accounts are seen by the bundle
+        let mut account_overrides = AccountOverrides::default();
+        account_overrides.set_account(
+            &key1.pubkey(),
+            Some(AccountSharedData::new(5_000_000, 0, &system_program::id())),
+        );
+        let transactions = vec![SanitizedTransaction::from_transaction_for_tests(
+            system_transaction::transfer(&key1, &key2, 2_000_000, blockhash),
+        )];
+        let results = bank.simulate_bundle(transactions, account_overrides, false);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result, Ok(()));
+    }
 }
@@ -128,13 +128,17 @@ pub fn write_keypair_file<F: AsRef<Path>>(
     keypair: &Keypair,
     outfile: F,
 ) -> Result<String, Box<dyn error::Error>> {
-    let outfile = outfile.as_ref();
+    let mut f = create_private_file(outfile.as_ref())?;
+    write_keypair(keypair, &mut f)
+}
 
+/// Creates a file for secrets, which only its owner can read on unix
+pub(crate) fn create_private_file(outfile: &Path) -> std::io::Result<File> {
     if let Some(outdir) = outfile.parent() {
         fs::create_dir_all(outdir)?;
     }
 
-    let mut f = {
+    {
         #[cfg(not(unix))]
         {
             OpenOptions::new()
@@ -148,9 +152,7 @@ pub fn write_keypair_file<F: AsRef<Path>>(
     .write(true)
     .truncate(true)
     .create(true)
-    .open(outfile)?;
-
-    write_keypair(keypair, &mut f)
+    .open(outfile)
 }
 
 /// Constructs a `Keypair` from caller-provided seed entropy
@@ -0,0 +1,399 @@
+//! Passphrase-encrypted keypair files.
+//!
+//! A keystore is a versioned JSON envelope holding a [`Keypair`] encrypted
+//! with AES-256-GCM-SIV, under a key derived from a passphrase with scrypt.
+//! The pubkey of the keypair is stored in the clear, so that it can be read
+//! without the passphrase, and is authenticated as associated data.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "pubkey": "<BASE58_PUBKEY>",
+//!   "kdf": { "name": "scrypt", "logN": 15, "r": 8, "p": 1, "salt": "<BASE64>" },
+//!   "cipher": { "name": "aes-256-gcm-siv", "nonce": "<BASE64>" },
+//!   "ciphertext": "<BASE64>"
+//! }
+//! ```
+#![cfg(feature = "keystore")]
+
+use {
+    crate::{
+        pubkey::Pubkey,
+        signer::{
+            keypair::{create_private_file, Keypair},
+            Signer,
+        },
+    },
+    aes_gcm_siv::{
+        aead::{Aead, NewAead, Payload},
+        Aes256GcmSiv, Key,
+    },
+    rand::{rngs::OsRng, RngCore},
+    serde_derive::{Deserialize, Serialize},
+    std::{
+        error,
+        fs::{self, File},
+        io::{Read, Write},
+        path::Path,
+        str::FromStr,
+    },
+    thiserror::Error,
+    zeroize::Zeroizing,
+};
+
+/// The keystore format version written by this module
+pub const KEYSTORE_VERSION: u8 = 1;
+
+/// Environment variable supplying the passphrase of keystore files, instead of a prompt
+pub const KEYSTORE_PASSPHRASE_ENV: &str = "RENEC_KEYSTORE_PASSPHRASE";
+
+/// Limits of the scrypt parameters of a keystore, so that a crafted keystore can't make
+/// unlocking it take gigabytes of memory or hours of computation
+pub const MAX_SCRYPT_LOG_N: u8 = 20;
+pub const MAX_SCRYPT_R: u32 = 32;
+pub const MAX_SCRYPT_P: u32 = 16;
+/// Memory of the largest scrypt parameters allowed, 128 * r * 2^log_n bytes
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum KeystoreError {
+    #[error("unsupported keystore version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("invalid keystore: {0}")]
+    Invalid(String),
+
+    #[error("incorrect passphrase, or corrupted keystore")]
+    Decryption,
+
+    #[error("empty passphrase")]
+    EmptyPassphrase,
+}
+
+/// scrypt cost parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl ScryptParams {
+    fn check(&self) -> Result<(), KeystoreError> {
+        if self.log_n > MAX_SCRYPT_LOG_N
+            || self.r > MAX_SCRYPT_R
+            || self.p > MAX_SCRYPT_P
+            || 128 * u64::from(self.r) << self.log_n > MAX_SCRYPT_MEMORY
+        {
+            return Err(KeystoreError::Invalid(format!(
+                "scrypt parameters logN {}, r {}, p {} exceed the limits",
+                self.log_n, self.r, self.p
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ScryptParams {
+    fn default() -> Self {
+        Self {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "name")]
+pub enum KeystoreKdf {
+    #[serde(rename = "scrypt", rename_all = "camelCase")]
+    Scrypt {
+        log_n: u8,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "name")]
+pub enum KeystoreCipher {
+    #[serde(rename = "aes-256-gcm-siv")]
+    Aes256GcmSiv { nonce: String },
+}
+
+/// An encrypted [`Keypair`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version: u8,
+    pub pubkey: String,
+    pub kdf: KeystoreKdf,
+    pub cipher: KeystoreCipher,
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    /// Encrypts `keypair` under `passphrase`, with the default scrypt parameters
+    pub fn encrypt(keypair: &Keypair, passphrase: &str) -> Result<Self, KeystoreError> {
+        Self::encrypt_with_params(keypair, passphrase, ScryptParams::default())
+    }
+
+    pub fn encrypt_with_params(
+        keypair: &Keypair,
+        passphrase: &str,
+        params: ScryptParams,
+    ) -> Result<Self, KeystoreError> {
+        if passphrase.is_empty() {
+            return Err(KeystoreError::EmptyPassphrase);
+        }
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let key = derive_key(passphrase, &salt, params)?;
+        let pubkey = keypair.pubkey();
+        let ciphertext = Aes256GcmSiv::new(Key::from_slice(key.as_ref()))
+            .encrypt(
+                &nonce.into(),
+                Payload {
+                    msg: Zeroizing::new(keypair.to_bytes()).as_ref(),
+                    aad: pubkey.as_ref(),
+                },
+            )
+            .map_err(|_| KeystoreError::Invalid("encryption failed".to_string()))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            pubkey: pubkey.to_string(),
+            kdf: KeystoreKdf::Scrypt {
+                log_n: params.log_n,
+                r: params.r,
+                p: params.p,
+                salt: base64::encode(salt),
+            },
+            cipher: KeystoreCipher::Aes256GcmSiv {
+                nonce: base64::encode(nonce),
+            },
+            ciphertext: base64::encode(ciphertext),
+        })
+    }
+
+    /// The pubkey of the encrypted keypair, which is readable without the passphrase
+    pub fn pubkey(&self) -> Result<Pubkey, KeystoreError> {
+        Pubkey::from_str(&self.pubkey)
+            .map_err(|err| KeystoreError::Invalid(format!("pubkey: {}", err)))
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<Keypair, KeystoreError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(self.version));
+        }
+        let pubkey = self.pubkey()?;
+        let key = match &self.kdf {
+            KeystoreKdf::Scrypt { log_n, r, p, salt } => derive_key(
+                passphrase,
+                &decode_base64("salt", salt)?,
+                ScryptParams {
+                    log_n: *log_n,
+                    r: *r,
+                    p: *p,
+                },
+            )?,
+        };
+        let plaintext = match &self.cipher {
+            KeystoreCipher::Aes256GcmSiv { nonce } => {
+                let nonce = decode_base64("nonce", nonce)?;
+                if nonce.len() != NONCE_LEN {
+                    return Err(KeystoreError::Invalid("nonce length".to_string()));
+                }
+                Aes256GcmSiv::new(Key::from_slice(key.as_ref()))
+                    .decrypt(
+                        aes_gcm_siv::Nonce::from_slice(&nonce),
+                        Payload {
+                            msg: &decode_base64("ciphertext", &self.ciphertext)?,
+                            aad: pubkey.as_ref(),
+                        },
+                    )
+                    .map(Zeroizing::new)
+                    .map_err(|_| KeystoreError::Decryption)?
+            }
+        };
+
+        let keypair = Keypair::from_bytes(&plaintext)
+            .map_err(|err| KeystoreError::Invalid(format!("keypair: {}", err)))?;
+        if keypair.pubkey() != pubkey {
+            return Err(KeystoreError::Invalid(format!(
+                "keypair {} does not match pubkey {}",
+                keypair.pubkey(),
+                pubkey
+            )));
+        }
+        Ok(keypair)
+    }
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: ScryptParams,
+) -> Result<Zeroizing<[u8; KEY_LEN]>, KeystoreError> {
+    params.check()?;
+    let params = scrypt::Params::new(params.log_n, params.r, params.p)
+        .map_err(|err| KeystoreError::Invalid(format!("scrypt parameters: {}", err)))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, key.as_mut())
+        .map_err(|err| KeystoreError::Invalid(format!("scrypt: {}", err)))?;
+    Ok(key)
+}
+
+fn decode_base64(field: &str, value: &str) -> Result<Vec<u8>, KeystoreError> {
+    base64::decode(value).map_err(|err| KeystoreError::Invalid(format!("{}: {}", field, err)))
+}
+
+/// Reads a JSON-encoded `Keystore` from a `Reader` implementor
+pub fn read_keystore<R: Read>(reader: &mut R) -> Result<Keystore, Box<dyn error::Error>> {
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Reads a `Keystore` from a file
+pub fn read_keystore_file<F: AsRef<Path>>(path: F) -> Result<Keystore, Box<dyn error::Error>> {
+    let mut file = File::open(path.as_ref())?;
+    read_keystore(&mut file)
+}
+
+/// Writes a `Keystore` to a `Write` implementor with JSON-encoding
+pub fn write_keystore<W: Write>(
+    keystore: &Keystore,
+    writer: &mut W,
+) -> Result<String, Box<dyn error::Error>> {
+    let serialized = serde_json::to_string_pretty(keystore)?;
+    writer.write_all(serialized.as_bytes())?;
+    Ok(serialized)
+}
+
+/// Writes a `Keystore` to a file with JSON-encoding
+pub fn write_keystore_file<F: AsRef<Path>>(
+    keystore: &Keystore,
+    outfile: F,
+) -> Result<String, Box<dyn error::Error>> {
+    let mut f = create_private_file(outfile.as_ref())?;
+    write_keystore(keystore, &mut f)
+}
+
+/// Whether the file at `path` is a keystore, rather than a plain keypair file
+///
+/// Keypair files hold a JSON array, and keystores a JSON object.
+pub fn is_keystore_file<F: AsRef<Path>>(path: F) -> bool {
+    fs::read(path.as_ref())
+        .map(|contents| {
+            contents
+                .iter()
+                .find(|byte| !byte.is_ascii_whitespace())
+                .map(|byte| *byte == b'{')
+                .unwrap_or(false)
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::signature::{read_keypair_file, write_keypair_file},
+    };
+
+    // Cheap parameters, as the defaults are slow in unoptimized builds
+    const TEST_PARAMS: ScryptParams = ScryptParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    fn tmp_file_path(name: &str) -> String {
+        let out_dir = std::env::var("FARF_DIR").unwrap_or_else(|_| "farf".to_string());
+        format!("{}/tmp/{}-{}", out_dir, name, Keypair::new().pubkey())
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let keypair = Keypair::new();
+        let keystore = Keystore::encrypt_with_params(&keypair, "hunter2", TEST_PARAMS).unwrap();
+        assert_eq!(keystore.pubkey().unwrap(), keypair.pubkey());
+        assert_eq!(
+            keystore.decrypt("hunter2").unwrap().to_bytes(),
+            keypair.to_bytes()
+        );
+        assert_eq!(
+            keystore.decrypt("hunter3").unwrap_err(),
+            KeystoreError::Decryption
+        );
+        assert_eq!(
+            Keystore::encrypt_with_params(&keypair, "", TEST_PARAMS).unwrap_err(),
+            KeystoreError::EmptyPassphrase
+        );
+
+        // The pubkey is authenticated
+        let mut forged = keystore.clone();
+        forged.pubkey = Keypair::new().pubkey().to_string();
+        assert_eq!(
+            forged.decrypt("hunter2").unwrap_err(),
+            KeystoreError::Decryption
+        );
+
+        // Unlocking with excessive scrypt parameters fails before deriving the key
+        for (log_n, r, p) in [(21, 8, 1), (20, 16, 1), (4, 33, 1), (4, 8, 17)] {
+            let mut excessive = keystore.clone();
+            excessive.kdf = match excessive.kdf {
+                KeystoreKdf::Scrypt { salt, .. } => KeystoreKdf::Scrypt { log_n, r, p, salt },
+            };
+            assert!(matches!(
+                excessive.decrypt("hunter2").unwrap_err(),
+                KeystoreError::Invalid(err) if err.contains("exceed the limits")
+            ));
+        }
+        assert!(ScryptParams {
+            log_n: MAX_SCRYPT_LOG_N,
+            r: 8,
+            p: MAX_SCRYPT_P
+        }
+        .check()
+        .is_ok());
+
+        let mut unsupported = keystore;
+        unsupported.version = 2;
+        assert_eq!(
+            unsupported.decrypt("hunter2").unwrap_err(),
+            KeystoreError::UnsupportedVersion(2)
+        );
+    }
+
+    #[test]
+    fn test_keystore_file() {
+        let keypair = Keypair::new();
+        let keystore = Keystore::encrypt_with_params(&keypair, "hunter2", TEST_PARAMS).unwrap();
+        let keystore_path = tmp_file_path("test_keystore_file");
+        let serialized = write_keystore_file(&keystore, &keystore_path).unwrap();
+        assert!(serialized.contains("\"name\": \"scrypt\""));
+        assert!(serialized.contains("\"logN\": 4"));
+        assert!(serialized.contains("\"name\": \"aes-256-gcm-siv\""));
+        assert!(is_keystore_file(&keystore_path));
+        assert_eq!(read_keystore_file(&keystore_path).unwrap(), keystore);
+        assert!(read_keypair_file(&keystore_path).is_err());
+
+        let keypair_path = tmp_file_path("test_keystore_file_keypair");
+        write_keypair_file(&keypair, &keypair_path).unwrap();
+        assert!(!is_keystore_file(&keypair_path));
+        assert!(!is_keystore_file(tmp_file_path(
+            "test_keystore_file_missing"
+        )));
+
+        fs::remove_file(&keystore_path).unwrap();
+        fs::remove_file(&keypair_path).unwrap();
+    }
+}
@@ -11,6 +11,7 @@ use {
 };
 
 pub mod keypair;
+pub mod keystore;
 pub mod null_signer;
 pub mod presigner;
 pub mod signers;
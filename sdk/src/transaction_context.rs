@@ -204,6 +204,7 @@ impl TransactionContext {
                 program_accounts: program_accounts.to_vec(),
                 instruction_accounts: instruction_accounts.to_vec(),
                 instruction_data: instruction_data.to_vec(),
+                compute_units_consumed: 0,
             };
             self.instruction_trace.push(vec![instruction_context]);
             self.instruction_trace.len().saturating_sub(1)
@@ -229,6 +230,7 @@ impl TransactionContext {
                     program_accounts: program_accounts.to_vec(),
                     instruction_accounts: instruction_accounts.to_vec(),
                     instruction_data: instruction_data.to_vec(),
+                    compute_units_consumed: 0,
                 };
                 instruction_trace.push(instruction_context);
                 instruction_trace.len().saturating_sub(1)
@@ -278,6 +280,30 @@ impl TransactionContext {
         }
     }
 
+    /// Records the compute units consumed by the current InstructionContext,
+    /// including the ones consumed by the instructions it invoked
+    ///
+    /// This is only used to trace the execution, so it never fails: nothing is
+    /// recorded if there is no current InstructionContext.
+    pub fn set_current_instruction_compute_units_consumed(&mut self, compute_units_consumed: u64) {
+        let top_level_index = match self.instruction_stack.first() {
+            Some(top_level_index) => *top_level_index,
+            None => return,
+        };
+        let cpi_index = if self.instruction_stack.len() == 1 {
+            0
+        } else {
+            self.instruction_stack.last().copied().unwrap_or_default()
+        };
+        if let Some(instruction_context) = self
+            .instruction_trace
+            .get_mut(top_level_index)
+            .and_then(|instruction_trace| instruction_trace.get_mut(cpi_index))
+        {
+            instruction_context.compute_units_consumed = compute_units_consumed;
+        }
+    }
+
     /// Gets the return data of the current InstructionContext or any above
     pub fn get_return_data(&self) -> (&Pubkey, &[u8]) {
         (&self.return_data.program_id, &self.return_data.data)
@@ -353,6 +379,7 @@ pub struct InstructionContext {
     program_accounts: Vec<usize>,
     instruction_accounts: Vec<InstructionAccount>,
     instruction_data: Vec<u8>,
+    compute_units_consumed: u64,
 }
 
 impl InstructionContext {
@@ -370,6 +397,7 @@ impl InstructionContext {
             program_accounts: program_accounts.to_vec(),
             instruction_accounts: instruction_accounts.to_vec(),
             instruction_data: instruction_data.to_vec(),
+            compute_units_consumed: 0,
         }
     }
 
@@ -380,6 +408,11 @@ impl InstructionContext {
         self.nesting_level.saturating_add(1)
     }
 
+    /// Compute units consumed by this Instruction and the ones it invoked
+    pub fn get_compute_units_consumed(&self) -> u64 {
+        self.compute_units_consumed
+    }
+
     /// Number of program accounts
     pub fn get_number_of_program_accounts(&self) -> usize {
         self.program_accounts.len()
@@ -3,10 +3,11 @@
 use {
     crate::{
         access_token::{AccessToken, Scope},
-        compression::{compress_best, decompress},
+        backend::Backend,
+        compression::decompress,
         root_ca_certificate, CredentialType,
     },
-    backoff::{future::retry, ExponentialBackoff},
+    async_trait::async_trait,
     log::*,
     std::{
         str::FromStr,
@@ -81,6 +82,9 @@ pub enum Error {
 
     #[error("Timeout")]
     Timeout,
+
+    #[error("RocksDB: {0}")]
+    RocksDb(rocksdb::Error),
 }
 
 impl std::convert::From<std::io::Error> for Error {
@@ -89,6 +93,12 @@ impl std::convert::From<std::io::Error> for Error {
     }
 }
 
+impl std::convert::From<rocksdb::Error> for Error {
+    fn from(err: rocksdb::Error) -> Self {
+        Self::RocksDb(err)
+    }
+}
+
 impl std::convert::From<tonic::transport::Error> for Error {
     fn from(err: tonic::transport::Error) -> Self {
         Self::Transport(err)
@@ -245,58 +255,59 @@ impl BigTableConnection {
             timeout: self.timeout,
         }
     }
+}
 
-    pub async fn put_bincode_cells_with_retry<T>(
+#[async_trait]
+impl Backend for BigTableConnection {
+    async fn get_row_keys(
         &self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: serde::ser::Serialize,
-    {
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.put_bincode_cells(table, cells).await?)
-        })
-        .await
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>> {
+        self.client()
+            .get_row_keys(table_name, start_at, end_at, rows_limit)
+            .await
     }
 
-    pub async fn delete_rows_with_retry(&self, table: &str, row_keys: &[RowKey]) -> Result<()> {
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.delete_rows(table, row_keys).await?)
-        })
-        .await
+    async fn get_row_data(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        self.client()
+            .get_row_data(table_name, start_at, end_at, rows_limit)
+            .await
     }
 
-    pub async fn get_bincode_cells_with_retry<T>(
+    async fn get_multi_row_data(
         &self,
-        table: &str,
+        table_name: &str,
         row_keys: &[RowKey],
-    ) -> Result<Vec<(RowKey, Result<T>)>>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.get_bincode_cells(table, row_keys).await?)
-        })
-        .await
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        self.client().get_multi_row_data(table_name, row_keys).await
     }
 
-    pub async fn put_protobuf_cells_with_retry<T>(
+    async fn get_single_row_data(&self, table_name: &str, row_key: RowKey) -> Result<RowData> {
+        self.client().get_single_row_data(table_name, row_key).await
+    }
+
+    async fn delete_rows(&self, table_name: &str, row_keys: &[RowKey]) -> Result<()> {
+        self.client().delete_rows(table_name, row_keys).await
+    }
+
+    async fn put_row_data(
         &self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: prost::Message,
-    {
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.put_protobuf_cells(table, cells).await?)
-        })
-        .await
+        table_name: &str,
+        family_name: &str,
+        row_data: &[(&RowKey, RowData)],
+    ) -> Result<()> {
+        self.client()
+            .put_row_data(table_name, family_name, row_data)
+            .await
     }
 }
 
@@ -670,116 +681,6 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
 
         Ok(())
     }
-
-    pub async fn get_bincode_cell<T>(&mut self, table: &str, key: RowKey) -> Result<T>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_bincode_cell_data(&row_data, table, key.to_string())
-    }
-
-    pub async fn get_bincode_cells<T>(
-        &mut self,
-        table: &str,
-        keys: &[RowKey],
-    ) -> Result<Vec<(RowKey, Result<T>)>>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        Ok(self
-            .get_multi_row_data(table, keys)
-            .await?
-            .into_iter()
-            .map(|(key, row_data)| {
-                let key_str = key.to_string();
-                (
-                    key,
-                    deserialize_bincode_cell_data(&row_data, table, key_str),
-                )
-            })
-            .collect())
-    }
-
-    pub async fn get_protobuf_or_bincode_cell<B, P>(
-        &mut self,
-        table: &str,
-        key: RowKey,
-    ) -> Result<CellData<B, P>>
-    where
-        B: serde::de::DeserializeOwned,
-        P: prost::Message + Default,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_protobuf_or_bincode_cell_data(&row_data, table, key)
-    }
-
-    pub async fn get_protobuf_or_bincode_cells<'a, B, P>(
-        &mut self,
-        table: &'a str,
-        row_keys: impl IntoIterator<Item = RowKey>,
-    ) -> Result<impl Iterator<Item = (RowKey, CellData<B, P>)> + 'a>
-    where
-        B: serde::de::DeserializeOwned,
-        P: prost::Message + Default,
-    {
-        Ok(self
-            .get_multi_row_data(
-                table,
-                row_keys.into_iter().collect::<Vec<RowKey>>().as_slice(),
-            )
-            .await?
-            .into_iter()
-            .map(|(key, row_data)| {
-                let key_str = key.to_string();
-                (
-                    key,
-                    deserialize_protobuf_or_bincode_cell_data(&row_data, table, key_str).unwrap(),
-                )
-            }))
-    }
-
-    pub async fn put_bincode_cells<T>(
-        &mut self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: serde::ser::Serialize,
-    {
-        let mut bytes_written = 0;
-        let mut new_row_data = vec![];
-        for (row_key, data) in cells {
-            let data = compress_best(&bincode::serialize(&data).unwrap())?;
-            bytes_written += data.len();
-            new_row_data.push((row_key, vec![("bin".to_string(), data)]));
-        }
-
-        self.put_row_data(table, "x", &new_row_data).await?;
-        Ok(bytes_written)
-    }
-
-    pub async fn put_protobuf_cells<T>(
-        &mut self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: prost::Message,
-    {
-        let mut bytes_written = 0;
-        let mut new_row_data = vec![];
-        for (row_key, data) in cells {
-            let mut buf = Vec::with_capacity(data.encoded_len());
-            data.encode(&mut buf).unwrap();
-            let data = compress_best(&buf)?;
-            bytes_written += data.len();
-            new_row_data.push((row_key, vec![("proto".to_string(), data)]));
-        }
-
-        self.put_row_data(table, "x", &new_row_data).await?;
-        Ok(bytes_written)
-    }
 }
 
 pub(crate) fn deserialize_protobuf_or_bincode_cell_data<B, P>(
@@ -847,7 +748,7 @@ where
 mod tests {
     use {
         super::*,
-        crate::StoredConfirmedBlock,
+        crate::{compression::compress_best, StoredConfirmedBlock},
         prost::Message,
         solana_sdk::{
             hash::Hash, message::v0::LoadedAddresses, signature::Keypair, system_transaction,
@@ -1,7 +1,11 @@
 #![allow(clippy::integer_arithmetic)]
 
 use {
-    crate::bigtable::RowKey,
+    crate::{
+        backend::{Backend, BackendExt},
+        bigtable::RowKey,
+        local_storage::LocalStorage,
+    },
     log::*,
     serde::{Deserialize, Serialize},
     solana_metrics::{datapoint_info, inc_new_counter_debug},
@@ -24,6 +28,8 @@ use {
     std::{
         collections::{HashMap, HashSet},
         convert::TryInto,
+        path::PathBuf,
+        sync::Arc,
     },
     thiserror::Error,
     tokio::task::JoinError,
@@ -36,8 +42,10 @@ extern crate solana_metrics;
 extern crate serde_derive;
 
 mod access_token;
+mod backend;
 mod bigtable;
 mod compression;
+mod local_storage;
 mod root_ca_certificate;
 
 #[derive(Debug, Error)]
@@ -384,6 +392,8 @@ pub struct LedgerStorageConfig {
     pub credential_type: CredentialType,
     pub instance_name: String,
     pub app_profile_id: String,
+    /// Store the ledger in a local database under this path instead of BigTable
+    pub local_path: Option<PathBuf>,
 }
 
 impl Default for LedgerStorageConfig {
@@ -394,13 +404,14 @@ impl Default for LedgerStorageConfig {
             credential_type: CredentialType::Filepath(None),
             instance_name: DEFAULT_INSTANCE_NAME.to_string(),
             app_profile_id: DEFAULT_APP_PROFILE_ID.to_string(),
+            local_path: None,
         }
     }
 }
 
 #[derive(Clone)]
 pub struct LedgerStorage {
-    connection: bigtable::BigTableConnection,
+    backend: Arc<dyn Backend>,
 }
 
 impl LedgerStorage {
@@ -425,16 +436,22 @@ impl LedgerStorage {
             instance_name,
             app_profile_id,
             credential_type,
+            local_path,
         } = config;
-        let connection = bigtable::BigTableConnection::new(
-            instance_name.as_str(),
-            app_profile_id.as_str(),
-            read_only,
-            timeout,
-            credential_type,
-        )
-        .await?;
-        Ok(Self { connection })
+        let backend: Arc<dyn Backend> = match local_path {
+            Some(local_path) => Arc::new(LocalStorage::open(&local_path, read_only)?),
+            None => Arc::new(
+                bigtable::BigTableConnection::new(
+                    instance_name.as_str(),
+                    app_profile_id.as_str(),
+                    read_only,
+                    timeout,
+                    credential_type,
+                )
+                .await?,
+            ),
+        };
+        Ok(Self { backend })
     }
 
     pub async fn new_with_stringified_credential(credential: String) -> Result<Self> {
@@ -449,8 +466,8 @@ impl LedgerStorage {
     pub async fn get_first_available_block(&self) -> Result<Option<Slot>> {
         debug!("LedgerStorage::get_first_available_block request received");
         inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable.get_row_keys("blocks", None, None, 1).await?;
+        let backend = &self.backend;
+        let blocks = backend.get_row_keys("blocks", None, None, 1).await?;
         if blocks.is_empty() {
             return Ok(None);
         }
@@ -467,8 +484,8 @@ impl LedgerStorage {
             start_slot, limit
         );
         inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable
+        let backend = &self.backend;
+        let blocks = backend
             .get_row_keys(
                 "blocks",
                 Some(slot_to_blocks_key(start_slot)),
@@ -489,11 +506,12 @@ impl LedgerStorage {
             slots
         );
         inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
-        let row_keys = slots.iter().copied().map(slot_to_blocks_key);
-        let data = bigtable
+        let backend = &self.backend;
+        let row_keys = slots.iter().copied().map(slot_to_blocks_key).collect();
+        let data = backend
             .get_protobuf_or_bincode_cells("blocks", row_keys)
             .await?
+            .into_iter()
             .filter_map(
                 |(row_key, block_cell_data): (
                     RowKey,
@@ -516,8 +534,8 @@ impl LedgerStorage {
             slot
         );
         inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
-        let block_cell_data = bigtable
+        let backend = &self.backend;
+        let block_cell_data = backend
             .get_protobuf_or_bincode_cell::<StoredConfirmedBlock, generated::ConfirmedBlock>(
                 "blocks",
                 slot_to_blocks_key(slot),
@@ -541,8 +559,8 @@ impl LedgerStorage {
             signature
         );
         inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
-        let transaction_info = bigtable
+        let backend = &self.backend;
+        let transaction_info = backend
             .get_bincode_cell::<TransactionInfo>("tx", signature.to_string())
             .await
             .map_err(|err| match err {
@@ -562,11 +580,11 @@ impl LedgerStorage {
             signatures
         );
         inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
+        let backend = &self.backend;
 
         // Fetch transactions info
         let keys = signatures.iter().map(|s| s.to_string()).collect::<Vec<_>>();
-        let cells = bigtable
+        let cells = backend
             .get_bincode_cells::<TransactionInfo>("tx", &keys)
             .await?;
 
@@ -624,10 +642,10 @@ impl LedgerStorage {
             signature
         );
         inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
+        let backend = &self.backend;
 
         // Figure out which block the transaction is located in
-        let TransactionInfo { slot, index, .. } = bigtable
+        let TransactionInfo { slot, index, .. } = backend
             .get_bincode_cell("tx", signature.to_string())
             .await
             .map_err(|err| match err {
@@ -684,14 +702,14 @@ impl LedgerStorage {
             address
         );
         inc_new_counter_debug!("storage-bigtable-query", 1);
-        let mut bigtable = self.connection.client();
+        let backend = &self.backend;
         let address_prefix = format!("{}/", address);
 
         // Figure out where to start listing from based on `before_signature`
         let (first_slot, before_transaction_index) = match before_signature {
             None => (Slot::MAX, 0),
             Some(before_signature) => {
-                let TransactionInfo { slot, index, .. } = bigtable
+                let TransactionInfo { slot, index, .. } = backend
                     .get_bincode_cell("tx", before_signature.to_string())
                     .await?;
 
@@ -703,7 +721,7 @@ impl LedgerStorage {
         let (last_slot, until_transaction_index) = match until_signature {
             None => (0, u32::MAX),
             Some(until_signature) => {
-                let TransactionInfo { slot, index, .. } = bigtable
+                let TransactionInfo { slot, index, .. } = backend
                     .get_bincode_cell("tx", until_signature.to_string())
                     .await?;
 
@@ -713,7 +731,7 @@ impl LedgerStorage {
 
         let mut infos = vec![];
 
-        let starting_slot_tx_len = bigtable
+        let starting_slot_tx_len = backend
             .get_protobuf_or_bincode_cell::<Vec<LegacyTransactionByAddrInfo>, tx_by_addr::TransactionByAddr>(
                 "tx-by-addr",
                 format!("{}{}", address_prefix, slot_to_tx_by_addr_key(first_slot)),
@@ -729,7 +747,7 @@ impl LedgerStorage {
 
         // Return the next tx-by-addr data of amount `limit` plus extra to account for the largest
         // number that might be flitered out
-        let tx_by_addr_data = bigtable
+        let tx_by_addr_data = backend
             .get_row_data(
                 "tx-by-addr",
                 Some(format!(
@@ -863,7 +881,7 @@ impl LedgerStorage {
         let mut tasks = vec![];
 
         if !tx_cells.is_empty() {
-            let conn = self.connection.clone();
+            let conn = self.backend.clone();
             tasks.push(tokio::spawn(async move {
                 conn.put_bincode_cells_with_retry::<TransactionInfo>("tx", &tx_cells)
                     .await
@@ -871,7 +889,7 @@ impl LedgerStorage {
         }
 
         if !tx_by_addr_cells.is_empty() {
-            let conn = self.connection.clone();
+            let conn = self.backend.clone();
             tasks.push(tokio::spawn(async move {
                 conn.put_protobuf_cells_with_retry::<tx_by_addr::TransactionByAddr>(
                     "tx-by-addr",
@@ -914,7 +932,7 @@ impl LedgerStorage {
         // `get_confirmed_block()` and `get_confirmed_blocks()`
         let blocks_cells = [(slot_to_blocks_key(slot), confirmed_block.into())];
         bytes_written += self
-            .connection
+            .backend
             .put_protobuf_cells_with_retry::<generated::ConfirmedBlock>("blocks", &blocks_cells)
             .await?;
         datapoint_info!(
@@ -977,7 +995,7 @@ impl LedgerStorage {
         let tx_deletion_rows = if !expected_tx_infos.is_empty() {
             let signatures = expected_tx_infos.keys().cloned().collect::<Vec<_>>();
             let fetched_tx_infos: HashMap<String, std::result::Result<UploadedTransaction, _>> =
-                self.connection
+                self.backend
                     .get_bincode_cells_with_retry::<TransactionInfo>("tx", &signatures)
                     .await?
                     .into_iter()
@@ -1016,18 +1034,18 @@ impl LedgerStorage {
 
         if !dry_run {
             if !address_slot_rows.is_empty() {
-                self.connection
+                self.backend
                     .delete_rows_with_retry("tx-by-addr", &address_slot_rows)
                     .await?;
             }
 
             if !tx_deletion_rows.is_empty() {
-                self.connection
+                self.backend
                     .delete_rows_with_retry("tx", &tx_deletion_rows)
                     .await?;
             }
 
-            self.connection
+            self.backend
                 .delete_rows_with_retry("blocks", &[slot_to_blocks_key(slot)])
                 .await?;
         }
@@ -1046,11 +1064,107 @@ impl LedgerStorage {
 
 #[cfg(test)]
 mod test {
-    use super::*;
+    use {
+        super::*,
+        solana_sdk::{
+            hash::Hash, signature::Keypair, system_transaction,
+            transaction_context::TransactionReturnData,
+        },
+    };
 
     #[test]
     fn test_slot_to_key() {
         assert_eq!(slot_to_key(0), "0000000000000000");
         assert_eq!(slot_to_key(!0), "ffffffffffffffff");
     }
+
+    #[tokio::test]
+    async fn test_local_ledger_storage() {
+        let path = tempfile::tempdir().unwrap();
+        let storage = LedgerStorage::new_with_config(LedgerStorageConfig {
+            read_only: false,
+            local_path: Some(path.path().to_path_buf()),
+            ..LedgerStorageConfig::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(storage.get_first_available_block().await.unwrap(), None);
+
+        let from = Keypair::new();
+        let recipient = solana_sdk::pubkey::new_rand();
+        let transaction = system_transaction::transfer(&from, &recipient, 42, Hash::default());
+        let signature = transaction.signatures[0];
+        let transaction_with_meta = VersionedTransactionWithStatusMeta {
+            transaction: VersionedTransaction::from(transaction),
+            meta: TransactionStatusMeta {
+                status: Ok(()),
+                fee: 1,
+                pre_balances: vec![43, 0, 1],
+                post_balances: vec![0, 42, 1],
+                inner_instructions: Some(vec![]),
+                log_messages: Some(vec![]),
+                pre_token_balances: Some(vec![]),
+                post_token_balances: Some(vec![]),
+                rewards: Some(vec![]),
+                loaded_addresses: LoadedAddresses::default(),
+                return_data: Some(TransactionReturnData::default()),
+                compute_units_consumed: Some(1234),
+            },
+        };
+        let block = VersionedConfirmedBlock {
+            previous_blockhash: Hash::default().to_string(),
+            blockhash: Hash::default().to_string(),
+            parent_slot: 1,
+            transactions: vec![transaction_with_meta],
+            rewards: vec![],
+            block_time: Some(1_234_567_890),
+            block_height: Some(1),
+        };
+        for slot in [2, 3] {
+            storage
+                .upload_confirmed_block(slot, block.clone())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(storage.get_first_available_block().await.unwrap(), Some(2));
+        assert_eq!(
+            storage.get_confirmed_blocks(0, 10).await.unwrap(),
+            vec![2, 3]
+        );
+        assert_eq!(
+            storage.get_confirmed_block(3).await.unwrap(),
+            ConfirmedBlock::from(block)
+        );
+        assert!(matches!(
+            storage.get_confirmed_block(4).await,
+            Err(Error::BlockNotFound(4))
+        ));
+        // The second upload of the transaction replaced its `tx` row
+        assert_eq!(
+            storage
+                .get_confirmed_transaction(&signature)
+                .await
+                .unwrap()
+                .map(|transaction| transaction.slot),
+            Some(3)
+        );
+        assert_eq!(
+            storage
+                .get_confirmed_signatures_for_address(&recipient, None, None, 10)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|(status, _)| status.slot)
+                .collect::<Vec<_>>(),
+            vec![3, 2]
+        );
+
+        storage.delete_confirmed_block(3, false).await.unwrap();
+        assert_eq!(storage.get_confirmed_blocks(0, 10).await.unwrap(), vec![2]);
+        assert!(matches!(
+            storage.get_signature_status(&signature).await,
+            Err(Error::SignatureNotFound)
+        ));
+    }
 }
@@ -0,0 +1,346 @@
+// A local stand-in for BigTable, keeping each table in a column family of a RocksDB database
+
+use {
+    crate::{
+        backend::Backend,
+        bigtable::{Error, Result, RowData, RowKey},
+    },
+    async_trait::async_trait,
+    rocksdb::{ColumnFamily, Direction, IteratorMode, Options, WriteBatch, DB},
+    std::{fs, path::Path},
+    tempfile::TempDir,
+};
+
+const TABLES: &[&str] = &["blocks", "tx", "tx-by-addr"];
+const PRIMARY_DIRECTORY: &str = "rocksdb";
+const SECONDARY_DIRECTORY: &str = "secondary";
+
+pub struct LocalStorage {
+    db: DB,
+    /// A read-only instance is a RocksDB secondary, which catches up with the writes of the
+    /// primary before each read
+    read_only: bool,
+    /// The directory of a secondary, removed once `db` is closed
+    _secondary_directory: Option<TempDir>,
+}
+
+impl LocalStorage {
+    /// Open the tables stored under `path`, creating them unless `read_only` is set.
+    ///
+    /// Only one writer may use `path` at a time, while any number of read-only instances can
+    /// follow its writes from other processes.
+    pub fn open(path: &Path, read_only: bool) -> Result<Self> {
+        let primary_path = path.join(PRIMARY_DIRECTORY);
+        if read_only {
+            // Each secondary needs a directory of its own
+            let secondaries_path = path.join(SECONDARY_DIRECTORY);
+            fs::create_dir_all(&secondaries_path)?;
+            let secondary_directory = TempDir::new_in(secondaries_path)?;
+            let db = DB::open_cf_as_secondary(
+                &Options::default(),
+                primary_path.as_path(),
+                secondary_directory.path(),
+                TABLES,
+            )?;
+            Ok(Self {
+                db,
+                read_only,
+                _secondary_directory: Some(secondary_directory),
+            })
+        } else {
+            fs::create_dir_all(path)?;
+            let mut options = Options::default();
+            options.create_if_missing(true);
+            options.create_missing_column_families(true);
+            let db = DB::open_cf(&options, primary_path, TABLES)?;
+            Ok(Self {
+                db,
+                read_only,
+                _secondary_directory: None,
+            })
+        }
+    }
+
+    fn table(&self, table_name: &str) -> Result<&ColumnFamily> {
+        self.db
+            .cf_handle(table_name)
+            .ok_or_else(|| Error::ObjectNotFound(format!("table {}", table_name)))
+    }
+
+    fn catch_up_with_primary(&self) -> Result<()> {
+        if self.read_only {
+            self.db.try_catch_up_with_primary()?;
+        }
+        Ok(())
+    }
+
+    fn read_rows(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        if rows_limit <= 0 {
+            return Ok(vec![]);
+        }
+        self.catch_up_with_primary()?;
+        let table = self.table(table_name)?;
+        let mode = match &start_at {
+            Some(row_key) => IteratorMode::From(row_key.as_bytes(), Direction::Forward),
+            None => IteratorMode::Start,
+        };
+
+        let mut rows = vec![];
+        for item in self.db.iterator_cf(table, mode) {
+            let (key, value) = item?;
+            let row_key = String::from_utf8(key.into_vec()).map_err(|err| {
+                Error::ObjectCorrupt(format!("{}/{:?}", table_name, err.as_bytes()))
+            })?;
+            if matches!(&end_at, Some(end_at) if row_key > *end_at) {
+                break;
+            }
+            let row_data = deserialize_row_data(&value, table_name, &row_key)?;
+            rows.push((row_key, row_data));
+            if rows.len() as i64 >= rows_limit {
+                break;
+            }
+        }
+        Ok(rows)
+    }
+
+    fn read_multi_rows(
+        &self,
+        table_name: &str,
+        row_keys: &[RowKey],
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        self.catch_up_with_primary()?;
+        let table = self.table(table_name)?;
+        // Return rows in lexical order, as BigTable does
+        let mut row_keys = row_keys.to_vec();
+        row_keys.sort();
+        row_keys.dedup();
+
+        let values = self
+            .db
+            .multi_get_cf(row_keys.iter().map(|row_key| (table, row_key.as_bytes())));
+        let mut rows = vec![];
+        for (row_key, value) in row_keys.into_iter().zip(values) {
+            if let Some(value) = value? {
+                let row_data = deserialize_row_data(&value, table_name, &row_key)?;
+                rows.push((row_key, row_data));
+            }
+        }
+        Ok(rows)
+    }
+
+    fn delete_multi_rows(&self, table_name: &str, row_keys: &[RowKey]) -> Result<()> {
+        let table = self.table(table_name)?;
+        let mut batch = WriteBatch::default();
+        for row_key in row_keys {
+            batch.delete_cf(table, row_key);
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Like BigTable, cells are set individually: cells of a row that are not written keep their
+    /// current value
+    fn write_rows(&self, table_name: &str, row_data: &[(&RowKey, RowData)]) -> Result<()> {
+        let table = self.table(table_name)?;
+        let mut batch = WriteBatch::default();
+        for (row_key, cells) in row_data {
+            let mut row = match self.db.get_cf(table, row_key)? {
+                Some(value) => deserialize_row_data(&value, table_name, row_key)?,
+                None => vec![],
+            };
+            for (name, value) in cells {
+                match row.iter_mut().find(|(cell_name, _)| cell_name == name) {
+                    Some((_, cell_value)) => *cell_value = value.clone(),
+                    None => row.push((name.clone(), value.clone())),
+                }
+            }
+            batch.put_cf(table, row_key, bincode::serialize(&row).unwrap());
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+}
+
+fn deserialize_row_data(value: &[u8], table_name: &str, row_key: &str) -> Result<RowData> {
+    bincode::deserialize(value)
+        .map_err(|_| Error::ObjectCorrupt(format!("{}/{}", table_name, row_key)))
+}
+
+#[async_trait]
+impl Backend for LocalStorage {
+    async fn get_row_keys(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>> {
+        let rows = self.read_rows(table_name, start_at, end_at, rows_limit)?;
+        Ok(rows.into_iter().map(|(row_key, _)| row_key).collect())
+    }
+
+    async fn get_row_data(
+        &self,
+        table_name: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        self.read_rows(table_name, start_at, end_at, rows_limit)
+    }
+
+    async fn get_multi_row_data(
+        &self,
+        table_name: &str,
+        row_keys: &[RowKey],
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        self.read_multi_rows(table_name, row_keys)
+    }
+
+    async fn get_single_row_data(&self, table_name: &str, row_key: RowKey) -> Result<RowData> {
+        self.read_multi_rows(table_name, &[row_key])?
+            .into_iter()
+            .next()
+            .map(|(_, row_data)| row_data)
+            .ok_or(Error::RowNotFound)
+    }
+
+    async fn delete_rows(&self, table_name: &str, row_keys: &[RowKey]) -> Result<()> {
+        self.delete_multi_rows(table_name, row_keys)
+    }
+
+    async fn put_row_data(
+        &self,
+        table_name: &str,
+        _family_name: &str,
+        row_data: &[(&RowKey, RowData)],
+    ) -> Result<()> {
+        self.write_rows(table_name, row_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::backend::BackendExt, futures::executor::block_on};
+
+    #[test]
+    fn test_local_storage_rows() {
+        let path = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::open(path.path(), false).unwrap();
+        let row_keys: Vec<RowKey> = ["a", "b/1", "b/2", "c"]
+            .iter()
+            .map(|row_key| row_key.to_string())
+            .collect();
+        let cells: Vec<_> = row_keys
+            .iter()
+            .enumerate()
+            .map(|(i, row_key)| (row_key.clone(), i as u64))
+            .collect();
+        block_on(storage.put_bincode_cells("tx", &cells)).unwrap();
+
+        assert_eq!(
+            block_on(storage.get_row_keys("tx", None, None, 10)).unwrap(),
+            row_keys
+        );
+        assert_eq!(
+            block_on(storage.get_row_keys(
+                "tx",
+                Some("b".to_string()),
+                Some("b/2".to_string()),
+                10
+            ))
+            .unwrap(),
+            vec!["b/1".to_string(), "b/2".to_string()]
+        );
+        assert_eq!(
+            block_on(storage.get_row_keys("tx", Some("b".to_string()), None, 1)).unwrap(),
+            vec!["b/1".to_string()]
+        );
+        assert!(block_on(storage.get_row_keys("tx", None, None, 0))
+            .unwrap()
+            .is_empty());
+        assert!(block_on(storage.get_row_keys("blocks", None, None, 10))
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            block_on(storage.get_bincode_cell::<u64>("tx", "c".to_string())).unwrap(),
+            3
+        );
+        let fetched = block_on(storage.get_bincode_cells::<u64>(
+            "tx",
+            &["c".to_string(), "missing".to_string(), "a".to_string()],
+        ))
+        .unwrap();
+        assert_eq!(
+            fetched
+                .into_iter()
+                .map(|(row_key, value)| (row_key, value.unwrap()))
+                .collect::<Vec<_>>(),
+            vec![("a".to_string(), 0), ("c".to_string(), 3)]
+        );
+
+        // Writing another cell keeps the existing cells of the row
+        block_on(storage.put_row_data(
+            "tx",
+            "x",
+            &[(&"a".to_string(), vec![("other".to_string(), vec![1])])],
+        ))
+        .unwrap();
+        let row_data = block_on(storage.get_single_row_data("tx", "a".to_string())).unwrap();
+        assert_eq!(row_data.len(), 2);
+        assert_eq!(
+            block_on(storage.get_bincode_cell::<u64>("tx", "a".to_string())).unwrap(),
+            0
+        );
+
+        block_on(storage.delete_rows("tx", &["a".to_string(), "c".to_string()])).unwrap();
+        assert_eq!(
+            block_on(storage.get_row_keys("tx", None, None, 10)).unwrap(),
+            vec!["b/1".to_string(), "b/2".to_string()]
+        );
+        assert!(matches!(
+            block_on(storage.get_single_row_data("tx", "a".to_string())),
+            Err(Error::RowNotFound)
+        ));
+        assert!(matches!(
+            block_on(storage.get_row_keys("unknown", None, None, 10)),
+            Err(Error::ObjectNotFound(_))
+        ));
+        drop(storage);
+
+        // A read-only instance sees the writes of the primary
+        let primary = LocalStorage::open(path.path(), false).unwrap();
+        let secondary = LocalStorage::open(path.path(), true).unwrap();
+        block_on(primary.put_bincode_cells("blocks", &[("0".to_string(), 7u64)])).unwrap();
+        assert_eq!(
+            block_on(secondary.get_bincode_cell::<u64>("blocks", "0".to_string())).unwrap(),
+            7
+        );
+        assert!(
+            block_on(secondary.put_bincode_cells("blocks", &[("1".to_string(), 8u64)])).is_err()
+        );
+
+        // Several read-only instances can follow the primary at once, each in a secondary
+        // directory removed when it is dropped
+        let other_secondary = LocalStorage::open(path.path(), true).unwrap();
+        assert_eq!(
+            block_on(other_secondary.get_bincode_cell::<u64>("blocks", "0".to_string())).unwrap(),
+            7
+        );
+        drop(secondary);
+        drop(other_secondary);
+        assert_eq!(
+            fs::read_dir(path.path().join(SECONDARY_DIRECTORY))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+}
@@ -6,7 +6,12 @@ fn main() -> Result<(), std::io::Error> {
     }
 
     let proto_base_path = std::path::PathBuf::from("proto");
-    let proto_files = ["confirmed_block.proto", "transaction_by_addr.proto"];
+    let proto_files = [
+        "block_archive.proto",
+        "confirmed_block.proto",
+        "streaming.proto",
+        "transaction_by_addr.proto",
+    ];
     let mut protos = Vec::new();
     for proto_file in &proto_files {
         let proto = proto_base_path.join(proto_file);
@@ -16,7 +21,7 @@ fn main() -> Result<(), std::io::Error> {
 
     tonic_build::configure()
         .build_client(true)
-        .build_server(false)
+        .build_server(true)
         .type_attribute(
             "TransactionErrorType",
             "#[cfg_attr(test, derive(enum_iterator::IntoEnumIterator))]",
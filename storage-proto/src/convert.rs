@@ -39,6 +39,17 @@ pub mod tx_by_addr {
     ));
 }
 
+pub mod streaming {
+    include!(concat!(env!("OUT_DIR"), "/solana.storage.streaming.rs"));
+}
+
+pub mod block_archive {
+    include!(concat!(env!("OUT_DIR"), "/solana.storage.block_archive.rs"));
+}
+
+// The streaming and block archive messages refer to the confirmed block messages by their package name
+use generated as confirmed_block;
+
 impl From<Vec<Reward>> for generated::Rewards {
     fn from(rewards: Vec<Reward>) -> Self {
         Self {
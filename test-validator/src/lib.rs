@@ -844,6 +844,7 @@ impl TestValidator {
             DEFAULT_TPU_USE_QUIC,
             DEFAULT_TPU_CONNECTION_POOL_SIZE,
             config.tpu_enable_udp,
+            None,
         ));
 
         // Needed to avoid panics in `solana-responder-gossip` in tests that create a number of
@@ -1,4 +1,5 @@
 use {
+    crossbeam_channel::{RecvTimeoutError, Sender},
     jsonrpc_core::{MetaIoHandler, Metadata, Result},
     jsonrpc_core_client::{transports::ipc, RpcError},
     jsonrpc_derive::rpc,
@@ -9,6 +10,7 @@ use {
     solana_core::{
         consensus::Tower, tower_storage::TowerStorage, validator::ValidatorStartProgress,
     },
+    solana_geyser_plugin_manager::geyser_plugin_manager::GeyserPluginManagerRequest,
     solana_gossip::{
         cluster_info::ClusterInfo, legacy_contact_info::LegacyContactInfo as ContactInfo,
     },
@@ -28,6 +30,10 @@ use {
     },
 };
 
+/// How long to wait for the geyser plugin service to process a request, which includes loading
+/// a plugin
+const GEYSER_PLUGIN_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(Clone)]
 pub struct AdminRpcRequestMetadataPostInit {
     pub cluster_info: Arc<ClusterInfo>,
@@ -44,6 +50,7 @@ pub struct AdminRpcRequestMetadata {
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
     pub tower_storage: Arc<dyn TowerStorage>,
     pub post_init: Arc<RwLock<Option<AdminRpcRequestMetadataPostInit>>>,
+    pub geyser_plugin_request_sender: Option<Sender<GeyserPluginManagerRequest>>,
 }
 impl Metadata for AdminRpcRequestMetadata {}
 
@@ -60,6 +67,32 @@ impl AdminRpcRequestMetadata {
             ))
         }
     }
+
+    fn send_geyser_plugin_request<T, F>(&self, make_request: F) -> Result<T>
+    where
+        F: FnOnce(Sender<T>) -> GeyserPluginManagerRequest,
+    {
+        let request_sender = self.geyser_plugin_request_sender.as_ref().ok_or_else(|| {
+            jsonrpc_core::error::Error::invalid_params(
+                "Geyser plugins are not enabled, restart the validator with \
+                 --geyser-plugin-config or --geyser-plugin-always-enabled",
+            )
+        })?;
+        let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+        let unavailable =
+            || jsonrpc_core::error::Error::invalid_params("Geyser plugin service is unavailable");
+        request_sender
+            .send(make_request(response_sender))
+            .map_err(|_| unavailable())?;
+        response_receiver
+            .recv_timeout(GEYSER_PLUGIN_REQUEST_TIMEOUT)
+            .map_err(|err| match err {
+                RecvTimeoutError::Timeout => jsonrpc_core::error::Error::invalid_params(
+                    "Timed out waiting for the geyser plugin service",
+                ),
+                RecvTimeoutError::Disconnected => unavailable(),
+            })
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -179,6 +212,23 @@ pub trait AdminRpc {
 
     #[rpc(meta, name = "contactInfo")]
     fn contact_info(&self, meta: Self::Metadata) -> Result<AdminRpcContactInfo>;
+
+    #[rpc(meta, name = "listPlugins")]
+    fn list_plugins(&self, meta: Self::Metadata) -> Result<Vec<String>>;
+
+    #[rpc(meta, name = "loadPlugin")]
+    fn load_plugin(&self, meta: Self::Metadata, config_file: String) -> Result<String>;
+
+    #[rpc(meta, name = "unloadPlugin")]
+    fn unload_plugin(&self, meta: Self::Metadata, name: String) -> Result<()>;
+
+    #[rpc(meta, name = "reloadPlugin")]
+    fn reload_plugin(
+        &self,
+        meta: Self::Metadata,
+        name: String,
+        config_file: Option<String>,
+    ) -> Result<()>;
 }
 
 pub struct AdminRpcImpl;
@@ -302,6 +352,50 @@ impl AdminRpc for AdminRpcImpl {
     fn contact_info(&self, meta: Self::Metadata) -> Result<AdminRpcContactInfo> {
         meta.with_post_init(|post_init| Ok(post_init.cluster_info.my_contact_info().into()))
     }
+
+    fn list_plugins(&self, meta: Self::Metadata) -> Result<Vec<String>> {
+        debug!("list_plugins request received");
+        meta.send_geyser_plugin_request(|response_sender| GeyserPluginManagerRequest::ListPlugins {
+            response_sender,
+        })
+    }
+
+    fn load_plugin(&self, meta: Self::Metadata, config_file: String) -> Result<String> {
+        debug!("load_plugin request received");
+        meta.send_geyser_plugin_request(|response_sender| GeyserPluginManagerRequest::LoadPlugin {
+            config_file,
+            response_sender,
+        })?
+        .map_err(jsonrpc_core::error::Error::invalid_params)
+    }
+
+    fn unload_plugin(&self, meta: Self::Metadata, name: String) -> Result<()> {
+        debug!("unload_plugin request received");
+        meta.send_geyser_plugin_request(|response_sender| {
+            GeyserPluginManagerRequest::UnloadPlugin {
+                name,
+                response_sender,
+            }
+        })?
+        .map_err(jsonrpc_core::error::Error::invalid_params)
+    }
+
+    fn reload_plugin(
+        &self,
+        meta: Self::Metadata,
+        name: String,
+        config_file: Option<String>,
+    ) -> Result<()> {
+        debug!("reload_plugin request received");
+        meta.send_geyser_plugin_request(|response_sender| {
+            GeyserPluginManagerRequest::ReloadPlugin {
+                name,
+                config_file,
+                response_sender,
+            }
+        })?
+        .map_err(jsonrpc_core::error::Error::invalid_params)
+    }
 }
 
 impl AdminRpcImpl {
@@ -699,6 +699,7 @@ fn main() {
             authorized_voter_keypairs: genesis.authorized_voter_keypairs.clone(),
             post_init: admin_service_post_init.clone(),
             tower_storage: tower_storage.clone(),
+            geyser_plugin_request_sender: None,
         },
     );
     let dashboard = if output == Output::Dashboard {
@@ -724,6 +725,7 @@ fn main() {
                 String
             ),
             timeout: None,
+            local_storage_path: None,
         })
     } else {
         None
@@ -7,6 +7,7 @@ use {
         AppSettings, Arg, ArgMatches, SubCommand,
     },
     console::style,
+    crossbeam_channel::unbounded,
     log::*,
     rand::{seq::SliceRandom, thread_rng},
     solana_clap_utils::{
@@ -40,6 +41,8 @@ use {
     solana_perf::recycler::enable_recycler_warming,
     solana_poh::poh_service,
     solana_rpc::{
+        account_history_service::DEFAULT_ACCOUNT_HISTORY_RETENTION_SLOTS,
+        grpc_streaming_service::GrpcStreamingConfig,
         rpc::{JsonRpcConfig, RpcBigtableConfig},
         rpc_pubsub_service::PubSubConfig,
     },
@@ -383,6 +386,17 @@ fn wait_for_restart_window(
     Ok(())
 }
 
+fn canonical_plugin_config(matches: &ArgMatches<'_>) -> String {
+    let config = value_t_or_exit!(matches, "config", String);
+    fs::canonicalize(&config)
+        .unwrap_or_else(|err| {
+            println!("Unable to access path: {}: {:?}", config, err);
+            exit(1);
+        })
+        .display()
+        .to_string()
+}
+
 fn hash_validator(hash: String) -> Result<(), String> {
     Hash::from_str(&hash)
         .map(|_| ())
@@ -455,6 +469,14 @@ pub fn main() {
         PubSubConfig::default().queue_capacity_items.to_string();
     let default_rpc_pubsub_queue_capacity_bytes =
         PubSubConfig::default().queue_capacity_bytes.to_string();
+    let default_rpc_grpc_queue_capacity_items = GrpcStreamingConfig::default()
+        .queue_capacity_items
+        .to_string();
+    let default_rpc_grpc_max_replay_slots =
+        GrpcStreamingConfig::default().max_replay_slots.to_string();
+    let default_rpc_grpc_worker_threads = GrpcStreamingConfig::default().worker_threads.to_string();
+    let default_rpc_account_history_retention_slots =
+        &DEFAULT_ACCOUNT_HISTORY_RETENTION_SLOTS.to_string();
     let default_send_transaction_service_config = send_transaction_service::Config::default();
     let default_rpc_send_transaction_retry_ms = default_send_transaction_service_config
         .retry_rate_ms
@@ -1298,6 +1320,15 @@ pub fn main() {
                 .validator(solana_net_utils::is_host)
                 .help("IP address to bind the RPC port [default: 127.0.0.1 if --private-rpc is present, otherwise use --bind-address]"),
         )
+        .arg(
+            Arg::with_name("prometheus_bind_address")
+                .long("prometheus-bind-address")
+                .value_name("HOST:PORT")
+                .takes_value(true)
+                .validator(solana_net_utils::is_host_port)
+                .help("Serve the metrics of the validator on http://HOST:PORT/metrics, \
+                       for Prometheus to scrape"),
+        )
         .arg(
             Arg::with_name("rpc_threads")
                 .long("rpc-threads")
@@ -1342,6 +1373,13 @@ pub fn main() {
                 .default_value(solana_storage_bigtable::DEFAULT_APP_PROFILE_ID)
                 .help("Bigtable application profile id to use in requests")
         )
+        .arg(
+            Arg::with_name("rpc_bigtable_local_storage_path")
+                .long("rpc-bigtable-local-storage-path")
+                .takes_value(true)
+                .value_name("DIR")
+                .help("Use a local database in this directory instead of a Bigtable instance")
+        )
         .arg(
             Arg::with_name("rpc_pubsub_worker_threads")
                 .long("rpc-pubsub-worker-threads")
@@ -1445,6 +1483,52 @@ pub fn main() {
                 .help("The maximum number of threads that RPC PubSub will use \
                        for generating notifications. 0 will disable RPC PubSub notifications"),
         )
+        .arg(
+            Arg::with_name("rpc_grpc_port")
+                .long("rpc-grpc-port")
+                .value_name("PORT")
+                .takes_value(true)
+                .requires("rpc_port")
+                .validator(renec_validator::port_validator)
+                .help("Enable the gRPC streaming service on this port"),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_enable_block_subscription")
+                .long("rpc-grpc-enable-block-subscription")
+                .requires("rpc_grpc_port")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Enable streaming blocks and transactions over gRPC"),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_queue_capacity_items")
+                .long("rpc-grpc-queue-capacity-items")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_grpc_queue_capacity_items)
+                .help("The maximum number of updates queued for a gRPC subscription. \
+                       Subscriptions that fall further behind are dropped."),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_max_replay_slots")
+                .long("rpc-grpc-max-replay-slots")
+                .takes_value(true)
+                .value_name("SLOTS")
+                .validator(is_parsable::<u64>)
+                .default_value(&default_rpc_grpc_max_replay_slots)
+                .help("How many slots behind the highest root a gRPC subscription \
+                       may replay the ledger from"),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_worker_threads")
+                .long("rpc-grpc-worker-threads")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_rpc_grpc_worker_threads)
+                .help("gRPC streaming worker threads"),
+        )
         .arg(
             Arg::with_name("rpc_send_transaction_retry_ms")
                 .long("rpc-send-retry-ms")
@@ -1507,6 +1591,38 @@ pub fn main() {
                 .requires("enable_rpc_transaction_history")
                 .help("Verifies blockstore roots on boot and fixes any gaps"),
         )
+        .arg(
+            Arg::with_name("enable_rpc_account_history")
+                .long("enable-rpc-account-history")
+                .takes_value(false)
+                .requires("rpc_port")
+                .help("Record the state of the accounts written in each slot, enabling the \
+                       \"slot\" parameter of the getAccountInfo and getMultipleAccounts \
+                       RPC methods"),
+        )
+        .arg(
+            Arg::with_name("rpc_account_history_address")
+                .long("rpc-account-history-address")
+                .value_name("ADDRESS")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_pubkey)
+                .requires("enable_rpc_account_history")
+                .help("Only record the history of this account instead of every \
+                       account. May be specified multiple times"),
+        )
+        .arg(
+            Arg::with_name("rpc_account_history_retention_slots")
+                .long("rpc-account-history-retention-slots")
+                .value_name("SLOTS")
+                .takes_value(true)
+                .requires("enable_rpc_account_history")
+                .validator(is_parsable::<u64>)
+                .default_value(default_rpc_account_history_retention_slots)
+                .help("How many slots behind the highest root the account history \
+                       can be looked up at. The history is also bounded by the ledger \
+                       retained with --limit-ledger-size"),
+        )
         .arg(
             Arg::with_name("enable_accountsdb_repl")
                 .long("enable-accountsdb-repl")
@@ -1551,6 +1667,13 @@ pub fn main() {
                 .multiple(true)
                 .help("Specify the configuration file for the Geyser plugin."),
         )
+        .arg(
+            Arg::with_name("geyser_plugin_always_enabled")
+                .long("geyser-plugin-always-enabled")
+                .takes_value(false)
+                .help("Enable Geyser notifications even if no plugin is loaded at startup, \
+                       so that plugins can be loaded later with `renec-validator plugin load`"),
+        )
         .arg(
             Arg::with_name("halt_on_known_validators_accounts_hash_mismatch")
                 .alias("halt-on-trusted-validators-accounts-hash-mismatch")
@@ -1917,6 +2040,57 @@ pub fn main() {
             SubCommand::with_name("monitor")
             .about("Monitor the validator")
         )
+        .subcommand(
+            SubCommand::with_name("plugin")
+            .about("Manage and view Geyser plugins")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .setting(AppSettings::InferSubcommands)
+            .subcommand(
+                SubCommand::with_name("list")
+                .about("List all current running Geyser plugins")
+            )
+            .subcommand(
+                SubCommand::with_name("load")
+                .about("Load a new Geyser plugin")
+                .arg(
+                    Arg::with_name("config")
+                        .value_name("CONFIG")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Path to the plugin's configuration file")
+                )
+            )
+            .subcommand(
+                SubCommand::with_name("unload")
+                .about("Unload a Geyser plugin")
+                .arg(
+                    Arg::with_name("name")
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the plugin to unload")
+                )
+            )
+            .subcommand(
+                SubCommand::with_name("reload")
+                .about("Reload a Geyser plugin, picking up a rebuilt library or changed configuration")
+                .arg(
+                    Arg::with_name("name")
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the plugin to reload")
+                )
+                .arg(
+                    Arg::with_name("config")
+                        .value_name("CONFIG")
+                        .takes_value(true)
+                        .help("Path to the plugin's new configuration file \
+                               [default: the configuration file the plugin was loaded with]")
+                )
+            )
+            .after_help("Note: plugin changes only apply to the currently running validator instance")
+        )
         .subcommand(
             SubCommand::with_name("run")
             .about("Run the validator")
@@ -2138,6 +2312,70 @@ pub fn main() {
             monitor_validator(&ledger_path);
             return;
         }
+        ("plugin", Some(plugin_subcommand_matches)) => {
+            match plugin_subcommand_matches.subcommand() {
+                ("list", _) => {
+                    let admin_client = admin_rpc_service::connect(&ledger_path);
+                    let plugins = admin_rpc_service::runtime()
+                        .block_on(async move { admin_client.await?.list_plugins().await })
+                        .unwrap_or_else(|err| {
+                            println!("Failed to list plugins: {}", err);
+                            exit(1);
+                        });
+                    if plugins.is_empty() {
+                        println!("There are currently no plugins loaded");
+                    } else {
+                        println!("Currently the following plugins are loaded:");
+                        for plugin in plugins {
+                            println!("  - {}", plugin);
+                        }
+                    }
+                }
+                ("load", Some(subcommand_matches)) => {
+                    let config = canonical_plugin_config(subcommand_matches);
+                    let admin_client = admin_rpc_service::connect(&ledger_path);
+                    let name = admin_rpc_service::runtime()
+                        .block_on(async move { admin_client.await?.load_plugin(config).await })
+                        .unwrap_or_else(|err| {
+                            println!("Failed to load plugin: {}", err);
+                            exit(1);
+                        });
+                    println!("Successfully loaded plugin: {}", name);
+                }
+                ("unload", Some(subcommand_matches)) => {
+                    let name = value_t_or_exit!(subcommand_matches, "name", String);
+                    let admin_client = admin_rpc_service::connect(&ledger_path);
+                    admin_rpc_service::runtime()
+                        .block_on(async { admin_client.await?.unload_plugin(name.clone()).await })
+                        .unwrap_or_else(|err| {
+                            println!("Failed to unload plugin {}: {}", name, err);
+                            exit(1);
+                        });
+                    println!("Successfully unloaded plugin: {}", name);
+                }
+                ("reload", Some(subcommand_matches)) => {
+                    let name = value_t_or_exit!(subcommand_matches, "name", String);
+                    let config = subcommand_matches
+                        .is_present("config")
+                        .then(|| canonical_plugin_config(subcommand_matches));
+                    let admin_client = admin_rpc_service::connect(&ledger_path);
+                    admin_rpc_service::runtime()
+                        .block_on(async {
+                            admin_client
+                                .await?
+                                .reload_plugin(name.clone(), config)
+                                .await
+                        })
+                        .unwrap_or_else(|err| {
+                            println!("Failed to reload plugin {}: {}", name, err);
+                            exit(1);
+                        });
+                    println!("Successfully reloaded plugin: {}", name);
+                }
+                _ => unreachable!(),
+            }
+            return;
+        }
         ("set-identity", Some(subcommand_matches)) => {
             let require_tower = subcommand_matches.is_present("require_tower");
 
@@ -2517,6 +2755,14 @@ pub fn main() {
     } else {
         None
     };
+    let geyser_plugin_always_enabled = matches.is_present("geyser_plugin_always_enabled");
+    let (geyser_plugin_request_sender, geyser_plugin_request_receiver) =
+        if geyser_plugin_config_files.is_some() || geyser_plugin_always_enabled {
+            let (sender, receiver) = unbounded();
+            (Some(sender), Some(receiver))
+        } else {
+            (None, None)
+        };
 
     if matches.is_present("minimal_rpc_api") {
         warn!("--minimal-rpc-api is now the default behavior. This flag is deprecated and can be removed from the launch args");
@@ -2551,6 +2797,7 @@ pub fn main() {
             timeout: value_t!(matches, "rpc_bigtable_timeout", u64)
                 .ok()
                 .map(Duration::from_secs),
+            local_storage_path: value_t!(matches, "rpc_bigtable_local_storage_path", PathBuf).ok(),
         })
     } else {
         None
@@ -2628,8 +2875,21 @@ pub fn main() {
             rpc_niceness_adj: value_t_or_exit!(matches, "rpc_niceness_adj", i8),
             account_indexes: account_indexes.clone(),
             rpc_scan_and_fix_roots: matches.is_present("rpc_scan_and_fix_roots"),
+            account_history_retention_slots: if matches.is_present("enable_rpc_account_history") {
+                Some(value_t_or_exit!(
+                    matches,
+                    "rpc_account_history_retention_slots",
+                    u64
+                ))
+            } else {
+                None
+            },
+            account_history_addresses: values_t!(matches, "rpc_account_history_address", Pubkey)
+                .ok()
+                .map(|addresses| addresses.into_iter().collect()),
         },
         geyser_plugin_config_files,
+        geyser_plugin_always_enabled,
         rpc_addrs: value_t!(matches, "rpc_port", u16).ok().map(|rpc_port| {
             (
                 SocketAddr::new(rpc_bind_address, rpc_port),
@@ -2664,6 +2924,15 @@ pub fn main() {
                 Some(0)
             },
         },
+        grpc_streaming_addr: value_t!(matches, "rpc_grpc_port", u16)
+            .ok()
+            .map(|rpc_grpc_port| SocketAddr::new(rpc_bind_address, rpc_grpc_port)),
+        grpc_streaming_config: GrpcStreamingConfig {
+            enable_block_subscription: matches.is_present("rpc_grpc_enable_block_subscription"),
+            queue_capacity_items: value_t_or_exit!(matches, "rpc_grpc_queue_capacity_items", usize),
+            max_replay_slots: value_t_or_exit!(matches, "rpc_grpc_max_replay_slots", u64),
+            worker_threads: value_t_or_exit!(matches, "rpc_grpc_worker_threads", usize),
+        },
         voting_disabled: matches.is_present("no_voting") || restricted_repair_only_mode,
         wait_for_supermajority: value_t!(matches, "wait_for_supermajority", Slot).ok(),
         known_validators,
@@ -2990,6 +3259,7 @@ pub fn main() {
             authorized_voter_keypairs: authorized_voter_keypairs.clone(),
             post_init: admin_service_post_init.clone(),
             tower_storage: validator_config.tower_storage.clone(),
+            geyser_plugin_request_sender,
         },
     );
 
@@ -3095,6 +3365,17 @@ pub fn main() {
         let version = format!("{:?}", solana_version::version!());
         Some(version)
     });
+    if let Some(prometheus_bind_address) = matches.value_of("prometheus_bind_address") {
+        let prometheus_bind_address = solana_net_utils::parse_host_port(prometheus_bind_address)
+            .expect("invalid prometheus_bind_address");
+        if let Err(err) = solana_metrics::prometheus::start_exporter(prometheus_bind_address) {
+            eprintln!(
+                "Unable to serve metrics on {}: {}",
+                prometheus_bind_address, err
+            );
+            exit(1);
+        }
+    }
     solana_entry::entry::init_poh();
     snapshot_utils::remove_tmp_snapshot_archives(&full_snapshot_archives_dir);
     snapshot_utils::remove_tmp_snapshot_archives(&incremental_snapshot_archives_dir);
@@ -3145,6 +3426,7 @@ pub fn main() {
         tpu_use_quic,
         tpu_connection_pool_size,
         tpu_enable_udp,
+        geyser_plugin_request_receiver,
     );
     *admin_service_post_init.write().unwrap() =
         Some(admin_rpc_service::AdminRpcRequestMetadataPostInit {
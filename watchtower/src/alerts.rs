@@ -0,0 +1,276 @@
+//! Tracking of failures into alerts, which are notified once per route, after the escalation
+//! delay of the route, and cleared once resolved.
+//!
+//! Each alert is an incident with a key, so that notifiers such as PagerDuty resolve the incident
+//! they were triggered for.
+
+use {
+    crate::config::Route,
+    std::{
+        collections::{BTreeMap, HashMap},
+        time::{Duration, Instant},
+    },
+};
+
+/// A failed check
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// The group of validators, or `cluster`
+    pub group: String,
+    pub check: &'static str,
+    /// What failed the check, such as a validator, to tell apart its alerts
+    pub subject: String,
+    pub message: String,
+    /// Indexes into the routes
+    pub routes: Vec<usize>,
+}
+
+type AlertKey = (String, &'static str, String);
+
+struct Alert {
+    failure: Failure,
+    first_failure: Instant,
+    consecutive_failures: usize,
+    /// The time of the last notification, by route
+    notified: HashMap<usize, Instant>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Notification {
+    pub route: usize,
+    /// The key of the incident, `<group>/<check>/<subject>`
+    pub incident: String,
+    /// Whether the notification resolves the incident, or triggers it
+    pub resolved: bool,
+    pub message: String,
+}
+
+fn incident_key((group, check, subject): &AlertKey) -> String {
+    format!("{}/{}/{}", group, check, subject)
+}
+
+struct Escalation {
+    escalate_after: Duration,
+    repeat_every: Option<Duration>,
+}
+
+pub struct AlertTracker {
+    unhealthy_threshold: usize,
+    escalations: Vec<Escalation>,
+    alerts: BTreeMap<AlertKey, Alert>,
+}
+
+impl AlertTracker {
+    /// Failures become alerts after more than `unhealthy_threshold` in a row
+    pub fn new(unhealthy_threshold: usize, routes: &[Route]) -> Self {
+        Self {
+            unhealthy_threshold,
+            escalations: routes
+                .iter()
+                .map(|route| Escalation {
+                    escalate_after: route.escalate_after,
+                    repeat_every: route.repeat_every,
+                })
+                .collect(),
+            alerts: BTreeMap::new(),
+        }
+    }
+
+    /// Records the failures of a round of checks, returning the notifications to send.
+    ///
+    /// Alerts without a failure are resolved, unless the round is `partial`, as when the checks
+    /// could not be run.
+    pub fn update(
+        &mut self,
+        failures: Vec<Failure>,
+        partial: bool,
+        now: Instant,
+    ) -> Vec<Notification> {
+        let mut notifications = vec![];
+
+        let keys: Vec<AlertKey> = failures
+            .iter()
+            .map(|failure| {
+                (
+                    failure.group.clone(),
+                    failure.check,
+                    failure.subject.clone(),
+                )
+            })
+            .collect();
+        if !partial {
+            let resolved: Vec<AlertKey> = self
+                .alerts
+                .keys()
+                .filter(|key| !keys.contains(key))
+                .cloned()
+                .collect();
+            for key in resolved {
+                let alert = self.alerts.remove(&key).unwrap();
+                // Drop milliseconds in message
+                let duration =
+                    Duration::from_secs(now.duration_since(alert.first_failure).as_secs());
+                let mut routes: Vec<usize> = alert.notified.into_keys().collect();
+                routes.sort_unstable();
+                for route in routes {
+                    notifications.push(Notification {
+                        route,
+                        incident: incident_key(&key),
+                        resolved: true,
+                        message: format!(
+                            "All clear after {}: {}: {}",
+                            humantime::format_duration(duration),
+                            alert.failure.check,
+                            alert.failure.message
+                        ),
+                    });
+                }
+            }
+        }
+
+        for (key, failure) in keys.into_iter().zip(failures) {
+            let incident = incident_key(&key);
+            let alert = self.alerts.entry(key).or_insert_with(|| Alert {
+                failure: failure.clone(),
+                first_failure: now,
+                consecutive_failures: 0,
+                notified: HashMap::new(),
+            });
+            alert.failure = failure;
+            alert.consecutive_failures += 1;
+            if alert.consecutive_failures <= self.unhealthy_threshold {
+                continue;
+            }
+
+            for route in &alert.failure.routes {
+                let escalation = &self.escalations[*route];
+                if now.duration_since(alert.first_failure) < escalation.escalate_after {
+                    continue;
+                }
+                let notify = match (alert.notified.get(route), escalation.repeat_every) {
+                    (None, _) => true,
+                    (Some(last_notified), Some(repeat_every)) => {
+                        now.duration_since(*last_notified) >= repeat_every
+                    }
+                    (Some(_), None) => false,
+                };
+                if notify {
+                    alert.notified.insert(*route, now);
+                    notifications.push(Notification {
+                        route: *route,
+                        incident: incident.clone(),
+                        resolved: false,
+                        message: format!(
+                            "Error: {}: {}",
+                            alert.failure.check, alert.failure.message
+                        ),
+                    });
+                }
+            }
+        }
+
+        notifications
+    }
+
+    /// The failures which are alerts
+    pub fn active_alerts(&self) -> impl Iterator<Item = &Failure> {
+        self.alerts
+            .values()
+            .filter(|alert| alert.consecutive_failures > self.unhealthy_threshold)
+            .map(|alert| &alert.failure)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, solana_notifier::NotifierConfig};
+
+    fn route(escalate_after: u64, repeat_every: Option<u64>) -> Route {
+        Route {
+            name: String::new(),
+            notifier: NotifierConfig::default(),
+            escalate_after: Duration::from_secs(escalate_after),
+            repeat_every: repeat_every.map(Duration::from_secs),
+        }
+    }
+
+    fn failure(subject: &str, message: &str) -> Failure {
+        Failure {
+            group: "group".to_string(),
+            check: "delinquent",
+            subject: subject.to_string(),
+            message: message.to_string(),
+            routes: vec![0, 1],
+        }
+    }
+
+    fn notification(route: usize, subject: &str, message: &str) -> Notification {
+        Notification {
+            route,
+            incident: format!("group/delinquent/{}", subject),
+            resolved: message.starts_with("All clear"),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_alert_tracker() {
+        let mut tracker = AlertTracker::new(1, &[route(0, None), route(600, Some(3600))]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // The first failure is below the unhealthy threshold
+        assert_eq!(
+            tracker.update(vec![failure("a", "a")], false, at(0)),
+            vec![]
+        );
+        assert_eq!(tracker.active_alerts().count(), 0);
+        assert_eq!(
+            tracker.update(vec![failure("a", "a")], false, at(60)),
+            vec![notification(0, "a", "Error: delinquent: a")]
+        );
+        assert_eq!(tracker.active_alerts().count(), 1);
+
+        // Repeated failures are not notified again, even with a new message
+        assert_eq!(
+            tracker.update(vec![failure("a", "a again")], false, at(120)),
+            vec![]
+        );
+
+        // A partial round keeps the alerts
+        assert_eq!(tracker.update(vec![], true, at(300)), vec![]);
+
+        // Escalation to the second route
+        assert_eq!(
+            tracker.update(vec![failure("a", "a")], false, at(600)),
+            vec![notification(1, "a", "Error: delinquent: a")]
+        );
+        assert_eq!(
+            tracker.update(vec![failure("a", "a")], false, at(3000)),
+            vec![]
+        );
+        assert_eq!(
+            tracker.update(vec![failure("a", "a")], false, at(4200)),
+            vec![notification(1, "a", "Error: delinquent: a")]
+        );
+
+        // Resolved alerts are cleared on the routes they were notified to, and a new alert is
+        // tracked separately
+        tracker.update(vec![failure("a", "a"), failure("b", "b")], false, at(4260));
+        assert_eq!(
+            tracker.update(vec![failure("b", "b")], false, at(4320)),
+            vec![
+                notification(0, "a", "All clear after 1h 12m: delinquent: a"),
+                notification(1, "a", "All clear after 1h 12m: delinquent: a"),
+                notification(0, "b", "Error: delinquent: b"),
+            ]
+        );
+
+        // An alert resolved before escalation was not notified to the second route
+        assert_eq!(
+            tracker.update(vec![], false, at(4380)),
+            vec![notification(0, "b", "All clear after 2m: delinquent: b")]
+        );
+        assert_eq!(tracker.active_alerts().count(), 0);
+    }
+}
@@ -0,0 +1,231 @@
+//! The checks of a group of validators
+
+use {
+    crate::{
+        alerts::Failure,
+        config::{Group, VersionSkew},
+    },
+    renec_cli_output::display::format_labeled_address,
+    solana_client::rpc_response::RpcVoteAccountStatus,
+    solana_sdk::{native_token::Sol, pubkey::Pubkey},
+    std::collections::HashMap,
+};
+
+/// The state of the cluster that the checks need
+pub struct ClusterInfo {
+    pub vote_accounts: RpcVoteAccountStatus,
+    pub validator_balances: HashMap<Pubkey, u64>,
+    /// Versions of the nodes, by identity
+    pub node_versions: HashMap<String, String>,
+    /// The version run by the most stake
+    pub cluster_version: Option<String>,
+    /// Leader slots and blocks produced in the current epoch, by identity
+    pub block_production: HashMap<String, (usize, usize)>,
+}
+
+/// Parses the major, minor and patch numbers of a version
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut numbers = version.split('.').map(|number| {
+        number
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .ok()
+    });
+    Some((numbers.next()??, numbers.next()??, numbers.next()??))
+}
+
+fn is_version_skewed(version: &str, cluster_version: &str, version_skew: VersionSkew) -> bool {
+    match (parse_version(version), parse_version(cluster_version)) {
+        (Some(version), Some(cluster_version)) => match version_skew {
+            VersionSkew::Major => version.0 != cluster_version.0,
+            VersionSkew::Minor => (version.0, version.1) != (cluster_version.0, cluster_version.1),
+            VersionSkew::Patch => version != cluster_version,
+        },
+        _ => version != cluster_version,
+    }
+}
+
+pub fn check_group(
+    group: &Group,
+    cluster_info: &ClusterInfo,
+    address_labels: &HashMap<String, String>,
+) -> Vec<Failure> {
+    let mut failures = vec![];
+    let mut fail = |check, validator: &String, message| {
+        failures.push(Failure {
+            group: group.name.clone(),
+            check,
+            subject: validator.clone(),
+            message,
+            routes: group.routes.clone(),
+        })
+    };
+
+    for validator_identity in &group.validators {
+        let validator = validator_identity.to_string();
+        let formatted_validator_identity = format_labeled_address(&validator, address_labels);
+        let vote_accounts = &cluster_info.vote_accounts;
+
+        if group.checks.delinquent {
+            if vote_accounts
+                .delinquent
+                .iter()
+                .any(|vai| vai.node_pubkey == validator)
+            {
+                fail(
+                    "delinquent",
+                    &validator,
+                    format!("{} delinquent", formatted_validator_identity),
+                );
+            } else if !vote_accounts
+                .current
+                .iter()
+                .any(|vai| vai.node_pubkey == validator)
+            {
+                fail(
+                    "delinquent",
+                    &validator,
+                    format!("{} missing", formatted_validator_identity),
+                );
+            }
+        }
+
+        if let Some(minimum_balance) = group.checks.minimum_balance {
+            if let Some(balance) = cluster_info.validator_balances.get(validator_identity) {
+                if *balance < minimum_balance {
+                    fail(
+                        "balance",
+                        &validator,
+                        format!("{} has {}", formatted_validator_identity, Sol(*balance)),
+                    );
+                }
+            }
+        }
+
+        if let (Some(version_skew), Some(cluster_version), Some(version)) = (
+            group.checks.version_skew,
+            &cluster_info.cluster_version,
+            cluster_info.node_versions.get(&validator),
+        ) {
+            if is_version_skewed(version, cluster_version, version_skew) {
+                fail(
+                    "version",
+                    &validator,
+                    format!(
+                        "{} runs {}, but the cluster runs {}",
+                        formatted_validator_identity, version, cluster_version
+                    ),
+                );
+            }
+        }
+
+        if let (Some(maximum_skip_rate), Some((leader_slots, blocks_produced))) = (
+            group.checks.maximum_skip_rate,
+            cluster_info.block_production.get(&validator),
+        ) {
+            if *leader_slots > 0 {
+                let skip_rate = leader_slots.saturating_sub(*blocks_produced) as f64 * 100.
+                    / *leader_slots as f64;
+                if skip_rate > maximum_skip_rate {
+                    fail(
+                        "skipped-slots",
+                        &validator,
+                        format!(
+                            "{} skipped {:.2}% of its {} leader slots",
+                            formatted_validator_identity, skip_rate, leader_slots
+                        ),
+                    );
+                }
+            }
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*, crate::config::Checks, solana_client::rpc_response::RpcVoteAccountInfo,
+        solana_sdk::native_token::sol_to_lamports,
+    };
+
+    #[test]
+    fn test_is_version_skewed() {
+        assert!(!is_version_skewed("1.14.17", "1.14.17", VersionSkew::Patch));
+        assert!(is_version_skewed("1.14.16", "1.14.17", VersionSkew::Patch));
+        assert!(!is_version_skewed("1.14.16", "1.14.17", VersionSkew::Minor));
+        assert!(is_version_skewed("1.13.6", "1.14.17", VersionSkew::Minor));
+        assert!(!is_version_skewed("1.13.6", "1.14.17", VersionSkew::Major));
+        assert!(is_version_skewed("2.0.0", "1.14.17", VersionSkew::Major));
+        assert!(!is_version_skewed(
+            "1.14.17-rc1",
+            "1.14.17",
+            VersionSkew::Patch
+        ));
+        assert!(is_version_skewed("unknown", "1.14.17", VersionSkew::Major));
+    }
+
+    #[test]
+    fn test_check_group() {
+        let validators: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let vote_account_info = |validator: &Pubkey| RpcVoteAccountInfo {
+            vote_pubkey: Pubkey::new_unique().to_string(),
+            node_pubkey: validator.to_string(),
+            activated_stake: 1,
+            commission: 0,
+            epoch_vote_account: true,
+            epoch_credits: vec![],
+            last_vote: 0,
+            root_slot: 0,
+        };
+        let cluster_info = ClusterInfo {
+            vote_accounts: RpcVoteAccountStatus {
+                current: vec![vote_account_info(&validators[0])],
+                delinquent: vec![vote_account_info(&validators[1])],
+            },
+            validator_balances: validators
+                .iter()
+                .map(|validator| (*validator, sol_to_lamports(1.)))
+                .collect(),
+            node_versions: HashMap::from([
+                (validators[0].to_string(), "1.14.17".to_string()),
+                (validators[1].to_string(), "1.13.6".to_string()),
+            ]),
+            cluster_version: Some("1.14.17".to_string()),
+            block_production: HashMap::from([
+                (validators[0].to_string(), (100, 70)),
+                (validators[1].to_string(), (100, 90)),
+            ]),
+        };
+        let group = Group {
+            name: "group".to_string(),
+            validators: validators.clone(),
+            routes: vec![0],
+            checks: Checks {
+                delinquent: true,
+                minimum_balance: Some(sol_to_lamports(2.)),
+                version_skew: Some(VersionSkew::Minor),
+                maximum_skip_rate: Some(25.),
+            },
+        };
+
+        let failures: Vec<(&str, String)> = check_group(&group, &cluster_info, &HashMap::new())
+            .into_iter()
+            .map(|failure| (failure.check, failure.subject))
+            .collect();
+        assert_eq!(
+            failures,
+            vec![
+                ("balance", validators[0].to_string()),
+                ("skipped-slots", validators[0].to_string()),
+                ("delinquent", validators[1].to_string()),
+                ("balance", validators[1].to_string()),
+                ("version", validators[1].to_string()),
+                ("delinquent", validators[2].to_string()),
+                ("balance", validators[2].to_string()),
+            ]
+        );
+    }
+}
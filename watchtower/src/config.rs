@@ -0,0 +1,331 @@
+//! The monitor configuration file, which defines groups of validators with the checks to run on
+//! them, and routes their alerts to notifiers.
+//!
+//! ```yaml
+//! routes:
+//!   - name: chat
+//!     notifier:
+//!       slack_webhook: https://hooks.slack.com/services/...
+//!   - name: pager
+//!     notifier:
+//!       twilio: { account: ..., token: ..., to: ..., from: ... }
+//!     # Only alerts unresolved for this long are sent to this route
+//!     escalate_after: 15m
+//!     # Resend unresolved alerts this often
+//!     repeat_every: 1h
+//! # Routes of the cluster-wide checks and RPC errors [default: all routes]
+//! cluster_routes: [chat]
+//! groups:
+//!   - name: mainnet
+//!     validators: [<IDENTITY PUBKEY>, ...]
+//!     # [default: all routes]
+//!     routes: [chat, pager]
+//!     checks:
+//!       delinquent: true
+//!       minimum_balance: 10
+//!       version_skew: minor
+//!       maximum_skip_rate: 25
+//! ```
+
+use {
+    serde::Deserializer,
+    serde_derive::Deserialize,
+    solana_notifier::NotifierConfig,
+    solana_sdk::{native_token::sol_to_lamports, pubkey::Pubkey},
+    std::{collections::HashSet, fs, str::FromStr, time::Duration},
+};
+
+/// How far the version of a validator may be from the version of the cluster, the version run
+/// by most of the stake
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionSkew {
+    /// Alert when the major version differs
+    Major,
+    /// Alert when the major or minor version differs
+    Minor,
+    /// Alert on any difference
+    Patch,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RouteConfig {
+    name: String,
+    notifier: NotifierConfig,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    escalate_after: Option<Duration>,
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    repeat_every: Option<Duration>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChecksConfig {
+    #[serde(default = "default_delinquent")]
+    delinquent: bool,
+    /// In RENEC
+    minimum_balance: Option<f64>,
+    version_skew: Option<VersionSkew>,
+    /// In percent of the leader slots of the epoch
+    maximum_skip_rate: Option<f64>,
+}
+
+fn default_delinquent() -> bool {
+    true
+}
+
+impl Default for ChecksConfig {
+    fn default() -> Self {
+        Self {
+            delinquent: default_delinquent(),
+            minimum_balance: None,
+            version_skew: None,
+            maximum_skip_rate: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GroupConfig {
+    name: String,
+    validators: Vec<String>,
+    routes: Option<Vec<String>>,
+    #[serde(default)]
+    checks: ChecksConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MonitorConfigFile {
+    routes: Vec<RouteConfig>,
+    cluster_routes: Option<Vec<String>>,
+    #[serde(default)]
+    groups: Vec<GroupConfig>,
+}
+
+fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let duration: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+    duration
+        .map(|duration| humantime::parse_duration(&duration).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Route {
+    pub name: String,
+    pub notifier: NotifierConfig,
+    pub escalate_after: Duration,
+    pub repeat_every: Option<Duration>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Checks {
+    pub delinquent: bool,
+    pub minimum_balance: Option<u64>,
+    pub version_skew: Option<VersionSkew>,
+    pub maximum_skip_rate: Option<f64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub validators: Vec<Pubkey>,
+    /// Indexes into `MonitorConfig::routes`
+    pub routes: Vec<usize>,
+    pub checks: Checks,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MonitorConfig {
+    pub routes: Vec<Route>,
+    /// Indexes into `routes`
+    pub cluster_routes: Vec<usize>,
+    pub groups: Vec<Group>,
+}
+
+impl MonitorConfig {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let yaml = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+        Self::from_yaml(&yaml).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        let config: MonitorConfigFile =
+            serde_yaml::from_str(yaml).map_err(|err| err.to_string())?;
+
+        if config.routes.is_empty() {
+            return Err("no routes".to_string());
+        }
+        let mut route_names = HashSet::new();
+        for route in &config.routes {
+            if !route_names.insert(route.name.as_str()) {
+                return Err(format!("duplicate route \"{}\"", route.name));
+            }
+        }
+        let route_indexes = |names: &Option<Vec<String>>| match names {
+            None => Ok((0..config.routes.len()).collect()),
+            Some(names) => names
+                .iter()
+                .map(|name| {
+                    config
+                        .routes
+                        .iter()
+                        .position(|route| &route.name == name)
+                        .ok_or_else(|| format!("unknown route \"{}\"", name))
+                })
+                .collect::<Result<Vec<_>, _>>(),
+        };
+        let cluster_routes = route_indexes(&config.cluster_routes)?;
+
+        let mut groups = vec![];
+        for group in &config.groups {
+            let validators = group
+                .validators
+                .iter()
+                .map(|validator| {
+                    Pubkey::from_str(validator).map_err(|err| {
+                        format!("group \"{}\": validator {}: {}", group.name, validator, err)
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let routes = route_indexes(&group.routes)
+                .map_err(|err| format!("group \"{}\": {}", group.name, err))?;
+            let checks = &group.checks;
+            if let Some(maximum_skip_rate) = checks.maximum_skip_rate {
+                if !(0.0..=100.0).contains(&maximum_skip_rate) {
+                    return Err(format!(
+                        "group \"{}\": maximum_skip_rate is a percentage",
+                        group.name
+                    ));
+                }
+            }
+            groups.push(Group {
+                name: group.name.clone(),
+                validators,
+                routes,
+                checks: Checks {
+                    delinquent: checks.delinquent,
+                    minimum_balance: checks.minimum_balance.map(sol_to_lamports),
+                    version_skew: checks.version_skew,
+                    maximum_skip_rate: checks.maximum_skip_rate,
+                },
+            });
+        }
+
+        Ok(Self {
+            routes: config
+                .routes
+                .into_iter()
+                .map(|route| Route {
+                    name: route.name,
+                    notifier: route.notifier,
+                    escalate_after: route.escalate_after.unwrap_or_default(),
+                    repeat_every: route.repeat_every,
+                })
+                .collect(),
+            cluster_routes,
+            groups,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_yaml() {
+        let validator = Pubkey::new_unique();
+        let config = MonitorConfig::from_yaml(&format!(
+            r#"
+routes:
+  - name: chat
+    notifier:
+      slack_webhook: https://hooks.example.com/chat
+  - name: pager
+    notifier:
+      log_level: error
+    escalate_after: 15m
+    repeat_every: 1h
+cluster_routes: [chat]
+groups:
+  - name: mainnet
+    validators: [{}]
+    checks:
+      minimum_balance: 2.5
+      version_skew: minor
+      maximum_skip_rate: 25
+  - name: testnet
+    validators: []
+    routes: [chat]
+    checks:
+      delinquent: false
+"#,
+            validator
+        ))
+        .unwrap();
+
+        assert_eq!(
+            config.routes[1],
+            Route {
+                name: "pager".to_string(),
+                notifier: NotifierConfig {
+                    log_level: Some("error".to_string()),
+                    ..NotifierConfig::default()
+                },
+                escalate_after: Duration::from_secs(15 * 60),
+                repeat_every: Some(Duration::from_secs(60 * 60)),
+            }
+        );
+        assert_eq!(config.routes[0].escalate_after, Duration::ZERO);
+        assert_eq!(config.cluster_routes, vec![0]);
+        assert_eq!(
+            config.groups[0],
+            Group {
+                name: "mainnet".to_string(),
+                validators: vec![validator],
+                routes: vec![0, 1],
+                checks: Checks {
+                    delinquent: true,
+                    minimum_balance: Some(2_500_000_000),
+                    version_skew: Some(VersionSkew::Minor),
+                    maximum_skip_rate: Some(25.0),
+                },
+            }
+        );
+        assert_eq!(config.groups[1].routes, vec![0]);
+        assert!(!config.groups[1].checks.delinquent);
+    }
+
+    #[test]
+    fn test_from_yaml_invalid() {
+        let route = "routes: [{ name: chat, notifier: {} }]\n";
+        assert!(MonitorConfig::from_yaml(route).is_ok());
+        assert!(MonitorConfig::from_yaml("routes: []").is_err());
+        assert!(MonitorConfig::from_yaml(&format!("{}cluster_routes: [pager]", route)).is_err());
+        assert!(MonitorConfig::from_yaml(&format!(
+            "{}groups: [{{ name: a, validators: [not-a-pubkey] }}]",
+            route
+        ))
+        .is_err());
+        assert!(MonitorConfig::from_yaml(&format!(
+            "{}groups: [{{ name: a, validators: [], checks: {{ maximum_skip_rate: 101 }} }}]",
+            route
+        ))
+        .is_err());
+        assert!(MonitorConfig::from_yaml(&format!(
+            "{}groups: [{{ name: a, validators: [], checks: {{ unknown: 1 }} }}]",
+            route
+        ))
+        .is_err());
+        assert!(MonitorConfig::from_yaml(
+            "routes: [{ name: chat, notifier: {}, escalate_after: soon }]"
+        )
+        .is_err());
+    }
+}
@@ -2,39 +2,44 @@
 #![allow(clippy::integer_arithmetic)]
 
 use {
+    alerts::{AlertTracker, Failure},
+    checks::ClusterInfo,
     clap::{crate_description, crate_name, value_t, value_t_or_exit, App, Arg},
+    config::{Checks, Group, MonitorConfig, Route},
     log::*,
     solana_clap_utils::{
         input_parsers::pubkeys_of,
         input_validators::{is_parsable, is_pubkey_or_keypair, is_url},
     },
-    renec_cli_output::display::format_labeled_address,
-    solana_client::{client_error, rpc_client::RpcClient, rpc_response::RpcVoteAccountStatus},
+    solana_client::{client_error, rpc_client::RpcClient},
     solana_metrics::{datapoint_error, datapoint_info},
-    solana_notifier::Notifier,
+    solana_notifier::{Notifier, NotifierConfig},
     solana_sdk::{
         hash::Hash,
         native_token::{sol_to_lamports, Sol},
-        pubkey::Pubkey,
     },
     std::{
         collections::HashMap,
         error,
+        process::exit,
         thread::sleep,
         time::{Duration, Instant},
     },
 };
 
+mod alerts;
+mod checks;
+mod config;
+
 struct Config {
     address_labels: HashMap<String, String>,
     ignore_http_bad_gateway: bool,
     interval: Duration,
     json_rpc_url: String,
     rpc_timeout: Duration,
-    minimum_validator_identity_balance: u64,
     monitor_active_stake: bool,
     unhealthy_threshold: usize,
-    validator_identity_pubkeys: Vec<Pubkey>,
+    monitor: MonitorConfig,
     name_suffix: String,
 }
 
@@ -58,7 +63,10 @@ fn get_config() -> Config {
         and a sending number owned by that account,
         define environment variable before running `solana-watchtower`:
 
-        export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'")
+        export TWILIO_CONFIG='ACCOUNT=<account>,TOKEN=<securityToken>,TO=<receivingNumber>,FROM=<sendingNumber>'
+
+        To monitor groups of validators with their own checks and notifications,
+        use a --monitor-config file instead.")
         .arg({
             let arg = Arg::with_name("config_file")
                 .short("C")
@@ -73,6 +81,17 @@ fn get_config() -> Config {
                 arg
             }
         })
+        .arg(
+            Arg::with_name("monitor_config")
+                .long("monitor-config")
+                .value_name("PATH")
+                .takes_value(true)
+                .conflicts_with("validator_identities")
+                .help("YAML file defining groups of validators to monitor, their checks, \
+                       and the routes of their alerts to notifiers. \
+                       Replaces --validator-identity, --minimum-validator-identity-balance \
+                       and the notifier environment variables"),
+        )
         .arg(
             Arg::with_name("json_rpc_url")
                 .long("url")
@@ -171,10 +190,38 @@ fn get_config() -> Config {
         value_t!(matches, "json_rpc_url", String).unwrap_or_else(|_| config.json_rpc_url.clone());
     let rpc_timeout = value_t_or_exit!(matches, "rpc_timeout", u64);
     let rpc_timeout = Duration::from_secs(rpc_timeout);
-    let validator_identity_pubkeys: Vec<_> = pubkeys_of(&matches, "validator_identities")
-        .unwrap_or_default()
-        .into_iter()
-        .collect();
+
+    let monitor = if let Some(monitor_config) = matches.value_of("monitor_config") {
+        MonitorConfig::load(monitor_config).unwrap_or_else(|err| {
+            eprintln!("Invalid monitor configuration: {}", err);
+            exit(1);
+        })
+    } else {
+        let validator_identity_pubkeys: Vec<_> = pubkeys_of(&matches, "validator_identities")
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        MonitorConfig {
+            routes: vec![Route {
+                name: "default".to_string(),
+                notifier: NotifierConfig::from_env(""),
+                escalate_after: Duration::ZERO,
+                repeat_every: None,
+            }],
+            cluster_routes: vec![0],
+            groups: vec![Group {
+                name: "validators".to_string(),
+                validators: validator_identity_pubkeys,
+                routes: vec![0],
+                checks: Checks {
+                    delinquent: true,
+                    minimum_balance: Some(minimum_validator_identity_balance),
+                    version_skew: None,
+                    maximum_skip_rate: None,
+                },
+            }],
+        }
+    };
 
     let monitor_active_stake = matches.is_present("monitor_active_stake");
     let ignore_http_bad_gateway = matches.is_present("ignore_http_bad_gateway");
@@ -187,42 +234,92 @@ fn get_config() -> Config {
         interval,
         json_rpc_url,
         rpc_timeout,
-        minimum_validator_identity_balance,
         monitor_active_stake,
         unhealthy_threshold,
-        validator_identity_pubkeys,
+        monitor,
         name_suffix,
     };
 
     info!("RPC URL: {}", config.json_rpc_url);
-    info!(
-        "Monitored validators: {:?}",
-        config.validator_identity_pubkeys
-    );
+    for group in &config.monitor.groups {
+        info!(
+            "Monitored validators of {}: {:?}",
+            group.name, group.validators
+        );
+    }
     config
 }
 
 fn get_cluster_info(
     config: &Config,
     rpc_client: &RpcClient,
-) -> client_error::Result<(u64, Hash, RpcVoteAccountStatus, HashMap<Pubkey, u64>)> {
+) -> client_error::Result<(u64, Hash, ClusterInfo)> {
     let transaction_count = rpc_client.get_transaction_count()?;
     let recent_blockhash = rpc_client.get_latest_blockhash()?;
     let vote_accounts = rpc_client.get_vote_accounts()?;
+    let groups = &config.monitor.groups;
 
     let mut validator_balances = HashMap::new();
-    for validator_identity in &config.validator_identity_pubkeys {
-        validator_balances.insert(
-            *validator_identity,
-            rpc_client.get_balance(validator_identity)?,
-        );
+    for group in groups {
+        if group.checks.minimum_balance.is_some() {
+            for validator_identity in &group.validators {
+                if !validator_balances.contains_key(validator_identity) {
+                    validator_balances.insert(
+                        *validator_identity,
+                        rpc_client.get_balance(validator_identity)?,
+                    );
+                }
+            }
+        }
+    }
+
+    let mut node_versions = HashMap::new();
+    let mut cluster_version = None;
+    if groups
+        .iter()
+        .any(|group| group.checks.version_skew.is_some())
+    {
+        node_versions = rpc_client
+            .get_cluster_nodes()?
+            .into_iter()
+            .filter_map(|node| Some((node.pubkey, node.version?)))
+            .collect();
+
+        let mut stake_by_version = HashMap::<&String, u64>::new();
+        for vote_account in vote_accounts
+            .current
+            .iter()
+            .chain(&vote_accounts.delinquent)
+        {
+            if let Some(version) = node_versions.get(&vote_account.node_pubkey) {
+                *stake_by_version.entry(version).or_default() += vote_account.activated_stake;
+            }
+        }
+        cluster_version = stake_by_version
+            .into_iter()
+            .max_by_key(|(version, stake)| (*stake, version.to_string()))
+            .map(|(version, _)| version.clone());
     }
 
+    let block_production = if groups
+        .iter()
+        .any(|group| group.checks.maximum_skip_rate.is_some())
+    {
+        rpc_client.get_block_production()?.value.by_identity
+    } else {
+        HashMap::new()
+    };
+
     Ok((
         transaction_count,
         recent_blockhash,
-        vote_accounts,
-        validator_balances,
+        ClusterInfo {
+            vote_accounts,
+            validator_balances,
+            node_versions,
+            cluster_version,
+            block_production,
+        },
     ))
 }
 
@@ -233,16 +330,31 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let config = get_config();
 
     let rpc_client = RpcClient::new_with_timeout(config.json_rpc_url.clone(), config.rpc_timeout);
-    let notifier = Notifier::default();
+    let notifiers = config
+        .monitor
+        .routes
+        .iter()
+        .map(|route| {
+            Notifier::from_config(&route.notifier)
+                .map_err(|err| format!("route \"{}\": {}", route.name, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut alert_tracker = AlertTracker::new(config.unhealthy_threshold, &config.monitor.routes);
     let mut last_transaction_count = 0;
     let mut last_recent_blockhash = Hash::default();
-    let mut last_notification_msg = "".into();
-    let mut num_consecutive_failures = 0;
-    let mut last_success = Instant::now();
 
     loop {
-        let failure = match get_cluster_info(&config, &rpc_client) {
-            Ok((transaction_count, recent_blockhash, vote_accounts, validator_balances)) => {
+        let cluster_failure = |check, message| Failure {
+            group: "cluster".to_string(),
+            check,
+            subject: String::new(),
+            message,
+            routes: config.monitor.cluster_routes.clone(),
+        };
+
+        let (failures, partial) = match get_cluster_info(&config, &rpc_client) {
+            Ok((transaction_count, recent_blockhash, cluster_info)) => {
+                let vote_accounts = &cluster_info.vote_accounts;
                 info!("Current transaction count: {}", transaction_count);
                 info!("Recent blockhash: {}", recent_blockhash);
                 info!("Current validator count: {}", vote_accounts.current.len());
@@ -277,7 +389,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 if transaction_count > last_transaction_count {
                     last_transaction_count = transaction_count;
                 } else {
-                    failures.push((
+                    failures.push(cluster_failure(
                         "transaction-count",
                         format!(
                             "Transaction count is not advancing: {} <= {}",
@@ -289,61 +401,30 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 if recent_blockhash != last_recent_blockhash {
                     last_recent_blockhash = recent_blockhash;
                 } else {
-                    failures.push((
+                    failures.push(cluster_failure(
                         "recent-blockhash",
                         format!("Unable to get new blockhash: {}", recent_blockhash),
                     ));
                 }
 
                 if config.monitor_active_stake && current_stake_percent < 80. {
-                    failures.push((
+                    failures.push(cluster_failure(
                         "current-stake",
                         format!("Current stake is {:.2}%", current_stake_percent),
                     ));
                 }
 
-                let mut validator_errors = vec![];
-                for validator_identity in config.validator_identity_pubkeys.iter() {
-                    let formatted_validator_identity = format_labeled_address(
-                        &validator_identity.to_string(),
+                for group in &config.monitor.groups {
+                    failures.extend(checks::check_group(
+                        group,
+                        &cluster_info,
                         &config.address_labels,
-                    );
-                    if vote_accounts
-                        .delinquent
-                        .iter()
-                        .any(|vai| vai.node_pubkey == *validator_identity.to_string())
-                    {
-                        validator_errors
-                            .push(format!("{} delinquent", formatted_validator_identity));
-                    } else if !vote_accounts
-                        .current
-                        .iter()
-                        .any(|vai| vai.node_pubkey == *validator_identity.to_string())
-                    {
-                        validator_errors.push(format!("{} missing", formatted_validator_identity));
-                    }
-
-                    if let Some(balance) = validator_balances.get(validator_identity) {
-                        if *balance < config.minimum_validator_identity_balance {
-                            failures.push((
-                                "balance",
-                                format!("{} has {}", formatted_validator_identity, Sol(*balance)),
-                            ));
-                        }
-                    }
-                }
-
-                if !validator_errors.is_empty() {
-                    failures.push(("delinquent", validator_errors.join(",")));
-                }
-
-                for failure in failures.iter() {
-                    error!("{} sanity failure: {}", failure.0, failure.1);
+                    ));
                 }
-                failures.into_iter().next() // Only report the first failure if any
+                (failures, false)
             }
             Err(err) => {
-                let mut failure = Some(("rpc-error", err.to_string()));
+                let mut failures = vec![cluster_failure("rpc-error", err.to_string())];
 
                 if let client_error::ClientErrorKind::Reqwest(reqwest_err) = err.kind() {
                     if let Some(client_error::reqwest::StatusCode::BAD_GATEWAY) =
@@ -351,57 +432,44 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                     {
                         if config.ignore_http_bad_gateway {
                             warn!("Error suppressed: {}", err);
-                            failure = None;
+                            failures.clear();
                         }
                     }
                 }
-                failure
+                // The other checks could not run, so their alerts remain
+                (failures, true)
             }
         };
 
-        if let Some((failure_test_name, failure_error_message)) = &failure {
+        for failure in &failures {
+            error!("{} sanity failure: {}", failure.check, failure.message);
+        }
+        datapoint_info!("watchtower-sanity", ("ok", failures.is_empty(), bool));
+
+        let notifications = alert_tracker.update(failures, partial, Instant::now());
+        for failure in alert_tracker.active_alerts() {
+            datapoint_error!(
+                "watchtower-sanity-failure",
+                ("test", failure.check, String),
+                ("err", failure.message, String)
+            );
+        }
+        for notification in notifications {
             let notification_msg = format!(
-                "solana-watchtower{}: Error: {}: {}",
-                config.name_suffix, failure_test_name, failure_error_message
+                "solana-watchtower{}: {}",
+                config.name_suffix, notification.message
             );
-            num_consecutive_failures += 1;
-            if num_consecutive_failures > config.unhealthy_threshold {
-                datapoint_info!("watchtower-sanity", ("ok", false, bool));
-                if last_notification_msg != notification_msg {
-                    notifier.send(&notification_msg);
-                }
-                datapoint_error!(
-                    "watchtower-sanity-failure",
-                    ("test", failure_test_name, String),
-                    ("err", failure_error_message, String)
-                );
-                last_notification_msg = notification_msg;
+            info!("{}", notification_msg);
+            let incident = format!(
+                "solana-watchtower{}/{}",
+                config.name_suffix, notification.incident
+            );
+            let notifier = &notifiers[notification.route];
+            if notification.resolved {
+                notifier.resolve(&incident, &notification_msg);
             } else {
-                info!(
-                    "Failure {} of {}: {}",
-                    num_consecutive_failures, config.unhealthy_threshold, notification_msg
-                );
-            }
-        } else {
-            datapoint_info!("watchtower-sanity", ("ok", true, bool));
-            if !last_notification_msg.is_empty() {
-                let alarm_duration = Instant::now().duration_since(last_success);
-                let alarm_duration = alarm_duration - config.interval; // Subtract the period before the first error
-                let alarm_duration = Duration::from_secs(alarm_duration.as_secs()); // Drop milliseconds in message
-
-                let all_clear_msg = format!(
-                    "All clear after {}",
-                    humantime::format_duration(alarm_duration)
-                );
-                info!("{}", all_clear_msg);
-                notifier.send(&format!(
-                    "solana-watchtower{}: {}",
-                    config.name_suffix, all_clear_msg
-                ));
+                notifier.trigger(&incident, &notification_msg);
             }
-            last_notification_msg = "".into();
-            last_success = Instant::now();
-            num_consecutive_failures = 0;
         }
         sleep(config.interval);
     }